@@ -1,6 +1,10 @@
-use crate::commands::{init::init_cmd, install::install_cmd, run::run_cmd};
-use clap::{builder::Styles, Arg, ArgAction, Command};
+use crate::commands::{
+    add::add_cmd, check::check_cmd, explain::explain_cmd, init::init_cmd, install::install_cmd,
+    new::new_cmd, remove::remove_cmd, run::run_cmd, test::test_cmd, watch::watch_cmd,
+};
+use clap::{builder::Styles, Arg, ArgAction, ArgMatches, Command};
 use roan_shell::styles::*;
+use std::collections::HashSet;
 
 pub fn opt(name: &'static str, help: &'static str) -> Arg {
     Arg::new(name).long(name).help(help).action(ArgAction::Set)
@@ -10,6 +14,15 @@ pub fn positional(name: &'static str, help: &'static str) -> Arg {
     Arg::new(name).help(help).index(1)
 }
 
+/// Returns the passes given with `--skip-pass`.
+pub fn skipped_passes(matches: &ArgMatches) -> HashSet<String> {
+    matches
+        .get_many::<String>("skip-pass")
+        .unwrap_or_default()
+        .cloned()
+        .collect()
+}
+
 pub fn cli() -> Command {
     let styles = {
         Styles::styled()
@@ -29,6 +42,29 @@ pub fn cli() -> Command {
             opt("verbose", "Use verbose output")
                 .short('v')
                 .action(ArgAction::SetTrue)
+                .global(true)
+                .conflicts_with("quiet"),
+        )
+        .arg(
+            opt("quiet", "Do not print status messages")
+                .short('q')
+                .action(ArgAction::SetTrue)
+                .global(true),
+        )
+        .arg(
+            opt(
+                "no-interactive",
+                "Do not prompt for input, use the default answers",
+            )
+            .long("no-interactive")
+            .action(ArgAction::SetTrue)
+            .global(true),
+        )
+        .arg(
+            opt("log-format", "Format of the log output")
+                .value_name("FORMAT")
+                .value_parser(["text", "json"])
+                .default_value("text")
                 .global(true),
         )
         .arg(
@@ -37,7 +73,23 @@ pub fn cli() -> Command {
                 .action(ArgAction::SetTrue)
                 .global(true),
         )
+        .arg(
+            opt(
+                "skip-pass",
+                "Skip a compiler pass, e.g. TypePass. Can be used multiple times",
+            )
+            .value_name("PASS")
+            .action(ArgAction::Append)
+            .global(true),
+        )
         .subcommand(run_cmd())
         .subcommand(init_cmd())
+        .subcommand(new_cmd())
         .subcommand(install_cmd())
+        .subcommand(add_cmd())
+        .subcommand(remove_cmd())
+        .subcommand(explain_cmd())
+        .subcommand(test_cmd())
+        .subcommand(check_cmd())
+        .subcommand(watch_cmd())
 }
@@ -0,0 +1,100 @@
+use crate::{
+    commands::install::install_packages,
+    context::GlobalContext,
+    pm::{
+        manifest::add_dependency,
+        packs::{parse_pack, PackVersion},
+        registry::{resolve_latest, PackageVersion},
+        semver::version_to_req,
+    },
+};
+use anyhow::Result;
+use clap::{Arg, ArgMatches, Command};
+use semver::{Op, VersionReq};
+
+pub fn add_cmd() -> Command {
+    Command::new("add")
+        .about("Add a dependency to roan.toml and install it")
+        .arg(
+            Arg::new("pack")
+                .value_name("PACK[@<VER>]")
+                .help("Package to add, defaults to its latest version")
+                .value_parser(parse_pack)
+                .required(true),
+        )
+}
+
+pub async fn add_command(ctx: &mut GlobalContext, matches: &ArgMatches) -> Result<()> {
+    let (name, req) = matches.get_one::<PackVersion>("pack").unwrap().clone();
+
+    add_package(ctx, &name, req.as_ref()).await
+}
+
+/// Resolves the latest version of `name` matching `req` in the registry, adds it to the
+/// dependencies in `roan.toml` and installs it.
+///
+/// A requirement given by the user is written as is, otherwise the resolved version is written,
+/// so later compatible versions are accepted.
+pub async fn add_package(
+    ctx: &mut GlobalContext,
+    name: &str,
+    req: Option<&VersionReq>,
+) -> Result<()> {
+    let path = ctx.config_path()?;
+
+    ctx.shell.status("Resolving", name)?;
+    let registry = ctx.registry.clone();
+    let PackageVersion { version, .. } = resolve_latest(registry.as_ref(), name, req).await?;
+
+    let requirement = match req {
+        Some(req) => req.to_string(),
+        None => version.to_string(),
+    };
+    add_dependency(&path, name, &requirement)?;
+
+    install_packages(
+        ctx,
+        &[(
+            name.to_string(),
+            Some(version_to_req(version.clone(), Op::Exact)),
+        )],
+    )?;
+
+    ctx.shell
+        .success(format!("Added {} v{} to dependencies", name, version))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pm::registry::MockRegistry;
+    use anstream::ColorChoice;
+    use std::{fs, sync::Arc};
+
+    #[tokio::test]
+    async fn test_add_package() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("roan.toml"),
+            "[project]\nname = \"app\"\nversion = \"0.1.0\"\ntype = \"bin\"\n",
+        )
+        .unwrap();
+        let mut ctx = GlobalContext::from_cwd(dir.path().into(), ColorChoice::Never).unwrap();
+        ctx.registry = Arc::new(
+            MockRegistry::default()
+                .with("json", "1.4.0", "")
+                .with("xml", "0.2.1", ""),
+        );
+
+        add_package(&mut ctx, "json", None).await.unwrap();
+        add_package(&mut ctx, "xml", Some(&"~0.2".parse().unwrap()))
+            .await
+            .unwrap();
+        assert!(add_package(&mut ctx, "yaml", None).await.is_err());
+
+        let deps = ctx.load_config().unwrap().dependencies.unwrap();
+        assert_eq!(deps["json"].version.as_deref(), Some("1.4.0"));
+        assert_eq!(deps["xml"].version.as_deref(), Some("~0.2"));
+        assert!(!deps.contains_key("yaml"));
+    }
+}
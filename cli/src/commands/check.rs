@@ -0,0 +1,106 @@
+use crate::{
+    cli::{positional, skipped_passes},
+    context::GlobalContext,
+    diagnostics::{print_diagnostics, print_error},
+    module_loader::RoanModuleLoader,
+};
+use anyhow::{bail, Result};
+use clap::{ArgMatches, Command};
+use roan_engine::{context::Context, module::Module, path::normalize_path, source::Source, vm::VM};
+use roan_shell::Shell;
+use std::{cell::RefCell, collections::HashSet, path::PathBuf, rc::Rc, time::Instant};
+
+pub fn check_cmd() -> Command {
+    Command::new("check")
+        .about("Check a project for errors without running it")
+        .arg(positional(
+            "file",
+            "File to check, defaults to the main file of the project",
+        ))
+}
+
+pub fn check_command(global: &mut GlobalContext, matches: &ArgMatches) -> Result<()> {
+    let path = match matches.get_one::<String>("file") {
+        Some(file) => normalize_path(PathBuf::from(file), global.cwd.clone())?,
+        None => {
+            global.load_config()?;
+            global.get_main_file()?
+        }
+    };
+
+    global
+        .shell
+        .status("Checking", &path.display().to_string())?;
+
+    let start = Instant::now();
+    if check_source(
+        &mut global.shell,
+        Source::from_file(path.clone())?,
+        global.cwd.clone(),
+        skipped_passes(matches),
+    )
+    .is_err()
+    {
+        bail!("Could not check {} due to previous errors", path.display());
+    }
+
+    global
+        .shell
+        .status("Finished", format!("checking in {:?}", start.elapsed()))?;
+
+    Ok(())
+}
+
+/// Lexes and parses `source` and runs every pass over it, including the imported modules, but
+/// never interprets it. Diagnostics are written to stderr through `shell`.
+///
+/// Passes named in `disabled_passes` are skipped.
+pub fn check_source(
+    shell: &mut Shell,
+    source: Source,
+    cwd: PathBuf,
+    disabled_passes: HashSet<String>,
+) -> Result<Module> {
+    let ctx = &mut Context::builder()
+        .cwd(cwd)
+        .module_loader(Rc::new(RefCell::new(RoanModuleLoader::new())))
+        .disabled_passes(disabled_passes)
+        .build();
+    let vm = &mut VM::new();
+    let mut module = Module::new(source);
+
+    match module.parse(ctx, vm) {
+        Ok(warnings) => print_diagnostics(shell, &warnings)?,
+        Err(err) => {
+            print_error(shell, &err, &module)?;
+            return Err(err);
+        }
+    }
+
+    Ok(module)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anstream::ColorChoice;
+
+    fn check(src: &str) -> Result<Module> {
+        check_source(
+            &mut Shell::new(ColorChoice::Never),
+            Source::from_string(src.to_string()),
+            std::env::current_dir().unwrap(),
+            HashSet::new(),
+        )
+    }
+
+    #[test]
+    fn test_check_does_not_run_code() {
+        assert!(check("__exit(3);\nfn main() { __panic(\"ran\"); }\nmain();").is_ok());
+    }
+
+    #[test]
+    fn test_check_reports_errors() {
+        assert!(check("__exit(3);\nfn f(a: int) {}\nf(\"a\");").is_err());
+    }
+}
@@ -0,0 +1,959 @@
+use crate::{cli::positional, context::GlobalContext};
+use anyhow::{bail, Result};
+use clap::{ArgMatches, Command};
+use roan_engine::error::{format_error_code, parse_error_code};
+
+pub fn explain_cmd() -> Command {
+    Command::new("explain")
+        .about("Print a detailed explanation of an error code")
+        .arg(positional("code", "The error code to explain, e.g. E0019").required(true))
+}
+
+pub fn explain_command(ctx: &mut GlobalContext, args: &ArgMatches) -> Result<()> {
+    let code = args.get_one::<String>("code").unwrap();
+
+    let explanation = match parse_error_code(code) {
+        Some(code) => EXPLANATIONS
+            .iter()
+            .find(|(c, _)| *c == code)
+            .map(|(code, text)| (*code, *text)),
+        None => bail!(
+            "'{}' is not a valid error code, expected a code like E0019",
+            code
+        ),
+    };
+
+    match explanation {
+        Some((code, text)) => {
+            ctx.shell.status("Explaining", format_error_code(code))?;
+            println!("\n{}", text.trim());
+        }
+        None => bail!("No explanation found for error code {}", code),
+    }
+
+    Ok(())
+}
+
+/// Long-form descriptions of every error code, indexed by the value returned from
+/// `RoanError::error_code`. Codes are never renumbered, new errors get new entries.
+const EXPLANATIONS: &[(u16, &str)] = &[
+    (
+        1,
+        r#"
+An I/O operation failed.
+
+This usually happens when a source file or a module can't be read, e.g. because it doesn't
+exist or the current user isn't allowed to read it.
+
+Check that the path in the error message exists and is readable.
+"#,
+    ),
+    (
+        2,
+        r#"
+The lexer found a character that isn't part of the Roan syntax.
+
+Erroneous code example:
+
+    let price = 10$;
+
+Remove the character or, if it was meant to be text, put it inside a string:
+
+    let price = "10$";
+"#,
+    ),
+    (
+        3,
+        r#"
+The parser expected a specific token but found something else.
+
+This is most often caused by a missing closing delimiter or a missing separator.
+
+Erroneous code example:
+
+    fn add(a: int, b: int -> int {
+        return a + b;
+    }
+
+Add the missing token:
+
+    fn add(a: int, b: int) -> int {
+        return a + b;
+    }
+"#,
+    ),
+    (
+        4,
+        r#"
+The parser found a token that can't appear at this position.
+
+Erroneous code example:
+
+    let = 5;
+
+Make sure the statement is complete:
+
+    let x = 5;
+"#,
+    ),
+    (
+        5,
+        r#"
+The code is syntactically valid, but its meaning is invalid.
+
+Read the message attached to the error, it describes which rule was broken.
+"#,
+    ),
+    (
+        6,
+        r#"
+A name or a declaration couldn't be resolved before the module was run.
+
+This usually means that a declaration refers to something that doesn't exist. Read the message
+attached to the error to find which declaration failed.
+"#,
+    ),
+    (
+        7,
+        r#"
+A module couldn't be loaded or processed.
+
+Check that the module exists, that it's spelled correctly in the `use` statement and that the
+project's `roan.toml` lists the dependency it belongs to.
+"#,
+    ),
+    (
+        8,
+        r#"
+An item was imported from a module that doesn't export it.
+
+Erroneous code example:
+
+    use { substract } from "./math.roan";
+
+Either fix the name of the item, or make sure it's declared with `pub` in the imported module:
+
+    // math.roan
+    pub fn subtract(a: int, b: int) -> int {
+        return a - b;
+    }
+"#,
+    ),
+    (
+        9,
+        r#"
+A module listed in a `use` statement couldn't be imported.
+
+Erroneous code example:
+
+    use { add } from "./mth.roan";
+
+Check the path (relative paths are resolved from the importing file) or the name of the
+dependency:
+
+    use { add } from "./math.roan";
+"#,
+    ),
+    (
+        10,
+        r#"
+A variable was used but it isn't declared in the current scope.
+
+Erroneous code example:
+
+    fn main() {
+        count = count + 1;
+    }
+
+Declare the variable before using it:
+
+    fn main() {
+        let count = 0;
+        count = count + 1;
+    }
+"#,
+    ),
+    (
+        11,
+        r#"
+A function was called but no function with that name exists in the module.
+
+Erroneous code example:
+
+    let total = sum(1, 2);
+
+Declare the function or import it from the module that defines it:
+
+    use { sum } from "./math.roan";
+
+    let total = sum(1, 2);
+"#,
+    ),
+    (
+        12,
+        r#"
+A normal parameter was declared after a rest parameter.
+
+Erroneous code example:
+
+    fn log(...messages: string, level: int) {}
+
+The rest parameter collects all remaining arguments, so it has to be the last one:
+
+    fn log(level: int, ...messages: string) {}
+"#,
+    ),
+    (
+        13,
+        r#"
+A rest parameter was declared in a position other than the last one.
+
+Erroneous code example:
+
+    fn log(...messages: string, level: int) {}
+
+Move the rest parameter to the end of the parameter list:
+
+    fn log(level: int, ...messages: string) {}
+"#,
+    ),
+    (
+        14,
+        r#"
+A function declares more than one rest parameter.
+
+Erroneous code example:
+
+    fn merge(...a: int, ...b: int) {}
+
+Only one rest parameter is allowed. Pass the other values as a vector instead:
+
+    fn merge(a: vec<int>, ...b: int) {}
+"#,
+    ),
+    (
+        15,
+        r#"
+A value was thrown with `throw` and never caught.
+
+Erroneous code example:
+
+    throw "Something went wrong";
+
+Wrap the code that can throw in a `try` block and handle the error:
+
+    try {
+        throw "Something went wrong";
+    } catch err {
+        // handle the error
+    }
+"#,
+    ),
+    (
+        16,
+        r#"
+A string or char literal contains an unknown escape sequence.
+
+Erroneous code example:
+
+    let path = "C:\data";
+
+Escape the backslash:
+
+    let path = "C:\\data";
+"#,
+    ),
+    (
+        17,
+        r#"
+The condition of an `if` or `while` doesn't evaluate to a boolean.
+
+Erroneous code example:
+
+    let items = 3;
+    if items {
+        // ...
+    }
+
+Compare the value explicitly:
+
+    let items = 3;
+    if items > 0 {
+        // ...
+    }
+"#,
+    ),
+    (
+        18,
+        r#"
+An index is outside of the bounds of a vector.
+
+Erroneous code example:
+
+    let items = [1, 2, 3];
+    items[3] = 4;
+
+Indices start at 0, check the length of the vector before assigning to an index:
+
+    let items = [1, 2, 3];
+    items[2] = 4;
+"#,
+    ),
+    (
+        19,
+        r#"
+A value doesn't have the type that is expected in this position.
+
+Erroneous code example:
+
+    let total = 1 + true;
+
+Make sure both sides of an operation, function arguments and assigned values have compatible
+types:
+
+    let total = 1 + 2;
+"#,
+    ),
+    (
+        20,
+        r#"
+The left-hand side of an assignment can't be assigned to.
+
+Erroneous code example:
+
+    5 = x;
+
+Only variables, fields and indices can be assigned to:
+
+    x = 5;
+"#,
+    ),
+    (
+        21,
+        r#"
+A field or method was accessed on a value that doesn't have it.
+
+Erroneous code example:
+
+    struct Point {
+        x: int,
+        y: int,
+    }
+
+    let p = Point { x: 1, y: 2 };
+    let z = p.z;
+
+Check the name of the field or method, or add it to the struct definition.
+"#,
+    ),
+    (
+        22,
+        r#"
+A property access has an invalid form.
+
+Only field names and method calls can follow a `.`:
+
+    let length = name.len();
+"#,
+    ),
+    (
+        23,
+        r#"
+A `break` or `continue` statement was found outside of a loop.
+
+Erroneous code example:
+
+    fn main() {
+        break;
+    }
+
+`break` and `continue` can only be used inside `loop` and `while` blocks:
+
+    loop {
+        break;
+    }
+"#,
+    ),
+    (
+        24,
+        r#"
+A `break` statement was executed outside of a loop.
+
+`break` can only be used inside `loop` and `while` blocks to exit the loop early.
+"#,
+    ),
+    (
+        25,
+        r#"
+A `continue` statement was executed outside of a loop.
+
+`continue` can only be used inside `loop` and `while` blocks to skip to the next iteration.
+"#,
+    ),
+    (
+        26,
+        r#"
+The spread operator `...` was used where it isn't allowed.
+
+Erroneous code example:
+
+    let x = ...items;
+
+The spread operator can only be used in function call arguments and vector literals:
+
+    let copy = [...items];
+    sum(...items);
+"#,
+    ),
+    (
+        27,
+        r#"
+A method declares more than one `self` parameter.
+
+Erroneous code example:
+
+    impl Point {
+        fn add(self, self) {}
+    }
+
+A method receives the instance it was called on only once:
+
+    impl Point {
+        fn add(self, other: Point) {}
+    }
+"#,
+    ),
+    (
+        28,
+        r#"
+The `self` parameter isn't the first parameter of a method.
+
+Erroneous code example:
+
+    impl Point {
+        fn add(other: Point, self) {}
+    }
+
+Move `self` to the beginning of the parameter list:
+
+    impl Point {
+        fn add(self, other: Point) {}
+    }
+"#,
+    ),
+    (
+        29,
+        r#"
+The `self` parameter was declared as a rest parameter.
+
+Erroneous code example:
+
+    impl Point {
+        fn print(...self) {}
+    }
+
+`self` is always a single value:
+
+    impl Point {
+        fn print(self) {}
+    }
+"#,
+    ),
+    (
+        30,
+        r#"
+A struct was used but no struct with that name is declared or imported.
+
+Erroneous code example:
+
+    let p = Pointt { x: 1, y: 2 };
+
+Fix the name of the struct, or import it from the module that declares it:
+
+    use { Point } from "./geometry.roan";
+"#,
+    ),
+    (
+        31,
+        r#"
+A trait was implemented but no trait with that name is declared or imported.
+
+Check the spelling of the trait name, or import the trait from the module that declares it.
+"#,
+    ),
+    (
+        32,
+        r#"
+A struct implements the same trait more than once.
+
+Each trait can only be implemented once for a struct. Merge the methods of the duplicate
+implementations into a single `impl` block.
+"#,
+    ),
+    (
+        33,
+        r#"
+A trait implementation is missing some of the methods declared by the trait.
+
+Every method of a trait has to be implemented. The error lists the missing methods, add them
+to the `impl` block.
+"#,
+    ),
+    (
+        34,
+        r#"
+A value was assigned to a static member.
+
+Erroneous code example:
+
+    Point::origin = Point { x: 0, y: 0 };
+
+Static members can only be called, they can't be assigned to.
+"#,
+    ),
+    (
+        35,
+        r#"
+A static member was accessed on something that isn't a struct.
+
+Erroneous code example:
+
+    let p = Point::new();
+    p::new();
+
+The left side of `::` has to be the name of a struct:
+
+    let p = Point::new();
+"#,
+    ),
+    (
+        36,
+        r#"
+Something other than a call was accessed in a static context.
+
+Erroneous code example:
+
+    let origin = Point::origin;
+
+Only static methods can be accessed with `::`, call the method instead:
+
+    let origin = Point::origin();
+"#,
+    ),
+    (
+        37,
+        r#"
+A unary operator was applied to a value it doesn't support.
+
+Erroneous code example:
+
+    let x = -"text";
+
+`-` and `~` only work on numbers, `!` works on any value.
+"#,
+    ),
+    (
+        38,
+        r#"
+A function was called without a value for a non-nullable parameter.
+
+Erroneous code example:
+
+    fn greet(name: string) {}
+
+    greet();
+
+Pass a value for every parameter, or make the parameter nullable:
+
+    fn greet(name: string?) {}
+
+    greet();
+"#,
+    ),
+    (
+        39,
+        r#"
+A type annotation refers to a type that doesn't exist.
+
+Erroneous code example:
+
+    let count: integer = 5;
+
+Use one of the built-in types (int, float, bool, string, char, vec, object, anytype, void)
+or the name of a declared struct:
+
+    let count: int = 5;
+"#,
+    ),
+    (
+        40,
+        r#"
+A struct was constructed without a value for one of its non-nullable fields.
+
+Erroneous code example:
+
+    struct Point {
+        x: int,
+        y: int,
+    }
+
+    let p = Point { x: 1 };
+
+Provide every field, or make the field nullable:
+
+    let p = Point { x: 1, y: 2 };
+"#,
+    ),
+    (
+        41,
+        r#"
+A function was called with more arguments than it accepts.
+
+Erroneous code example:
+
+    fn square(x: int) -> int {
+        return x * x;
+    }
+
+    square(2, 3);
+
+Remove the extra arguments, or add a rest parameter to the function if it should accept any
+number of them.
+"#,
+    ),
+    (
+        42,
+        r#"
+A value was assigned to a struct field that doesn't exist.
+
+Erroneous code example:
+
+    struct Point {
+        x: int,
+        y: int,
+    }
+
+    let p = Point { x: 1, y: 2 };
+    p.z = 3;
+
+Check the name of the field, or add it to the struct definition.
+"#,
+    ),
+    (
+        43,
+        r#"
+Checking the module reported errors, so it wasn't run.
+
+All the errors found while checking the module are printed before this one. Fix them and run
+the module again.
+"#,
+    ),
+    (
+        44,
+        r#"
+A module that an item was declared in couldn't be found.
+
+This usually happens when importing one of the modules the current module depends on failed,
+so the structs and functions it declares can't be looked up anymore.
+
+Fix the errors reported for the imported module, or check the `use` statements of the
+current module.
+"#,
+    ),
+    (
+        45,
+        r#"
+The value of a const can't be computed before the module runs.
+
+Erroneous code example:
+
+    const LIMIT = max(10, 20);
+
+A const can only contain literals, operators and other consts:
+
+    const LIMIT = 10 * 2;
+"#,
+    ),
+    (
+        46,
+        r#"
+The file ended in the middle of a statement or expression.
+
+Erroneous code example:
+
+    fn main() {
+        let x = 1 +
+
+Usually a closing bracket or the rest of an expression is missing. The error points at the end
+of the file and names what was expected there:
+
+    fn main() {
+        let x = 1 + 2;
+    }
+"#,
+    ),
+    (
+        47,
+        r#"
+A function, struct, trait or const was added to a module that already has one with the same
+name.
+
+This happens when all the exports of another module are merged into a module, and one of them
+is named like an item the module declares or imported before.
+
+Rename one of the items, or only import the items that are needed:
+
+    use { parse } from "./json.roan";
+"#,
+    ),
+    (
+        48,
+        r#"
+A `throw` without a value was used outside of a `catch` block.
+
+Erroneous code example:
+
+    fn check(x: int) {
+        if x < 0 {
+            throw;
+        }
+    }
+
+A bare `throw;` rethrows the error the enclosing `catch` block caught, with its original
+backtrace. Outside of a `catch` block there is no such error, so a value has to be thrown:
+
+    fn check(x: int) {
+        if x < 0 {
+            throw "x must not be negative";
+        }
+    }
+"#,
+    ),
+    (
+        49,
+        r#"
+A raw or multiline string literal was not closed before the end of the file.
+
+Erroneous code example:
+
+    let path = r"C:\Users\roan;
+
+    let text = """
+        first line
+        second line
+    ";
+
+A raw string ends at the next `"` and a multiline string at the next `"""`. Close the string
+with the same quotes it was opened with:
+
+    let path = r"C:\Users\roan";
+
+    let text = """
+        first line
+        second line
+    """;
+"#,
+    ),
+    (
+        50,
+        r#"
+A comparison operator was used on the result of another comparison without parentheses.
+
+Erroneous code example:
+
+    if 1 < x < 10 {
+        println("in range");
+    }
+
+In mathematics `1 < x < 10` means that `x` lies between 1 and 10, but comparing the boolean
+`1 < x` with `10` would mean something else entirely. Compare each pair separately and combine
+the results:
+
+    if 1 < x && x < 10 {
+        println("in range");
+    }
+
+If comparing the boolean really is intended, wrap the first comparison in parentheses:
+
+    let same = (a < b) == (c < d);
+"#,
+    ),
+    (
+        51,
+        r#"
+A native function was called that needs a capability the script isn't allowed to use.
+
+Erroneous code example, run with `roan run --sandbox`:
+
+    __exit(1);
+
+Natives that access the filesystem, the process, the environment or the network each need a
+capability. Scripts run with `--sandbox`, or by a program that disabled the capability, can't
+call them. The error can be caught to fall back to something else:
+
+    try {
+        let config = read_file("config.json");
+    } catch e {
+        println("Using the default configuration");
+    }
+"#,
+    ),
+    (
+        52,
+        r#"
+The result of a function that doesn't return a value was used as a value.
+
+Erroneous code example:
+
+    fn greet(name: string) {
+        println("Hello " + name);
+    }
+
+    let greeting = greet("roan");
+
+A function without a return type that never returns a value, or one declared to return `void`,
+has no result. It can be called as a statement, but its result can't be assigned or passed
+to another function. Return a value from the function to use it:
+
+    fn greet(name: string) -> string {
+        return "Hello " + name;
+    }
+
+    let greeting = greet("roan");
+
+A missing value is written `null`, which is a value like any other:
+
+    let nothing = null;
+"#,
+    ),
+    (
+        53,
+        r#"
+An item that isn't `pub` was imported from another module.
+
+Erroneous code example:
+
+    // math.roan
+    fn square(x: int) -> int {
+        return x * x;
+    }
+
+    // main.roan
+    use { square } from "./math.roan";
+
+Functions, structs, traits and consts are private to the module they're declared in, they can
+be used anywhere in it but can't be imported. Declare the item with `pub` to export it:
+
+    pub fn square(x: int) -> int {
+        return x * x;
+    }
+"#,
+    ),
+    (
+        54,
+        r#"
+The result of an operation on ints is too big to be stored in an int.
+
+Erroneous code example:
+
+    let big = 2 ** 63;
+
+Ints are 64 bits wide, so they range from -9223372036854775808 to 9223372036854775807. Results
+outside of that range aren't wrapped around, the program stops instead. Use a float operand to
+get a float result, which can be much bigger at the cost of precision:
+
+    let big = 2.0 ** 63;
+"#,
+    ),
+    (
+        55,
+        r#"
+A call made the call stack deeper than the program is allowed to.
+
+Erroneous code example:
+
+    fn count(n: int) -> int {
+        return count(n + 1);
+    }
+
+    count(0);
+
+Every call that hasn't returned yet takes a frame on the call stack. `roan run` stops the program
+once it's 1000 calls deep, most often because a recursive function never reaches the case that
+stops it. Make sure it does:
+
+    fn count(n: int) -> int {
+        if n == 10 {
+            return n;
+        }
+        return count(n + 1);
+    }
+
+Programs that recurse deeply on purpose can raise the limit with `--max-depth`, or with
+`max_depth` in the `[run]` table of roan.toml.
+"#,
+    ),
+    (
+        56,
+        r#"
+The program ran for longer than it's allowed to.
+
+Erroneous code example:
+
+    // roan.toml
+    [run]
+    timeout = 5
+
+    // main.roan
+    while true {}
+
+A time limit is set with `--timeout` or with `timeout` in the `[run]` table of roan.toml, in
+seconds. The program is stopped once it runs for longer than that, most often because a loop
+never ends. Make sure it does, or raise the limit.
+"#,
+    ),
+    (
+        57,
+        r#"
+A function declared to return a value can reach its end without returning one.
+
+Erroneous code example:
+
+    fn sign(x: int) -> int {
+        if x < 0 {
+            return -1;
+        } else if x > 0 {
+            return 1;
+        }
+    }
+
+Every path through the function has to end with a `return` or a `throw`. An `if` only covers
+every path when it has an `else` branch and all of its branches return, a `while` loop may not
+run at all, and a `loop` only ends with a `break`. Return a value in the missing case:
+
+    fn sign(x: int) -> int {
+        if x < 0 {
+            return -1;
+        } else if x > 0 {
+            return 1;
+        }
+        return 0;
+    }
+"#,
+    ),
+    (
+        58,
+        r#"
+The program called `__exit`.
+
+Example:
+
+    __print("done");
+    __exit(3);
+
+`__exit` stops the program with the given exit code. It isn't an error that can be caught with
+`try`/`catch`, it unwinds every call until it reaches `roan run`, which exits with the code, or
+the program embedding Roan, which decides what to do with it.
+"#,
+    ),
+];
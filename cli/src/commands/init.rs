@@ -4,12 +4,20 @@ use crate::{
 };
 use anyhow::{bail, Result};
 use clap::{ArgAction, ArgMatches, Command};
-use std::{fmt::Display, fs, process};
+use std::{
+    fmt::Display,
+    fs,
+    io::{self, BufRead},
+    process,
+};
 
 pub fn init_cmd() -> Command {
     Command::new("init")
         .about("Initialize a new project")
-        .arg(positional("name", "The name of the project"))
+        .arg(positional(
+            "name",
+            "The name of the project, the current directory is initialized if it's omitted",
+        ))
         .arg(
             opt("bin", "Create a binary project")
                 .short('b')
@@ -51,13 +59,15 @@ impl Display for ProjectType {
 }
 
 pub fn init_command(ctx: &mut GlobalContext, args: &ArgMatches) -> Result<()> {
-    let name = args.get_one::<String>("name");
-
-    if name.is_none() {
-        bail!("Project name is required");
-    }
-    let name = name.unwrap().clone();
+    init_project(ctx, args, &mut io::stdin().lock())
+}
 
+/// Initializes a project, reading the answers to the prompts from `input`.
+fn init_project<R: BufRead>(
+    ctx: &mut GlobalContext,
+    args: &ArgMatches,
+    input: &mut R,
+) -> Result<()> {
     let project_type = match (args.get_flag("bin"), args.get_flag("lib")) {
         (true, false) => ProjectType::Bin,
         (false, true) => ProjectType::Lib,
@@ -66,12 +76,55 @@ pub fn init_command(ctx: &mut GlobalContext, args: &ArgMatches) -> Result<()> {
     };
     let force = args.get_flag("force");
 
-    let project_dir = ctx.cwd.join(name.clone());
+    // Without a name, the current directory becomes the project
+    let (name, project_dir) = match args.get_one::<String>("name") {
+        Some(name) => (name.clone(), ctx.cwd.join(name)),
+        None => {
+            let default = ctx
+                .cwd
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let name = ctx.shell.prompt_from(input, "Project name", &default)?;
+
+            (name, ctx.cwd.clone())
+        }
+    };
+
+    if name.is_empty() {
+        bail!("Project name is required");
+    }
 
-    if project_dir.exists() {
+    let author = ctx
+        .shell
+        .prompt_from(input, "Author", &git_user_name().unwrap_or_default())?;
+    let license = ctx.shell.prompt_from(input, "License", "MIT")?;
+    let git = !args.get_flag("no-git")
+        && ctx
+            .shell
+            .confirm_from(input, "Initialize a git repository?", true)?;
+
+    if project_dir == ctx.cwd {
+        if project_dir.join("roan.toml").exists() {
+            if force {
+                ctx.shell.warn("Force flag is enabled")?;
+            } else {
+                bail!("{} is already a project", project_dir.display());
+            }
+        }
+    } else if project_dir.exists() {
         if force {
             ctx.shell.warn("Force flag is enabled")?;
-            fs::remove_dir_all(project_dir)?;
+            fs::remove_dir_all(&project_dir)?;
+        } else if ctx.shell.confirm_from(
+            input,
+            &format!(
+                "Directory {} already exists, do you want to replace it?",
+                project_dir.display()
+            ),
+            false,
+        )? {
+            fs::remove_dir_all(&project_dir)?;
         } else {
             bail!("Project directory already exists");
         }
@@ -90,21 +143,37 @@ pub fn init_command(ctx: &mut GlobalContext, args: &ArgMatches) -> Result<()> {
             .warn("Project name contains non-ascii characters")?;
     }
 
-    let project_dir = ctx.cwd.join(name.clone());
+    fs::create_dir_all(&project_dir)?;
 
-    std::fs::create_dir(&project_dir)?;
-
-    if !args.get_flag("no-git") {
+    if git {
         init_git(ctx, &project_dir)?;
         create_gitignore(ctx, &project_dir)?;
     }
 
-    create_roan_toml(ctx, &project_dir, &name, project_type.clone())?;
+    create_roan_toml(
+        ctx,
+        &project_dir,
+        &name,
+        project_type.clone(),
+        &author,
+        &license,
+    )?;
     create_source_files(ctx, &project_dir, project_type)?;
 
     Ok(())
 }
 
+/// Returns the `user.name` set in the git config, if any.
+fn git_user_name() -> Option<String> {
+    let output = process::Command::new("git")
+        .args(["config", "user.name"])
+        .output()
+        .ok()?;
+    let name = String::from_utf8(output.stdout).ok()?.trim().to_string();
+
+    (output.status.success() && !name.is_empty()).then_some(name)
+}
+
 const GITIGNORE: &str = r#"# Logs
 
 logs
@@ -168,7 +237,29 @@ fn create_roan_toml(
     project_dir: &std::path::Path,
     name: &str,
     project_type: ProjectType,
+    author: &str,
+    license: &str,
 ) -> Result<()> {
+    let mut toml = manifest(name, &project_type);
+    if !author.is_empty() {
+        toml["project"]["author"] = toml_edit::value(author);
+    }
+    if !license.is_empty() {
+        toml["project"]["license"] = toml_edit::value(license);
+    }
+    let toml = toml.to_string();
+
+    let roan_toml = project_dir.join("roan.toml");
+
+    ctx.shell.status("Creating", "roan.toml")?;
+
+    fs::write(&roan_toml, toml)?;
+
+    Ok(())
+}
+
+/// Returns the `roan.toml` of a new project, depending on the standard library.
+pub fn manifest(name: &str, project_type: &ProjectType) -> toml_edit::DocumentMut {
     let r#type = match project_type {
         ProjectType::Bin => "bin",
         ProjectType::Lib => "lib",
@@ -190,15 +281,7 @@ fn create_roan_toml(
     );
     file["dependencies"]["std"] = toml_edit::Item::Value(toml_edit::Value::InlineTable(std_dep));
 
-    let toml = file.to_string();
-
-    let roan_toml = project_dir.join("roan.toml");
-
-    ctx.shell.status("Creating", "roan.toml")?;
-
-    fs::write(&roan_toml, toml)?;
-
-    Ok(())
+    file
 }
 
 const BIN_CONTENT: &str = r#"
@@ -211,7 +294,7 @@ fn main() {
 main();
 "#;
 
-const LIB_CONTENT: &str = r#"
+pub const LIB_CONTENT: &str = r#"
 pub fn add(a: int, b: int) -> int {
     a + b
 }
@@ -224,13 +307,74 @@ fn create_source_files(
 ) -> Result<()> {
     let src_dir = project_dir.join("src");
     ctx.shell.status("Creating", "source files")?;
-    std::fs::create_dir(&src_dir)?;
+    std::fs::create_dir_all(&src_dir)?;
 
     let (content, file) = match project_type {
         ProjectType::Bin => (BIN_CONTENT, src_dir.join("main.roan")),
         ProjectType::Lib => (LIB_CONTENT, src_dir.join("lib.roan")),
     };
 
-    fs::write(&file, content)?;
+    // Source files of a directory initialized in place are kept
+    if !file.exists() {
+        fs::write(&file, content)?;
+    }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config_file::RoanConfig;
+    use anstream::ColorChoice;
+    use std::{io::Cursor, path::Path};
+
+    fn init(cwd: &Path, args: &[&str], answers: &str) -> Result<()> {
+        let mut ctx = GlobalContext::from_cwd(cwd.into(), ColorChoice::Never)?;
+        let matches = init_cmd().try_get_matches_from([&["init"], args].concat())?;
+
+        init_project(&mut ctx, &matches, &mut Cursor::new(answers))
+    }
+
+    fn read_config(dir: &Path) -> RoanConfig {
+        toml::from_str(&fs::read_to_string(dir.join("roan.toml")).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_init_with_answers() {
+        let dir = tempfile::tempdir().unwrap();
+        init(dir.path(), &[], "calc\nJane Doe\nApache-2.0\nn\n").unwrap();
+
+        let config = read_config(dir.path());
+        assert_eq!(config.project.name, "calc");
+        assert_eq!(config.project.author.as_deref(), Some("Jane Doe"));
+        assert_eq!(config.project.license.as_deref(), Some("Apache-2.0"));
+        assert!(dir.path().join("src").join("main.roan").exists());
+        assert!(!dir.path().join(".git").exists());
+        assert!(!dir.path().join(".gitignore").exists());
+
+        // The current directory is already a project
+        assert!(init(dir.path(), &[], "\n\n\nn\n").is_err());
+    }
+
+    #[test]
+    fn test_init_with_defaults() {
+        let dir = tempfile::tempdir().unwrap();
+        init(dir.path(), &["app", "--lib", "--no-git"], "\n\n").unwrap();
+
+        let project = dir.path().join("app");
+        let config = read_config(&project);
+        assert_eq!(config.project.name, "app");
+        assert_eq!(config.project.author, git_user_name());
+        assert_eq!(config.project.license.as_deref(), Some("MIT"));
+        assert!(project.join("src").join("lib.roan").exists());
+        assert!(!project.join(".gitignore").exists());
+
+        let dir = tempfile::tempdir().unwrap();
+        let cwd = dir.path().join("tool");
+        fs::create_dir(&cwd).unwrap();
+        init(&cwd, &[], "\n\n\ny\n").unwrap();
+
+        assert_eq!(read_config(&cwd).project.name, "tool");
+        assert!(cwd.join(".gitignore").exists());
+    }
+}
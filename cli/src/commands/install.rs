@@ -2,11 +2,13 @@ use crate::{
     cli::opt,
     context::GlobalContext,
     pm::{
+        lockfile::{LockFile, LOCKFILE_NAME},
+        manager::{is_up_to_date, PackageManager},
         packs::{parse_pack, PackVersion},
         source::PackageSource,
     },
 };
-use anyhow::Result;
+use anyhow::{Context, Result};
 use clap::{Arg, ArgMatches, Command};
 use itertools::Itertools;
 
@@ -46,7 +48,7 @@ pub fn install_cmd() -> Command {
         )
 }
 
-pub async fn install_command(_: &mut GlobalContext, matches: &ArgMatches) -> Result<()> {
+pub async fn install_command(ctx: &mut GlobalContext, matches: &ArgMatches) -> Result<()> {
     let packages = matches
         .get_many::<PackVersion>("packs")
         .unwrap_or_default()
@@ -56,7 +58,55 @@ pub async fn install_command(_: &mut GlobalContext, matches: &ArgMatches) -> Res
 
     let _source = PackageSource::from_arg_matches(matches)?;
 
-    println!("Installing packages: {:?}", packages);
+    if packages.is_empty() {
+        return install_project(ctx).await;
+    }
+
+    install_packages(ctx, &packages)
+}
+
+/// Installs the dependencies in `roan.toml` at the versions pinned in `roan.lock`. The lockfile
+/// is created first if there is none or if it's missing a dependency.
+async fn install_project(ctx: &mut GlobalContext) -> Result<()> {
+    let config = ctx.load_config()?;
+    let root = ctx.workspace_root().context("Failed to find roan.toml")?;
+    let manager = PackageManager::new(ctx.registry.clone(), root, ctx.deps_dir()?);
+
+    let path = manager.lockfile_path();
+    let existing = path.exists().then(|| LockFile::read(&path)).transpose()?;
+
+    let lock = match existing {
+        Some(lock) if is_up_to_date(&lock, &config)? => {
+            ctx.shell.status("Using", LOCKFILE_NAME)?;
+            lock
+        }
+        _ => {
+            ctx.shell.status("Locking", "dependencies")?;
+            manager.lock(&config).await?
+        }
+    };
+
+    ctx.shell
+        .status("Installing", format!("{} package(s)", lock.packages.len()))?;
+    manager.install_from_lockfile(&lock).await?;
+
+    ctx.shell
+        .success(format!("Installed {} package(s)", lock.packages.len()))
+}
+
+/// Installs `packages`, also used by `roan add` after adding a dependency.
+pub fn install_packages(ctx: &mut GlobalContext, packages: &[PackVersion]) -> Result<()> {
+    let progress = ctx.shell.progress(packages.len() as u64);
+    for (name, version) in packages.iter() {
+        match version {
+            Some(version) => ctx
+                .shell
+                .status("Installing", format!("{name}@{version}"))?,
+            None => ctx.shell.status("Installing", name)?,
+        }
+        progress.inc(1);
+    }
+    progress.finish();
 
     Ok(())
 }
@@ -1,3 +1,10 @@
+pub mod add;
+pub mod check;
+pub mod explain;
 pub mod init;
 pub mod install;
+pub mod new;
+pub mod remove;
 pub mod run;
+pub mod test;
+pub mod watch;
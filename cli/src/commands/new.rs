@@ -0,0 +1,157 @@
+use crate::{
+    cli::{opt, positional},
+    commands::init::{manifest, ProjectType, LIB_CONTENT},
+    context::GlobalContext,
+};
+use anyhow::{bail, Result};
+use clap::{ArgMatches, Command};
+use std::{fs, path::Path};
+
+pub fn new_cmd() -> Command {
+    Command::new("new")
+        .about("Create a new project in a new directory")
+        .arg(positional("name", "The name of the project").required(true))
+        .arg(
+            opt("template", "Template to create the project from")
+                .value_name("TEMPLATE")
+                .value_parser(["binary", "library"])
+                .default_value("binary"),
+        )
+}
+
+const MAIN_CONTENT: &str = r#"use { println } from "std::debug";
+
+fn main() {
+    println("Hello, world!");
+}
+
+main();
+"#;
+
+const GITIGNORE: &str = r#"# Build output and installed packages
+build/
+
+roan.lock
+"#;
+
+pub fn new_command(ctx: &mut GlobalContext, args: &ArgMatches) -> Result<()> {
+    let name = args.get_one::<String>("name").unwrap();
+    let project_type = match args.get_one::<String>("template").map(String::as_str) {
+        Some("library") => ProjectType::Lib,
+        _ => ProjectType::Bin,
+    };
+
+    create_project(ctx, name, project_type)
+}
+
+/// Creates the project `name` in a new directory of the same name, with a `roan.toml`, an entry
+/// file and a `.gitignore`.
+pub fn create_project(
+    ctx: &mut GlobalContext,
+    name: &str,
+    project_type: ProjectType,
+) -> Result<()> {
+    let project_dir = ctx.cwd.join(name);
+    if project_dir.exists() {
+        bail!("Destination {} already exists", project_dir.display());
+    }
+
+    ctx.shell
+        .status("Creating", format!("{} project `{}`", project_type, name))?;
+
+    let (entry, content) = match project_type {
+        ProjectType::Bin => ("src/main.roan", MAIN_CONTENT),
+        ProjectType::Lib => ("src/lib.roan", LIB_CONTENT.trim_start()),
+    };
+
+    let mut manifest = manifest(name, &project_type);
+    manifest["project"]["entry"] = toml_edit::value(entry);
+
+    create_file(ctx, &project_dir, "roan.toml", &manifest.to_string())?;
+    create_file(ctx, &project_dir, entry, content)?;
+    create_file(ctx, &project_dir, ".gitignore", GITIGNORE)?;
+
+    Ok(())
+}
+
+/// Writes `content` to `path` inside `project_dir`, creating the directories it's in.
+fn create_file(
+    ctx: &mut GlobalContext,
+    project_dir: &Path,
+    path: &str,
+    content: &str,
+) -> Result<()> {
+    let file = project_dir.join(path);
+    if let Some(parent) = file.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    fs::write(&file, content)?;
+    ctx.shell.status("Created", path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config_file::RoanConfig;
+    use anstream::ColorChoice;
+    use std::path::PathBuf;
+    use tempfile::TempDir;
+
+    fn new_project(parent: &TempDir, args: &[&str]) -> Result<()> {
+        let mut ctx = GlobalContext::from_cwd(parent.path().into(), ColorChoice::Never)?;
+        let matches = new_cmd().try_get_matches_from([&["new"], args].concat())?;
+
+        new_command(&mut ctx, &matches)
+    }
+
+    fn read_config(dir: &Path) -> RoanConfig {
+        toml::from_str(&fs::read_to_string(dir.join("roan.toml")).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_new_binary() {
+        let parent = tempfile::tempdir().unwrap();
+        new_project(&parent, &["app"]).unwrap();
+
+        let dir = parent.path().join("app");
+        let config = read_config(&dir);
+        assert_eq!(config.project.name, "app");
+        assert_eq!(config.project.r#type.as_deref(), Some("bin"));
+        assert_eq!(config.project.entry, Some(PathBuf::from("src/main.roan")));
+
+        let main = fs::read_to_string(dir.join("src").join("main.roan")).unwrap();
+        assert!(main.contains("fn main()"));
+        assert!(main.contains("println(\"Hello, world!\");"));
+
+        let gitignore = fs::read_to_string(dir.join(".gitignore")).unwrap();
+        assert!(gitignore.lines().any(|line| line == "build/"));
+        assert!(gitignore.lines().any(|line| line == "roan.lock"));
+    }
+
+    #[test]
+    fn test_new_library() {
+        let parent = tempfile::tempdir().unwrap();
+        new_project(&parent, &["json", "--template", "library"]).unwrap();
+
+        let dir = parent.path().join("json");
+        let config = read_config(&dir);
+        assert_eq!(config.project.r#type.as_deref(), Some("lib"));
+        assert_eq!(config.project.entry, Some(PathBuf::from("src/lib.roan")));
+
+        let lib = fs::read_to_string(dir.join("src").join("lib.roan")).unwrap();
+        assert!(lib.starts_with("pub fn add"));
+        assert!(!lib.contains("main"));
+        assert!(!dir.join("src").join("main.roan").exists());
+    }
+
+    #[test]
+    fn test_new_does_not_overwrite() {
+        let parent = tempfile::tempdir().unwrap();
+        fs::create_dir(parent.path().join("app")).unwrap();
+
+        assert!(new_project(&parent, &["app"]).is_err());
+        assert!(!parent.path().join("app").join("roan.toml").exists());
+        assert!(new_project(&parent, &["lib", "--template", "unknown"]).is_err());
+    }
+}
@@ -0,0 +1,98 @@
+use crate::{cli::positional, context::GlobalContext, pm::manifest::remove_dependency};
+use anyhow::{bail, Result};
+use clap::{ArgMatches, Command};
+use std::{
+    fs,
+    io::{self, BufRead},
+};
+
+pub fn remove_cmd() -> Command {
+    Command::new("remove")
+        .about("Remove a dependency from roan.toml")
+        .arg(positional("name", "Name of the dependency").required(true))
+}
+
+pub fn remove_command(ctx: &mut GlobalContext, matches: &ArgMatches) -> Result<()> {
+    let name = matches.get_one::<String>("name").unwrap();
+
+    remove_package(ctx, name, &mut io::stdin().lock())
+}
+
+/// Removes `name` from the dependencies in `roan.toml` and deletes its installed copy once the
+/// answer read from `input` confirms it. Without an interactive shell the copy is kept.
+pub fn remove_package<R: BufRead>(
+    ctx: &mut GlobalContext,
+    name: &str,
+    input: &mut R,
+) -> Result<()> {
+    if !remove_dependency(&ctx.config_path()?, name)? {
+        bail!("{} is not a dependency of the project", name);
+    }
+
+    let dir = ctx.deps_dir()?.join(name);
+    if dir.exists() {
+        if ctx
+            .shell
+            .confirm_from(input, &format!("Remove {}?", dir.display()), false)?
+        {
+            fs::remove_dir_all(&dir)?;
+            ctx.shell.status("Removed", dir.display())?;
+        } else {
+            ctx.shell.status("Kept", dir.display())?;
+        }
+    }
+
+    ctx.shell
+        .success(format!("Removed {} from dependencies", name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anstream::ColorChoice;
+    use std::{io::Cursor, path::PathBuf};
+
+    /// Creates a project depending on `json` and `xml`, with `json` installed.
+    fn project(dir: &tempfile::TempDir) -> (GlobalContext, PathBuf) {
+        fs::write(
+            dir.path().join("roan.toml"),
+            "[project]\nname = \"app\"\n\n[dependencies]\njson = \"1.4.0\"\nxml = \"0.2.1\"\n",
+        )
+        .unwrap();
+        let ctx = GlobalContext::from_cwd(dir.path().into(), ColorChoice::Never).unwrap();
+        let installed = ctx.deps_dir().unwrap().join("json");
+        fs::create_dir_all(&installed).unwrap();
+        fs::write(installed.join("lib.roan"), "").unwrap();
+
+        (ctx, installed)
+    }
+
+    #[tokio::test]
+    async fn test_remove_package() {
+        let dir = tempfile::tempdir().unwrap();
+        let (mut ctx, installed) = project(&dir);
+
+        remove_package(&mut ctx, "json", &mut Cursor::new("y\n")).unwrap();
+
+        assert!(!installed.exists());
+        assert_eq!(
+            fs::read_to_string(dir.path().join("roan.toml")).unwrap(),
+            "[project]\nname = \"app\"\n\n[dependencies]\nxml = \"0.2.1\"\n"
+        );
+        assert!(remove_package(&mut ctx, "json", &mut Cursor::new("y\n")).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_remove_package_declined() {
+        let dir = tempfile::tempdir().unwrap();
+        let (mut ctx, installed) = project(&dir);
+
+        remove_package(&mut ctx, "json", &mut Cursor::new("\n")).unwrap();
+        assert!(installed.exists());
+
+        let (mut ctx, installed) = project(&dir);
+        ctx.set_interactive(false);
+        remove_package(&mut ctx, "json", &mut Cursor::new("y\n")).unwrap();
+        assert!(installed.exists());
+    }
+}
@@ -1,81 +1,226 @@
-use crate::{cli::opt, context::GlobalContext, module_loader::RoanModuleLoader};
-use anyhow::Result;
+use crate::{
+    cli::{opt, positional, skipped_passes},
+    context::GlobalContext,
+    diagnostics::{print_diagnostics, print_error},
+    module_loader::RoanModuleLoader,
+};
+use anyhow::{Context as _, Result};
 use clap::{ArgAction, ArgMatches, Command};
 use colored::Colorize;
-use roan_engine::{context::Context, module::Module, print_diagnostic, source::Source, vm::VM};
+use roan_engine::{
+    context::{Capabilities, Context, Timings},
+    error::RoanError,
+    module::Module,
+    path::normalize_path,
+    source::Source,
+    vm::VM,
+};
+use serde::Serialize;
 use std::{
     cell::RefCell,
-    fs::{create_dir, read_to_string},
-    process::exit,
+    collections::HashMap,
+    fs::create_dir,
+    io::{self, Write},
+    path::PathBuf,
+    process::{self, exit},
     rc::Rc,
+    time::Duration,
 };
 use tracing::debug;
 
+/// Name shown in place of a file path for code passed with `--eval`.
+const EVAL_FILE_NAME: &str = "<eval>";
+
 pub fn run_cmd() -> Command {
-    Command::new("run").about("Run a project").arg(
-        opt("time", "Prints the time taken to run the project")
-            .short('t')
+    Command::new("run")
+        .about("Run a project")
+        .arg(positional(
+            "target",
+            "Task from roan.toml or file to run, defaults to the entry of the project",
+        ))
+        .arg(
+            opt("time", "Prints the time taken to run the project")
+                .short('t')
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            opt(
+                "profile",
+                "Prints how long the calls of each function took, slowest first",
+            )
+            .action(ArgAction::SetTrue),
+        )
+        .arg(
+            opt(
+                "sandbox",
+                "Denies the program access to the filesystem, the process, the environment and the network",
+            )
             .action(ArgAction::SetTrue),
-    )
+        )
+        .arg(
+            opt("max-depth", "Maximum depth of the call stack, 1000 by default")
+                .value_name("N")
+                .value_parser(clap::value_parser!(usize)),
+        )
+        .arg(
+            opt("timeout", "Stops the program once it ran for the given time")
+                .value_name("SECONDS")
+                .value_parser(clap::value_parser!(f64)),
+        )
+        .arg(
+            opt("eval", "Run the given code instead of the project")
+                .short('e')
+                .value_name("CODE")
+                .conflicts_with("target"),
+        )
+        .arg(
+            opt(
+                "output-format",
+                "Format of the output of the program, `json` captures it into a report",
+            )
+            .value_name("FORMAT")
+            .value_parser(["text", "json"])
+            .default_value("text"),
+        )
+        .arg(
+            opt(
+                "capture-output",
+                "Captures the output of the program into a JSON report, same as `--output-format json`",
+            )
+            .action(ArgAction::SetTrue),
+        )
 }
 
-pub fn run_command(global: &mut GlobalContext, matches: &ArgMatches) -> Result<()> {
-    global.load_config()?;
-    let path = global.get_main_file()?;
+/// Result of a program run with `--capture-output`.
+#[derive(Debug, Serialize, PartialEq)]
+pub struct RunReport {
+    /// Lines written with `__print`.
+    pub stdout: Vec<String>,
+    /// Lines written with `__eprint`.
+    pub stderr: Vec<String>,
+    pub exit_code: i32,
+}
 
-    global
-        .shell
-        .status("Running", &path.display().to_string())?;
+/// In-memory writer shared with the context the program runs in.
+#[derive(Clone, Default)]
+struct CapturedOutput(Rc<RefCell<Vec<u8>>>);
 
-    global.assert_type("bin")?;
+impl CapturedOutput {
+    fn lines(&self) -> Vec<String> {
+        String::from_utf8_lossy(&self.0.borrow())
+            .lines()
+            .map(String::from)
+            .collect()
+    }
+}
 
-    let build_dir = global.build_dir()?;
+impl Write for CapturedOutput {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.borrow_mut().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
 
-    if !build_dir.exists() {
-        create_dir(&build_dir)?;
-        debug!("Created build directory at {:?}", build_dir);
+pub fn run_command(global: &mut GlobalContext, matches: &ArgMatches) -> Result<()> {
+    let target = matches.get_one::<String>("target");
+    let task = target.and_then(|name| {
+        global
+            .config
+            .as_ref()
+            .and_then(|config| config.tasks.as_ref()?.get(name))
+            .cloned()
+    });
+    if let Some(task) = task {
+        return run_task(global, target.unwrap(), &task);
     }
 
-    let content = read_to_string(&path)?;
+    let source = match (matches.get_one::<String>("eval"), target) {
+        (Some(code), _) => {
+            global.shell.status("Running", EVAL_FILE_NAME)?;
 
-    let ctx = &mut Context::builder()
-        .cwd(global.cwd.clone())
-        .module_loader(Rc::new(RefCell::new(RoanModuleLoader::new())))
-        .build();
-    let source = Source::from_string(content.clone()).with_path(path);
-    let vm = &mut VM::new();
-    let mut module = Module::new(source);
+            // Imports are resolved relative to the directory of the module
+            Source::from_string(code.clone()).with_path(global.cwd.join(EVAL_FILE_NAME))
+        }
+        (None, Some(file)) => {
+            let path = normalize_path(PathBuf::from(file), global.cwd.clone())?;
 
-    let result: Result<(), anyhow::Error> = {
-        let parse_start = std::time::Instant::now();
+            global
+                .shell
+                .status("Running", &path.display().to_string())?;
 
-        match module.parse(ctx, vm) {
-            Ok(..) => {}
-            Err(err) => {
-                print_diagnostic(&err, Some(content), module.path());
-                exit(1);
-            }
+            Source::from_file(path)?
         }
+        (None, None) => {
+            global.load_config()?;
+            let path = global.get_main_file()?;
 
-        global.shell.status(
-            "Finished",
-            format!("parsing in {:?}", parse_start.elapsed()),
-        )?;
+            global
+                .shell
+                .status("Running", &path.display().to_string())?;
 
-        module.interpret(ctx, vm)?;
+            global.assert_type("bin")?;
 
-        Ok(())
+            let build_dir = global.build_dir()?;
+
+            if !build_dir.exists() {
+                create_dir(&build_dir)?;
+                debug!("Created build directory at {:?}", build_dir);
+            }
+
+            Source::from_file(path)?
+        }
     };
 
-    match result {
-        Ok(_) => {}
-        Err(e) => {
-            print_diagnostic(&e, Some(content), module.path());
-            exit(1);
+    global.set_run_limits(
+        matches.get_one::<usize>("max-depth").copied(),
+        matches.get_one::<f64>("timeout").copied(),
+    )?;
+
+    let mut ctx = Context::builder()
+        .cwd(global.cwd.clone())
+        .module_loader(Rc::new(RefCell::new(RoanModuleLoader::new())))
+        .disabled_passes(skipped_passes(matches))
+        .capabilities(if matches.get_flag("sandbox") {
+            Capabilities::none()
+        } else {
+            Capabilities::all()
+        })
+        .build();
+
+    if matches.get_flag("capture-output")
+        || matches
+            .get_one::<String>("output-format")
+            .map(String::as_str)
+            == Some("json")
+    {
+        let report = run_captured(global, &mut ctx, source)?;
+        println!("{}", serde_json::to_string(&report)?);
+
+        if report.exit_code != 0 {
+            exit(report.exit_code);
         }
+        return Ok(());
+    }
+
+    let mut vm = VM::with_max_depth(global.max_depth);
+    if matches.get_flag("profile") {
+        vm.enable_profiling();
+    }
+
+    let exit_code = execute(global, &mut ctx, &mut vm, source)?;
+    if matches.get_flag("profile") {
+        print_profile(global, vm.frame_timings())?;
+    }
+
+    if exit_code != 0 {
+        exit(exit_code);
     }
 
     if matches.get_flag("time") {
+        print_timings(global, &ctx.timings)?;
         println!(
             "Finished program in: {}",
             format!("{:?}", global.start.elapsed()).cyan()
@@ -84,3 +229,244 @@ pub fn run_command(global: &mut GlobalContext, matches: &ArgMatches) -> Result<(
 
     Ok(())
 }
+
+/// Runs `source` with the output of `__print` and `__eprint` captured instead of written to
+/// stdout and stderr. Diagnostics are still written to stderr.
+pub fn run_captured(
+    global: &mut GlobalContext,
+    ctx: &mut Context,
+    source: Source,
+) -> Result<RunReport> {
+    let (stdout, stderr) = (CapturedOutput::default(), CapturedOutput::default());
+    ctx.set_output(Box::new(stdout.clone()));
+    ctx.set_error_output(Box::new(stderr.clone()));
+
+    let exit_code = execute(
+        global,
+        ctx,
+        &mut VM::with_max_depth(global.max_depth),
+        source,
+    )?;
+
+    Ok(RunReport {
+        stdout: stdout.lines(),
+        stderr: stderr.lines(),
+        exit_code,
+    })
+}
+
+/// Parses and interprets `source`, returning the code the program exits with: the one passed to
+/// `__exit`, or 1 if it failed. Its errors are reported to stderr.
+///
+/// The timeout of `global` only starts once `source` is parsed.
+fn execute(
+    global: &mut GlobalContext,
+    ctx: &mut Context,
+    vm: &mut VM,
+    source: Source,
+) -> Result<i32> {
+    let mut module = Module::new(source);
+
+    let parse_start = std::time::Instant::now();
+    match module.parse(ctx, vm) {
+        Ok(warnings) => print_diagnostics(&mut global.shell, &warnings)?,
+        Err(err) => {
+            print_error(&mut global.shell, &err, &module)?;
+            return Ok(1);
+        }
+    }
+
+    global.shell.status(
+        "Finished",
+        format!("parsing in {:?}", parse_start.elapsed()),
+    )?;
+
+    if let Some(timeout) = global.timeout {
+        vm.set_timeout(timeout);
+    }
+    if let Err(err) = module.interpret(ctx, vm) {
+        if let Some(RoanError::Exit(code)) = err.downcast_ref::<RoanError>() {
+            return Ok(*code);
+        }
+
+        print_error(&mut global.shell, &err, &module)?;
+        return Ok(1);
+    }
+
+    Ok(0)
+}
+
+/// Prints the time spent in every phase of running the program, summed over the modules it
+/// imports.
+pub fn print_timings(global: &mut GlobalContext, timings: &Timings) -> Result<()> {
+    for (phase, total, runs) in timings.phases() {
+        let runs = match runs {
+            1 => String::new(),
+            runs => format!(" over {} runs", runs),
+        };
+        global
+            .shell
+            .status("Timing", format!("{} took {:?}{}", phase, total, runs))?;
+    }
+
+    Ok(())
+}
+
+/// Prints the number of calls and the total time of every function in `timings`, slowest first.
+///
+/// The time of a function includes the calls it made, and the time of a recursive call is
+/// counted again for each of its callers.
+fn print_profile(global: &mut GlobalContext, timings: &[(String, Duration)]) -> Result<()> {
+    let mut functions: HashMap<&str, (usize, Duration)> = HashMap::new();
+    for (name, elapsed) in timings {
+        let (calls, total) = functions.entry(name).or_default();
+        *calls += 1;
+        *total += *elapsed;
+    }
+
+    let mut functions: Vec<_> = functions.into_iter().collect();
+    functions.sort_by(|(a_name, (_, a)), (b_name, (_, b))| b.cmp(a).then(a_name.cmp(b_name)));
+
+    let rows: Vec<Vec<String>> = functions
+        .into_iter()
+        .map(|(name, (calls, total))| {
+            vec![name.to_string(), calls.to_string(), format!("{:?}", total)]
+        })
+        .collect();
+
+    global.shell.table(&["Function", "Calls", "Time"], &rows)
+}
+
+/// Runs `task` with the shell of the platform in the workspace root, exiting with its code if
+/// it fails.
+fn run_task(global: &mut GlobalContext, name: &str, task: &str) -> Result<()> {
+    global
+        .shell
+        .status("Running", format!("`{}`: {}", name, task))?;
+
+    let mut command = if cfg!(windows) {
+        let mut command = process::Command::new("cmd");
+        command.arg("/C");
+        command
+    } else {
+        let mut command = process::Command::new("sh");
+        command.arg("-c");
+        command
+    };
+
+    let status = command
+        .arg(task)
+        .current_dir(
+            global
+                .workspace_root()
+                .unwrap_or_else(|| global.cwd.clone()),
+        )
+        .status()
+        .with_context(|| format!("Failed to run task `{}`", name))?;
+
+    if !status.success() {
+        exit(status.code().unwrap_or(1));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anstream::ColorChoice;
+
+    fn run(code: &str) -> RunReport {
+        run_with(code, |_| {})
+    }
+
+    /// Runs `code` with the global context changed by `configure`.
+    fn run_with(code: &str, configure: impl FnOnce(&mut GlobalContext)) -> RunReport {
+        let dir = tempfile::tempdir().unwrap();
+        let mut global = GlobalContext::from_cwd(dir.path().into(), ColorChoice::Never).unwrap();
+        configure(&mut global);
+        let mut ctx = Context::builder()
+            .cwd(global.cwd.clone())
+            .module_loader(Rc::new(RefCell::new(RoanModuleLoader::new())))
+            .build();
+
+        run_captured(&mut global, &mut ctx, Source::from_string(code.to_string())).unwrap()
+    }
+
+    #[test]
+    fn test_capture_output() {
+        let report = run("__print(\"hi\\n\");\n__eprint(\"oops\");\n__print(\"a\\nb\");\n");
+
+        assert_eq!(
+            report,
+            RunReport {
+                stdout: vec!["hi".to_string(), "a".to_string(), "b".to_string()],
+                stderr: vec!["oops".to_string()],
+                exit_code: 0,
+            }
+        );
+        assert_eq!(
+            serde_json::to_string(&report).unwrap(),
+            r#"{"stdout":["hi","a","b"],"stderr":["oops"],"exit_code":0}"#
+        );
+    }
+
+    #[test]
+    fn test_capture_output_of_failed_program() {
+        let report = run("__print(\"before\");\nthrow \"error\";\n__print(\"after\");\n");
+
+        assert_eq!(report.stdout, ["before"]);
+        assert_eq!(report.exit_code, 1);
+    }
+
+    #[test]
+    fn test_capture_output_of_exit() {
+        let report =
+            run("fn stop() { __exit(3); }\n__print(\"hi\");\nstop();\n__print(\"after\");\n");
+
+        assert_eq!(report.stdout, ["hi"]);
+        assert_eq!(report.stderr, Vec::<String>::new());
+        assert_eq!(report.exit_code, 3);
+
+        let report = run("__print(\"hi\");\n__exit(3);\n");
+        assert_eq!(report.stdout, ["hi"]);
+        assert_eq!(report.exit_code, 3);
+    }
+
+    #[test]
+    fn test_max_depth() {
+        let code = "fn f(n: int) -> int { return f(n + 1); }\n__print(\"start\");\nf(0);\n";
+        let report = run_with(code, |global| global.max_depth = 100);
+
+        assert_eq!(report.stdout, ["start"]);
+        assert_eq!(report.exit_code, 1);
+    }
+
+    #[test]
+    fn test_max_depth_with_caught_errors() {
+        let code = "fn fail() { throw \"x\"; }\n\
+                    let caught = 0;\n\
+                    let i = 0;\n\
+                    while i < 150 { try { fail(); } catch e { caught += 1; } i += 1; }\n\
+                    __print(__format(caught));\n";
+        let report = run_with(code, |global| global.max_depth = 100);
+
+        assert_eq!(report.stdout, ["150"]);
+        assert_eq!(report.exit_code, 0);
+    }
+
+    #[test]
+    fn test_timeout() {
+        let limit = |global: &mut GlobalContext| global.timeout = Some(Duration::from_millis(100));
+
+        let report = run_with("__print(\"start\");\nwhile true {}\n", limit);
+        assert_eq!(report.stdout, ["start"]);
+        assert_eq!(report.exit_code, 1);
+
+        let report = run_with("fn spin() { loop {} }\nspin();\n", limit);
+        assert_eq!(report.exit_code, 1);
+
+        let report = run_with("let i = 0;\nwhile i < 10 { i += 1; }\n", limit);
+        assert_eq!(report.exit_code, 0);
+    }
+}
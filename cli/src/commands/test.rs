@@ -0,0 +1,264 @@
+use crate::{
+    cli::skipped_passes,
+    context::GlobalContext,
+    diagnostics::{print_diagnostics, print_error},
+    module_loader::RoanModuleLoader,
+};
+use anyhow::{bail, Result};
+use clap::{Arg, ArgMatches, Command};
+use roan_engine::{
+    context::Context, error::RoanError, module::Module, path::normalize_path, source::Source,
+    vm::VM, CallExpr, Fn, Stmt,
+};
+use roan_shell::styles::{ERROR, GOOD};
+use std::{
+    any::Any,
+    cell::RefCell,
+    panic::{self, AssertUnwindSafe},
+    path::PathBuf,
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+pub fn test_cmd() -> Command {
+    Command::new("test")
+        .about("Run the tests of a project")
+        .arg(
+            Arg::new("files")
+                .value_name("FILE")
+                .help("Files to run the tests of, defaults to the main file of the project")
+                .num_args(0..),
+        )
+}
+
+/// Outcome of a single test function.
+#[derive(Debug)]
+pub struct TestResult {
+    pub name: String,
+    pub duration: Duration,
+    /// Why the test failed, `None` if it passed.
+    pub failure: Option<String>,
+}
+
+pub fn test_command(global: &mut GlobalContext, matches: &ArgMatches) -> Result<()> {
+    let files = match matches.get_many::<String>("files") {
+        Some(files) => files
+            .map(|file| normalize_path(PathBuf::from(file), global.cwd.clone()))
+            .collect::<Result<Vec<_>>>()?,
+        None => {
+            global.load_config()?;
+            vec![global.get_main_file()?]
+        }
+    };
+
+    let mut results = vec![];
+    for path in files {
+        global
+            .shell
+            .status("Testing", &path.display().to_string())?;
+
+        let ctx = &mut Context::builder()
+            .cwd(global.cwd.clone())
+            .module_loader(Rc::new(RefCell::new(RoanModuleLoader::new())))
+            .disabled_passes(skipped_passes(matches))
+            .build();
+        let vm = &mut VM::new();
+        let source = Source::from_file(path.clone())?;
+        let mut module = Module::new(source);
+
+        match module.parse(ctx, vm) {
+            Ok(warnings) => print_diagnostics(&mut global.shell, &warnings)?,
+            Err(err) => {
+                print_error(&mut global.shell, &err, &module)?;
+                bail!("Could not compile {}", path.display());
+            }
+        }
+
+        let tests = find_tests(&module);
+        if tests.is_empty() {
+            global.shell.note("no tests found")?;
+            continue;
+        }
+
+        for func in tests {
+            let result = run_test(&module, func, ctx);
+
+            let (status, style) = match result.failure {
+                None => ("ok", &GOOD),
+                Some(_) => ("FAILED", &ERROR),
+            };
+            global.shell.print(
+                &status,
+                Some(&format!("{} ({:.2?})", result.name, result.duration)),
+                style,
+                true,
+            )?;
+
+            results.push(result);
+        }
+    }
+
+    let failed: Vec<&TestResult> = results.iter().filter(|r| r.failure.is_some()).collect();
+    for result in failed.iter() {
+        global.shell.error(format!(
+            "test {} failed: {}",
+            result.name,
+            result.failure.as_ref().unwrap()
+        ))?;
+    }
+
+    let total: Duration = results.iter().map(|r| r.duration).sum();
+    global.shell.status(
+        "Finished",
+        format!(
+            "{} passed, {} failed in {:.2?}",
+            results.len() - failed.len(),
+            failed.len(),
+            total
+        ),
+    )?;
+
+    if !failed.is_empty() {
+        bail!("{} test(s) failed", failed.len());
+    }
+
+    Ok(())
+}
+
+/// Returns the top-level functions without parameters that are marked with `@test` or whose name
+/// starts with `test_`.
+pub fn find_tests(module: &Module) -> Vec<&Fn> {
+    module
+        .ast
+        .stmts
+        .iter()
+        .filter_map(|stmt| match stmt {
+            Stmt::Fn(func)
+                if func.params.is_empty()
+                    && (func.has_attribute("test") || func.name.starts_with("test_")) =>
+            {
+                Some(func)
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// Calls `func` on a copy of the parsed `module`, so state left behind by one test can't leak
+/// into the next one.
+///
+/// Thrown values, runtime errors and panics are all turned into a failure. Runtime errors are
+/// reported by the interpreter before they get here.
+pub fn run_test(module: &Module, func: &Fn, ctx: &mut Context) -> TestResult {
+    let mut module = module.clone();
+    let mut vm = VM::new();
+    let call = CallExpr {
+        callee: func.name.clone(),
+        args: vec![],
+        token: func.fn_token.clone(),
+    };
+
+    // Panics are reported as failures, the default hook would print them as a crash
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(|_| {}));
+
+    let start = Instant::now();
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        module.interpret_call(&call, ctx, &mut vm)
+    }));
+    let duration = start.elapsed();
+
+    panic::set_hook(hook);
+
+    let failure = match result {
+        Ok(Ok(_)) => None,
+        Ok(Err(err)) => Some(match err.downcast_ref::<RoanError>() {
+            Some(RoanError::Throw(value, _)) => format!("uncaught error: {}", value),
+            Some(RoanError::AbortedDueToPreviousErrors(diagnostics)) => diagnostics
+                .iter()
+                .map(|diagnostic| diagnostic.title.clone())
+                .collect::<Vec<_>>()
+                .join(", "),
+            _ => err.to_string(),
+        }),
+        Err(payload) => Some(format!("panicked: {}", panic_message(payload.as_ref()))),
+    };
+
+    TestResult {
+        name: func.name.clone(),
+        duration,
+        failure,
+    }
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    match (
+        payload.downcast_ref::<&str>(),
+        payload.downcast_ref::<String>(),
+    ) {
+        (Some(s), _) => (*s).to_string(),
+        (_, Some(s)) => s.clone(),
+        (None, None) => "unknown error".into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parsed(src: &str) -> (Module, Context) {
+        let mut ctx = Context::builder()
+            .module_loader(Rc::new(RefCell::new(RoanModuleLoader::new())))
+            .build();
+        let mut module = Module::new(Source::from_string(src.to_string()));
+        module.parse(&mut ctx, &mut VM::new()).unwrap();
+
+        (module, ctx)
+    }
+
+    #[test]
+    fn test_find_tests() {
+        let (module, _) = parsed(
+            "@test\n\
+             fn adds() {}\n\
+             fn test_prefixed() {}\n\
+             fn test_with_param(a: int) {}\n\
+             fn helper() {}\n",
+        );
+
+        let names: Vec<&str> = find_tests(&module)
+            .iter()
+            .map(|f| f.name.as_str())
+            .collect();
+        assert_eq!(names, ["adds", "test_prefixed"]);
+    }
+
+    #[test]
+    fn test_failures_do_not_stop_the_run() {
+        let (module, mut ctx) = parsed(
+            "fn helper() { throw \"nested\"; }\n\
+             fn test_passes() { let x = 1 + 1; }\n\
+             fn test_throws() { helper(); }\n\
+             fn test_panics() { __panic(\"boom\"); }\n\
+             fn test_errors() { let x = type_of(1); x.nope(); }\n\
+             fn test_after() {}\n",
+        );
+
+        let results: Vec<TestResult> = find_tests(&module)
+            .into_iter()
+            .map(|func| run_test(&module, func, &mut ctx))
+            .collect();
+
+        let failures: Vec<Option<&str>> = results.iter().map(|r| r.failure.as_deref()).collect();
+        assert_eq!(
+            failures,
+            [
+                None,
+                Some("uncaught error: nested"),
+                Some("uncaught error: boom"),
+                Some("Attempted to access non-existent property: nope"),
+                None
+            ]
+        );
+    }
+}
@@ -0,0 +1,310 @@
+use crate::{
+    cli::{opt, positional, skipped_passes},
+    commands::run::print_timings,
+    context::GlobalContext,
+    diagnostics::{print_diagnostics, print_error},
+    module_loader::RoanModuleLoader,
+};
+use anyhow::Result;
+use chrono::Local;
+use clap::{ArgAction, ArgMatches, Command};
+use roan_engine::{
+    context::Context,
+    module::{cache::ModuleCache, Module},
+    path::normalize_path,
+    source::Source,
+    vm::VM,
+};
+use roan_shell::Shell;
+use std::{
+    cell::RefCell,
+    collections::HashSet,
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process,
+    rc::Rc,
+    thread,
+    time::{Duration, Instant, SystemTime},
+};
+
+/// How often the watched files are checked for changes.
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// How long the watched files have to stay unchanged after a change before the program runs
+/// again, so saving several files at once only runs it once.
+const DEBOUNCE: Duration = Duration::from_millis(100);
+
+pub fn watch_cmd() -> Command {
+    Command::new("watch")
+        .about("Run a file again whenever it or a module it imports changes")
+        .arg(positional(
+            "file",
+            "File to watch, defaults to the main file of the project",
+        ))
+        .arg(
+            opt("check", "Check the file for errors instead of running it")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(opt("clear", "Clear the screen before every run").action(ArgAction::SetTrue))
+        .arg(
+            opt("time", "Prints the time spent in every phase of each run")
+                .short('t')
+                .action(ArgAction::SetTrue),
+        )
+}
+
+pub fn watch_command(global: &mut GlobalContext, matches: &ArgMatches) -> Result<()> {
+    let path = match matches.get_one::<String>("file") {
+        Some(file) => normalize_path(PathBuf::from(file), global.cwd.clone())?,
+        None => {
+            global.load_config()?;
+            global.get_main_file()?
+        }
+    };
+
+    stop_on_ctrl_c(global);
+
+    // Shared by the contexts of every run, so only the files that changed are parsed again
+    let cache = Rc::new(RefCell::new(ModuleCache::new()));
+    let mut watched = vec![path.clone()];
+
+    loop {
+        if matches.get_flag("clear") {
+            clear_screen()?;
+        }
+
+        let verb = if matches.get_flag("check") {
+            "Checking"
+        } else {
+            "Running"
+        };
+        global.shell.status(
+            verb,
+            format!("{} at {}", path.display(), Local::now().format("%H:%M:%S")),
+        )?;
+
+        let (success, files) = run_once(global, &path, matches, &cache)?;
+        if success {
+            watched = files;
+        } else {
+            // Modules that failed to parse may not have imported everything yet
+            for file in files {
+                if !watched.contains(&file) {
+                    watched.push(file);
+                }
+            }
+        }
+
+        global.shell.status(
+            "Watching",
+            format!("{} files for changes, press Ctrl-C to stop", watched.len()),
+        )?;
+
+        wait_for_change(&watched);
+    }
+}
+
+/// Checks or runs the file at `path` once, reporting its errors to stderr.
+///
+/// Returns whether it succeeded and the files the program is made of, the file itself and every
+/// module it imports, directly or not.
+fn run_once(
+    global: &mut GlobalContext,
+    path: &Path,
+    matches: &ArgMatches,
+    cache: &Rc<RefCell<ModuleCache>>,
+) -> Result<(bool, Vec<PathBuf>)> {
+    let source = match Source::from_file(path.to_path_buf()) {
+        Ok(source) => source,
+        // Editors may remove the file for a moment when saving it
+        Err(err) => {
+            global.shell.error(err)?;
+            return Ok((false, vec![path.to_path_buf()]));
+        }
+    };
+
+    let mut ctx = Context::builder()
+        .cwd(global.cwd.clone())
+        .module_loader(Rc::new(RefCell::new(RoanModuleLoader::new())))
+        .disabled_passes(skipped_passes(matches))
+        .module_cache(cache.clone())
+        .build();
+    let vm = &mut VM::new();
+    let mut module = Module::new(source);
+
+    let start = Instant::now();
+    let result = module.parse(&mut ctx, vm).and_then(|warnings| {
+        print_diagnostics(&mut global.shell, &warnings)?;
+
+        if matches.get_flag("check") {
+            Ok(())
+        } else {
+            module.interpret(&mut ctx, vm)
+        }
+    });
+
+    let success = match result {
+        Ok(_) => {
+            global
+                .shell
+                .status("Finished", format!("in {:?}", start.elapsed()))?;
+            true
+        }
+        Err(err) => {
+            print_error(&mut global.shell, &err, &module)?;
+            false
+        }
+    };
+
+    if matches.get_flag("time") {
+        print_timings(global, &ctx.timings)?;
+    }
+
+    Ok((success, watched_files(path, &module, &cache.borrow())))
+}
+
+/// Returns the file of `module` and the files of the modules it imports, directly or not.
+fn watched_files(path: &Path, module: &Module, cache: &ModuleCache) -> Vec<PathBuf> {
+    let mut files = vec![path.to_path_buf()];
+    for import in &module.imports {
+        files.push(import.clone());
+        files.extend(
+            cache
+                .imported_modules(import)
+                .iter()
+                .filter_map(Module::path),
+        );
+    }
+
+    let mut seen = HashSet::new();
+    files.retain(|file| seen.insert(file.clone()));
+    files
+}
+
+/// Returns when one of `files` changed, once none of them changed for [DEBOUNCE].
+fn wait_for_change(files: &[PathBuf]) {
+    let before = modified_times(files);
+    while modified_times(files) == before {
+        thread::sleep(POLL_INTERVAL);
+    }
+
+    let mut last = modified_times(files);
+    loop {
+        thread::sleep(DEBOUNCE);
+
+        let current = modified_times(files);
+        if current == last {
+            return;
+        }
+        last = current;
+    }
+}
+
+/// Returns when each of `files` was last modified, `None` for files that can't be read.
+fn modified_times(files: &[PathBuf]) -> Vec<Option<SystemTime>> {
+    files
+        .iter()
+        .map(|file| fs::metadata(file).and_then(|meta| meta.modified()).ok())
+        .collect()
+}
+
+/// Exits once Ctrl-C is pressed, even in the middle of a run.
+///
+/// Runs block the thread watching the files, so the signal is waited for on a thread of the
+/// runtime instead.
+fn stop_on_ctrl_c(global: &GlobalContext) {
+    let color = global.shell.output.color;
+    let verbosity = global.shell.verbosity();
+
+    tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_ok() {
+            let mut shell = Shell::new(color);
+            shell.set_verbosity(verbosity);
+
+            let _ = io::stdout().flush();
+            let _ = shell.status("Stopped", "watching");
+            process::exit(0);
+        }
+    });
+}
+
+/// Clears the terminal and moves the cursor to its top left corner.
+fn clear_screen() -> Result<()> {
+    let mut stdout = io::stdout();
+    write!(stdout, "\x1b[2J\x1b[1;1H")?;
+    stdout.flush()?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::cli;
+    use anstream::ColorChoice;
+    use roan_engine::path::canonicalize_path;
+
+    #[test]
+    fn test_watches_imported_modules() {
+        let dir = tempfile::tempdir().unwrap();
+        let dir = canonicalize_path(dir.path().to_path_buf()).unwrap();
+        fs::write(
+            dir.join("main.roan"),
+            "use { half } from \"./math.roan\";\n__print(__format(half(4)));\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.join("math.roan"),
+            "use { one } from \"./one.roan\";\npub fn half(x: int) -> int { return x / 2; }\n",
+        )
+        .unwrap();
+        fs::write(dir.join("one.roan"), "pub fn one() -> int { return 1; }\n").unwrap();
+        fs::write(
+            dir.join("unused.roan"),
+            "pub fn two() -> int { return 2; }\n",
+        )
+        .unwrap();
+
+        let mut global = GlobalContext::from_cwd(dir.clone(), ColorChoice::Never).unwrap();
+        let matches = cli()
+            .try_get_matches_from(["roan", "watch", "main.roan", "--check"])
+            .unwrap();
+        let matches = matches.subcommand_matches("watch").unwrap();
+        let cache = Rc::new(RefCell::new(ModuleCache::new()));
+
+        let main = dir.join("main.roan");
+        let (success, mut watched) = run_once(&mut global, &main, matches, &cache).unwrap();
+        assert!(success);
+        watched.sort();
+        assert_eq!(
+            watched,
+            [main.clone(), dir.join("math.roan"), dir.join("one.roan")]
+        );
+
+        // The module failing to parse is still watched
+        fs::write(
+            dir.join("math.roan"),
+            "use { one } from \"./one.roan\";\npub fn half(x: int) -> int {",
+        )
+        .unwrap();
+        let (success, watched) = run_once(&mut global, &main, matches, &cache).unwrap();
+        assert!(!success);
+        assert_eq!(watched, [main.clone(), dir.join("math.roan")]);
+    }
+
+    #[test]
+    fn test_modified_times() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("main.roan");
+        let missing = dir.path().join("missing.roan");
+        fs::write(&file, "").unwrap();
+
+        let times = modified_times(&[file.clone(), missing.clone()]);
+        assert!(times[0].is_some());
+        assert!(times[1].is_none());
+
+        fs::write(&missing, "").unwrap();
+        assert_ne!(modified_times(&[file, missing]), times);
+    }
+}
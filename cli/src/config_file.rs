@@ -4,8 +4,21 @@ use std::{collections::HashMap, path::PathBuf};
 #[derive(Deserialize, Debug, Clone, Serialize)]
 pub struct RoanConfig {
     pub project: ProjectConfig,
+    /// Shell commands run by `roan run <name>`, keyed by name.
     pub tasks: Option<HashMap<String, String>>,
     pub dependencies: Option<HashMap<String, Dependency>>,
+    /// Limits of the programs run by `roan run`, overridden by its flags.
+    #[serde(default)]
+    pub run: RunConfig,
+}
+
+/// The `[run]` table of roan.toml.
+#[derive(Deserialize, Debug, Clone, Default, Serialize)]
+pub struct RunConfig {
+    /// Maximum depth of the call stack.
+    pub max_depth: Option<usize>,
+    /// Seconds after which the program is stopped.
+    pub timeout: Option<f64>,
 }
 
 #[derive(Deserialize, Debug, Clone, Serialize)]
@@ -14,19 +27,115 @@ pub struct ProjectConfig {
     pub version: String,
     pub description: Option<String>,
     pub author: Option<String>,
+    #[serde(default)]
+    pub authors: Vec<String>,
     pub license: Option<String>,
     pub repository: Option<String>,
     pub homepage: Option<String>,
     pub keywords: Option<Vec<String>>,
     pub r#type: Option<String>,
+    /// File run by default, takes precedence over `lib` and `bin`.
+    pub entry: Option<PathBuf>,
     pub lib: Option<PathBuf>,
     pub bin: Option<PathBuf>,
 }
 
 #[derive(Deserialize, Debug, Clone, Serialize)]
+#[serde(from = "DependencySpec")]
 pub struct Dependency {
     pub version: Option<String>,
     pub path: Option<String>,
     pub github: Option<String>,
     pub branch: Option<String>,
 }
+
+/// A dependency is either written as a version range, `std = "^0.1"`, or as a table.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum DependencySpec {
+    Version(String),
+    Table {
+        version: Option<String>,
+        path: Option<String>,
+        github: Option<String>,
+        branch: Option<String>,
+    },
+}
+
+impl From<DependencySpec> for Dependency {
+    fn from(spec: DependencySpec) -> Self {
+        match spec {
+            DependencySpec::Version(version) => Dependency {
+                version: Some(version),
+                path: None,
+                github: None,
+                branch: None,
+            },
+            DependencySpec::Table {
+                version,
+                path,
+                github,
+                branch,
+            } => Dependency {
+                version,
+                path,
+                github,
+                branch,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_config() {
+        let config: RoanConfig = toml::from_str(
+            r#"
+            [project]
+            name = "app"
+            version = "0.1.0"
+            type = "bin"
+            entry = "src/app.roan"
+            authors = ["Jane", "John"]
+
+            [dependencies]
+            json = "^1.2"
+            std = { version = "0.1.0", github = "roan-rs/std" }
+
+            [tasks]
+            hello = "echo hello"
+
+            [run]
+            max_depth = 200
+            timeout = 2.5
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.project.entry, Some(PathBuf::from("src/app.roan")));
+        assert_eq!(config.project.authors, ["Jane", "John"]);
+        assert_eq!(config.tasks.unwrap()["hello"], "echo hello");
+        assert_eq!(config.run.max_depth, Some(200));
+        assert_eq!(config.run.timeout, Some(2.5));
+
+        let deps = config.dependencies.unwrap();
+        assert_eq!(deps["json"].version.as_deref(), Some("^1.2"));
+        assert_eq!(deps["json"].github, None);
+        assert_eq!(deps["std"].github.as_deref(), Some("roan-rs/std"));
+    }
+
+    #[test]
+    fn test_optional_fields_default() {
+        let config: RoanConfig =
+            toml::from_str("[project]\nname = \"app\"\nversion = \"0.1.0\"").unwrap();
+
+        assert!(config.project.entry.is_none());
+        assert!(config.project.authors.is_empty());
+        assert!(config.tasks.is_none());
+        assert!(config.dependencies.is_none());
+        assert!(config.run.max_depth.is_none() && config.run.timeout.is_none());
+    }
+}
@@ -1,52 +1,138 @@
-use crate::{config_file::RoanConfig, fs::walk_for_file};
+use crate::{
+    config_file::RoanConfig,
+    fs::walk_for_file,
+    pm::registry::{default_registry, PackageRegistry},
+};
 use anstream::ColorChoice;
 use anyhow::{anyhow, bail, Context, Result};
 use colored::Colorize;
 use octocrab::Octocrab;
 use roan_engine::path::{canonicalize_path, normalize_without_canonicalize};
-use std::{fs::read_to_string, path::PathBuf, sync::Arc, time::Instant};
-use roan_shell::Shell;
+use roan_shell::{Shell, VerbosityLevel};
+use std::{
+    cell::OnceCell,
+    fs::read_to_string,
+    path::PathBuf,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+/// Maximum depth of the call stack of programs run, unless set with `--max-depth` or in roan.toml.
+pub const DEFAULT_MAX_DEPTH: usize = 1000;
+
+const CONFIG_NOT_FOUND: &str = "Failed to find roan.toml. Make sure you are running the command inside project root or in a subdirectory";
 
 #[derive(Debug)]
 pub struct GlobalContext {
     pub verbose: bool,
+    pub interactive: bool,
     pub cwd: PathBuf,
     pub config: Option<RoanConfig>,
     pub start: Instant,
     pub shell: Shell,
     pub octocrab: Arc<Octocrab>,
+    /// Registry packages are resolved in and downloaded from.
+    pub registry: Arc<dyn PackageRegistry>,
+    /// Maximum depth of the call stack of programs run.
+    pub max_depth: usize,
+    /// How long programs are allowed to run, unlimited if `None`.
+    pub timeout: Option<Duration>,
+    /// Directory containing `roan.toml`, looked up once by [GlobalContext::workspace_root].
+    workspace_root: OnceCell<Option<PathBuf>>,
 }
 
 impl GlobalContext {
+    /// Creates a context for the current directory, with the `roan.toml` of its workspace loaded
+    /// if there is one.
     pub fn default(color_choice: ColorChoice) -> Result<Self> {
-        Ok(Self {
-            verbose: false,
-            cwd: std::env::current_dir().context("Failed to get current directory")?,
-            config: None,
-            start: Instant::now(),
-            shell: Shell::new(color_choice),
-            octocrab: octocrab::instance(),
-        })
+        let mut ctx = Self::from_cwd(
+            std::env::current_dir().context("Failed to get current directory")?,
+            color_choice,
+        )?;
+        ctx.config = ctx.read_config()?;
+
+        Ok(ctx)
     }
 
     pub fn from_cwd(cwd: PathBuf, color_choice: ColorChoice) -> Result<Self> {
+        let octocrab = octocrab::instance();
+
         Ok(Self {
             verbose: false,
+            interactive: true,
             cwd,
             config: None,
             start: Instant::now(),
             shell: Shell::new(color_choice),
-            octocrab: octocrab::instance(),
+            registry: default_registry(octocrab.clone()),
+            octocrab,
+            max_depth: DEFAULT_MAX_DEPTH,
+            timeout: None,
+            workspace_root: OnceCell::new(),
         })
     }
 
-    pub fn load_config(&mut self) -> Result<RoanConfig> {
-        let path = walk_for_file(self.cwd.clone(), "roan.toml").context(
-            "Failed to find roan.toml. Make sure you are running the command inside project root or in a subdirectory",
-        )?;
+    pub fn set_verbosity(&mut self, level: VerbosityLevel) {
+        self.verbose = level == VerbosityLevel::Verbose;
+        self.shell.set_verbosity(level);
+    }
+
+    pub fn set_interactive(&mut self, interactive: bool) {
+        self.interactive = interactive;
+        self.shell.set_interactive(interactive);
+    }
+
+    /// Sets the limits of the programs run, `max_depth` and `timeout` (in seconds) are given on
+    /// the command line and take precedence over the `[run]` table of roan.toml.
+    pub fn set_run_limits(&mut self, max_depth: Option<usize>, timeout: Option<f64>) -> Result<()> {
+        let run = self
+            .config
+            .as_ref()
+            .map(|config| config.run.clone())
+            .unwrap_or_default();
+
+        self.max_depth = max_depth.or(run.max_depth).unwrap_or(DEFAULT_MAX_DEPTH);
+        self.timeout = timeout
+            .or(run.timeout)
+            .map(Duration::try_from_secs_f64)
+            .transpose()
+            .context("Timeout must be a positive number of seconds")?;
+
+        Ok(())
+    }
+
+    /// Returns the closest directory containing a `roan.toml`, starting at `cwd` and walking up
+    /// through its parents. `None` if there is no `roan.toml` in any of them.
+    pub fn workspace_root(&self) -> Option<PathBuf> {
+        self.workspace_root
+            .get_or_init(|| {
+                walk_for_file(self.cwd.clone(), "roan.toml")
+                    .and_then(|file| file.parent().map(PathBuf::from))
+            })
+            .clone()
+    }
+
+    /// Parses the `roan.toml` of the workspace, `None` if there is no workspace.
+    fn read_config(&self) -> Result<Option<RoanConfig>> {
+        let Some(root) = self.workspace_root() else {
+            return Ok(None);
+        };
+
+        let content = read_to_string(root.join("roan.toml")).context("Failed to read roan.toml")?;
+        let config = toml::from_str(&content).context("Failed to parse roan.toml")?;
+
+        Ok(Some(config))
+    }
 
-        let content = read_to_string(&path).context("Failed to read roan.toml")?;
-        let config: RoanConfig = toml::from_str(&content)?;
+    /// Returns the path of the `roan.toml` of the workspace.
+    pub fn config_path(&self) -> Result<PathBuf> {
+        self.workspace_root()
+            .map(|root| root.join("roan.toml"))
+            .context(CONFIG_NOT_FOUND)
+    }
+
+    pub fn load_config(&mut self) -> Result<RoanConfig> {
+        let config = self.read_config()?.context(CONFIG_NOT_FOUND)?;
 
         self.config = Some(config.clone());
 
@@ -82,17 +168,10 @@ impl GlobalContext {
         let config = self.get_config()?.clone();
 
         // We unwrap here because we have already checked that project type is specified in config
-        let file: PathBuf = match self.project_type()? {
-            "lib" => config
-                .project
-                .lib
-                .clone()
-                .unwrap_or_else(|| "src/lib.roan".into()),
-            "bin" => config
-                .project
-                .bin
-                .clone()
-                .unwrap_or_else(|| "src/main.roan".into()),
+        let file: PathBuf = match (config.project.entry, self.project_type()?) {
+            (Some(entry), _) => entry,
+            (None, "lib") => config.project.lib.unwrap_or_else(|| "src/lib.roan".into()),
+            (None, "bin") => config.project.bin.unwrap_or_else(|| "src/main.roan".into()),
             _ => unreachable!(),
         };
 
@@ -263,3 +342,79 @@ impl GlobalContext {
     //     Ok(())
     // }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    // Creating the context needs a runtime for the GitHub client
+    #[tokio::test]
+    async fn test_workspace_root() {
+        let root = std::env::temp_dir().join("roan_test_workspace_root");
+        let nested = root.join("src").join("nested");
+        fs::create_dir_all(&nested).unwrap();
+        fs::write(root.join("roan.toml"), "").unwrap();
+
+        let ctx = GlobalContext::from_cwd(nested.clone(), ColorChoice::Never).unwrap();
+        assert_eq!(ctx.workspace_root(), Some(root.clone()));
+
+        // The result is cached
+        fs::remove_file(root.join("roan.toml")).unwrap();
+        assert_eq!(ctx.workspace_root(), Some(root.clone()));
+
+        let ctx = GlobalContext::from_cwd(nested, ColorChoice::Never).unwrap();
+        assert_eq!(ctx.workspace_root(), None);
+
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_main_file_defaults_to_entry() {
+        let root = std::env::temp_dir().join("roan_test_config_entry");
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("src").join("app.roan"), "").unwrap();
+        fs::write(
+            root.join("roan.toml"),
+            "[project]\nname = \"app\"\nversion = \"0.1.0\"\ntype = \"bin\"\nentry = \"src/app.roan\"\n\n\
+             [tasks]\nhello = \"echo hello\"\n",
+        )
+        .unwrap();
+
+        let mut ctx = GlobalContext::from_cwd(root.clone(), ColorChoice::Never).unwrap();
+        let config = ctx.load_config().unwrap();
+
+        assert_eq!(config.tasks.unwrap()["hello"], "echo hello");
+        assert_eq!(
+            ctx.get_main_file().unwrap(),
+            root.join("src").join("app.roan").canonicalize().unwrap()
+        );
+
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_run_limits() {
+        let mut ctx = GlobalContext::from_cwd(std::env::temp_dir(), ColorChoice::Never).unwrap();
+        ctx.set_run_limits(None, None).unwrap();
+        assert_eq!(ctx.max_depth, DEFAULT_MAX_DEPTH);
+        assert_eq!(ctx.timeout, None);
+
+        ctx.config = Some(
+            toml::from_str(
+                "[project]\nname = \"app\"\nversion = \"0.1.0\"\n\n[run]\nmax_depth = 50\ntimeout = 1.5\n",
+            )
+            .unwrap(),
+        );
+        ctx.set_run_limits(None, None).unwrap();
+        assert_eq!(ctx.max_depth, 50);
+        assert_eq!(ctx.timeout, Some(Duration::from_millis(1500)));
+
+        // Flags take precedence over roan.toml
+        ctx.set_run_limits(Some(20), Some(3.0)).unwrap();
+        assert_eq!(ctx.max_depth, 20);
+        assert_eq!(ctx.timeout, Some(Duration::from_secs(3)));
+
+        assert!(ctx.set_run_limits(None, Some(-1.0)).is_err());
+    }
+}
@@ -0,0 +1,28 @@
+use anyhow::Result;
+use roan_engine::{
+    error::RoanError, module::Module, print_diagnostic, Diagnostic, DiagnosticBuffer,
+};
+use roan_shell::Shell;
+
+/// Writes `diagnostics`, e.g. the warnings returned by [Module::parse], to stderr.
+pub fn print_diagnostics(shell: &mut Shell, diagnostics: &[Diagnostic]) -> Result<()> {
+    diagnostics
+        .iter()
+        .cloned()
+        .collect::<DiagnosticBuffer>()
+        .flush(shell)
+}
+
+/// Writes an error returned while parsing or interpreting `module` to stderr, starting with the
+/// diagnostics it carries if it aborted due to them.
+pub fn print_error(shell: &mut Shell, err: &anyhow::Error, module: &Module) -> Result<()> {
+    if let Some(RoanError::AbortedDueToPreviousErrors(diagnostics)) =
+        err.downcast_ref::<RoanError>()
+    {
+        print_diagnostics(shell, diagnostics)?;
+    }
+
+    print_diagnostic(err, Some(module.source().content()), module.path());
+
+    Ok(())
+}
@@ -0,0 +1,10 @@
+pub mod cli;
+pub mod commands;
+pub mod config_file;
+pub mod context;
+pub mod diagnostics;
+mod fs;
+pub mod logger;
+pub mod module_loader;
+pub mod panic_handler;
+pub mod pm;
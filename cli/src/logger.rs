@@ -1,8 +1,39 @@
-use std::io;
-use tracing::subscriber;
-use tracing_subscriber::{fmt, fmt::time::ChronoLocal, prelude::*};
+use std::{io, str::FromStr};
+use tracing::{subscriber, Subscriber};
+use tracing_subscriber::{
+    fmt,
+    fmt::{time::ChronoLocal, MakeWriter},
+    prelude::*,
+    registry::LookupSpan,
+    Layer,
+};
 
-pub fn setup_tracing(verbose: bool) {
+/// Format of the lines written by the tracing logger.
+///
+/// This only affects tracing output, messages printed with the `Shell` are not logs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LogFormat {
+    /// Human-readable lines.
+    #[default]
+    Text,
+    /// One JSON object per line with `timestamp`, `level`, `target`, `span` and `message`
+    /// fields, for log aggregators.
+    Json,
+}
+
+impl FromStr for LogFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(LogFormat::Text),
+            "json" => Ok(LogFormat::Json),
+            _ => Err(format!("Unknown log format: {}", s)),
+        }
+    }
+}
+
+pub fn setup_tracing(verbose: bool, format: LogFormat) {
     let env = tracing_subscriber::EnvFilter::from_env("ROAN_LOG");
 
     // Set common time format
@@ -12,13 +43,13 @@ pub fn setup_tracing(verbose: bool) {
         "%H:%M:%S%.3f"
     };
 
-    let console_layer = fmt::Layer::new()
-        .with_writer(io::stderr)
-        .with_timer(ChronoLocal::new(time_format.into()))
-        .with_ansi(std::io::IsTerminal::is_terminal(&io::stderr()))
-        .with_line_number(true)
-        .with_file(true)
-        .with_target(verbose);
+    let console_layer = log_layer(
+        format,
+        io::stderr,
+        time_format,
+        std::io::IsTerminal::is_terminal(&io::stderr()),
+        verbose,
+    );
 
     if verbose {
         let file_appender = tracing_appender::rolling::hourly(
@@ -28,15 +59,7 @@ pub fn setup_tracing(verbose: bool) {
 
         let subscriber = tracing_subscriber::Registry::default()
             .with(console_layer)
-            .with(
-                fmt::Layer::new()
-                    .with_writer(file_appender)
-                    .with_timer(ChronoLocal::new(time_format.into()))
-                    .with_ansi(false)
-                    .with_target(true)
-                    .with_line_number(true)
-                    .with_file(true),
-            )
+            .with(log_layer(format, file_appender, time_format, false, true))
             .with(env);
 
         subscriber::set_global_default(subscriber).expect("Failed to set logger");
@@ -47,3 +70,96 @@ pub fn setup_tracing(verbose: bool) {
         subscriber::set_global_default(subscriber).expect("Failed to set logger");
     };
 }
+
+/// Creates a layer writing logs in `format` to `writer`.
+fn log_layer<S, W>(
+    format: LogFormat,
+    writer: W,
+    time_format: &str,
+    ansi: bool,
+    with_target: bool,
+) -> Box<dyn Layer<S> + Send + Sync>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    W: for<'w> MakeWriter<'w> + Send + Sync + 'static,
+{
+    let layer = fmt::Layer::new()
+        .with_writer(writer)
+        .with_timer(ChronoLocal::new(time_format.into()))
+        .with_line_number(true)
+        .with_file(true);
+
+    match format {
+        LogFormat::Text => layer.with_ansi(ansi).with_target(with_target).boxed(),
+        // Aggregators expect every field to be present, so the target is always included
+        LogFormat::Json => layer
+            .json()
+            .with_ansi(false)
+            .flatten_event(true)
+            .with_current_span(true)
+            .with_span_list(false)
+            .with_target(true)
+            .boxed(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Clone, Default)]
+    struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+    impl io::Write for Buffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl<'a> MakeWriter<'a> for Buffer {
+        type Writer = Buffer;
+
+        fn make_writer(&'a self) -> Self::Writer {
+            self.clone()
+        }
+    }
+
+    #[test]
+    fn test_json_log_format() {
+        let buffer = Buffer::default();
+        let subscriber = tracing_subscriber::Registry::default().with(log_layer(
+            LogFormat::Json,
+            buffer.clone(),
+            "%H:%M:%S%.3f",
+            false,
+            false,
+        ));
+
+        tracing::subscriber::with_default(subscriber, || {
+            let _span = tracing::info_span!("run", file = "main.roan").entered();
+            tracing::info!("Running module");
+        });
+
+        let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        let line: serde_json::Value = serde_json::from_str(output.trim()).unwrap();
+
+        assert!(line["timestamp"].is_string());
+        assert_eq!(line["level"], "INFO");
+        assert_eq!(line["target"], "roan_cli::logger::tests");
+        assert_eq!(line["message"], "Running module");
+        assert_eq!(line["span"]["name"], "run");
+        assert_eq!(line["span"]["file"], "main.roan");
+    }
+
+    #[test]
+    fn test_parse_log_format() {
+        assert_eq!("text".parse(), Ok(LogFormat::Text));
+        assert_eq!("json".parse(), Ok(LogFormat::Json));
+        assert!("yaml".parse::<LogFormat>().is_err());
+    }
+}
@@ -1,27 +1,21 @@
-use crate::{
-    commands::{init::init_command, install::install_command},
-    context::GlobalContext,
-};
 use anstream::ColorChoice;
 use anyhow::Result;
 use clap::ArgMatches;
-use cli::cli;
-use commands::run::run_command;
-use logger::setup_tracing;
-use panic_handler::setup_panic_handler;
+use roan_cli::{
+    cli::cli,
+    commands::{
+        add::add_command, check::check_command, explain::explain_command, init::init_command,
+        install::install_command, new::new_command, remove::remove_command, run::run_command,
+        test::test_command, watch::watch_command,
+    },
+    context::GlobalContext,
+    logger::{setup_tracing, LogFormat},
+    panic_handler::setup_panic_handler,
+};
 use roan_engine::print_diagnostic;
+use roan_shell::{Shell, VerbosityLevel};
 use std::{env, process::exit};
 
-pub mod cli;
-pub mod commands;
-mod config_file;
-mod context;
-mod fs;
-pub mod logger;
-mod module_loader;
-pub mod panic_handler;
-pub mod pm;
-
 #[tokio::main]
 async fn main() -> Result<()> {
     setup_panic_handler();
@@ -32,7 +26,11 @@ async fn main() -> Result<()> {
     let verbose = args.get_flag("verbose");
 
     env::set_var("ROAN_LOG", if verbose { "trace" } else { "info" });
-    setup_tracing(verbose);
+    let log_format = args
+        .get_one::<String>("log-format")
+        .and_then(|format| format.parse().ok())
+        .unwrap_or(LogFormat::Text);
+    setup_tracing(verbose, log_format);
 
     tracing::debug!("Starting roan-cli");
 
@@ -48,11 +46,20 @@ async fn main() -> Result<()> {
     let color_choice = if args.get_flag("no-color") {
         ColorChoice::Never
     } else {
-        ColorChoice::Auto
+        Shell::detect_color_choice()
+    };
+
+    let verbosity = if args.get_flag("quiet") {
+        VerbosityLevel::Quiet
+    } else if verbose {
+        VerbosityLevel::Verbose
+    } else {
+        VerbosityLevel::Normal
     };
 
     let mut ctx = GlobalContext::default(color_choice)?;
-    ctx.verbose = verbose;
+    ctx.set_verbosity(verbosity);
+    ctx.set_interactive(!args.get_flag("no-interactive"));
 
     match run_cmd(&mut ctx, cmd).await {
         Ok(()) => Ok(()),
@@ -70,7 +77,14 @@ pub async fn run_cmd(ctx: &mut GlobalContext, cmd: (&str, &ArgMatches)) -> Resul
     match cmd.0 {
         "run" => run_command(ctx, cmd.1),
         "init" => init_command(ctx, cmd.1),
+        "new" => new_command(ctx, cmd.1),
         "install" => install_command(ctx, cmd.1).await,
+        "add" => add_command(ctx, cmd.1).await,
+        "remove" => remove_command(ctx, cmd.1),
+        "explain" => explain_command(ctx, cmd.1),
+        "test" => test_command(ctx, cmd.1),
+        "check" => check_command(ctx, cmd.1),
+        "watch" => watch_command(ctx, cmd.1),
         _ => {
             cli().print_help()?;
             exit(1);
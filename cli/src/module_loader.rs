@@ -81,7 +81,7 @@ impl ModuleLoader for RoanModuleLoader {
             "Module not found in cache. Loading from path: {:?}",
             resolved_path
         );
-        let source = Source::from_path(resolved_path)?;
+        let source = Source::from_file(resolved_path)?;
         let module = Module::new(source);
 
         self.modules.insert(cache_key.clone(), module.clone());
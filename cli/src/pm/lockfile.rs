@@ -0,0 +1,42 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::{fs, path::Path};
+
+/// Name of the lockfile, next to `roan.toml`.
+pub const LOCKFILE_NAME: &str = "roan.lock";
+
+const HEADER: &str = "# This file is generated by roan, it is not meant to be edited by hand.\n\n";
+
+/// Exact versions of every dependency of a project, direct or not, so installs are
+/// reproducible.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct LockFile {
+    #[serde(default, rename = "package")]
+    pub packages: Vec<LockedPackage>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct LockedPackage {
+    pub name: String,
+    pub version: String,
+    /// SHA-256 of the archive of the package, as lowercase hex.
+    pub checksum: String,
+}
+
+impl LockFile {
+    pub fn read(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path).context("Failed to read roan.lock")?;
+
+        toml::from_str(&content).context("Failed to parse roan.lock")
+    }
+
+    pub fn write(&self, path: &Path) -> Result<()> {
+        let content = format!("{}{}", HEADER, toml::to_string(self)?);
+
+        fs::write(path, content).context("Failed to write roan.lock")
+    }
+
+    pub fn find(&self, name: &str) -> Option<&LockedPackage> {
+        self.packages.iter().find(|package| package.name == name)
+    }
+}
@@ -0,0 +1,347 @@
+use crate::{
+    config_file::RoanConfig,
+    pm::{
+        lockfile::{LockFile, LockedPackage, LOCKFILE_NAME},
+        registry::{resolve_latest, PackageRegistry, PackageVersion},
+    },
+};
+use anyhow::{bail, Context, Result};
+use flate2::read::GzDecoder;
+use roan_engine::natives::hash::sha256_hex;
+use semver::{Version, VersionReq};
+use std::{
+    collections::VecDeque,
+    fs,
+    io::Read,
+    path::{Component, Path, PathBuf},
+    sync::Arc,
+};
+use tar::Archive;
+
+/// Resolves, locks and installs the dependencies of a project.
+pub struct PackageManager {
+    registry: Arc<dyn PackageRegistry>,
+    /// Directory containing `roan.toml`, the lockfile is written next to it.
+    root: PathBuf,
+    /// Directory the packages are installed into, each in a directory named after it.
+    deps_dir: PathBuf,
+}
+
+impl PackageManager {
+    pub fn new(registry: Arc<dyn PackageRegistry>, root: PathBuf, deps_dir: PathBuf) -> Self {
+        Self {
+            registry,
+            root,
+            deps_dir,
+        }
+    }
+
+    pub fn lockfile_path(&self) -> PathBuf {
+        self.root.join(LOCKFILE_NAME)
+    }
+
+    /// Resolves the dependencies of `config`, and the dependencies of those, to their latest
+    /// matching versions and writes them with their checksums to `roan.lock`.
+    ///
+    /// Every package is resolved once, a requirement the version picked for it doesn't match is
+    /// an error. Path dependencies are not locked.
+    pub async fn lock(&self, config: &RoanConfig) -> Result<LockFile> {
+        let mut lock = LockFile::default();
+        let mut queue = VecDeque::from(registry_requirements(config)?);
+
+        while let Some((name, req)) = queue.pop_front() {
+            if let Some(locked) = lock.find(&name) {
+                if !req.matches(&locked.version.parse()?) {
+                    bail!(
+                        "Conflicting requirements for {}: {} was picked, but {} is required",
+                        name,
+                        locked.version,
+                        req
+                    );
+                }
+                continue;
+            }
+
+            let version = resolve_latest(self.registry.as_ref(), &name, Some(&req)).await?;
+            let archive = self.registry.download(&version).await?;
+            if let Some(manifest) = read_manifest(&archive)? {
+                queue.extend(registry_requirements(&manifest)?);
+            }
+
+            lock.packages.push(LockedPackage {
+                name,
+                version: version.version.to_string(),
+                checksum: sha256_hex(&archive),
+            });
+        }
+
+        lock.packages.sort_by(|a, b| a.name.cmp(&b.name));
+        lock.write(&self.lockfile_path())?;
+
+        Ok(lock)
+    }
+
+    /// Installs exactly the versions pinned in `lock`. Every archive is checked against its
+    /// checksum before anything is installed.
+    pub async fn install_from_lockfile(&self, lock: &LockFile) -> Result<()> {
+        let mut archives = vec![];
+        for package in lock.packages.iter() {
+            let version: Version = package
+                .version
+                .parse()
+                .with_context(|| format!("Invalid version of {} in roan.lock", package.name))?;
+            let archive = self
+                .registry
+                .download(&PackageVersion::new(&package.name, version))
+                .await?;
+
+            let checksum = sha256_hex(&archive);
+            if checksum != package.checksum {
+                bail!(
+                    "Checksum of {}@{} does not match roan.lock, expected {} but got {}",
+                    package.name,
+                    package.version,
+                    package.checksum,
+                    checksum
+                );
+            }
+
+            archives.push((package, archive));
+        }
+
+        for (package, archive) in archives {
+            let dest = self.deps_dir.join(&package.name);
+            if dest.exists() {
+                fs::remove_dir_all(&dest)?;
+            }
+
+            unpack(&archive, &dest)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns `true` if `lock` pins a matching version of every dependency of `config` that would
+/// be locked.
+pub fn is_up_to_date(lock: &LockFile, config: &RoanConfig) -> Result<bool> {
+    for (name, req) in registry_requirements(config)? {
+        let matches = match lock.find(&name) {
+            Some(locked) => req.matches(&locked.version.parse()?),
+            None => false,
+        };
+
+        if !matches {
+            return Ok(false);
+        }
+    }
+
+    Ok(true)
+}
+
+/// Returns the version requirements of the dependencies of `config` resolved in the registry,
+/// sorted by name so locking is deterministic.
+fn registry_requirements(config: &RoanConfig) -> Result<Vec<(String, VersionReq)>> {
+    let mut requirements = config
+        .dependencies
+        .iter()
+        .flatten()
+        .filter(|(_, dep)| dep.path.is_none())
+        .map(|(name, dep)| {
+            let req = match &dep.version {
+                Some(version) => version.parse().with_context(|| {
+                    format!("Invalid version requirement for {}: {}", name, version)
+                })?,
+                None => VersionReq::STAR,
+            };
+
+            Ok((name.clone(), req))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    requirements.sort_by(|a, b| a.0.cmp(&b.0));
+
+    Ok(requirements)
+}
+
+/// Returns the path of an archive entry inside the package, without the top-level directory.
+fn entry_path(path: &Path) -> Result<PathBuf> {
+    let path: PathBuf = path.components().skip(1).collect();
+
+    if !path.components().all(|c| matches!(c, Component::Normal(_))) {
+        bail!(
+            "Package archive contains an invalid path: {}",
+            path.display()
+        );
+    }
+
+    Ok(path)
+}
+
+/// Reads the `roan.toml` of the package in `archive`, `None` if it doesn't have one.
+fn read_manifest(archive: &[u8]) -> Result<Option<RoanConfig>> {
+    let mut archive = Archive::new(GzDecoder::new(archive));
+
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+
+        if entry_path(&entry.path()?)? == Path::new("roan.toml") {
+            let mut content = String::new();
+            entry.read_to_string(&mut content)?;
+
+            let manifest = toml::from_str(&content).context("Failed to parse roan.toml")?;
+            return Ok(Some(manifest));
+        }
+    }
+
+    Ok(None)
+}
+
+fn unpack(archive: &[u8], to: &Path) -> Result<()> {
+    let mut archive = Archive::new(GzDecoder::new(archive));
+
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let path = entry_path(&entry.path()?)?;
+
+        // The top-level directory and global headers
+        if path.as_os_str().is_empty() {
+            continue;
+        }
+
+        let dest = to.join(path);
+        if let Some(parent) = dest.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        entry.unpack(dest)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pm::registry::MockRegistry;
+    use flate2::{write::GzEncoder, Compression};
+
+    fn archive(files: &[(&str, &str)]) -> Vec<u8> {
+        let mut builder = tar::Builder::new(GzEncoder::new(vec![], Compression::default()));
+
+        for (path, content) in files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder
+                .append_data(
+                    &mut header,
+                    format!("pkg-1a2b3c/{}", path),
+                    content.as_bytes(),
+                )
+                .unwrap();
+        }
+
+        builder.into_inner().unwrap().finish().unwrap()
+    }
+
+    fn registry(json_lib: &str) -> MockRegistry {
+        let json = archive(&[
+            (
+                "roan.toml",
+                "[project]\nname = \"json\"\nversion = \"1.2.0\"\n\n[dependencies]\ntext = \"^0.3\"\n",
+            ),
+            ("src/lib.roan", json_lib),
+        ]);
+        let text = archive(&[("src/lib.roan", "pub fn trim() {}")]);
+
+        MockRegistry::default()
+            .with("json", "1.2.0", json)
+            .with("text", "0.3.1", text)
+    }
+
+    fn config() -> RoanConfig {
+        toml::from_str("[project]\nname = \"app\"\nversion = \"0.1.0\"\n\n[dependencies]\njson = \"^1\"\nlocal = { path = \"../local\" }\n")
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_lock_and_install() {
+        let dir = tempfile::tempdir().unwrap();
+        let deps_dir = dir.path().join("build").join("deps");
+        let registry = registry("pub fn parse() {}");
+        let checksums: Vec<String> = ["json", "text"]
+            .iter()
+            .map(|name| sha256_hex(&registry.packages[*name][0].1))
+            .collect();
+        let manager = PackageManager::new(Arc::new(registry), dir.path().into(), deps_dir.clone());
+
+        let lock = manager.lock(&config()).await.unwrap();
+
+        let locked: Vec<(&str, &str, &str)> = lock
+            .packages
+            .iter()
+            .map(|p| (p.name.as_str(), p.version.as_str(), p.checksum.as_str()))
+            .collect();
+        assert_eq!(
+            locked,
+            [
+                ("json", "1.2.0", checksums[0].as_str()),
+                ("text", "0.3.1", checksums[1].as_str())
+            ]
+        );
+        assert_eq!(LockFile::read(&manager.lockfile_path()).unwrap(), lock);
+        assert!(is_up_to_date(&lock, &config()).unwrap());
+
+        manager.install_from_lockfile(&lock).await.unwrap();
+        assert_eq!(
+            fs::read_to_string(deps_dir.join("json").join("src").join("lib.roan")).unwrap(),
+            "pub fn parse() {}"
+        );
+        assert_eq!(
+            fs::read_to_string(deps_dir.join("text").join("src").join("lib.roan")).unwrap(),
+            "pub fn trim() {}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_install_verifies_checksums() {
+        let dir = tempfile::tempdir().unwrap();
+        let deps_dir = dir.path().join("deps");
+        let lock = PackageManager::new(
+            Arc::new(registry("pub fn parse() {}")),
+            dir.path().into(),
+            deps_dir.clone(),
+        )
+        .lock(&config())
+        .await
+        .unwrap();
+
+        // The same version was published again with different content
+        let manager = PackageManager::new(
+            Arc::new(registry("pub fn evil() {}")),
+            dir.path().into(),
+            deps_dir.clone(),
+        );
+        let err = manager.install_from_lockfile(&lock).await.unwrap_err();
+
+        assert!(err
+            .to_string()
+            .starts_with("Checksum of json@1.2.0 does not match"));
+        assert!(!deps_dir.exists());
+    }
+
+    #[test]
+    fn test_is_up_to_date() {
+        let lock = LockFile {
+            packages: vec![LockedPackage {
+                name: "json".to_string(),
+                version: "0.9.0".to_string(),
+                checksum: String::new(),
+            }],
+        };
+
+        assert!(!is_up_to_date(&lock, &config()).unwrap());
+        assert!(!is_up_to_date(&LockFile::default(), &config()).unwrap());
+    }
+}
@@ -0,0 +1,84 @@
+use anyhow::{Context, Result};
+use std::{fs, path::Path};
+use toml_edit::DocumentMut;
+
+/// Sets the version requirement of `name` in the `[dependencies]` of the `roan.toml` at `path`,
+/// creating the table if needed. The formatting of the rest of the file is kept.
+pub fn add_dependency(path: &Path, name: &str, version: &str) -> Result<()> {
+    let mut doc = read_manifest(path)?;
+
+    doc.entry("dependencies")
+        .or_insert(toml_edit::table())
+        .as_table_like_mut()
+        .context("[dependencies] in roan.toml is not a table")?
+        .insert(name, toml_edit::value(version));
+
+    fs::write(path, doc.to_string()).context("Failed to write roan.toml")
+}
+
+/// Removes `name` from the `[dependencies]` of the `roan.toml` at `path`, returns `false` if it
+/// wasn't a dependency.
+pub fn remove_dependency(path: &Path, name: &str) -> Result<bool> {
+    let mut doc = read_manifest(path)?;
+
+    let removed = doc
+        .get_mut("dependencies")
+        .and_then(|deps| deps.as_table_like_mut())
+        .and_then(|deps| deps.remove(name))
+        .is_some();
+    if removed {
+        fs::write(path, doc.to_string()).context("Failed to write roan.toml")?;
+    }
+
+    Ok(removed)
+}
+
+fn read_manifest(path: &Path) -> Result<DocumentMut> {
+    let content = fs::read_to_string(path).context("Failed to read roan.toml")?;
+
+    content.parse().context("Failed to parse roan.toml")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MANIFEST: &str = r#"# My project
+[project]
+name = "app"   # the name
+version = "0.1.0"
+
+[dependencies]
+std = { version = "0.1.0", github = "roan-rs/std" }
+"#;
+
+    #[test]
+    fn test_add_and_remove_keep_formatting() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("roan.toml");
+        fs::write(&path, MANIFEST).unwrap();
+
+        add_dependency(&path, "json", "1.4.0").unwrap();
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            format!("{}json = \"1.4.0\"\n", MANIFEST)
+        );
+
+        assert!(remove_dependency(&path, "json").unwrap());
+        assert!(!remove_dependency(&path, "json").unwrap());
+        assert_eq!(fs::read_to_string(&path).unwrap(), MANIFEST);
+    }
+
+    #[test]
+    fn test_add_creates_dependencies() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("roan.toml");
+        fs::write(&path, "[project]\nname = \"app\"\n").unwrap();
+
+        add_dependency(&path, "json", "^1").unwrap();
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "[project]\nname = \"app\"\n\n[dependencies]\njson = \"^1\"\n"
+        );
+    }
+}
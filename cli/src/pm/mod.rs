@@ -1,3 +1,7 @@
+pub mod lockfile;
+pub mod manager;
+pub mod manifest;
 pub mod packs;
+pub mod registry;
 pub mod semver;
 pub mod source;
@@ -0,0 +1,346 @@
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use bytes::Bytes;
+use http_body_util::BodyExt;
+use octocrab::Octocrab;
+use reqwest::StatusCode;
+use semver::{Version, VersionReq};
+use serde::Deserialize;
+use std::{fmt, sync::Arc};
+
+/// Environment variable with the URL of an HTTP registry to use instead of GitHub.
+pub const REGISTRY_ENV: &str = "ROAN_REGISTRY";
+
+/// A version of a package published to a registry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackageVersion {
+    pub name: String,
+    pub version: Version,
+}
+
+impl PackageVersion {
+    pub fn new(name: impl Into<String>, version: Version) -> Self {
+        Self {
+            name: name.into(),
+            version,
+        }
+    }
+}
+
+impl fmt::Display for PackageVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}@{}", self.name, self.version)
+    }
+}
+
+/// Source of the published versions of packages.
+#[async_trait]
+pub trait PackageRegistry: Send + Sync {
+    /// Returns every version of `name` matching `constraint`, a semver requirement such as
+    /// `^1.2`, from oldest to latest.
+    async fn resolve(&self, name: &str, constraint: &str) -> Result<Vec<PackageVersion>>;
+
+    /// Downloads `version` as a gzipped tarball. Every file of the package is in one top-level
+    /// directory, named however the registry likes.
+    async fn download(&self, version: &PackageVersion) -> Result<Bytes>;
+}
+
+impl fmt::Debug for dyn PackageRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("PackageRegistry")
+    }
+}
+
+/// Returns the registry packages are installed from, an [HttpRegistry] if `ROAN_REGISTRY` is set
+/// and GitHub otherwise.
+pub fn default_registry(octocrab: Arc<Octocrab>) -> Arc<dyn PackageRegistry> {
+    match std::env::var(REGISTRY_ENV) {
+        Ok(url) => Arc::new(HttpRegistry::new(&url)),
+        Err(_) => Arc::new(GithubRegistry::new(octocrab)),
+    }
+}
+
+/// Returns the latest version of `name` matching `req`, or the latest stable version if `req` is
+/// `None`.
+pub async fn resolve_latest(
+    registry: &dyn PackageRegistry,
+    name: &str,
+    req: Option<&VersionReq>,
+) -> Result<PackageVersion> {
+    let req = req.cloned().unwrap_or(VersionReq::STAR);
+
+    match registry.resolve(name, &req.to_string()).await?.pop() {
+        Some(version) => Ok(version),
+        None => bail!("No version of {} matches {}", name, req),
+    }
+}
+
+/// Returns the versions of `name` out of `versions` that match `constraint`, sorted.
+fn matching(
+    name: &str,
+    versions: impl IntoIterator<Item = Version>,
+    constraint: &str,
+) -> Result<Vec<PackageVersion>> {
+    let req: VersionReq = constraint
+        .parse()
+        .with_context(|| format!("Invalid version requirement for {}: {}", name, constraint))?;
+
+    let mut versions: Vec<PackageVersion> = versions
+        .into_iter()
+        .filter(|version| req.matches(version))
+        .map(|version| PackageVersion::new(name, version))
+        .collect();
+    versions.sort_by(|a, b| a.version.cmp(&b.version));
+
+    Ok(versions)
+}
+
+/// Registry backed by GitHub. Package `name` is the repository `roan-rs/<name>`, its versions
+/// are the tags of the repository, with or without a `v` prefix.
+pub struct GithubRegistry {
+    octocrab: Arc<Octocrab>,
+    owner: String,
+}
+
+impl GithubRegistry {
+    pub fn new(octocrab: Arc<Octocrab>) -> Self {
+        Self {
+            octocrab,
+            owner: "roan-rs".to_string(),
+        }
+    }
+}
+
+impl GithubRegistry {
+    /// Returns the names of the tags of `name` with the version they stand for.
+    async fn tags(&self, name: &str) -> Result<Vec<(String, Version)>> {
+        let tags = self
+            .octocrab
+            .repos(&self.owner, name)
+            .list_tags()
+            .per_page(100)
+            .send()
+            .await?;
+
+        Ok(tags
+            .items
+            .into_iter()
+            .filter_map(|tag| {
+                let version = tag.name.trim_start_matches('v').parse().ok()?;
+                Some((tag.name, version))
+            })
+            .collect())
+    }
+}
+
+#[async_trait]
+impl PackageRegistry for GithubRegistry {
+    async fn resolve(&self, name: &str, constraint: &str) -> Result<Vec<PackageVersion>> {
+        let versions = self
+            .tags(name)
+            .await?
+            .into_iter()
+            .map(|(_, version)| version);
+
+        matching(name, versions, constraint)
+    }
+
+    async fn download(&self, version: &PackageVersion) -> Result<Bytes> {
+        let (tag, _) = self
+            .tags(&version.name)
+            .await?
+            .into_iter()
+            .find(|(_, v)| *v == version.version)
+            .with_context(|| {
+                format!(
+                    "{} has no tag for version {}",
+                    version.name, version.version
+                )
+            })?;
+
+        let response = self
+            .octocrab
+            .repos(&self.owner, &version.name)
+            .download_tarball(tag)
+            .await?;
+        let body = response.into_body().collect().await?;
+
+        Ok(body.to_bytes())
+    }
+}
+
+/// Registry served over HTTP.
+///
+/// `GET <base>/packages/<name>` returns the published versions as `{"versions": ["1.0.0"]}` and
+/// `GET <base>/packages/<name>/<version>/download` returns the tarball of a version.
+pub struct HttpRegistry {
+    client: reqwest::Client,
+    base_url: String,
+}
+
+/// Response of the registry listing the versions of a package.
+#[derive(Deserialize)]
+struct PackageIndex {
+    versions: Vec<String>,
+}
+
+impl HttpRegistry {
+    pub fn new(base_url: &str) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    fn package_url(&self, name: &str) -> String {
+        format!("{}/packages/{}", self.base_url, name)
+    }
+
+    fn download_url(&self, version: &PackageVersion) -> String {
+        format!(
+            "{}/{}/download",
+            self.package_url(&version.name),
+            version.version
+        )
+    }
+}
+
+#[async_trait]
+impl PackageRegistry for HttpRegistry {
+    async fn resolve(&self, name: &str, constraint: &str) -> Result<Vec<PackageVersion>> {
+        let response = self.client.get(self.package_url(name)).send().await?;
+        if response.status() == StatusCode::NOT_FOUND {
+            bail!("Package {} does not exist", name);
+        }
+
+        let index: PackageIndex = response.error_for_status()?.json().await?;
+        let versions = index
+            .versions
+            .iter()
+            .map(|version| {
+                version
+                    .parse()
+                    .with_context(|| format!("Registry returned an invalid version: {}", version))
+            })
+            .collect::<Result<Vec<Version>>>()?;
+
+        matching(name, versions, constraint)
+    }
+
+    async fn download(&self, version: &PackageVersion) -> Result<Bytes> {
+        let response = self
+            .client
+            .get(self.download_url(version))
+            .send()
+            .await?
+            .error_for_status()
+            .with_context(|| format!("Failed to download {}", version))?;
+
+        Ok(response.bytes().await?)
+    }
+}
+
+/// Registry returning fixed versions and archives.
+#[cfg(test)]
+#[derive(Default)]
+pub struct MockRegistry {
+    pub packages: std::collections::HashMap<String, Vec<(Version, Bytes)>>,
+}
+
+#[cfg(test)]
+impl MockRegistry {
+    /// Publishes `version` of `name` with `archive` as its content.
+    pub fn with(mut self, name: &str, version: &str, archive: impl Into<Bytes>) -> Self {
+        self.packages
+            .entry(name.to_string())
+            .or_default()
+            .push((version.parse().unwrap(), archive.into()));
+        self
+    }
+}
+
+#[cfg(test)]
+#[async_trait]
+impl PackageRegistry for MockRegistry {
+    async fn resolve(&self, name: &str, constraint: &str) -> Result<Vec<PackageVersion>> {
+        match self.packages.get(name) {
+            Some(published) => matching(
+                name,
+                published.iter().map(|(version, _)| version.clone()),
+                constraint,
+            ),
+            None => bail!("Package {} does not exist", name),
+        }
+    }
+
+    async fn download(&self, version: &PackageVersion) -> Result<Bytes> {
+        self.packages
+            .get(&version.name)
+            .and_then(|published| published.iter().find(|(v, _)| *v == version.version))
+            .map(|(_, archive)| archive.clone())
+            .with_context(|| format!("{} is not published", version))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn registry() -> MockRegistry {
+        MockRegistry::default()
+            .with("json", "1.4.0", "")
+            .with("json", "1.2.3", "")
+            .with("json", "2.0.0-beta.1", "")
+    }
+
+    #[tokio::test]
+    async fn test_resolve() {
+        let registry = registry();
+
+        assert_eq!(
+            resolve_latest(&registry, "json", None).await.unwrap(),
+            PackageVersion::new("json", Version::new(1, 4, 0))
+        );
+        assert_eq!(
+            resolve_latest(&registry, "json", Some(&"~1.2".parse().unwrap()))
+                .await
+                .unwrap(),
+            PackageVersion::new("json", Version::new(1, 2, 3))
+        );
+        assert!(
+            resolve_latest(&registry, "json", Some(&"^3".parse().unwrap()))
+                .await
+                .is_err()
+        );
+        assert!(resolve_latest(&registry, "xml", None).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_resolve_sorts_versions() {
+        let versions: Vec<String> = registry()
+            .resolve("json", ">=1.0.0")
+            .await
+            .unwrap()
+            .iter()
+            .map(|version| version.to_string())
+            .collect();
+
+        assert_eq!(versions, ["json@1.2.3", "json@1.4.0"]);
+        assert!(registry().resolve("json", "not a version").await.is_err());
+    }
+
+    #[test]
+    fn test_http_registry_urls() {
+        let registry = HttpRegistry::new("https://registry.roan.dev/");
+        let version = PackageVersion::new("json", Version::new(1, 4, 0));
+
+        assert_eq!(
+            registry.package_url("json"),
+            "https://registry.roan.dev/packages/json"
+        );
+        assert_eq!(
+            registry.download_url(&version),
+            "https://registry.roan.dev/packages/json/1.4.0/download"
+        );
+    }
+}
@@ -1,4 +1,8 @@
-use crate::{statements::Stmt, GetSpan, Token, TokenKind};
+use crate::{
+    ast::{walk_expr, ExprVisitor},
+    statements::Stmt,
+    GetSpan, Token, TokenKind,
+};
 use indexmap::IndexMap;
 use roan_error::TextSpan;
 use std::fmt::{Display, Formatter};
@@ -9,6 +13,8 @@ use std::fmt::{Display, Formatter};
 pub struct VecExpr {
     /// The vector containing the expressions.
     pub exprs: Vec<Expr>,
+    /// The tokens representing the opening and closing brackets.
+    pub brackets: (Token, Token),
 }
 
 /// Enum that defines the possible literal types in the language.
@@ -120,6 +126,77 @@ impl BinOpKind {
         }
     }
 
+    /// Returns the precedence of the operator, higher numbers bind tighter.
+    pub fn precedence(&self) -> u8 {
+        match self {
+            // Highest precedence
+            BinOpKind::Power => 20,
+            BinOpKind::Multiply | BinOpKind::Divide | BinOpKind::Modulo => 19,
+            BinOpKind::Plus | BinOpKind::Minus => 18,
+            BinOpKind::ShiftLeft | BinOpKind::ShiftRight => 17,
+            BinOpKind::BitwiseAnd => 16,
+            BinOpKind::BitwiseXor => 15,
+            BinOpKind::BitwiseOr => 14,
+            // Relational operators
+            BinOpKind::LessThan
+            | BinOpKind::LessThanOrEqual
+            | BinOpKind::GreaterThan
+            | BinOpKind::GreaterThanOrEqual => 13,
+            // Equality operators
+            BinOpKind::Equals | BinOpKind::EqualsEquals | BinOpKind::BangEquals => 12,
+            // Logical operators
+            BinOpKind::And => 11,
+            BinOpKind::Or => 10,
+            // Increment/Decrement operators
+            BinOpKind::Increment | BinOpKind::Decrement => 9,
+        }
+    }
+
+    /// Returns whether the operator groups from the left or from the right.
+    pub fn associativity(&self) -> BinOpAssociativity {
+        match self {
+            BinOpKind::Power => BinOpAssociativity::Right,
+            _ => BinOpAssociativity::Left,
+        }
+    }
+
+    /// Returns whether the operator is one of `<`, `<=`, `>` and `>=`.
+    pub fn is_relational_operator(&self) -> bool {
+        matches!(
+            self,
+            BinOpKind::LessThan
+                | BinOpKind::LessThanOrEqual
+                | BinOpKind::GreaterThan
+                | BinOpKind::GreaterThanOrEqual
+        )
+    }
+
+    /// Returns whether the operator is one of `&`, `|`, `^`, `<<` and `>>`.
+    pub fn is_bitwise_operator(&self) -> bool {
+        matches!(
+            self,
+            BinOpKind::BitwiseAnd
+                | BinOpKind::BitwiseOr
+                | BinOpKind::BitwiseXor
+                | BinOpKind::ShiftLeft
+                | BinOpKind::ShiftRight
+        )
+    }
+
+    /// Returns whether the operator is a relational or an equality operator.
+    pub fn is_comparison_operator(&self) -> bool {
+        self.is_relational_operator()
+            || matches!(
+                self,
+                BinOpKind::Equals | BinOpKind::EqualsEquals | BinOpKind::BangEquals
+            )
+    }
+
+    /// Returns whether the operator is `&&` or `||`.
+    pub fn is_logical_operator(&self) -> bool {
+        matches!(self, BinOpKind::And | BinOpKind::Or)
+    }
+
     pub fn is_boolean_operator(&self) -> bool {
         match self {
             BinOpKind::Equals
@@ -136,6 +213,35 @@ impl BinOpKind {
     }
 }
 
+impl Display for BinOpKind {
+    /// Formats the binary operator as it's written in the source code.
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BinOpKind::Plus => write!(f, "+"),
+            BinOpKind::Minus => write!(f, "-"),
+            BinOpKind::Multiply => write!(f, "*"),
+            BinOpKind::Divide => write!(f, "/"),
+            BinOpKind::Power => write!(f, "**"),
+            BinOpKind::Modulo => write!(f, "%"),
+            BinOpKind::BitwiseAnd => write!(f, "&"),
+            BinOpKind::BitwiseOr => write!(f, "|"),
+            BinOpKind::BitwiseXor => write!(f, "^"),
+            BinOpKind::ShiftLeft => write!(f, "<<"),
+            BinOpKind::ShiftRight => write!(f, ">>"),
+            BinOpKind::Equals | BinOpKind::EqualsEquals => write!(f, "=="),
+            BinOpKind::LessThan => write!(f, "<"),
+            BinOpKind::LessThanOrEqual => write!(f, "<="),
+            BinOpKind::GreaterThan => write!(f, ">"),
+            BinOpKind::GreaterThanOrEqual => write!(f, ">="),
+            BinOpKind::BangEquals => write!(f, "!="),
+            BinOpKind::And => write!(f, "&&"),
+            BinOpKind::Or => write!(f, "||"),
+            BinOpKind::Increment => write!(f, "++"),
+            BinOpKind::Decrement => write!(f, "--"),
+        }
+    }
+}
+
 /// Represents a binary expression in the AST.
 /// A binary expression consists of two operands and an operator (e.g., `a + b`).
 #[derive(Debug, Clone, PartialEq)]
@@ -211,17 +317,10 @@ impl GetSpan for CallExpr {
     /// Returns the source span of the function call expression.
     fn span(&self) -> TextSpan {
         // TODO: get the span of the closing parenthesis
-        let callee_span = self.token.span.clone();
-        let args_span: Vec<TextSpan> = self.args.iter().map(|arg| arg.span()).collect();
-        let args_span = TextSpan::combine(args_span);
-
-        let mut spans = vec![callee_span];
-
-        if args_span.is_some() {
-            spans.push(args_span.unwrap());
+        match self.args.last() {
+            Some(arg) => self.token.span.to(&arg.span()),
+            None => self.token.span.clone(),
         }
-
-        TextSpan::combine(spans).unwrap()
     }
 }
 
@@ -237,6 +336,10 @@ pub enum AssignOperator {
     MultiplyEquals,
     /// Division assignment operator (`/=`).
     DivideEquals,
+    /// Modulo assignment operator (`%=`).
+    ModuloEquals,
+    /// Exponentiation assignment operator (`**=`).
+    PowerEquals,
 }
 
 impl Display for AssignOperator {
@@ -248,6 +351,8 @@ impl Display for AssignOperator {
             AssignOperator::MinusEquals => write!(f, "-="),
             AssignOperator::MultiplyEquals => write!(f, "*="),
             AssignOperator::DivideEquals => write!(f, "/="),
+            AssignOperator::ModuloEquals => write!(f, "%="),
+            AssignOperator::PowerEquals => write!(f, "**="),
         }
     }
 }
@@ -260,6 +365,8 @@ impl AssignOperator {
             TokenKind::MinusEquals => AssignOperator::MinusEquals,
             TokenKind::MultiplyEquals => AssignOperator::MultiplyEquals,
             TokenKind::DivideEquals => AssignOperator::DivideEquals,
+            TokenKind::ModuloEquals => AssignOperator::ModuloEquals,
+            TokenKind::PowerEquals => AssignOperator::PowerEquals,
             _ => todo!("Proper error"),
         }
     }
@@ -365,28 +472,7 @@ impl BinOperator {
     ///
     /// An unsigned 8-bit integer representing the operator's precedence.
     pub fn precedence(&self) -> u8 {
-        match self.kind {
-            // Highest precedence
-            BinOpKind::Power => 20,
-            BinOpKind::Multiply | BinOpKind::Divide | BinOpKind::Modulo => 19,
-            BinOpKind::Plus | BinOpKind::Minus => 18,
-            BinOpKind::ShiftLeft | BinOpKind::ShiftRight => 17,
-            BinOpKind::BitwiseAnd => 16,
-            BinOpKind::BitwiseXor => 15,
-            BinOpKind::BitwiseOr => 14,
-            // Relational operators
-            BinOpKind::LessThan
-            | BinOpKind::LessThanOrEqual
-            | BinOpKind::GreaterThan
-            | BinOpKind::GreaterThanOrEqual => 13,
-            // Equality operators
-            BinOpKind::Equals | BinOpKind::EqualsEquals | BinOpKind::BangEquals => 12,
-            // Logical operators
-            BinOpKind::And => 11,
-            BinOpKind::Or => 10,
-            // Increment/Decrement operators
-            BinOpKind::Increment | BinOpKind::Decrement => 9,
-        }
+        self.kind.precedence()
     }
 
     /// Returns the associativity of the operator.
@@ -397,10 +483,7 @@ impl BinOperator {
     ///
     /// A `BinOpAssociativity` enum indicating the associativity.
     pub fn associativity(&self) -> BinOpAssociativity {
-        match self.kind {
-            BinOpKind::Power => BinOpAssociativity::Right,
-            _ => BinOpAssociativity::Left,
-        }
+        self.kind.associativity()
     }
 }
 
@@ -454,6 +537,31 @@ pub enum Expr {
     ThenElse(ThenElse),
     /// Object expression.
     Object(ObjectExpr),
+    /// Interpolated string literal. (e.g., `f"Hello {name}"`)
+    Interpolated(Interpolated),
+}
+
+/// A piece of an interpolated string literal.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Segment {
+    /// Text outside of the braces.
+    Text(String),
+    /// An expression between braces, its value is converted to a string.
+    Expr(Box<Expr>),
+}
+
+/// Represents an interpolated string literal in the AST.
+///
+/// # Examples
+/// ```roan
+/// let greeting = f"Hello {name}, you are {age}"
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Interpolated {
+    /// The text and expressions of the literal, in order.
+    pub segments: Vec<Segment>,
+    /// The token of the whole literal.
+    pub token: Token,
 }
 
 /// Represents an object expression in the AST.
@@ -541,7 +649,8 @@ impl GetSpan for AccessExpr {
     fn span(&self) -> TextSpan {
         let base_span = self.base.span();
         let access_span = match &self.access {
-            AccessKind::Field(_) => self.token.span.clone(), // Span includes the '.' and the field name
+            // Span includes the '.' and the field name, or the method call
+            AccessKind::Field(field) => self.token.span.to(&field.span()),
             AccessKind::Index(index_expr) => {
                 TextSpan::combine(vec![self.token.span.clone(), index_expr.span()]).unwrap()
             } // Span includes '[' , index, and ']'
@@ -566,16 +675,13 @@ impl GetSpan for Expr {
             Expr::Unary(u) => u.clone().token.span,
             Expr::Variable(v) => v.clone().token.span,
             Expr::Parenthesized(p) => p.expr.span(),
-            Expr::Call(c) => c.clone().token.span,
+            Expr::Call(c) => c.span(),
             Expr::Assign(a) => {
                 let left = a.left.span();
                 let right = a.right.span();
                 TextSpan::combine(vec![left, right]).unwrap()
             }
-            Expr::Vec(v) => {
-                let spans: Vec<TextSpan> = v.exprs.iter().map(|e| e.span()).collect();
-                TextSpan::combine(spans).unwrap()
-            }
+            Expr::Vec(v) => v.brackets.0.span.to(&v.brackets.1.span),
             Expr::Access(a) => a.span(),
             Expr::Spread(s) => {
                 TextSpan::combine(vec![s.token.span.clone(), s.expr.span()]).unwrap()
@@ -583,14 +689,22 @@ impl GetSpan for Expr {
             Expr::Null(t) => t.span.clone(),
             Expr::StructConstructor(s) => s.token.span.clone(),
             Expr::ThenElse(t) => t.span(),
-            Expr::Object(o) => {
-                TextSpan::combine(vec![o.braces.0.span.clone(), o.braces.1.span.clone()]).unwrap()
-            }
+            Expr::Object(o) => o.braces.0.span.to(&o.braces.1.span),
+            Expr::Interpolated(i) => i.token.span.clone(),
         }
     }
 }
 
 impl Expr {
+    /// Calls `f` with the expression and then with every expression nested in it, parents before
+    /// their children and children in source order.
+    ///
+    /// A shorthand for [walk_expr](crate::walk_expr) with a [Visitor](crate::Visitor) that only
+    /// implements `visit_expr`.
+    pub fn walk<F: FnMut(&Expr)>(&self, f: &mut F) {
+        walk_expr(&mut ExprVisitor(f), self);
+    }
+
     /// Converts the expression into a statement.
     ///
     /// # Returns
@@ -900,12 +1014,13 @@ impl Expr {
     /// # Arguments
     ///
     /// * `exprs` - The list of expressions in the vector.
+    /// * `brackets` - The tokens representing the opening and closing brackets.
     ///
     /// # Returns
     ///
     /// A new `Expr::Vec` variant.
-    pub fn new_vec(exprs: Vec<Expr>) -> Self {
-        Expr::Vec(VecExpr { exprs })
+    pub fn new_vec(exprs: Vec<Expr>, brackets: (Token, Token)) -> Self {
+        Expr::Vec(VecExpr { exprs, brackets })
     }
 
     /// Creates a new struct constructor expression.
@@ -960,4 +1075,49 @@ impl Expr {
     pub fn new_object(fields: IndexMap<String, Expr>, braces: (Token, Token)) -> Self {
         Expr::Object(ObjectExpr { fields, braces })
     }
+
+    /// Creates a new interpolated string expression.
+    ///
+    /// # Arguments
+    /// * `token` - The token of the whole literal.
+    /// * `segments` - The text and expressions of the literal.
+    ///
+    /// # Returns
+    ///
+    /// A new `Expr::Interpolated` variant.
+    pub fn new_interpolated(token: Token, segments: Vec<Segment>) -> Self {
+        Expr::Interpolated(Interpolated { segments, token })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_bitwise_operator() {
+        assert!(BinOpKind::BitwiseAnd.is_bitwise_operator());
+        assert!(BinOpKind::BitwiseXor.is_bitwise_operator());
+        assert!(BinOpKind::ShiftRight.is_bitwise_operator());
+        assert!(!BinOpKind::And.is_bitwise_operator());
+        assert!(!BinOpKind::Plus.is_bitwise_operator());
+    }
+
+    #[test]
+    fn test_is_comparison_operator() {
+        assert!(BinOpKind::LessThan.is_comparison_operator());
+        assert!(BinOpKind::GreaterThanOrEqual.is_comparison_operator());
+        assert!(BinOpKind::EqualsEquals.is_comparison_operator());
+        assert!(BinOpKind::BangEquals.is_comparison_operator());
+        assert!(!BinOpKind::Or.is_comparison_operator());
+        assert!(!BinOpKind::BitwiseAnd.is_comparison_operator());
+    }
+
+    #[test]
+    fn test_is_logical_operator() {
+        assert!(BinOpKind::And.is_logical_operator());
+        assert!(BinOpKind::Or.is_logical_operator());
+        assert!(!BinOpKind::BitwiseOr.is_logical_operator());
+        assert!(!BinOpKind::Equals.is_logical_operator());
+    }
 }
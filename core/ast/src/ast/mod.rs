@@ -49,6 +49,226 @@ impl Ast {
     pub fn statements(&self) -> &Vec<Stmt> {
         &self.stmts
     }
+
+    /// Walks every statement and expression of the AST, calling the hooks of `visitor`.
+    ///
+    /// Parents are visited before their children and children in source order.
+    pub fn walk(&self, visitor: &mut dyn Visitor) {
+        for stmt in self.stmts.iter() {
+            walk_stmt(visitor, stmt);
+        }
+    }
+
+    /// Returns the top-level functions marked with the `@test` attribute, in source order.
+    pub fn test_functions(&self) -> Vec<&Fn> {
+        self.stmts
+            .iter()
+            .filter_map(|stmt| match stmt {
+                Stmt::Fn(func) if func.has_attribute("test") => Some(func),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+/// Hooks called for the nodes of an AST by [Ast::walk].
+///
+/// Every hook does nothing by default, so a visitor only implements the ones for the nodes it's
+/// interested in. The walk recurses into child nodes on its own, hooks don't have to.
+pub trait Visitor {
+    /// Called for every statement, before the hook for its kind.
+    fn visit_stmt(&mut self, _stmt: &Stmt) {}
+
+    /// Called for every expression, including the ones nested in other expressions.
+    fn visit_expr(&mut self, _expr: &Expr) {}
+
+    /// Called for functions and for the methods of traits and impl blocks.
+    fn visit_fn(&mut self, _func: &Fn) {}
+
+    /// Called for every block, including the bodies of functions, before its statements.
+    fn visit_block(&mut self, _block: &Block) {}
+
+    fn visit_struct(&mut self, _struct_def: &Struct) {}
+
+    fn visit_trait_def(&mut self, _trait_def: &TraitDef) {}
+
+    fn visit_struct_impl(&mut self, _impl_stmt: &StructImpl) {}
+
+    fn visit_trait_impl(&mut self, _impl_stmt: &TraitImpl) {}
+
+    fn visit_let(&mut self, _let_stmt: &Let) {}
+
+    fn visit_use(&mut self, _use_stmt: &Use) {}
+
+    fn visit_const(&mut self, _const_stmt: &Const) {}
+}
+
+/// Visits `stmt` and everything nested in it.
+pub fn walk_stmt(visitor: &mut dyn Visitor, stmt: &Stmt) {
+    visitor.visit_stmt(stmt);
+
+    match stmt {
+        Stmt::Expr(expr) => walk_expr(visitor, expr),
+        Stmt::Use(use_stmt) => visitor.visit_use(use_stmt),
+        Stmt::Block(block) => walk_block(visitor, block),
+        Stmt::If(if_stmt) => {
+            walk_expr(visitor, &if_stmt.condition);
+            walk_block(visitor, &if_stmt.then_block);
+            for else_if in if_stmt.else_ifs.iter() {
+                walk_expr(visitor, &else_if.condition);
+                walk_block(visitor, &else_if.block);
+            }
+            // The condition of the else block is a copy of the `if` condition
+            if let Some(else_block) = &if_stmt.else_block {
+                walk_block(visitor, &else_block.block);
+            }
+        }
+        Stmt::Return(return_stmt) => {
+            if let Some(expr) = &return_stmt.expr {
+                walk_expr(visitor, expr);
+            }
+        }
+        Stmt::Fn(func) => walk_fn(visitor, func),
+        Stmt::Let(let_stmt) => {
+            visitor.visit_let(let_stmt);
+            walk_expr(visitor, &let_stmt.initializer);
+        }
+        Stmt::Throw(throw) => {
+            if let Some(value) = &throw.value {
+                walk_expr(visitor, value);
+            }
+        }
+        Stmt::Try(try_stmt) => {
+            walk_block(visitor, &try_stmt.try_block);
+            walk_block(visitor, &try_stmt.catch_block);
+            if let Some(finally_block) = &try_stmt.finally_block {
+                walk_block(visitor, finally_block);
+            }
+        }
+        Stmt::Break(_) | Stmt::Continue(_) => {}
+        Stmt::Loop(loop_stmt) => walk_block(visitor, &loop_stmt.block),
+        Stmt::While(while_stmt) => {
+            walk_expr(visitor, &while_stmt.condition);
+            walk_block(visitor, &while_stmt.block);
+        }
+        Stmt::For(for_stmt) => {
+            walk_expr(visitor, &for_stmt.iterable);
+            walk_block(visitor, &for_stmt.block);
+        }
+        Stmt::Struct(struct_def) => visitor.visit_struct(struct_def),
+        Stmt::TraitDef(trait_def) => {
+            visitor.visit_trait_def(trait_def);
+            for method in trait_def.methods.iter() {
+                walk_fn(visitor, method);
+            }
+        }
+        Stmt::StructImpl(impl_stmt) => {
+            visitor.visit_struct_impl(impl_stmt);
+            for method in impl_stmt.methods.iter() {
+                walk_fn(visitor, method);
+            }
+        }
+        Stmt::TraitImpl(impl_stmt) => {
+            visitor.visit_trait_impl(impl_stmt);
+            for method in impl_stmt.methods.iter() {
+                walk_fn(visitor, method);
+            }
+        }
+        Stmt::Const(const_stmt) => {
+            visitor.visit_const(const_stmt);
+            walk_expr(visitor, &const_stmt.expr);
+        }
+    }
+}
+
+/// Visits `expr` and every expression nested in it.
+pub fn walk_expr(visitor: &mut dyn Visitor, expr: &Expr) {
+    visitor.visit_expr(expr);
+
+    match expr {
+        Expr::Literal(_) | Expr::Variable(_) | Expr::Null(_) => {}
+        Expr::Binary(binary) => {
+            walk_expr(visitor, &binary.left);
+            walk_expr(visitor, &binary.right);
+        }
+        Expr::Unary(unary) => walk_expr(visitor, &unary.expr),
+        Expr::Parenthesized(parenthesized) => walk_expr(visitor, &parenthesized.expr),
+        Expr::Call(call) => {
+            for arg in call.args.iter() {
+                walk_expr(visitor, arg);
+            }
+        }
+        Expr::Assign(assign) => {
+            walk_expr(visitor, &assign.left);
+            walk_expr(visitor, &assign.right);
+        }
+        Expr::Vec(vec) => {
+            for expr in vec.exprs.iter() {
+                walk_expr(visitor, expr);
+            }
+        }
+        Expr::Access(access) => {
+            walk_expr(visitor, &access.base);
+            match &access.access {
+                AccessKind::Field(expr)
+                | AccessKind::Index(expr)
+                | AccessKind::StaticMethod(expr) => walk_expr(visitor, expr),
+            }
+        }
+        Expr::Spread(spread) => walk_expr(visitor, &spread.expr),
+        Expr::StructConstructor(constructor) => {
+            for value in constructor.fields.values() {
+                walk_expr(visitor, value);
+            }
+        }
+        Expr::ThenElse(then_else) => {
+            walk_expr(visitor, &then_else.condition);
+            walk_expr(visitor, &then_else.then_expr);
+            walk_expr(visitor, &then_else.else_expr);
+        }
+        Expr::Object(object) => {
+            for value in object.fields.values() {
+                walk_expr(visitor, value);
+            }
+        }
+        Expr::Interpolated(interpolated) => {
+            for segment in interpolated.segments.iter() {
+                if let Segment::Expr(expr) = segment {
+                    walk_expr(visitor, expr);
+                }
+            }
+        }
+    }
+}
+
+/// [Visitor] calling a closure for every expression, used by [Expr::walk].
+struct ExprVisitor<F>(F);
+
+impl<F: FnMut(&Expr)> Visitor for ExprVisitor<F> {
+    fn visit_expr(&mut self, expr: &Expr) {
+        (self.0)(expr)
+    }
+}
+
+/// [Visitor] calling a closure for every statement, used by [Stmt::walk].
+struct StmtVisitor<F>(F);
+
+impl<F: FnMut(&Stmt)> Visitor for StmtVisitor<F> {
+    fn visit_stmt(&mut self, stmt: &Stmt) {
+        (self.0)(stmt)
+    }
+}
+
+fn walk_fn(visitor: &mut dyn Visitor, func: &Fn) {
+    visitor.visit_fn(func);
+    walk_block(visitor, &func.body);
+}
+
+fn walk_block(visitor: &mut dyn Visitor, block: &Block) {
+    visitor.visit_block(block);
+    for stmt in block.stmts.iter() {
+        walk_stmt(visitor, stmt);
+    }
 }
 
 /// A trait to get the source code position (span) of a node in the AST.
@@ -59,3 +279,92 @@ pub trait GetSpan {
     /// Returns the `TextSpan` that shows where this AST node is in the source code.
     fn span(&self) -> TextSpan;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{source::Source, Lexer, Parser};
+
+    #[derive(Default)]
+    struct CounterVisitor {
+        calls: Vec<String>,
+        functions: usize,
+    }
+
+    impl Visitor for CounterVisitor {
+        fn visit_expr(&mut self, expr: &Expr) {
+            if let Expr::Call(call) = expr {
+                self.calls.push(call.callee.clone());
+            }
+        }
+
+        fn visit_fn(&mut self, _func: &Fn) {
+            self.functions += 1;
+        }
+    }
+
+    #[test]
+    fn test_visitor_counts_calls() {
+        let tokens = Lexer::new(Source::from_string(
+            "fn main() {\n\
+                 let x = add(one(), 2);\n\
+                 if x > 1 { print(x); } else { print(items.len()); }\n\
+             }\n\
+             impl Point { fn new() -> Point { return Point { x: make() }; } }\n\
+             main();"
+                .to_string(),
+        ))
+        .lex(false)
+        .unwrap();
+        let ast = Parser::new(tokens).parse().unwrap();
+
+        let mut visitor = CounterVisitor::default();
+        ast.walk(&mut visitor);
+
+        assert_eq!(
+            visitor.calls,
+            ["add", "one", "print", "print", "len", "make", "main"]
+        );
+        assert_eq!(visitor.functions, 2);
+    }
+
+    #[test]
+    fn test_walk_counts_calls() {
+        let tokens = Lexer::new(Source::from_string(
+            "fn main() {\n\
+                 let x = add(one(), [two()]);\n\
+                 while x > 1 { x = dec(x); }\n\
+                 items.push(make(f\"{three()}\"));\n\
+             }\n\
+             main();"
+                .to_string(),
+        ))
+        .lex(false)
+        .unwrap();
+        let ast = Parser::new(tokens).parse().unwrap();
+
+        let mut stmts = 0;
+        let mut calls = vec![];
+        for stmt in ast.stmts.iter() {
+            stmt.walk(&mut |stmt| {
+                stmts += 1;
+                let expr = match stmt {
+                    Stmt::Expr(expr) => expr,
+                    Stmt::Let(let_stmt) => &let_stmt.initializer,
+                    _ => return,
+                };
+                expr.walk(&mut |expr| {
+                    if let Expr::Call(call) = expr {
+                        calls.push(call.callee.clone());
+                    }
+                });
+            });
+        }
+
+        assert_eq!(stmts, 6);
+        assert_eq!(
+            calls,
+            ["add", "one", "two", "dec", "push", "make", "three", "main"]
+        );
+    }
+}
@@ -1,4 +1,7 @@
-use crate::{ast::expr::Expr, GetSpan, Token};
+use crate::{
+    ast::{expr::Expr, walk_stmt, StmtVisitor},
+    GetSpan, Token,
+};
 use indexmap::IndexMap;
 use roan_error::TextSpan;
 use std::fmt::{Debug, Display, Formatter};
@@ -34,6 +37,8 @@ pub enum Stmt {
     Loop(Loop),
     /// A `while` statement to create a loop with a condition.
     While(While),
+    /// A `for` statement to iterate over the elements of a value.
+    For(For),
     /// A struct definition.
     Struct(Struct),
     /// A trait definition.
@@ -46,11 +51,47 @@ pub enum Stmt {
     Const(Const),
 }
 
+/// An attribute written before a declaration, e.g. `@test` or `@deprecated("use new")`.
+///
+/// Attributes don't change how a declaration is executed, they are hints for tooling such as the
+/// test runner.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Attribute {
+    /// The token representing the `@`.
+    pub at_token: Token,
+    /// The name of the attribute.
+    pub name: Token,
+    /// The arguments passed to the attribute, empty if it has no parentheses.
+    pub args: Vec<Expr>,
+}
+
+impl GetSpan for Attribute {
+    fn span(&self) -> TextSpan {
+        let mut spans = vec![self.at_token.span.clone(), self.name.span.clone()];
+        spans.extend(self.args.iter().map(|arg| arg.span()));
+
+        TextSpan::combine(spans).unwrap()
+    }
+}
+
+/// Returns `true` if one of `attributes` is called `name`.
+fn has_attribute(attributes: &[Attribute], name: &str) -> bool {
+    attributes.iter().any(|attr| attr.name.literal() == name)
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct Const {
     pub expr: Box<Expr>,
     pub ident: Token,
     pub public: bool,
+    pub attributes: Vec<Attribute>,
+}
+
+impl Const {
+    /// Returns `true` if the const has an attribute called `name`.
+    pub fn has_attribute(&self, name: &str) -> bool {
+        has_attribute(&self.attributes, name)
+    }
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -61,6 +102,14 @@ pub struct Struct {
     pub public: bool,
     pub impls: Vec<StructImpl>,
     pub trait_impls: Vec<TraitImpl>,
+    pub attributes: Vec<Attribute>,
+}
+
+impl Struct {
+    /// Returns `true` if the struct has an attribute called `name`.
+    pub fn has_attribute(&self, name: &str) -> bool {
+        has_attribute(&self.attributes, name)
+    }
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -106,13 +155,34 @@ pub struct While {
     pub block: Block,
 }
 
+/// Represents a `for` statement in the AST.
+///
+/// The `for` statement iterates over a vector, string, object or iterator and binds every element
+/// to a variable.
+#[derive(Clone, Debug, PartialEq)]
+pub struct For {
+    /// The token corresponding to the `for` keyword in the source code.
+    pub for_token: Token,
+    /// The identifier token for the variable holding the current element, or its key in
+    /// `for key, value in`.
+    pub ident: Token,
+    /// The identifier token for the variable holding the value in `for key, value in`, every
+    /// element is destructured into a key and a value then.
+    pub value_ident: Option<Token>,
+    /// The expression producing the value to iterate over.
+    pub iterable: Box<Expr>,
+    /// The block of code to execute for every element.
+    pub block: Block,
+}
+
 /// Represents a `throw` statement in the AST.
 ///
-/// The `throw` statement is used to raise an exception with a specified value.
+/// The `throw` statement is used to raise an exception with a specified value. A bare `throw;`
+/// inside a `catch` block re-raises the caught error.
 #[derive(Clone, Debug, PartialEq)]
 pub struct Throw {
-    /// The expression representing the value to be thrown.
-    pub value: Box<Expr>,
+    /// The expression representing the value to be thrown, `None` for a rethrow.
+    pub value: Option<Box<Expr>>,
     /// The token corresponding to the `throw` keyword in the source code.
     pub token: Token,
 }
@@ -131,6 +201,9 @@ pub struct Try {
     pub error_ident: Token,
     /// The block of code to execute if an error is caught.
     pub catch_block: Block,
+    /// The block of code to execute after the `try` and `catch` blocks, whether they succeeded
+    /// or not.
+    pub finally_block: Option<Block>,
 }
 
 /// Represents a variable declaration (`let` statement) in the AST.
@@ -176,6 +249,17 @@ impl From<Expr> for Stmt {
 }
 
 impl Stmt {
+    /// Calls `f` with the statement and then with every statement nested in it: the statements of
+    /// blocks and the bodies of functions and methods. Parents are visited before their children
+    /// and children in source order.
+    ///
+    /// Expressions aren't visited, [Expr::walk] does that. A shorthand for
+    /// [walk_stmt](crate::walk_stmt) with a [Visitor](crate::Visitor) that only implements
+    /// `visit_stmt`.
+    pub fn walk<F: FnMut(&Stmt)>(&self, f: &mut F) {
+        walk_stmt(&mut StmtVisitor(f), self);
+    }
+
     pub fn into_function(self) -> Fn {
         match self {
             Stmt::Fn(f) => f,
@@ -212,6 +296,33 @@ impl Stmt {
         })
     }
 
+    /// Creates a new `For` statement.
+    ///
+    /// # Arguments
+    /// * `for_token` - The token representing the `for` keyword.
+    /// * `ident` - The token representing the loop variable.
+    /// * `value_ident` - The token representing the value variable of `for key, value in`.
+    /// * `iterable` - The expression to iterate over.
+    /// * `block` - The block of code to execute for every element.
+    ///
+    /// # Returns
+    /// A `Stmt::For` variant containing the provided components.
+    pub fn new_for(
+        for_token: Token,
+        ident: Token,
+        value_ident: Option<Token>,
+        iterable: Expr,
+        block: Block,
+    ) -> Self {
+        Stmt::For(For {
+            for_token,
+            ident,
+            value_ident,
+            iterable: Box::new(iterable),
+            block,
+        })
+    }
+
     /// Creates a new `Break` statement.
     ///
     /// # Arguments
@@ -242,6 +353,7 @@ impl Stmt {
     /// * `try_block` - The block of code to execute within the `try`.
     /// * `error_ident` - The identifier token for the caught error.
     /// * `catch_block` - The block of code to execute if an error is caught.
+    /// * `finally_block` - The block of code to execute last, if any.
     ///
     /// # Returns
     ///
@@ -251,12 +363,14 @@ impl Stmt {
         try_block: Block,
         error_ident: Token,
         catch_block: Block,
+        finally_block: Option<Block>,
     ) -> Self {
         Stmt::Try(Try {
             try_token,
             try_block,
             error_ident,
             catch_block,
+            finally_block,
         })
     }
 
@@ -265,14 +379,14 @@ impl Stmt {
     /// # Arguments
     ///
     /// * `token` - The token representing the `throw` keyword.
-    /// * `value` - The expression to be thrown.
+    /// * `value` - The expression to be thrown, `None` to rethrow the caught error.
     ///
     /// # Returns
     ///
     /// A `Stmt::Throw` variant containing the provided value and token.
-    pub fn new_throw(token: Token, value: Expr) -> Self {
+    pub fn new_throw(token: Token, value: Option<Expr>) -> Self {
         Stmt::Throw(Throw {
-            value: Box::new(value),
+            value: value.map(Box::new),
             token,
         })
     }
@@ -309,6 +423,7 @@ impl Stmt {
             public,
             return_type,
             is_static,
+            attributes: vec![],
         })
     }
 
@@ -418,6 +533,7 @@ impl Stmt {
             public,
             impls: vec![],
             trait_impls: vec![],
+            attributes: vec![],
         })
     }
 
@@ -435,6 +551,7 @@ impl Stmt {
             expr,
             ident,
             public,
+            attributes: vec![],
         })
     }
 
@@ -607,6 +724,30 @@ impl Display for TypeKind {
     }
 }
 
+impl Display for TypeAnnotation {
+    /// Formats the annotation using the generic syntax, e.g. `vec<int>?`.
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.kind)?;
+
+        if !self.generics.is_empty() {
+            write!(f, "<")?;
+            for (i, generic) in self.generics.iter().enumerate() {
+                if i > 0 {
+                    write!(f, ", ")?;
+                }
+                write!(f, "{}", generic)?;
+            }
+            write!(f, ">")?;
+        }
+
+        if self.is_nullable {
+            write!(f, "?")?;
+        }
+
+        Ok(())
+    }
+}
+
 impl TypeKind {
     pub fn from_str(s: &str) -> Self {
         match s {
@@ -632,7 +773,7 @@ impl TypeAnnotation {
     pub fn is_generic(&self) -> bool {
         self.generics.len() > 0
     }
-    
+
     pub fn match_generic(&self, generic: TypeKind, args: Vec<TypeKind>) -> bool {
         let generics_names = self
             .generics
@@ -660,7 +801,8 @@ impl GetSpan for TypeAnnotation {
             spans.push(generic.span());
         }
 
-        TextSpan::combine(spans).unwrap()
+        // Annotations created by the passes, e.g. for native functions, have no tokens
+        TextSpan::combine(spans).unwrap_or_default()
     }
 }
 
@@ -683,6 +825,15 @@ pub struct Fn {
     pub return_type: Option<TypeAnnotation>,
     /// Indicates whether the function is static.
     pub is_static: bool,
+    /// The attributes written before the function.
+    pub attributes: Vec<Attribute>,
+}
+
+impl Fn {
+    /// Returns `true` if the function has an attribute called `name`.
+    pub fn has_attribute(&self, name: &str) -> bool {
+        has_attribute(&self.attributes, name)
+    }
 }
 
 /// Represents an `if` statement in the AST.
@@ -735,6 +886,8 @@ pub struct Use {
 pub struct Block {
     /// The list of statements contained within the block.
     pub stmts: Vec<Stmt>,
+    /// Span from the opening brace of the block to its closing one.
+    pub span: TextSpan,
 }
 
 impl Debug for Block {
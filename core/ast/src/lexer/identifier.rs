@@ -33,6 +33,7 @@ impl Identifier {
             "throw" => TokenKind::Throw,
             "try" => TokenKind::Try,
             "catch" => TokenKind::Catch,
+            "finally" => TokenKind::Finally,
             "loop" => TokenKind::Loop,
             "struct" => TokenKind::Struct,
             "impl" => TokenKind::Impl,
@@ -20,6 +20,8 @@ pub struct Lexer {
     pub source: Source,
     pub tokens: Vec<Token>,
     pub position: Position,
+    /// The characters of the source, [Position::index] is an index into it.
+    chars: Vec<char>,
 }
 
 impl Lexer {
@@ -40,6 +42,7 @@ impl Lexer {
     /// ```
     pub fn new(source: Source) -> Self {
         Self {
+            chars: source.chars().collect(),
             source,
             tokens: vec![],
             position: Position::new(1, 0, 0),
@@ -53,46 +56,82 @@ impl Lexer {
     /// During the lexing process, the lexer will consume the source code character by character
     /// and convert it into a list of tokens. The lexer will skip whitespace and comments.
     ///
-    /// When EOF is reached, the lexer will return the list of tokens.
+    /// When EOF is reached, the lexer will return the list of tokens, leaving
+    /// [Lexer::tokens] empty.
     pub fn lex(&mut self, lex_comments: bool) -> Result<Vec<Token>> {
-        loop {
-            let token = self.next_token()?;
-
-            if let Some(token) = token {
-                if (token.kind == TokenKind::Comment && !lex_comments)
-                    || token.kind == TokenKind::Whitespace
-                {
-                    continue;
-                }
+        while let Some(token) = self.lex_token(lex_comments)? {
+            self.tokens.push(token);
+        }
 
-                if token.kind == TokenKind::EOF {
-                    break;
-                }
+        Ok(std::mem::take(&mut self.tokens))
+    }
 
-                self.tokens.push(token);
-            } else {
-                break;
+    /// Lex the next token, skipping whitespace and comments.
+    ///
+    /// Unlike [Lexer::lex], the token isn't stored in the lexer, so the source can be lexed one
+    /// token at a time, e.g. while the user is typing.
+    ///
+    /// Returns `None` when EOF is reached.
+    ///
+    /// # Example
+    /// ```rust
+    /// use roan_ast::{Lexer, TokenKind};
+    /// use roan_ast::source::Source;
+    /// let mut lexer = Lexer::new(Source::from_string("let x".to_string()));
+    ///
+    /// assert_eq!(lexer.lex_one().unwrap().unwrap().kind, TokenKind::Let);
+    /// assert_eq!(lexer.lex_one().unwrap().unwrap().literal(), "x");
+    /// assert!(lexer.lex_one().unwrap().is_none());
+    /// ```
+    pub fn lex_one(&mut self) -> Result<Option<Token>> {
+        self.lex_token(false)
+    }
+
+    /// Lex the next token that isn't whitespace, or a comment unless `lex_comments` is set.
+    fn lex_token(&mut self, lex_comments: bool) -> Result<Option<Token>> {
+        self.skip_shebang();
+
+        while let Some(token) = self.next_token()? {
+            match token.kind {
+                TokenKind::Whitespace => continue,
+                TokenKind::Comment if !lex_comments => continue,
+                TokenKind::EOF => break,
+                _ => return Ok(Some(token)),
             }
         }
 
-        Ok(self.tokens.clone())
+        Ok(None)
+    }
+
+    /// Skip a `#!` line at the very start of the source, e.g. `#!/usr/bin/env roan`.
+    ///
+    /// The characters are consumed one by one, so positions of later tokens still count the
+    /// skipped line.
+    fn skip_shebang(&mut self) {
+        if self.position.index != 0 || !self.source.content().starts_with("#!") {
+            return;
+        }
+
+        while let Some(c) = self.current() {
+            if c == '\n' {
+                break;
+            }
+            self.consume();
+        }
     }
 
     /// Check if the lexer has reached the end of the source code.
     pub fn is_eof(&self) -> bool {
-        self.position.index >= self.source.len()
+        self.position.index >= self.chars.len()
     }
 
     /// Get the current character in the source code.
     pub fn current(&mut self) -> Option<char> {
-        self.source.chars().nth(self.position.index)
+        self.chars.get(self.position.index).copied()
     }
 
     /// Consume the current character and move to the next one.
     pub fn consume(&mut self) -> Option<char> {
-        if self.position.index >= self.source.len() {
-            return None;
-        }
         let c = self.current();
 
         self.update_position(c?);
@@ -128,11 +167,23 @@ impl Lexer {
 
     /// Peek at the next character in the source code.
     pub fn peek(&self) -> Option<char> {
-        if self.position.index + 1 >= self.source.len() {
-            None
-        } else {
-            self.source.chars().nth(self.position.index + 1)
-        }
+        self.chars.get(self.position.index + 1).copied()
+    }
+
+    /// Returns the characters of the source from the char index `start` up to `end`.
+    pub(crate) fn get_between(&self, start: usize, end: usize) -> String {
+        self.chars[start.min(end)..end.min(self.chars.len())]
+            .iter()
+            .collect()
+    }
+
+    /// Returns `true` if the source continues with `s` at the current position.
+    pub(crate) fn at(&self, s: &str) -> bool {
+        self.chars[self.position.index.min(self.chars.len())..]
+            .iter()
+            .copied()
+            .take(s.chars().count())
+            .eq(s.chars())
     }
 
     /// Get the next token in the source code.
@@ -154,6 +205,8 @@ impl Lexer {
             }
 
             _ if c == '"' => StringLiteral::lex_string(self)?,
+            'f' if self.peek() == Some('"') => StringLiteral::lex_interpolated(self)?,
+            'r' if self.peek() == Some('"') => StringLiteral::lex_raw(self)?,
             _ if c.is_ascii_digit() => NumberLiteral::lex_number(self, c)?,
             _ if c == '\'' => TokenKind::Char(self.parse_char()?),
 
@@ -221,7 +274,12 @@ impl Lexer {
                     '*' => {
                         if self.match_next('*') {
                             self.consume();
-                            TokenKind::DoubleAsterisk
+                            if self.match_next('=') {
+                                self.consume();
+                                TokenKind::PowerEquals
+                            } else {
+                                TokenKind::DoubleAsterisk
+                            }
                         } else if self.match_next('=') {
                             self.consume();
                             TokenKind::MultiplyEquals
@@ -229,7 +287,9 @@ impl Lexer {
                             TokenKind::Asterisk
                         }
                     }
-                    '%' => TokenKind::Percent,
+                    '%' => {
+                        self.lex_potential_double('=', TokenKind::Percent, TokenKind::ModuloEquals)
+                    }
                     '^' => TokenKind::Caret,
                     '!' => self.lex_potential_double('=', TokenKind::Bang, TokenKind::BangEquals),
                     '=' => {
@@ -261,6 +321,7 @@ impl Lexer {
                         }
                     }
                     '?' => TokenKind::QuestionMark,
+                    '@' => TokenKind::At,
                     '&' => self.lex_potential_double('&', TokenKind::Ampersand, TokenKind::And),
                     '|' => self.lex_potential_double('|', TokenKind::Pipe, TokenKind::Or),
                     _ => {
@@ -279,7 +340,7 @@ impl Lexer {
         };
 
         let end_pos = self.position;
-        let literal = self.source.get_between(start.index, end_pos.index);
+        let literal = self.get_between(start.index, end_pos.index);
         Ok(Some(Token::new(
             kind,
             TextSpan::new(start, end_pos, literal),
@@ -384,7 +445,8 @@ pub enum NumberType {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::source::Source;
+    use crate::{lexer::token::StringPart, source::Source};
+    use roan_error::error::RoanError;
 
     macro_rules! test_tokens {
         ($source:expr, $expected:expr) => {{
@@ -493,7 +555,7 @@ mod tests {
             ),
             // All Multi-Character Operators
             (
-                "== != <= >= ++ -- += -= *= /= && || ::",
+                "== != <= >= ++ -- += -= *= /= %= **= && || ::",
                 vec![
                     TokenKind::EqualsEquals,
                     TokenKind::BangEquals,
@@ -505,6 +567,8 @@ mod tests {
                     TokenKind::MinusEquals,
                     TokenKind::MultiplyEquals,
                     TokenKind::DivideEquals,
+                    TokenKind::ModuloEquals,
+                    TokenKind::PowerEquals,
                     TokenKind::And,
                     TokenKind::Or,
                     TokenKind::DoubleColon,
@@ -538,6 +602,19 @@ mod tests {
             ("0b1010", vec![TokenKind::Integer(10)]),
             ("0o755", vec![TokenKind::Integer(493)]),
             ("0xdeadbeef", vec![TokenKind::Integer(0xdeadbeef)]),
+            ("0.5", vec![TokenKind::Float(0.5)]),
+            ("5f", vec![TokenKind::Float(5.0)]),
+            ("3.0f", vec![TokenKind::Float(3.0)]),
+            ("10i", vec![TokenKind::Integer(10)]),
+            (
+                "v[0]",
+                vec![
+                    TokenKind::Identifier, // v
+                    TokenKind::LeftBracket,
+                    TokenKind::Integer(0),
+                    TokenKind::RightBracket,
+                ],
+            ),
             // Complex Expressions
             (
                 "fn add(a, b) -> a + b;",
@@ -612,9 +689,176 @@ mod tests {
 
     #[test]
     fn test_invalid_token() {
-        let source = Source::from_string(r#"@@"#.to_string());
+        let source = Source::from_string(r#"``"#.to_string());
         let mut lexer = Lexer::new(source);
         let result = lexer.lex(false);
         assert!(result.is_err(), "Expected an error for invalid tokens");
     }
+
+    #[test]
+    fn test_invalid_number_suffix() {
+        for src in ["5x", "3.5i", "10ff", "2_000"] {
+            let source = Source::from_string(src.to_string());
+            assert!(Lexer::new(source).lex(false).is_err(), "{}", src);
+        }
+    }
+
+    #[test]
+    fn test_token_kind_hash() {
+        use std::{
+            collections::HashSet,
+            hash::{BuildHasher, RandomState},
+        };
+
+        let interpolated =
+            |text: &str| TokenKind::InterpolatedString(vec![StringPart::Text(text.to_string())]);
+        let kinds: HashSet<TokenKind> = [
+            TokenKind::Float(1.5),
+            TokenKind::Float(1.5),
+            TokenKind::Float(f64::NAN),
+            TokenKind::Float(f64::NAN),
+            TokenKind::Integer(1),
+            TokenKind::Fn,
+            TokenKind::Fn,
+            interpolated("a"),
+            interpolated("a"),
+            interpolated("b"),
+        ]
+        .into_iter()
+        .collect();
+
+        assert_eq!(kinds.len(), 6);
+        assert_eq!(TokenKind::Float(f64::NAN), TokenKind::Float(f64::NAN));
+        assert_ne!(TokenKind::Float(0.0), TokenKind::Float(-0.0));
+
+        // The parts are hashed, not only the kind of the token
+        let state = RandomState::new();
+        assert_ne!(
+            state.hash_one(interpolated("a")),
+            state.hash_one(interpolated("b"))
+        );
+    }
+
+    #[test]
+    fn test_interpolated_string() {
+        let source = Source::from_string(r#"f"{{a}} {x + 1}}}""#.to_string());
+        let tokens = Lexer::new(source).lex(false).unwrap();
+
+        let TokenKind::InterpolatedString(parts) = &tokens[0].kind else {
+            panic!("Expected an interpolated string, got {:?}", tokens[0].kind);
+        };
+        assert_eq!(parts.len(), 3);
+        assert_eq!(parts[0], StringPart::Text("{a} ".to_string()));
+        assert_eq!(parts[2], StringPart::Text("}".to_string()));
+        match &parts[1] {
+            StringPart::Expr(tokens, span) => {
+                assert_eq!(tokens.len(), 3);
+                assert_eq!(span.literal, "{x + 1}");
+                assert_eq!(span.start.column, 8);
+            }
+            part => panic!("Expected an expression, got {:?}", part),
+        }
+
+        for src in [r#"f"a } b""#, r#"f"a {x""#] {
+            let source = Source::from_string(src.to_string());
+            assert!(Lexer::new(source).lex(false).is_err(), "{}", src);
+        }
+    }
+
+    #[test]
+    fn test_raw_and_multiline_strings() {
+        let source = Source::from_string(
+            "r\"\\n\" r\"C:\\path\\no\\escapes\" \"\"\"first\n  \"second\"\\tline\n\"\"\" x"
+                .to_string(),
+        );
+        let tokens: Vec<Token> = Lexer::new(source)
+            .lex(false)
+            .unwrap()
+            .into_iter()
+            .filter(|token| token.kind != TokenKind::Whitespace)
+            .collect();
+
+        assert_eq!(tokens[0].kind, TokenKind::String("\\n".to_string()));
+        assert_eq!(
+            tokens[1].kind,
+            TokenKind::String(r"C:\path\no\escapes".to_string())
+        );
+        assert_eq!(
+            tokens[2].kind,
+            TokenKind::String("first\n  \"second\"\tline\n".to_string())
+        );
+        assert_eq!(tokens[3].span.start.line, 3);
+        assert_eq!(tokens[3].span.start.column, 4);
+    }
+
+    #[test]
+    fn test_unterminated_string() {
+        for (src, opening, column) in [
+            ("let a = r\"abc", "r\"", 8),
+            ("let a = \"\"\"abc\n\"\"", "\"\"\"", 8),
+            ("let a = \"\"\"abc\\", "\"\"\"", 8),
+        ] {
+            let source = Source::from_string(src.to_string());
+            let err = Lexer::new(source).lex(false).unwrap_err();
+
+            match err.downcast::<RoanError>() {
+                Ok(RoanError::UnterminatedString(span)) => {
+                    assert_eq!(span.literal, opening, "{}", src);
+                    assert_eq!(span.start.column, column, "{}", src);
+                    assert_eq!(span.start.line, 1, "{}", src);
+                }
+                err => panic!("Expected an unterminated string in {}, got {:?}", src, err),
+            }
+        }
+    }
+
+    #[test]
+    fn test_large_source() {
+        let source = Source::from_string("let é = \"ü\"; // ß\n".repeat(100_000));
+        let tokens: Vec<Token> = Lexer::new(source)
+            .lex(false)
+            .unwrap()
+            .into_iter()
+            .filter(|token| token.kind != TokenKind::Whitespace)
+            .collect();
+
+        assert_eq!(tokens.len(), 500_000);
+        let last = tokens.last().unwrap();
+        assert_eq!(last.kind, TokenKind::Semicolon);
+        assert_eq!(last.span.start.line, 100_000);
+        assert_eq!(last.span.start.column, 11);
+        assert_eq!(tokens[1].span.literal, "é");
+        assert_eq!(tokens[3].span.literal, "\"ü\"");
+    }
+
+    #[test]
+    fn test_shebang() {
+        let source = Source::from_string("#!/usr/bin/env roan\nlet x = 1;".to_string());
+        let tokens = Lexer::new(source).lex(false).unwrap();
+
+        assert_eq!(tokens[0].kind, TokenKind::Let);
+        assert_eq!(tokens[0].span.start.line, 2);
+
+        let source = Source::from_string("let x = 1;\n#!/usr/bin/env roan".to_string());
+        assert!(Lexer::new(source).lex(false).is_err());
+    }
+
+    #[test]
+    fn test_lex_one() {
+        let src = "#!/usr/bin/env roan\nlet x = 10; // ten\n";
+        let mut lexer = Lexer::new(Source::from_string(src.to_string()));
+        let tokens = lexer.lex(false).unwrap();
+        assert!(lexer.tokens.is_empty());
+
+        let mut lexer = Lexer::new(Source::from_string(src.to_string()));
+        let mut streamed = vec![];
+        while let Some(token) = lexer.lex_one().unwrap() {
+            streamed.push(token);
+        }
+
+        assert_eq!(streamed, tokens);
+        assert_eq!(streamed.len(), 5);
+        assert!(lexer.tokens.is_empty());
+        assert!(lexer.lex_one().unwrap().is_none());
+    }
 }
@@ -1,5 +1,6 @@
 use crate::{Lexer, TokenKind};
 use anyhow::Result;
+use roan_error::{error::RoanError::InvalidToken, span::TextSpan};
 
 /// The type of number.
 #[derive(Debug)]
@@ -12,78 +13,38 @@ pub enum NumberType {
 pub struct NumberLiteral {}
 
 impl NumberLiteral {
+    /// Lexes a number starting with `c`. Decimal numbers can end with a suffix forcing their type,
+    /// `i` for an int or `f` for a float, any other suffix is an invalid token.
     pub fn lex_number(lexer: &mut Lexer, c: char) -> Result<TokenKind> {
-        let mut number = NumberType::Integer(0);
+        let start = lexer.position;
+        let number;
 
         lexer.consume();
 
-        if c == '0' {
-            if let Some(c) = lexer.consume() {
-                match c {
-                    'x' | 'X' => {
-                        // Hexadecimal
-                        let mut hex = String::new();
-                        while let Some(c) = lexer.current() {
-                            if c.is_digit(16) {
-                                hex.push(c);
-                            } else {
-                                break;
-                            }
-                            lexer.consume();
-                        }
-
-                        number = NumberType::Integer(i64::from_str_radix(&hex, 16)?);
-                    }
-                    'o' | 'O' => {
-                        // Octal
-                        let mut oct = String::new();
-                        while let Some(c) = lexer.current() {
-                            if c.is_digit(8) {
-                                oct.push(c);
-                            } else {
-                                break;
-                            }
-                            lexer.consume();
-                        }
+        let radix = match (c, lexer.current()) {
+            ('0', Some('x' | 'X')) => Some(16),
+            ('0', Some('o' | 'O')) => Some(8),
+            ('0', Some('b' | 'B')) => Some(2),
+            _ => None,
+        };
 
-                        number = NumberType::Integer(i64::from_str_radix(&oct, 8)?);
-                    }
-                    'b' | 'B' => {
-                        // Binary
-                        let mut bin = String::new();
-                        while let Some(c) = lexer.current() {
-                            if c == '0' || c == '1' {
-                                bin.push(c);
-                            } else {
-                                break;
-                            }
-                            lexer.consume();
-                        }
-
-                        number = NumberType::Integer(i64::from_str_radix(&bin, 2)?);
-                    }
-                    c if c.is_digit(10) => {
-                        let mut num_str = String::from("0");
-                        num_str.push(c);
-                        while let Some(c) = lexer.current() {
-                            if c.is_digit(10) {
-                                num_str.push(c);
-                                lexer.consume();
-                            } else {
-                                break;
-                            }
-                        }
+        if let Some(radix) = radix {
+            lexer.consume();
 
-                        number = NumberType::Integer(num_str.parse()?);
-                    }
-                    _ => {
-                        return Ok(TokenKind::Integer(0));
-                    }
+            let mut digits = String::new();
+            while let Some(c) = lexer.current() {
+                if !c.is_digit(radix) {
+                    break;
                 }
+                digits.push(c);
+                lexer.consume();
             }
+
+            number = NumberType::Integer(i64::from_str_radix(&digits, radix)?);
         } else {
             let mut num_str = String::new();
             num_str.push(c);
+            let mut is_float = false;
 
             while let Some(c) = lexer.current() {
                 if c.is_digit(10) {
@@ -100,16 +61,35 @@ impl NumberLiteral {
                             break;
                         }
                     }
-                    number = NumberType::Float(num_str.parse()?);
+                    is_float = true;
                     break;
                 } else {
                     break;
                 }
             }
 
-            if let NumberType::Integer(_) = number {
-                number = NumberType::Integer(num_str.parse()?);
+            // A suffix forces the type of the literal: `5f` is a float and `5i` an int.
+            let mut suffix = String::new();
+            while let Some(c) = lexer.current() {
+                if !c.is_alphanumeric() && c != '_' {
+                    break;
+                }
+                suffix.push(c);
+                lexer.consume();
             }
+
+            number = match (suffix.as_str(), is_float) {
+                ("", false) | ("i", false) => NumberType::Integer(num_str.parse()?),
+                ("", true) | ("f", _) => NumberType::Float(num_str.parse()?),
+                _ => {
+                    let literal = format!("{}{}", num_str, suffix);
+                    return Err(InvalidToken(
+                        literal.clone(),
+                        TextSpan::new(start, lexer.position, literal),
+                    )
+                    .into());
+                }
+            };
         }
 
         Ok(match number {
@@ -1,16 +1,80 @@
-use crate::{Lexer, TokenKind};
+use crate::{lexer::token::StringPart, Lexer, Token, TokenKind};
 use anyhow::Result;
-use roan_error::{error::RoanError::InvalidEscapeSequence, TextSpan};
+use roan_error::{
+    error::RoanError::{InvalidEscapeSequence, InvalidToken, UnexpectedEof, UnterminatedString},
+    TextSpan,
+};
+use std::mem;
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct StringLiteral {}
 
 impl StringLiteral {
     pub fn lex_string(lexer: &mut Lexer) -> Result<TokenKind> {
+        if StringLiteral::at_triple_quote(lexer) {
+            return StringLiteral::lex_multiline(lexer);
+        }
+
         let string = StringLiteral::consume_string(lexer)?;
         Ok(TokenKind::String(string))
     }
 
+    /// Lexes a raw string starting at its `r` prefix, e.g. `r"C:\path"`. Backslashes are kept as
+    /// they are and the string ends at the next quote.
+    pub fn lex_raw(lexer: &mut Lexer) -> Result<TokenKind> {
+        let start = lexer.position;
+        lexer.consume();
+        lexer.consume();
+        let opening = TextSpan::new(start, lexer.position, "r\"".to_string());
+
+        let mut str = String::new();
+        loop {
+            match lexer.consume() {
+                Some('"') => return Ok(TokenKind::String(str)),
+                Some(c) => str.push(c),
+                None => return Err(UnterminatedString(opening).into()),
+            }
+        }
+    }
+
+    /// Lexes a multiline string between `"""`. Its newlines are kept and escapes work like in
+    /// other strings.
+    fn lex_multiline(lexer: &mut Lexer) -> Result<TokenKind> {
+        let start = lexer.position;
+        StringLiteral::consume_triple_quote(lexer);
+        let opening = TextSpan::new(start, lexer.position, "\"\"\"".to_string());
+
+        let mut str = String::new();
+        loop {
+            match lexer.current() {
+                Some('"') if StringLiteral::at_triple_quote(lexer) => {
+                    StringLiteral::consume_triple_quote(lexer);
+                    return Ok(TokenKind::String(str));
+                }
+                Some('\\') => {
+                    if let Some(c) = StringLiteral::consume_escape(lexer)? {
+                        str.push(c);
+                    }
+                }
+                Some(c) => {
+                    str.push(c);
+                    lexer.consume();
+                }
+                None => return Err(UnterminatedString(opening).into()),
+            }
+        }
+    }
+
+    fn at_triple_quote(lexer: &Lexer) -> bool {
+        lexer.at("\"\"\"")
+    }
+
+    fn consume_triple_quote(lexer: &mut Lexer) {
+        for _ in 0..3 {
+            lexer.consume();
+        }
+    }
+
     pub fn consume_string(lexer: &mut Lexer) -> Result<String> {
         let mut str = String::new();
 
@@ -23,23 +87,8 @@ impl StringLiteral {
             }
 
             if c == '\\' {
-                lexer.consume();
-                if let Some(next) = lexer.current() {
-                    match next {
-                        'n' => str.push('\n'),
-                        'r' => str.push('\r'),
-                        't' => str.push('\t'),
-                        '\\' => str.push('\\'),
-                        '"' => str.push('"'),
-                        _ => {
-                            return Err(InvalidEscapeSequence(
-                                next.to_string(),
-                                TextSpan::new(lexer.position, lexer.position, next.to_string()),
-                            )
-                            .into())
-                        }
-                    }
-                    lexer.consume();
+                if let Some(c) = StringLiteral::consume_escape(lexer)? {
+                    str.push(c);
                 }
             } else {
                 str.push(c);
@@ -49,4 +98,128 @@ impl StringLiteral {
 
         Ok(str)
     }
+
+    /// Consumes an escape sequence starting at the backslash and returns the character it stands
+    /// for, `None` if the source ends after the backslash.
+    fn consume_escape(lexer: &mut Lexer) -> Result<Option<char>> {
+        lexer.consume();
+
+        let Some(next) = lexer.current() else {
+            return Ok(None);
+        };
+        let c = match next {
+            'n' => '\n',
+            'r' => '\r',
+            't' => '\t',
+            '\\' => '\\',
+            '"' => '"',
+            _ => {
+                return Err(InvalidEscapeSequence(
+                    next.to_string(),
+                    TextSpan::new(lexer.position, lexer.position, next.to_string()),
+                )
+                .into())
+            }
+        };
+        lexer.consume();
+
+        Ok(Some(c))
+    }
+
+    /// Lexes an interpolated string starting at its `f` prefix, e.g. `f"Hello {name}!"`.
+    ///
+    /// The expressions between braces are lexed like the rest of the source, so their tokens
+    /// point at the right place in it. `{{` and `}}` stand for literal braces.
+    pub fn lex_interpolated(lexer: &mut Lexer) -> Result<TokenKind> {
+        let mut parts = vec![];
+        let mut text = String::new();
+
+        // The `f` prefix and the opening quote
+        lexer.consume();
+        lexer.consume();
+
+        while let Some(c) = lexer.current() {
+            match c {
+                '"' => {
+                    lexer.consume();
+                    break;
+                }
+                '\\' => {
+                    if let Some(c) = StringLiteral::consume_escape(lexer)? {
+                        text.push(c);
+                    }
+                }
+                '{' | '}' if lexer.peek() == Some(c) => {
+                    text.push(c);
+                    lexer.consume();
+                    lexer.consume();
+                }
+                '{' => {
+                    if !text.is_empty() {
+                        parts.push(StringPart::Text(mem::take(&mut text)));
+                    }
+
+                    let start = lexer.position;
+                    lexer.consume();
+                    let tokens = StringLiteral::lex_interpolation(lexer)?;
+                    lexer.consume();
+
+                    let literal = lexer.get_between(start.index, lexer.position.index);
+                    parts.push(StringPart::Expr(
+                        tokens,
+                        TextSpan::new(start, lexer.position, literal),
+                    ));
+                }
+                '}' => {
+                    return Err(InvalidToken(
+                        "}".to_string(),
+                        TextSpan::new(lexer.position, lexer.position, "}".to_string()),
+                    )
+                    .into())
+                }
+                _ => {
+                    text.push(c);
+                    lexer.consume();
+                }
+            }
+        }
+
+        if !text.is_empty() {
+            parts.push(StringPart::Text(text));
+        }
+
+        Ok(TokenKind::InterpolatedString(parts))
+    }
+
+    /// Lexes the tokens of an interpolated expression, up to the brace closing it.
+    fn lex_interpolation(lexer: &mut Lexer) -> Result<Vec<Token>> {
+        let mut tokens = vec![];
+        let mut depth = 0;
+
+        loop {
+            match lexer.current() {
+                None => {
+                    return Err(UnexpectedEof(
+                        "}".to_string(),
+                        TextSpan::new(lexer.position, lexer.position, String::new()),
+                    )
+                    .into())
+                }
+                Some('}') if depth == 0 => return Ok(tokens),
+                _ => {}
+            }
+
+            let Some(token) = lexer.next_token()? else {
+                continue;
+            };
+            match token.kind {
+                TokenKind::Whitespace | TokenKind::Comment => continue,
+                TokenKind::LeftBrace => depth += 1,
+                TokenKind::RightBrace => depth -= 1,
+                _ => {}
+            }
+
+            tokens.push(token);
+        }
+    }
 }
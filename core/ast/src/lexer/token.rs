@@ -2,9 +2,11 @@ use roan_error::span::TextSpan;
 use std::{
     fmt,
     fmt::{Debug, Display, Formatter},
+    hash::{Hash, Hasher},
+    mem,
 };
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct Token {
     pub kind: TokenKind,
     pub span: TextSpan,
@@ -22,6 +24,8 @@ impl Token {
     }
 
     /// Checks if the token is a string.
+    ///
+    /// Interpolated strings are not, their value is only known at runtime.
     pub fn is_string(&self) -> bool {
         matches!(self.kind, TokenKind::String(_))
     }
@@ -60,6 +64,7 @@ impl Display for TokenKind {
             // Literals
             TokenKind::Identifier => write!(f, "Identifier"),
             TokenKind::String(s) => write!(f, "{}", s),
+            TokenKind::InterpolatedString(_) => write!(f, "interpolated string"),
             TokenKind::Float(r) => write!(f, "{}", r),
             TokenKind::Integer(i) => write!(f, "{}", i),
             TokenKind::Char(c) => write!(f, "{}", c),
@@ -81,6 +86,7 @@ impl Display for TokenKind {
             TokenKind::Throw => write!(f, "throw"),
             TokenKind::Try => write!(f, "try"),
             TokenKind::Catch => write!(f, "catch"),
+            TokenKind::Finally => write!(f, "finally"),
             TokenKind::Loop => write!(f, "loop"),
             TokenKind::True => write!(f, "true"),
             TokenKind::False => write!(f, "false"),
@@ -118,9 +124,12 @@ impl Display for TokenKind {
             TokenKind::PlusEquals => write!(f, "+="),
             TokenKind::MultiplyEquals => write!(f, "*="),
             TokenKind::DivideEquals => write!(f, "/="),
+            TokenKind::ModuloEquals => write!(f, "%="),
+            TokenKind::PowerEquals => write!(f, "**="),
             TokenKind::DoubleLessThan => write!(f, "<<"),
             TokenKind::DoubleGreaterThan => write!(f, ">>"),
             TokenKind::QuestionMark => write!(f, "?"),
+            TokenKind::At => write!(f, "@"),
 
             // Others
             TokenKind::EOF => write!(f, "EOF"),
@@ -131,7 +140,16 @@ impl Display for TokenKind {
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+/// A piece of an interpolated string.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum StringPart {
+    /// Text outside of the braces, with escapes resolved and `{{`/`}}` turned into braces.
+    Text(String),
+    /// The tokens of an interpolated expression, and the span of its braces.
+    Expr(Vec<Token>, TextSpan),
+}
+
+#[derive(Debug, Clone)]
 pub enum TokenKind {
     // Separators
     LeftParen,
@@ -152,6 +170,8 @@ pub enum TokenKind {
     // Literals
     Identifier,
     String(String),
+    /// `f"..."`, the text and the interpolated expressions in order.
+    InterpolatedString(Vec<StringPart>),
     Float(f64),
     Integer(i64),
     Char(char),
@@ -173,6 +193,7 @@ pub enum TokenKind {
     Throw,
     Try,
     Catch,
+    Finally,
     Loop,
     True,
     False,
@@ -210,9 +231,12 @@ pub enum TokenKind {
     PlusEquals,        // +=
     MultiplyEquals,    // *=
     DivideEquals,      // /=
+    ModuloEquals,      // %=
+    PowerEquals,       // **=
     DoubleLessThan,    // <<,
     DoubleGreaterThan, // >>,
     QuestionMark,      // ?
+    At,                // @
 
     EOF,
     Whitespace,
@@ -220,6 +244,195 @@ pub enum TokenKind {
     Comment,
 }
 
+/// Floats are compared by their bits so that equality is reflexive and agrees with [Hash].
+impl PartialEq for TokenKind {
+    fn eq(&self, other: &Self) -> bool {
+        // Every variant is listed, so that a new variant carrying a value can't be compared by
+        // its discriminant only
+        match self {
+            TokenKind::String(a) => matches!(other, TokenKind::String(b) if a == b),
+            TokenKind::InterpolatedString(a) => {
+                matches!(other, TokenKind::InterpolatedString(b) if a == b)
+            }
+            TokenKind::Float(a) => {
+                matches!(other, TokenKind::Float(b) if a.to_bits() == b.to_bits())
+            }
+            TokenKind::Integer(a) => matches!(other, TokenKind::Integer(b) if a == b),
+            TokenKind::Char(a) => matches!(other, TokenKind::Char(b) if a == b),
+            TokenKind::LeftParen
+            | TokenKind::RightParen
+            | TokenKind::LeftBrace
+            | TokenKind::RightBrace
+            | TokenKind::LeftBracket
+            | TokenKind::RightBracket
+            | TokenKind::Comma
+            | TokenKind::Dot
+            | TokenKind::Colon
+            | TokenKind::Semicolon
+            | TokenKind::Arrow
+            | TokenKind::DoubleDot
+            | TokenKind::TripleDot
+            | TokenKind::DoubleColon
+            | TokenKind::Identifier
+            | TokenKind::Fn
+            | TokenKind::Let
+            | TokenKind::If
+            | TokenKind::Else
+            | TokenKind::While
+            | TokenKind::For
+            | TokenKind::In
+            | TokenKind::Return
+            | TokenKind::Break
+            | TokenKind::Continue
+            | TokenKind::Use
+            | TokenKind::Pub
+            | TokenKind::From
+            | TokenKind::Throw
+            | TokenKind::Try
+            | TokenKind::Catch
+            | TokenKind::Finally
+            | TokenKind::Loop
+            | TokenKind::True
+            | TokenKind::False
+            | TokenKind::Null
+            | TokenKind::Impl
+            | TokenKind::Struct
+            | TokenKind::Trait
+            | TokenKind::Then
+            | TokenKind::Const
+            | TokenKind::Plus
+            | TokenKind::Minus
+            | TokenKind::Asterisk
+            | TokenKind::Slash
+            | TokenKind::Equals
+            | TokenKind::Ampersand
+            | TokenKind::Pipe
+            | TokenKind::Caret
+            | TokenKind::DoubleAsterisk
+            | TokenKind::Percent
+            | TokenKind::Tilde
+            | TokenKind::GreaterThan
+            | TokenKind::LessThan
+            | TokenKind::GreaterThanEquals
+            | TokenKind::LessThanEquals
+            | TokenKind::EqualsEquals
+            | TokenKind::BangEquals
+            | TokenKind::Bang
+            | TokenKind::And
+            | TokenKind::Or
+            | TokenKind::Increment
+            | TokenKind::Decrement
+            | TokenKind::MinusEquals
+            | TokenKind::PlusEquals
+            | TokenKind::MultiplyEquals
+            | TokenKind::DivideEquals
+            | TokenKind::ModuloEquals
+            | TokenKind::PowerEquals
+            | TokenKind::DoubleLessThan
+            | TokenKind::DoubleGreaterThan
+            | TokenKind::QuestionMark
+            | TokenKind::At
+            | TokenKind::EOF
+            | TokenKind::Whitespace
+            | TokenKind::Bad
+            | TokenKind::Comment => mem::discriminant(self) == mem::discriminant(other),
+        }
+    }
+}
+
+impl Eq for TokenKind {}
+
+impl Hash for TokenKind {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        mem::discriminant(self).hash(state);
+
+        match self {
+            TokenKind::String(s) => s.hash(state),
+            TokenKind::InterpolatedString(parts) => parts.hash(state),
+            TokenKind::Float(f) => f.to_bits().hash(state),
+            TokenKind::Integer(i) => i.hash(state),
+            TokenKind::Char(c) => c.hash(state),
+            TokenKind::LeftParen
+            | TokenKind::RightParen
+            | TokenKind::LeftBrace
+            | TokenKind::RightBrace
+            | TokenKind::LeftBracket
+            | TokenKind::RightBracket
+            | TokenKind::Comma
+            | TokenKind::Dot
+            | TokenKind::Colon
+            | TokenKind::Semicolon
+            | TokenKind::Arrow
+            | TokenKind::DoubleDot
+            | TokenKind::TripleDot
+            | TokenKind::DoubleColon
+            | TokenKind::Identifier
+            | TokenKind::Fn
+            | TokenKind::Let
+            | TokenKind::If
+            | TokenKind::Else
+            | TokenKind::While
+            | TokenKind::For
+            | TokenKind::In
+            | TokenKind::Return
+            | TokenKind::Break
+            | TokenKind::Continue
+            | TokenKind::Use
+            | TokenKind::Pub
+            | TokenKind::From
+            | TokenKind::Throw
+            | TokenKind::Try
+            | TokenKind::Catch
+            | TokenKind::Finally
+            | TokenKind::Loop
+            | TokenKind::True
+            | TokenKind::False
+            | TokenKind::Null
+            | TokenKind::Impl
+            | TokenKind::Struct
+            | TokenKind::Trait
+            | TokenKind::Then
+            | TokenKind::Const
+            | TokenKind::Plus
+            | TokenKind::Minus
+            | TokenKind::Asterisk
+            | TokenKind::Slash
+            | TokenKind::Equals
+            | TokenKind::Ampersand
+            | TokenKind::Pipe
+            | TokenKind::Caret
+            | TokenKind::DoubleAsterisk
+            | TokenKind::Percent
+            | TokenKind::Tilde
+            | TokenKind::GreaterThan
+            | TokenKind::LessThan
+            | TokenKind::GreaterThanEquals
+            | TokenKind::LessThanEquals
+            | TokenKind::EqualsEquals
+            | TokenKind::BangEquals
+            | TokenKind::Bang
+            | TokenKind::And
+            | TokenKind::Or
+            | TokenKind::Increment
+            | TokenKind::Decrement
+            | TokenKind::MinusEquals
+            | TokenKind::PlusEquals
+            | TokenKind::MultiplyEquals
+            | TokenKind::DivideEquals
+            | TokenKind::ModuloEquals
+            | TokenKind::PowerEquals
+            | TokenKind::DoubleLessThan
+            | TokenKind::DoubleGreaterThan
+            | TokenKind::QuestionMark
+            | TokenKind::At
+            | TokenKind::EOF
+            | TokenKind::Whitespace
+            | TokenKind::Bad
+            | TokenKind::Comment => {}
+        }
+    }
+}
+
 impl TokenKind {
     pub fn is_keyword(&self) -> bool {
         matches!(
@@ -240,6 +453,7 @@ impl TokenKind {
                 | TokenKind::Throw
                 | TokenKind::Try
                 | TokenKind::Catch
+                | TokenKind::Finally
                 | TokenKind::Loop
                 | TokenKind::True
                 | TokenKind::False
@@ -281,6 +495,8 @@ impl TokenKind {
                 | TokenKind::PlusEquals
                 | TokenKind::MultiplyEquals
                 | TokenKind::DivideEquals
+                | TokenKind::ModuloEquals
+                | TokenKind::PowerEquals
                 | TokenKind::DoubleLessThan
                 | TokenKind::DoubleGreaterThan
                 | TokenKind::QuestionMark
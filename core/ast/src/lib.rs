@@ -1,8 +1,10 @@
 pub mod ast;
 pub mod lexer;
 pub mod parser;
+pub mod printer;
 pub mod source;
 
 pub use ast::*;
 pub use lexer::{token::*, *};
 pub use parser::*;
+pub use printer::AstPrinter;
@@ -1,11 +1,18 @@
 use crate::{
-    AssignOperator, BinOpAssociativity, BinOpKind, BinOperator, Expr, ParseContext, Parser, Stmt,
-    Token, TokenKind, TypeAnnotation, UnOpKind, UnOperator,
+    AccessKind, AssignOperator, AstPrinter, BinOpAssociativity, BinOpKind, BinOperator, Expr,
+    GetSpan, ParseContext, Parser, Segment, Stmt, StringPart, Token, TokenKind, TypeAnnotation,
+    UnOpKind, UnOperator,
 };
 use indexmap::IndexMap;
-use roan_error::error::RoanError::{ExpectedToken, UnexpectedToken};
+use roan_error::error::RoanError::{
+    ChainedComparison, ExpectedToken, InvalidAssignment, UnexpectedEof, UnexpectedToken,
+};
 use tracing::debug;
 
+/// Reason of the error for an assignment to something that isn't stored anywhere.
+const TEMPORARY_ASSIGNMENT: &str =
+    "to a temporary value, only variables and their fields or elements can be assigned to";
+
 impl Parser {
     /// Parses any expression, starting with an assignment.
     ///
@@ -120,12 +127,34 @@ impl Parser {
                 }
             }
 
+            if let Expr::Binary(binary) = &left {
+                if operator.kind.is_relational_operator()
+                    && binary.operator.is_relational_operator()
+                {
+                    return Err(Self::chained_comparison(&binary.right, &operator));
+                }
+            }
+
             left = Expr::new_binary(left, operator, right);
         }
 
         Ok(left)
     }
 
+    /// Creates the error for a comparison chained after another, like the second `<` of
+    /// `a < b < c`.
+    ///
+    /// The fix repeats the middle operand so both comparisons hold: `a < b && b < c`.
+    fn chained_comparison(middle: &Expr, operator: &BinOperator) -> anyhow::Error {
+        let mut printer = AstPrinter::new();
+        printer.print_expr(middle);
+        let replacement = format!("&& {} {}", printer.finish(), operator.kind);
+
+        ChainedComparison(operator.token.span.clone())
+            .with_fix(operator.token.span.clone(), replacement)
+            .into()
+    }
+
     /// Attempts to parse a unary operator.
     ///
     /// This method checks the next token to see if it's a unary operator and returns it if found.
@@ -185,7 +214,13 @@ impl Parser {
                 expr = Expr::new_index_access(expr, index, token);
             } else if token.kind == TokenKind::DoubleColon {
                 let colons = self.consume();
-                let field = self.parse_expr()?;
+                // Only the member itself, so the access can continue after it, e.g. `P::new().x`
+                let member = self.expect(TokenKind::Identifier)?;
+                let field = if self.peek().kind == TokenKind::LeftParen {
+                    self.parse_call_expr(member)?
+                } else {
+                    Expr::new_variable(member.clone(), member.literal())
+                };
 
                 expr = Expr::new_static_method_access(expr, field, colons);
             } else {
@@ -237,6 +272,10 @@ impl Parser {
     /// - `Ok(Expr)`: The parsed primary expression if successful.
     /// - `Err(anyhow::Error)`: An error if parsing fails.
     pub fn parse_primary_expression(&mut self) -> anyhow::Result<Expr> {
+        if self.is_eof() {
+            return Err(UnexpectedEof("expression".to_string(), self.peek().span).into());
+        }
+
         let token = self.consume();
 
         match &token.kind {
@@ -247,7 +286,7 @@ impl Parser {
                 Ok(Expr::new_bool(token.clone(), token.as_bool().unwrap()))
             }
             TokenKind::TripleDot => Ok(Expr::new_spread(token.clone(), self.parse_expr()?)),
-            TokenKind::LeftBracket => self.parse_vector(),
+            TokenKind::LeftBracket => self.parse_vector(token),
             TokenKind::LeftBrace => {
                 let mut fields: IndexMap<String, Expr> = IndexMap::new();
 
@@ -311,7 +350,23 @@ impl Parser {
                 Ok(Expr::new_parenthesized(expr))
             }
             TokenKind::String(s) => Ok(Expr::new_string(token.clone(), s.clone())),
+            TokenKind::InterpolatedString(parts) => {
+                let segments = parts
+                    .iter()
+                    .map(|part| self.parse_string_part(part))
+                    .collect::<anyhow::Result<Vec<_>>>()?;
+
+                Ok(Expr::new_interpolated(token.clone(), segments))
+            }
             TokenKind::Char(c) => Ok(Expr::new_char(token.clone(), *c)),
+            TokenKind::Semicolon | TokenKind::RightParen | TokenKind::RightBrace => {
+                Err(ExpectedToken(
+                    "expression".to_string(),
+                    format!("Expected an expression before '{}'", token.literal()),
+                    token.span.clone(),
+                )
+                .into())
+            }
             _ => {
                 debug!("Unexpected token: {:?}", token);
                 Err(UnexpectedToken(token.kind.to_string(), token.span.clone()).into())
@@ -319,6 +374,34 @@ impl Parser {
         }
     }
 
+    /// Parses a piece of an interpolated string. An expression has to use all the tokens between
+    /// its braces.
+    fn parse_string_part(&self, part: &StringPart) -> anyhow::Result<Segment> {
+        let (tokens, span) = match part {
+            StringPart::Text(text) => return Ok(Segment::Text(text.clone())),
+            StringPart::Expr(tokens, span) => (tokens, span),
+        };
+
+        if tokens.is_empty() {
+            return Err(ExpectedToken(
+                "expression".to_string(),
+                "Expected an expression between the braces, use '{{' for a literal brace"
+                    .to_string(),
+                span.clone(),
+            )
+            .into());
+        }
+
+        let mut parser = Parser::new(tokens.clone());
+        let expr = parser.parse_expr()?;
+        if !parser.is_eof() {
+            let token = parser.peek();
+            return Err(UnexpectedToken(token.kind.to_string(), token.span.clone()).into());
+        }
+
+        Ok(Segment::Expr(Box::new(expr)))
+    }
+
     /// Parses a then-else expression.
     ///
     /// This method expects an identifier followed by a then keyword and two expressions.
@@ -364,7 +447,8 @@ impl Parser {
 
                 args.push(arg);
 
-                if self.peek().kind != TokenKind::RightParen {
+                // At EOF the missing `)` is reported instead of the comma
+                if !matches!(self.peek().kind, TokenKind::RightParen | TokenKind::EOF) {
                     self.expect(TokenKind::Comma)?;
                 }
             }
@@ -392,12 +476,16 @@ impl Parser {
 
     /// Parses a vector expression.
     ///
-    /// This method expects a left bracket followed by a list of expressions and a closing right bracket.
+    /// This method expects a list of expressions and a closing right bracket, the left bracket
+    /// has already been consumed.
+    ///
+    /// # Arguments
+    /// - `opening_bracket`: The left bracket token.
     ///
     /// # Returns
     /// - `Ok(Expr)`: The parsed vector expression if successful.
     /// - `Err(anyhow::Error)`: An error if parsing fails.
-    pub fn parse_vector(&mut self) -> anyhow::Result<Expr> {
+    pub fn parse_vector(&mut self, opening_bracket: Token) -> anyhow::Result<Expr> {
         debug!("Parsing vector");
 
         let mut elements = vec![];
@@ -413,9 +501,9 @@ impl Parser {
             }
         }
 
-        self.expect(TokenKind::RightBracket)?;
+        let closing_bracket = self.expect(TokenKind::RightBracket)?;
 
-        Ok(Expr::new_vec(elements))
+        Ok(Expr::new_vec(elements, (opening_bracket, closing_bracket)))
     }
 
     /// Parses an assignment expression.
@@ -430,6 +518,7 @@ impl Parser {
 
         let expr = self.parse_binary_expression()?;
         if let Some(assign_op) = self.parse_assignment_operator() {
+            Self::check_assignment_target(&expr)?;
             self.consume();
             let right = self.parse_expr()?;
 
@@ -442,6 +531,27 @@ impl Parser {
         Ok(expr)
     }
 
+    /// Checks that a value can be assigned to `target`: a variable or a field or element of one,
+    /// like `a`, `a.b` or `a[0].b`.
+    ///
+    /// Values returned by calls are temporary, so `get_vec()[0] = 1` can't change anything.
+    fn check_assignment_target(target: &Expr) -> anyhow::Result<()> {
+        match target {
+            Expr::Variable(_) => Ok(()),
+            Expr::Access(access) => match &access.access {
+                AccessKind::Field(field) if !matches!(field.as_ref(), Expr::Variable(_)) => {
+                    Err(InvalidAssignment(TEMPORARY_ASSIGNMENT.to_string(), field.span()).into())
+                }
+                AccessKind::Field(_) | AccessKind::Index(_) => {
+                    Self::check_assignment_target(&access.base)
+                }
+                // Reported by the interpreter, with its own error
+                AccessKind::StaticMethod(_) => Ok(()),
+            },
+            _ => Err(InvalidAssignment(TEMPORARY_ASSIGNMENT.to_string(), target.span()).into()),
+        }
+    }
+
     /// Attempts to parse an assignment operator.
     ///
     /// This method checks the next token to see if it's an assignment operator and returns it if found.
@@ -455,7 +565,9 @@ impl Parser {
             | TokenKind::PlusEquals
             | TokenKind::MinusEquals
             | TokenKind::MultiplyEquals
-            | TokenKind::DivideEquals => Some(self.peek().kind.clone()),
+            | TokenKind::DivideEquals
+            | TokenKind::ModuloEquals
+            | TokenKind::PowerEquals => Some(self.peek().kind.clone()),
             _ => None,
         }
     }
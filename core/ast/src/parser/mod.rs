@@ -6,7 +6,10 @@ use crate::{
     Ast,
 };
 use anyhow::Result;
-use roan_error::error::RoanError::ExpectedToken;
+use roan_error::{
+    error::RoanError::{ExpectedToken, UnexpectedEof},
+    TextSpan,
+};
 use tracing::debug;
 
 #[derive(Debug, PartialEq, Eq)]
@@ -14,6 +17,7 @@ pub enum ParseContext {
     Normal,
     IfCondition,
     WhileCondition,
+    ForIterable,
 }
 
 /// A parser that converts a list of tokens into an Abstract Syntax Tree (AST).
@@ -28,24 +32,52 @@ pub struct Parser {
     pub current: usize,
     /// The current context stack for parsing.
     pub context_stack: Vec<ParseContext>,
+    /// Whether errors inside blocks are collected in `errors` instead of being returned.
+    pub recover: bool,
+    /// Errors collected while parsing with recovery.
+    pub errors: Vec<anyhow::Error>,
+    /// Number of `catch` blocks enclosing the current token, a bare `throw;` needs at least one.
+    pub catch_depth: usize,
 }
 
 impl Parser {
     /// Creates a new `Parser` instance with a set of tokens to parse.
     ///
+    /// An EOF token is appended if the tokens don't end with one, so the parser never reads
+    /// past the end of the stream. It has an empty span right after the last token.
+    ///
     /// # Arguments
     /// * `tokens` - The list of tokens generated by the lexer.
     ///
     /// # Returns
     /// * A new `Parser` instance ready to parse the tokens.
-    pub fn new(tokens: Vec<Token>) -> Self {
+    pub fn new(mut tokens: Vec<Token>) -> Self {
+        if !matches!(tokens.last(), Some(token) if token.kind == TokenKind::EOF) {
+            let span = tokens
+                .last()
+                .map(|token| TextSpan::new(token.span.end, token.span.end, String::new()))
+                .unwrap_or_default();
+            tokens.push(Token::new(TokenKind::EOF, span));
+        }
+
         Self {
             tokens,
             current: 0,
             context_stack: vec![ParseContext::Normal],
+            recover: false,
+            errors: vec![],
+            catch_depth: 0,
         }
     }
 
+    /// Consumes the parser and returns its tokens, ending with an EOF token.
+    ///
+    /// A `>>` closing nested generics is split while parsing, so the tokens have its second `>`
+    /// in its place.
+    pub fn into_tokens(self) -> Vec<Token> {
+        self.tokens
+    }
+
     /// Returns a reference to the current parser context.
     ///
     /// # Panics
@@ -119,6 +151,76 @@ impl Parser {
         Ok(ast)
     }
 
+    /// Parses the list of tokens and collects every syntax error instead of stopping at the
+    /// first one.
+    ///
+    /// When a statement fails to parse, the error is stored and the tokens are skipped until
+    /// the start of the next statement, see [Parser::recover_from_error]. The same happens for
+    /// statements inside blocks, so an error in one function doesn't hide errors in the next.
+    ///
+    /// # Returns
+    /// * The AST built from the statements that were parsed successfully.
+    /// * The errors found while parsing, in source order.
+    pub fn parse_with_recovery(&mut self) -> (Ast, Vec<anyhow::Error>) {
+        let mut ast = Ast::new();
+        self.recover = true;
+
+        while !self.is_eof() {
+            let start = self.current;
+
+            match self.parse_stmt() {
+                Ok(Some(stmt)) => ast.stmts.push(stmt),
+                Ok(None) => {}
+                Err(err) => {
+                    // Make sure the parser moves forward if the statement didn't consume anything
+                    if self.current == start {
+                        self.consume();
+                    }
+                    let err = self.recover_from_error(err);
+                    self.errors.push(err);
+                    // A closing brace left behind by a broken declaration can't start a statement
+                    self.possible_check(TokenKind::RightBrace);
+                }
+            }
+        }
+
+        self.recover = false;
+        (ast, std::mem::take(&mut self.errors))
+    }
+
+    /// Skips tokens after a parse error until a point where parsing can continue and returns the
+    /// error with context about the skipped tokens.
+    ///
+    /// Parsing stops before the first of these synchronization tokens:
+    /// * `;` ends the broken statement, the next statement starts right after it.
+    /// * `}` ends the enclosing block, so the block (or function) around the error can still be
+    ///   closed normally.
+    /// * `fn`, `let`, `if` and `return` almost always start a new statement, so they are a safe
+    ///   place to resume even if the broken statement wasn't terminated.
+    /// * EOF, because there is nothing left to parse.
+    ///
+    /// # Arguments
+    /// * `err` - The error that stopped the statement from being parsed.
+    ///
+    /// # Returns
+    /// * The original error with context added.
+    pub fn recover_from_error(&mut self, err: anyhow::Error) -> anyhow::Error {
+        while !matches!(
+            self.peek().kind,
+            TokenKind::Semicolon
+                | TokenKind::RightBrace
+                | TokenKind::Fn
+                | TokenKind::Let
+                | TokenKind::If
+                | TokenKind::Return
+                | TokenKind::EOF
+        ) {
+            self.consume();
+        }
+
+        err.context("skipped to next statement due to parse error")
+    }
+
     /// Consumes the current token and advances to the next token in the stream.
     ///
     /// # Returns
@@ -143,22 +245,17 @@ impl Parser {
     /// Peeks at the current token without consuming it.
     ///
     /// # Returns
-    /// * A copy of the current token.
+    /// * A copy of the current token, or the EOF token at the end of the stream.
     pub fn peek(&self) -> Token {
-        self.tokens.get(self.current).cloned().unwrap_or_else(|| {
-            Token::new(
-                TokenKind::EOF,
-                self.tokens.get(self.tokens.len() - 1).unwrap().span.clone(),
-            )
-        })
+        self.tokens[self.current.min(self.tokens.len() - 1)].clone()
     }
 
     /// Peeks at the next token without consuming the current one.
     ///
     /// # Returns
-    /// * A copy of the next token.
+    /// * A copy of the next token, or the EOF token at the end of the stream.
     pub fn peek_next(&self) -> Token {
-        self.tokens[self.current + 1].clone()
+        self.tokens[(self.current + 1).min(self.tokens.len() - 1)].clone()
     }
 
     /// Checks if the current token is the end of file (EOF).
@@ -196,13 +293,10 @@ impl Parser {
         debug!("Expected token: {:?}, found: {:?}", kind, token.kind);
         if token.kind == kind {
             Ok(self.consume())
+        } else if token.kind == TokenKind::EOF {
+            Err(UnexpectedEof(kind.to_string(), token.span).into())
         } else {
-            Err(ExpectedToken(
-                kind.to_string(),
-                format!("Expected token of kind: {}", kind),
-                self.previous().span.clone(),
-            )
-            .into())
+            Err(self.expected_token(kind, self.previous().span.clone()))
         }
     }
 
@@ -223,17 +317,391 @@ impl Parser {
         debug!("Expected token: {:?}, found: {:?}", kind, token.kind);
         if token.kind == kind {
             Ok(self.consume())
+        } else if token.kind == TokenKind::EOF {
+            Err(UnexpectedEof(kind.to_string(), token.span).into())
         } else {
             let prev = self.previous();
             let len = kind.to_string().len();
-            Err(ExpectedToken(
-                kind.to_string(),
-                format!("Expected token of kind: {}", kind),
+            Err(self.expected_token(
+                kind,
                 prev.span
                     .move_right(len + prev.span.literal.len())
                     .shorten(len),
-            )
-            .into())
+            ))
+        }
+    }
+
+    /// Creates the error for a missing token of `kind`, reported at `span`.
+    ///
+    /// Punctuation is always spelled the same, so the error suggests inserting it after the
+    /// previous token.
+    fn expected_token(&self, kind: TokenKind, span: TextSpan) -> anyhow::Error {
+        let err = ExpectedToken(
+            kind.to_string(),
+            format!("Expected token of kind: {}", kind),
+            span,
+        );
+
+        if kind.is_separator() || kind.is_operator() {
+            let prev = self.previous();
+            let replacement = format!("{}{}", prev.literal(), kind);
+            err.with_fix(prev.span, replacement).into()
+        } else {
+            err.into()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{source::Source, AccessKind, Expr, GetSpan, Lexer, Parser, Stmt, TokenKind};
+    use roan_error::error::RoanError;
+
+    fn parse_with_recovery(src: &str) -> (crate::Ast, Vec<anyhow::Error>) {
+        let tokens = Lexer::new(Source::from_string(src.to_string()))
+            .lex(false)
+            .unwrap();
+        Parser::new(tokens).parse_with_recovery()
+    }
+
+    #[test]
+    fn test_recover_from_two_errors() {
+        let (ast, errors) = parse_with_recovery(
+            "fn first() {\n\
+                 let a = ;\n\
+                 let b = 1;\n\
+             }\n\
+             fn second(x: ) {}\n\
+             let c = 2;\n",
+        );
+
+        assert_eq!(errors.len(), 2, "{:?}", errors);
+        for err in &errors {
+            assert!(err.downcast_ref::<RoanError>().is_some());
         }
+
+        let Stmt::Fn(first) = &ast.stmts[0] else {
+            panic!("Expected function, got {:?}", ast.stmts[0]);
+        };
+        assert_eq!(first.body.stmts.len(), 1);
+        assert!(matches!(ast.stmts.last(), Some(Stmt::Let(_))));
+    }
+
+    #[test]
+    fn test_parse_returns_first_error() {
+        let tokens = Lexer::new(Source::from_string("let a = ;\nlet b = ;\n".to_string()))
+            .lex(false)
+            .unwrap();
+
+        assert!(Parser::new(tokens).parse().is_err());
+    }
+
+    #[test]
+    fn test_recover_from_broken_expression() {
+        let (ast, errors) = parse_with_recovery("1 + * 2\nlet x = 2;\n");
+
+        assert_eq!(errors.len(), 1, "{:?}", errors);
+        assert_eq!(
+            errors[0].to_string(),
+            "skipped to next statement due to parse error"
+        );
+        assert_eq!(ast.stmts.len(), 1);
+        match &ast.stmts[0] {
+            Stmt::Let(let_stmt) => assert_eq!(let_stmt.ident.literal(), "x"),
+            stmt => panic!("Expected let statement, got {:?}", stmt),
+        }
+    }
+
+    #[test]
+    fn test_missing_punctuation_suggests_fix() {
+        let tokens = Lexer::new(Source::from_string("fn f() {}\nloop break; }".to_string()))
+            .lex(false)
+            .unwrap();
+        let err = Parser::new(tokens).parse().unwrap_err();
+
+        match err.downcast_ref::<RoanError>() {
+            Some(RoanError::WithFix(inner, span, replacement)) => {
+                assert!(matches!(inner.as_ref(), RoanError::ExpectedToken(..)));
+                assert_eq!(span.literal, "loop");
+                assert_eq!(span.start.line, 2);
+                assert_eq!(replacement, "loop{");
+            }
+            err => panic!("Expected an error with a fix, got {:?}", err),
+        }
+    }
+
+    fn parse_error(src: &str) -> RoanError {
+        let tokens = Lexer::new(Source::from_string(src.to_string()))
+            .lex(false)
+            .unwrap();
+        let err = Parser::new(tokens).parse().unwrap_err();
+
+        match err.downcast::<RoanError>() {
+            Ok(err) => err,
+            Err(err) => panic!("Expected a RoanError, got {:?}", err),
+        }
+    }
+
+    #[test]
+    fn test_unexpected_eof() {
+        for (src, expected, column) in [
+            ("fn f() {\n    let x = 1;", "}", 14),
+            ("let x = 1 +", "expression", 11),
+            ("f(1, 2", ")", 6),
+            ("fn f(a: int", ")", 11),
+            ("fn f(", ")", 5),
+            ("fn f(a: int,", "parameter name or ')'", 12),
+        ] {
+            match parse_error(src) {
+                RoanError::UnexpectedEof(found, span) => {
+                    assert_eq!(found, expected, "{}", src);
+                    assert_eq!(span.start.column, column, "{}", src);
+                    assert_eq!(span.start.index, src.len(), "{}", src);
+                }
+                err => panic!("Expected unexpected EOF for {}, got {:?}", src, err),
+            }
+        }
+    }
+
+    #[test]
+    fn test_missing_expression_and_parameter() {
+        match parse_error("let x = ;") {
+            RoanError::ExpectedToken(expected, _, span) => {
+                assert_eq!(expected, "expression");
+                assert_eq!(span.literal, ";");
+            }
+            err => panic!("Expected missing expression, got {:?}", err),
+        }
+
+        match parse_error("fn f( {") {
+            RoanError::ExpectedToken(expected, _, span) => {
+                assert_eq!(expected, "parameter name");
+                assert_eq!(span.literal, "{");
+            }
+            err => panic!("Expected missing parameter, got {:?}", err),
+        }
+    }
+
+    #[test]
+    fn test_parse_without_tokens() {
+        assert!(Parser::new(vec![]).parse().unwrap().stmts.is_empty());
+    }
+
+    #[test]
+    fn test_span_of_empty_vector_and_call() {
+        let (ast, errors) = parse_with_recovery("let x = [];\nf();\n");
+        assert!(errors.is_empty());
+
+        match &ast.stmts[0] {
+            Stmt::Let(let_stmt) => {
+                let span = let_stmt.initializer.span();
+                assert_eq!((span.start.index, span.end.index), (8, 10));
+            }
+            stmt => panic!("Expected let, got {:?}", stmt),
+        }
+        match &ast.stmts[1] {
+            Stmt::Expr(expr) => assert_eq!(expr.span().literal, "f"),
+            stmt => panic!("Expected expression, got {:?}", stmt),
+        }
+    }
+
+    #[test]
+    fn test_parse_finally() {
+        let (ast, errors) =
+            parse_with_recovery("try {} catch e {} finally { f(); }\ntry {} catch e {}\n");
+        assert!(errors.is_empty());
+
+        match (&ast.stmts[0], &ast.stmts[1]) {
+            (Stmt::Try(with_finally), Stmt::Try(without_finally)) => {
+                assert_eq!(with_finally.finally_block.as_ref().unwrap().stmts.len(), 1);
+                assert!(without_finally.finally_block.is_none());
+            }
+            stmts => panic!("Expected two try statements, got {:?}", stmts),
+        }
+    }
+
+    #[test]
+    fn test_interpolation_errors_point_into_string() {
+        match parse_error("let s = f\"a {1 +} b\";") {
+            RoanError::UnexpectedEof(_, span) => assert_eq!(span.start.column, 16),
+            err => panic!("Expected an unexpected end of interpolation, got {:?}", err),
+        }
+
+        match parse_error("let s = f\"a {} b\";") {
+            RoanError::ExpectedToken(_, _, span) => {
+                assert_eq!(span.literal, "{}");
+                assert_eq!(span.start.column, 12);
+            }
+            err => panic!("Expected a missing expression, got {:?}", err),
+        }
+
+        match parse_error("let s = f\"{1 2}\";") {
+            RoanError::UnexpectedToken(_, span) => assert_eq!(span.start.column, 13),
+            err => panic!("Expected an unexpected token, got {:?}", err),
+        }
+    }
+
+    #[test]
+    fn test_rethrow_only_in_catch() {
+        let (ast, errors) = parse_with_recovery("try {} catch e { if true { throw; } }\n");
+        assert!(errors.is_empty());
+        assert!(matches!(&ast.stmts[0], Stmt::Try(_)));
+
+        for src in [
+            "throw;",
+            "try { throw; } catch e {}",
+            "try {} catch e {} finally { throw; }",
+            "try {} catch e { fn f() { throw; } }",
+        ] {
+            match parse_error(src) {
+                RoanError::RethrowOutsideCatch(span) => {
+                    assert_eq!(span.literal, "throw", "{}", src)
+                }
+                err => panic!(
+                    "Expected rethrow outside of catch in {}, got {:?}",
+                    src, err
+                ),
+            }
+        }
+    }
+
+    #[test]
+    fn test_chained_comparison() {
+        for (src, column, replacement) in [
+            ("let x = 1 < 2 < 3;", 14, "&& 2 <"),
+            ("let x = a <= b + 1 > c;", 19, "&& b + 1 >"),
+            ("let x = 1 < 2 < 3 < 4;", 14, "&& 2 <"),
+        ] {
+            match parse_error(src) {
+                RoanError::WithFix(inner, span, fix) => {
+                    assert!(matches!(inner.as_ref(), RoanError::ChainedComparison(_)));
+                    assert_eq!(span.start.column, column, "{}", src);
+                    assert_eq!(fix, replacement);
+                }
+                err => panic!("Expected a chained comparison in {}, got {:?}", src, err),
+            }
+        }
+
+        for src in [
+            "let x = 1 < 2 && 2 < 3;",
+            "let x = (1 < 2) == true;",
+            "let x = 1 < 2 == 2 > 1;",
+        ] {
+            let (_, errors) = parse_with_recovery(src);
+            assert!(errors.is_empty(), "{}", src);
+        }
+    }
+
+    /// Parses `src`, a single expression statement.
+    fn parse_expr(src: &str) -> Expr {
+        let (ast, errors) = parse_with_recovery(src);
+        assert!(errors.is_empty(), "{}: {:?}", src, errors);
+
+        match ast.stmts.into_iter().next() {
+            Some(Stmt::Expr(expr)) => *expr,
+            stmt => panic!("Expected an expression statement, got {:?}", stmt),
+        }
+    }
+
+    /// Splits an access expression into its base and the access, with the columns of its span.
+    fn access(expr: &Expr) -> (&Expr, &AccessKind, (u32, u32)) {
+        match expr {
+            Expr::Access(access) => {
+                let span = expr.span();
+                (
+                    &access.base,
+                    &access.access,
+                    (span.start.column, span.end.column),
+                )
+            }
+            expr => panic!("Expected an access, got {:?}", expr),
+        }
+    }
+
+    #[test]
+    fn test_access_chain_after_call() {
+        let expr = parse_expr("a().b[0].c(1);");
+
+        let (base, kind, columns) = access(&expr);
+        assert_eq!(columns, (0, 12));
+        assert!(
+            matches!(kind, AccessKind::Field(field) if matches!(field.as_ref(), Expr::Call(c) if c.callee == "c"))
+        );
+
+        let (base, kind, columns) = access(base);
+        assert_eq!(columns, (0, 7));
+        assert!(matches!(kind, AccessKind::Index(_)));
+
+        let (base, kind, columns) = access(base);
+        assert_eq!(columns, (0, 5));
+        assert!(
+            matches!(kind, AccessKind::Field(field) if matches!(field.as_ref(), Expr::Variable(v) if v.ident == "b"))
+        );
+        assert!(matches!(base, Expr::Call(c) if c.callee == "a"));
+
+        let expr = parse_expr("\"a,b\".split(\",\").len();");
+        let (base, _, _) = access(&expr);
+        let (base, kind, _) = access(base);
+        assert!(matches!(base, Expr::Literal(_)));
+        assert!(
+            matches!(kind, AccessKind::Field(field) if matches!(field.as_ref(), Expr::Call(c) if c.callee == "split"))
+        );
+    }
+
+    #[test]
+    fn test_access_chain_after_static_method() {
+        let expr = parse_expr("Point::new().x + 1;");
+        let Expr::Binary(binary) = expr else {
+            panic!("Expected a binary expression, got {:?}", expr);
+        };
+
+        let (base, kind, columns) = access(&binary.left);
+        assert_eq!(columns, (0, 14));
+        assert!(matches!(kind, AccessKind::Field(_)));
+
+        let (base, kind, _) = access(base);
+        assert!(matches!(base, Expr::Variable(v) if v.ident == "Point"));
+        assert!(
+            matches!(kind, AccessKind::StaticMethod(method) if matches!(method.as_ref(), Expr::Call(c) if c.callee == "new"))
+        );
+    }
+
+    #[test]
+    fn test_assign_to_temporary() {
+        for src in ["a[0].b = 1;", "a.b[0] += 1;", "self.x = 1;"] {
+            let (_, errors) = parse_with_recovery(src);
+            assert!(errors.is_empty(), "{}", src);
+        }
+
+        for (src, literal) in [
+            ("get_vec()[0] = 1;", "get_vec"),
+            ("a.items().x = 1;", "items"),
+            ("1 = 2;", "1"),
+        ] {
+            match parse_error(src) {
+                RoanError::InvalidAssignment(_, span) => assert_eq!(span.literal, literal),
+                err => panic!("Expected an invalid assignment in {}, got {:?}", src, err),
+            }
+        }
+    }
+
+    #[test]
+    fn test_into_tokens() {
+        let tokens = Lexer::new(Source::from_string(
+            "let v: vec<vec<int>> = [];".to_string(),
+        ))
+        .lex(false)
+        .unwrap();
+        let len = tokens.len();
+
+        let mut parser = Parser::new(tokens);
+        parser.parse().unwrap();
+        let tokens = parser.into_tokens();
+
+        assert_eq!(tokens.len(), len + 1);
+        assert_eq!(tokens.last().unwrap().kind, TokenKind::EOF);
+        assert!(tokens
+            .iter()
+            .all(|token| token.kind != TokenKind::DoubleGreaterThan));
     }
 }
@@ -1,12 +1,16 @@
 use crate::{
-    Block, ElseBlock, FnParam, ParseContext, Parser, Stmt, StructField, Token, TokenKind,
-    TypeAnnotation, TypeKind,
+    Attribute, Block, ElseBlock, FnParam, ParseContext, Parser, Stmt, StructField, Token,
+    TokenKind, TypeAnnotation, TypeKind,
 };
 use anyhow::Result;
 use indexmap::IndexMap;
-use roan_error::error::RoanError::{
-    ExpectedToken, MultipleRestParameters, MultipleSelfParameters, RestParameterNotLastPosition,
-    SelfParameterCannotBeRest, SelfParameterNotFirst,
+use roan_error::{
+    error::RoanError::{
+        ExpectedToken, MultipleRestParameters, MultipleSelfParameters,
+        RestParameterNotLastPosition, RethrowOutsideCatch, SelfParameterCannotBeRest,
+        SelfParameterNotFirst, UnexpectedEof,
+    },
+    TextSpan,
 };
 use tracing::debug;
 
@@ -38,6 +42,7 @@ impl Parser {
                     None
                 }
             }
+            TokenKind::At => Some(self.parse_attributed_decl()?),
             TokenKind::Fn => Some(self.parse_fn()?),
             TokenKind::Struct => Some(self.parse_struct()?),
             TokenKind::Trait => Some(self.parse_trait()?),
@@ -75,10 +80,13 @@ impl Parser {
             }
             TokenKind::Loop => {
                 self.consume();
+                self.expect_punct(TokenKind::LeftBrace)?;
                 let block = self.parse_block()?;
+                self.expect_punct(TokenKind::RightBrace)?;
                 Some(Stmt::new_loop(token, block))
             }
             TokenKind::While => self.parse_while()?,
+            TokenKind::For => Some(self.parse_for()?),
             TokenKind::LeftBrace => {
                 self.consume();
                 let block = self.parse_block()?;
@@ -96,6 +104,93 @@ impl Parser {
         Ok(stmt)
     }
 
+    /// Parses the attributes written before a declaration, e.g. `@test` or `@deprecated("msg")`.
+    ///
+    /// # Returns
+    /// - `Ok(Vec<Attribute>)`: The attributes, empty if the next token isn't `@`.
+    /// - `Err`: If there is a parsing error.
+    pub fn parse_attributes(&mut self) -> Result<Vec<Attribute>> {
+        let mut attributes = vec![];
+
+        while self.peek().kind == TokenKind::At {
+            let at_token = self.consume();
+            let name = self.expect(TokenKind::Identifier)?;
+
+            let mut args = vec![];
+            if self.peek().kind == TokenKind::LeftParen {
+                self.consume();
+                while self.peek().kind != TokenKind::RightParen && !self.is_eof() {
+                    args.push(self.parse_expr()?);
+
+                    if self.peek().kind != TokenKind::RightParen {
+                        self.expect(TokenKind::Comma)?;
+                    }
+                }
+                self.expect(TokenKind::RightParen)?;
+            }
+
+            attributes.push(Attribute {
+                at_token,
+                name,
+                args,
+            });
+        }
+
+        Ok(attributes)
+    }
+
+    /// Parses a function, struct or const declaration preceded by attributes.
+    ///
+    /// # Returns
+    /// - `Ok(Stmt)`: The declaration with the attributes attached.
+    /// - `Err`: If there is a parsing error or the attributes aren't followed by a declaration.
+    fn parse_attributed_decl(&mut self) -> Result<Stmt> {
+        debug!("Parsing attributes");
+        let attributes = self.parse_attributes()?;
+        self.possible_check(TokenKind::Comment);
+
+        let kind = if self.peek().kind == TokenKind::Pub {
+            self.peek_next().kind
+        } else {
+            self.peek().kind
+        };
+
+        let mut stmt = match kind {
+            TokenKind::Fn => self.parse_fn()?,
+            TokenKind::Struct => self.parse_struct()?,
+            TokenKind::Const => self.parse_const()?,
+            _ => {
+                return Err(ExpectedToken(
+                    "declaration".to_string(),
+                    format!(
+                        "Attributes must be followed by a function, struct or const declaration, found '{}'",
+                        self.peek().literal()
+                    ),
+                    self.peek().span.clone(),
+                )
+                .into())
+            }
+        };
+
+        match &mut stmt {
+            Stmt::Fn(func) => func.attributes = attributes,
+            Stmt::Struct(struct_def) => struct_def.attributes = attributes,
+            Stmt::Const(const_stmt) => const_stmt.attributes = attributes,
+            _ => unreachable!("Only declarations are parsed"),
+        }
+
+        Ok(stmt)
+    }
+
+    /// Parses a method of an `impl` block or trait, including its attributes.
+    fn parse_method(&mut self) -> Result<crate::Fn> {
+        let attributes = self.parse_attributes()?;
+        let mut func = self.parse_fn()?.into_function();
+        func.attributes = attributes;
+
+        Ok(func)
+    }
+
     /// Parses an `impl` block for implementing a struct.
     ///
     /// An `impl` block is used to implement methods for a struct.
@@ -110,7 +205,7 @@ impl Parser {
         let mut methods: Vec<crate::Fn> = vec![];
 
         while self.peek().kind != TokenKind::RightBrace && !self.is_eof() {
-            let func = self.parse_fn()?.into_function();
+            let func = self.parse_method()?;
 
             methods.push(func);
         }
@@ -138,7 +233,7 @@ impl Parser {
         let mut methods: Vec<crate::Fn> = vec![];
 
         while self.peek().kind != TokenKind::RightBrace && !self.is_eof() {
-            let func = self.parse_fn()?.into_function();
+            let func = self.parse_method()?;
 
             methods.push(func);
         }
@@ -186,7 +281,7 @@ impl Parser {
         let mut methods: Vec<crate::Fn> = vec![];
 
         while self.peek().kind != TokenKind::RightBrace && !self.is_eof() {
-            let func = self.parse_fn()?.into_function();
+            let func = self.parse_method()?;
             methods.push(func);
         }
 
@@ -297,6 +392,43 @@ impl Parser {
         Ok(Some(Stmt::new_while(while_token, condition, block)))
     }
 
+    /// Parses a `for` statement.
+    ///
+    /// A `for` statement is used to execute a block of code for every element of a value. With
+    /// two variables, `for key, value in`, every element is destructured into a key and a value.
+    ///
+    /// # Returns
+    /// - `Ok(Stmt)`: A for statement.
+    /// - `Err`: If there is a parsing error.
+    pub fn parse_for(&mut self) -> Result<Stmt> {
+        debug!("Parsing for statement");
+        let for_token = self.consume();
+        let ident = self.expect(TokenKind::Identifier)?;
+        let value_ident = if self.peek().kind == TokenKind::Comma {
+            self.consume();
+            Some(self.expect(TokenKind::Identifier)?)
+        } else {
+            None
+        };
+        self.expect(TokenKind::In)?;
+
+        self.push_context(ParseContext::ForIterable);
+        let iterable = self.parse_expr()?;
+        self.pop_context();
+
+        self.expect_punct(TokenKind::LeftBrace)?;
+        let block = self.parse_block()?;
+        self.expect_punct(TokenKind::RightBrace)?;
+
+        Ok(Stmt::new_for(
+            for_token,
+            ident,
+            value_ident,
+            iterable,
+            block,
+        ))
+    }
+
     /// Parses a `throw` statement.
     ///
     /// A `throw` statement is used to raise an exception.
@@ -307,11 +439,21 @@ impl Parser {
     pub fn parse_throw(&mut self) -> Result<Stmt> {
         debug!("Parsing throw statement");
         let throw_token = self.consume();
+
+        if self.peek().kind == TokenKind::Semicolon {
+            if self.catch_depth == 0 {
+                return Err(RethrowOutsideCatch(throw_token.span).into());
+            }
+
+            self.consume();
+            return Ok(Stmt::new_throw(throw_token, None));
+        }
+
         let value = self.parse_expr()?;
 
         self.possible_check(TokenKind::Semicolon);
 
-        Ok(Stmt::new_throw(throw_token, value))
+        Ok(Stmt::new_throw(throw_token, Some(value)))
     }
 
     /// Parses a `try` statement with a `catch` block.
@@ -334,14 +476,30 @@ impl Parser {
         let error_ident = self.expect(TokenKind::Identifier)?;
 
         self.expect_punct(TokenKind::LeftBrace)?;
-        let catch_block = self.parse_block()?;
+        self.catch_depth += 1;
+        let catch_block = self.parse_block();
+        self.catch_depth -= 1;
+        let catch_block = catch_block?;
         self.expect_punct(TokenKind::RightBrace)?;
 
+        let finally_block = if self.peek().kind == TokenKind::Finally {
+            self.consume();
+
+            self.expect_punct(TokenKind::LeftBrace)?;
+            let block = self.parse_block()?;
+            self.expect_punct(TokenKind::RightBrace)?;
+
+            Some(block)
+        } else {
+            None
+        };
+
         Ok(Stmt::new_try(
             try_token,
             try_block,
             error_ident,
             catch_block,
+            finally_block,
         ))
     }
 
@@ -504,27 +662,133 @@ impl Parser {
         }
     }
 
-    /// Helper method to parse a type with optional array and nullability.
-    fn parse_type(&mut self) -> Result<(Token, Vec<TypeAnnotation>)> {
+    /// Helper method to parse a type with optional generics and array suffixes.
+    ///
+    /// Generics are written in angle brackets (`vec<int>`, `object<vec<string>>`) and every
+    /// `[]` suffix wraps the type in a vector, so `int[][]` is the same as `vec<vec<int>>`.
+    ///
+    /// The returned annotation has no separator and isn't nullable, callers fill those in.
+    fn parse_type(&mut self) -> Result<TypeAnnotation> {
         let type_name = self.expect(TokenKind::Identifier)?;
 
         let generics = if self.peek().kind == TokenKind::LessThan {
-            self.consume();
+            let less_than = self.consume();
+            if matches!(
+                self.peek().kind,
+                TokenKind::GreaterThan | TokenKind::DoubleGreaterThan
+            ) {
+                return Err(ExpectedToken(
+                    "type".to_string(),
+                    format!(
+                        "Expected at least one type between '<' and '>' after '{}'",
+                        type_name.literal()
+                    ),
+                    less_than.span.clone(),
+                )
+                .into());
+            }
+
             let mut generics = vec![];
-            while self.peek().kind != TokenKind::GreaterThan {
-                let type_annotation = self.parse_type_annotation(false)?;
-                generics.push(type_annotation);
-                if self.peek().kind != TokenKind::GreaterThan {
-                    self.expect(TokenKind::Comma)?;
+            loop {
+                generics.push(self.parse_type_annotation(false)?);
+
+                match self.peek().kind {
+                    TokenKind::GreaterThan | TokenKind::DoubleGreaterThan => break,
+                    TokenKind::Comma => {
+                        self.consume();
+                    }
+                    _ => {
+                        return Err(ExpectedToken(
+                            "'>'".to_string(),
+                            format!(
+                                "Expected ',' or '>' to close the generics of '{}', found '{}'",
+                                type_name.literal(),
+                                self.peek().literal()
+                            ),
+                            self.peek().span.clone(),
+                        )
+                        .into())
+                    }
                 }
             }
-            self.expect(TokenKind::GreaterThan)?;
+            self.expect_closing_angle()?;
             generics
         } else {
             vec![]
         };
 
-        Ok((type_name, generics))
+        let mut typ = TypeAnnotation {
+            token_name: Some(type_name.clone()),
+            kind: TypeKind::from_str(&type_name.literal()),
+            is_nullable: false,
+            separator: None,
+            generics,
+            module_id: None,
+        };
+
+        while self.peek().kind == TokenKind::LeftBracket
+            && self.peek_next().kind == TokenKind::RightBracket
+        {
+            self.consume();
+            let right_bracket = self.consume();
+
+            typ = TypeAnnotation {
+                token_name: Some(right_bracket),
+                kind: TypeKind::Vec,
+                is_nullable: false,
+                separator: None,
+                generics: vec![typ],
+                module_id: None,
+            };
+        }
+
+        Ok(typ)
+    }
+
+    /// Consumes the name of a function parameter.
+    ///
+    /// Anything else means the parameter list wasn't closed, e.g. `fn f( {`.
+    fn expect_param_name(&mut self) -> Result<Token> {
+        let token = self.peek();
+
+        match token.kind {
+            TokenKind::Identifier => Ok(self.consume()),
+            TokenKind::EOF => {
+                Err(UnexpectedEof("parameter name or ')'".to_string(), token.span).into())
+            }
+            _ => Err(ExpectedToken(
+                "parameter name".to_string(),
+                format!(
+                    "Expected a parameter name or ')' to close the parameter list, found '{}'",
+                    token.literal()
+                ),
+                token.span,
+            )
+            .into()),
+        }
+    }
+
+    /// Consumes a `>` closing a list of generics.
+    ///
+    /// Nested generics end with `>>` which the lexer reads as a single token. In that case the
+    /// token is split and the second `>` is left for the outer list.
+    fn expect_closing_angle(&mut self) -> Result<Token> {
+        let token = self.peek();
+
+        if token.kind != TokenKind::DoubleGreaterThan {
+            return self.expect(TokenKind::GreaterThan);
+        }
+
+        let middle = token.span.start.move_right(1);
+        self.tokens[self.current] = Token::new(
+            TokenKind::GreaterThan,
+            TextSpan::new(middle, token.span.end, ">".to_string()),
+        );
+
+        Ok(Token::new(
+            TokenKind::GreaterThan,
+            TextSpan::new(token.span.start, middle, ">".to_string()),
+        ))
     }
 
     /// Parses a type annotation following a variable or parameter.
@@ -553,16 +817,11 @@ impl Parser {
             None
         };
 
-        let (token, generics) = self.parse_type()?;
+        let mut typ = self.parse_type()?;
+        typ.is_nullable = self.is_nullable();
+        typ.separator = colon;
 
-        Ok(TypeAnnotation {
-            token_name: Some(token.clone()),
-            kind: TypeKind::from_str(&token.literal()),
-            is_nullable: self.is_nullable(),
-            separator: colon,
-            generics,
-            module_id: None,
-        })
+        Ok(typ)
     }
 
     /// Parses the return type of function.
@@ -589,16 +848,11 @@ impl Parser {
         }
 
         let arrow = self.consume(); // consume the arrow
-        let (token, generics) = self.parse_type()?;
+        let mut typ = self.parse_type()?;
+        typ.is_nullable = self.is_nullable();
+        typ.separator = Some(arrow);
 
-        Ok(Some(TypeAnnotation {
-            token_name: Some(token.clone()),
-            kind: TypeKind::from_str(&token.literal()),
-            is_nullable: self.is_nullable(),
-            separator: Some(arrow),
-            generics,
-            module_id: None,
-        }))
+        Ok(Some(typ))
     }
 
     /// Parses a block of statements enclosed by curly braces `{}`.
@@ -610,10 +864,25 @@ impl Parser {
     /// - `Err`: If there is a parsing error.
     pub fn parse_block(&mut self) -> Result<Block> {
         debug!("Parsing block");
+        // The opening brace is consumed by the caller
+        let open = self.previous();
         let mut stmts = vec![];
 
         while self.peek().kind != TokenKind::RightBrace && !self.is_eof() {
-            let stmt = self.parse_stmt()?;
+            let start = self.current;
+
+            let stmt = match self.parse_stmt() {
+                Ok(stmt) => stmt,
+                Err(err) if self.recover => {
+                    if self.current == start {
+                        self.consume();
+                    }
+                    let err = self.recover_from_error(err);
+                    self.errors.push(err);
+                    continue;
+                }
+                Err(err) => return Err(err),
+            };
 
             if let Some(stmt) = stmt {
                 debug!("Adding statement to block");
@@ -621,7 +890,10 @@ impl Parser {
             }
         }
 
-        Ok(Block { stmts })
+        Ok(Block {
+            stmts,
+            span: open.span.to(&self.peek().span),
+        })
     }
 
     /// Parses a function declaration.
@@ -659,7 +931,7 @@ impl Parser {
                     self.consume();
                 }
 
-                let param = self.consume();
+                let param = self.expect_param_name()?;
 
                 if param.literal() == "self" {
                     if !is_static {
@@ -707,7 +979,11 @@ impl Parser {
         let return_type = self.parse_return_type()?;
 
         self.expect_punct(TokenKind::LeftBrace)?;
-        let body = self.parse_block()?;
+        // A function declared in a catch block can't rethrow its error
+        let catch_depth = std::mem::take(&mut self.catch_depth);
+        let body = self.parse_block();
+        self.catch_depth = catch_depth;
+        let body = body?;
         self.expect_punct(TokenKind::RightBrace)?;
 
         Ok(Stmt::new_fn(
@@ -721,3 +997,134 @@ impl Parser {
         ))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{source::Source, Expr, Lexer, Parser, Stmt, TypeAnnotation, TypeKind};
+
+    fn parse_annotation(src: &str) -> anyhow::Result<TypeAnnotation> {
+        let tokens = Lexer::new(Source::from_string(src.to_string())).lex(false)?;
+        Parser::new(tokens).parse_type_annotation(false)
+    }
+
+    #[test]
+    fn test_parse_nested_generics() {
+        let typ = parse_annotation("vec<vec<int>>").unwrap();
+
+        assert_eq!(typ.kind, TypeKind::Vec);
+        assert_eq!(typ.generics[0].kind, TypeKind::Vec);
+        assert_eq!(typ.generics[0].generics[0].kind, TypeKind::Int);
+        assert_eq!(
+            parse_annotation("object<vec<vec<string?>>>")
+                .unwrap()
+                .to_string(),
+            "object<vec<vec<string?>>>"
+        );
+    }
+
+    #[test]
+    fn test_parse_struct_generic() {
+        let typ = parse_annotation("object<Person>").unwrap();
+
+        assert_eq!(typ.kind, TypeKind::Object);
+        assert_eq!(typ.generics[0].kind, TypeKind::Custom("Person".to_string()));
+    }
+
+    #[test]
+    fn test_parse_array_suffix() {
+        assert_eq!(parse_annotation("int[]").unwrap().to_string(), "vec<int>");
+        assert_eq!(
+            parse_annotation("int[][]?").unwrap().to_string(),
+            "vec<vec<int>>?"
+        );
+        assert_eq!(
+            parse_annotation("vec<string>[]").unwrap().to_string(),
+            "vec<vec<string>>"
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid_generics() {
+        assert!(parse_annotation("vec<>").is_err());
+        assert!(parse_annotation("vec<int").is_err());
+        assert!(parse_annotation("vec<int;").is_err());
+        assert!(parse_annotation("vec<vec<int>").is_err());
+    }
+
+    #[test]
+    fn test_parse_for() {
+        let tokens = Lexer::new(Source::from_string(
+            "for item in items { print(item); }".to_string(),
+        ))
+        .lex(false)
+        .unwrap();
+        let ast = Parser::new(tokens).parse().unwrap();
+
+        match &ast.stmts[0] {
+            Stmt::For(for_stmt) => {
+                assert_eq!(for_stmt.ident.literal(), "item");
+                assert!(matches!(for_stmt.iterable.as_ref(), Expr::Variable(_)));
+                assert_eq!(for_stmt.block.stmts.len(), 1);
+            }
+            stmt => panic!("Expected for statement, got {:?}", stmt),
+        }
+
+        let ast = parse("for key, value in obj {}").unwrap();
+        let Stmt::For(for_stmt) = &ast.stmts[0] else {
+            panic!("Expected for statement, got {:?}", ast.stmts[0]);
+        };
+        assert_eq!(for_stmt.ident.literal(), "key");
+        assert_eq!(for_stmt.value_ident.as_ref().unwrap().literal(), "value");
+        assert!(parse("for key, in obj {}").is_err());
+    }
+
+    fn parse(src: &str) -> anyhow::Result<crate::Ast> {
+        let tokens = Lexer::new(Source::from_string(src.to_string())).lex(false)?;
+        Parser::new(tokens).parse()
+    }
+
+    #[test]
+    fn test_parse_attributes() {
+        let ast = parse(
+            "@test fn check() {}
+             @deprecated(\"use Point\", 2) @internal pub struct Old {}
+             @inline const MAX = 1;
+             fn plain() {}
+             impl Old { @deprecated fn get(self) {} }",
+        )
+        .unwrap();
+
+        match &ast.stmts[1] {
+            Stmt::Struct(struct_def) => {
+                assert!(struct_def.public);
+                assert_eq!(struct_def.attributes.len(), 2);
+                assert_eq!(struct_def.attributes[0].name.literal(), "deprecated");
+                assert_eq!(struct_def.attributes[0].args.len(), 2);
+                assert!(struct_def.has_attribute("internal"));
+            }
+            stmt => panic!("Expected struct, got {:?}", stmt),
+        }
+        match &ast.stmts[2] {
+            Stmt::Const(const_stmt) => assert!(const_stmt.has_attribute("inline")),
+            stmt => panic!("Expected const, got {:?}", stmt),
+        }
+        match &ast.stmts[4] {
+            Stmt::StructImpl(impl_stmt) => {
+                assert!(impl_stmt.methods[0].has_attribute("deprecated"))
+            }
+            stmt => panic!("Expected impl, got {:?}", stmt),
+        }
+
+        let tests = ast.test_functions();
+        assert_eq!(tests.len(), 1);
+        assert_eq!(tests[0].name, "check");
+        assert!(ast.stmts[3].as_function().attributes.is_empty());
+    }
+
+    #[test]
+    fn test_attribute_without_declaration() {
+        assert!(parse("@test let x = 1;").is_err());
+        assert!(parse("@test").is_err());
+        assert!(parse("@(1) fn f() {}").is_err());
+    }
+}
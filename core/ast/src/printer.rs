@@ -0,0 +1,705 @@
+use crate::{
+    AccessKind, Ast, Attribute, BinOpAssociativity, Block, Expr, Fn, LiteralType, Segment, Stmt,
+    TokenKind, UnOpKind,
+};
+
+/// Turns an AST back into source code.
+///
+/// The output is formatted canonically: every statement is on its own line, blocks are indented
+/// and declarations such as functions and structs are separated by a blank line. Comments aren't
+/// part of the AST, so they aren't printed.
+///
+/// # Example
+///
+/// ```rust
+/// use roan_ast::{printer::AstPrinter, source::Source, Lexer, Parser};
+///
+/// let tokens = Lexer::new(Source::from_string("let   x=1+2".to_string())).lex(false).unwrap();
+/// let ast = Parser::new(tokens).parse().unwrap();
+///
+/// let mut printer = AstPrinter::new();
+/// printer.print_ast(&ast);
+/// assert_eq!(printer.finish(), "let x = 1 + 2;\n");
+/// ```
+#[derive(Debug, Clone)]
+pub struct AstPrinter {
+    /// Current indentation level.
+    indent: usize,
+    /// Number of spaces written for every indentation level.
+    indent_width: usize,
+    output: String,
+}
+
+impl Default for AstPrinter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AstPrinter {
+    /// Creates a printer that indents with 4 spaces.
+    pub fn new() -> Self {
+        Self::with_indent_width(4)
+    }
+
+    /// Creates a printer that indents with `width` spaces.
+    pub fn with_indent_width(width: usize) -> Self {
+        Self {
+            indent: 0,
+            indent_width: width,
+            output: String::new(),
+        }
+    }
+
+    /// Returns the printed source code.
+    pub fn finish(self) -> String {
+        self.output
+    }
+
+    /// Prints every statement of the AST.
+    pub fn print_ast(&mut self, ast: &Ast) {
+        self.print_stmts(&ast.stmts);
+    }
+
+    /// Prints a statement on its own line, including the trailing newline.
+    pub fn print_stmt(&mut self, stmt: &Stmt) {
+        self.write_indent();
+
+        match stmt {
+            Stmt::Expr(expr) => {
+                // An object at the start of a statement would be parsed as a block
+                if matches!(expr.as_ref(), Expr::Object(_)) {
+                    self.write("(");
+                    self.print_expr(expr);
+                    self.write(")");
+                } else {
+                    self.print_expr(expr);
+                }
+                self.write(";");
+            }
+            Stmt::Use(use_stmt) => {
+                let items = use_stmt
+                    .items
+                    .iter()
+                    .map(|item| item.literal())
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                let from = match &use_stmt.from.kind {
+                    TokenKind::String(path) => path.clone(),
+                    _ => use_stmt.from.literal(),
+                };
+
+                self.write(&format!("use {{ {} }} from ", items));
+                self.write_string(&from);
+                self.write(";");
+            }
+            Stmt::Block(block) => self.print_block(block),
+            Stmt::If(if_stmt) => {
+                self.write("if ");
+                self.print_expr(&if_stmt.condition);
+                self.write(" ");
+                self.print_block(&if_stmt.then_block);
+
+                for else_if in if_stmt.else_ifs.iter() {
+                    self.write(" else if ");
+                    self.print_expr(&else_if.condition);
+                    self.write(" ");
+                    self.print_block(&else_if.block);
+                }
+
+                if let Some(else_block) = &if_stmt.else_block {
+                    self.write(" else ");
+                    self.print_block(&else_block.block);
+                }
+            }
+            Stmt::Return(return_stmt) => match &return_stmt.expr {
+                Some(expr) => {
+                    self.write("return ");
+                    self.print_expr(expr);
+                    self.write(";");
+                }
+                None => self.write("return;"),
+            },
+            Stmt::Fn(func) => self.print_fn(func),
+            Stmt::Let(let_stmt) => {
+                self.write(&format!("let {}", let_stmt.ident.literal()));
+                if let Some(typ) = &let_stmt.type_annotation {
+                    self.write(&format!(": {}", typ));
+                }
+                self.write(" = ");
+                self.print_expr(&let_stmt.initializer);
+                self.write(";");
+            }
+            Stmt::Throw(throw) => {
+                self.write("throw");
+                if let Some(value) = &throw.value {
+                    self.write(" ");
+                    self.print_expr(value);
+                }
+                self.write(";");
+            }
+            Stmt::Try(try_stmt) => {
+                self.write("try ");
+                self.print_block(&try_stmt.try_block);
+                self.write(&format!(" catch {} ", try_stmt.error_ident.literal()));
+                self.print_block(&try_stmt.catch_block);
+                if let Some(finally_block) = &try_stmt.finally_block {
+                    self.write(" finally ");
+                    self.print_block(finally_block);
+                }
+            }
+            Stmt::Break(_) => self.write("break;"),
+            Stmt::Continue(_) => self.write("continue;"),
+            Stmt::Loop(loop_stmt) => {
+                self.write("loop ");
+                self.print_block(&loop_stmt.block);
+            }
+            Stmt::While(while_stmt) => {
+                self.write("while ");
+                self.print_expr(&while_stmt.condition);
+                self.write(" ");
+                self.print_block(&while_stmt.block);
+            }
+            Stmt::For(for_stmt) => {
+                self.write(&format!("for {}", for_stmt.ident.literal()));
+                if let Some(value_ident) = &for_stmt.value_ident {
+                    self.write(&format!(", {}", value_ident.literal()));
+                }
+                self.write(" in ");
+                self.print_expr(&for_stmt.iterable);
+                self.write(" ");
+                self.print_block(&for_stmt.block);
+            }
+            Stmt::Struct(struct_def) => {
+                self.print_attributes(&struct_def.attributes);
+                self.write_pub(struct_def.public);
+                self.write(&format!("struct {} {{", struct_def.name.literal()));
+
+                if !struct_def.fields.is_empty() {
+                    self.write("\n");
+                    self.indent += 1;
+                    for field in struct_def.fields.values() {
+                        self.write_indent();
+                        self.write(&format!(
+                            "{}: {},\n",
+                            field.ident.literal(),
+                            field.type_annotation
+                        ));
+                    }
+                    self.indent -= 1;
+                    self.write_indent();
+                }
+
+                self.write("}");
+            }
+            Stmt::TraitDef(trait_def) => {
+                self.write_pub(trait_def.public);
+                self.write(&format!("trait {} ", trait_def.name.literal()));
+                self.print_methods(&trait_def.methods);
+            }
+            Stmt::StructImpl(impl_stmt) => {
+                self.write(&format!("impl {} ", impl_stmt.struct_name.literal()));
+                self.print_methods(&impl_stmt.methods);
+            }
+            Stmt::TraitImpl(impl_stmt) => {
+                self.write(&format!(
+                    "impl {} for {} ",
+                    impl_stmt.trait_name.literal(),
+                    impl_stmt.struct_name.literal()
+                ));
+                self.print_methods(&impl_stmt.methods);
+            }
+            Stmt::Const(const_stmt) => {
+                self.print_attributes(&const_stmt.attributes);
+                self.write_pub(const_stmt.public);
+                self.write(&format!("const {} = ", const_stmt.ident.literal()));
+                self.print_expr(&const_stmt.expr);
+                self.write(";");
+            }
+        }
+
+        self.write("\n");
+    }
+
+    /// Prints an expression without a trailing newline.
+    ///
+    /// Parentheses are kept where the source had them and added where the structure of the
+    /// expression would be lost without them, e.g. for an AST built by hand.
+    pub fn print_expr(&mut self, expr: &Expr) {
+        match expr {
+            Expr::Literal(literal) => match &literal.value {
+                LiteralType::Int(int) => self.write(&int.to_string()),
+                LiteralType::Float(float) => {
+                    let float = float.to_string();
+                    self.write(&float);
+                    // Keep the dot so the literal isn't read back as an integer
+                    if !float.contains(['.', 'N', 'i']) {
+                        self.write(".0");
+                    }
+                }
+                LiteralType::String(string) => self.write_string(string),
+                LiteralType::Bool(bool) => self.write(&bool.to_string()),
+                LiteralType::Char(char) => self.write(&format!("'{}'", char)),
+                LiteralType::Null => self.write("null"),
+            },
+            Expr::Binary(binary) => {
+                let precedence = binary.operator.precedence();
+                let right_assoc = binary.operator.associativity() == BinOpAssociativity::Right;
+
+                self.print_operand(&binary.left, precedence, right_assoc);
+                self.write(&format!(" {} ", binary.operator));
+                self.print_operand(&binary.right, precedence, !right_assoc);
+            }
+            Expr::Unary(unary) => {
+                self.write(&unary.operator.kind.to_string());
+
+                let needs_parens = matches!(
+                    unary.expr.as_ref(),
+                    Expr::Binary(_) | Expr::Assign(_) | Expr::ThenElse(_)
+                );
+                // `- -x` would be read back as a decrement
+                let is_minus = |expr: &Expr| matches!(expr, Expr::Unary(inner) if inner.operator.kind == UnOpKind::Minus);
+                if unary.operator.kind == UnOpKind::Minus && is_minus(&unary.expr) {
+                    self.write(" ");
+                }
+
+                self.print_wrapped(&unary.expr, needs_parens);
+            }
+            Expr::Variable(variable) => self.write(&variable.ident),
+            Expr::Parenthesized(parenthesized) => {
+                self.write("(");
+                self.print_expr(&parenthesized.expr);
+                self.write(")");
+            }
+            Expr::Call(call) => {
+                self.write(&call.callee);
+                self.write("(");
+                self.print_list(&call.args);
+                self.write(")");
+            }
+            Expr::Assign(assign) => {
+                self.print_expr(&assign.left);
+                self.write(&format!(" {} ", assign.op));
+                self.print_expr(&assign.right);
+            }
+            Expr::Vec(vec) => {
+                self.write("[");
+                self.print_list(&vec.exprs);
+                self.write("]");
+            }
+            Expr::Access(access) => {
+                let needs_parens = matches!(
+                    access.base.as_ref(),
+                    Expr::Binary(_) | Expr::Unary(_) | Expr::Assign(_) | Expr::ThenElse(_)
+                );
+                self.print_wrapped(&access.base, needs_parens);
+
+                match &access.access {
+                    AccessKind::Field(field) => {
+                        self.write(".");
+                        self.print_expr(field);
+                    }
+                    AccessKind::Index(index) => {
+                        self.write("[");
+                        self.print_expr(index);
+                        self.write("]");
+                    }
+                    AccessKind::StaticMethod(method) => {
+                        self.write("::");
+                        self.print_expr(method);
+                    }
+                }
+            }
+            Expr::Spread(spread) => {
+                self.write("...");
+                self.print_expr(&spread.expr);
+            }
+            Expr::Null(_) => self.write("null"),
+            Expr::Interpolated(interpolated) => self.write_interpolated(&interpolated.segments),
+            Expr::StructConstructor(constructor) => {
+                self.write(&constructor.name);
+
+                if constructor.fields.is_empty() {
+                    self.write(" {}");
+                } else {
+                    self.write(" { ");
+                    for (i, (name, value)) in constructor.fields.iter().enumerate() {
+                        if i > 0 {
+                            self.write(", ");
+                        }
+                        self.write(&format!("{}: ", name));
+                        self.print_expr(value);
+                    }
+                    self.write(" }");
+                }
+            }
+            Expr::ThenElse(then_else) => {
+                self.print_expr(&then_else.condition);
+                self.write(" then ");
+                self.print_expr(&then_else.then_expr);
+                self.write(" else ");
+                self.print_expr(&then_else.else_expr);
+            }
+            Expr::Object(object) => {
+                if object.fields.is_empty() {
+                    self.write("{}");
+                } else {
+                    self.write("{ ");
+                    for (i, (key, value)) in object.fields.iter().enumerate() {
+                        if i > 0 {
+                            self.write(", ");
+                        }
+                        // Keys are stored as written, escapes included
+                        self.write(&format!("\"{}\": ", key));
+                        self.print_expr(value);
+                    }
+                    self.write(" }");
+                }
+            }
+        }
+    }
+
+    /// Prints statements, separating declarations from their neighbours with a blank line.
+    fn print_stmts(&mut self, stmts: &[Stmt]) {
+        for (i, stmt) in stmts.iter().enumerate() {
+            if i > 0 && (is_declaration(stmt) || is_declaration(&stmts[i - 1])) {
+                self.write("\n");
+            }
+            self.print_stmt(stmt);
+        }
+    }
+
+    /// Prints a block starting at the current position. The closing brace isn't followed by a
+    /// newline.
+    fn print_block(&mut self, block: &Block) {
+        if block.stmts.is_empty() {
+            self.write("{}");
+            return;
+        }
+
+        self.write("{\n");
+        self.indent += 1;
+        self.print_stmts(&block.stmts);
+        self.indent -= 1;
+        self.write_indent();
+        self.write("}");
+    }
+
+    /// Prints the body of a trait or impl block.
+    fn print_methods(&mut self, methods: &[Fn]) {
+        if methods.is_empty() {
+            self.write("{}");
+            return;
+        }
+
+        self.write("{\n");
+        self.indent += 1;
+        for (i, method) in methods.iter().enumerate() {
+            if i > 0 {
+                self.write("\n");
+            }
+            self.write_indent();
+            self.print_fn(method);
+            self.write("\n");
+        }
+        self.indent -= 1;
+        self.write_indent();
+        self.write("}");
+    }
+
+    /// Prints every attribute on its own line, followed by the indentation of the declaration.
+    fn print_attributes(&mut self, attributes: &[Attribute]) {
+        for attribute in attributes.iter() {
+            self.write(&format!("@{}", attribute.name.literal()));
+
+            if !attribute.args.is_empty() {
+                self.write("(");
+                for (i, arg) in attribute.args.iter().enumerate() {
+                    if i > 0 {
+                        self.write(", ");
+                    }
+                    self.print_expr(arg);
+                }
+                self.write(")");
+            }
+
+            self.write("\n");
+            self.write_indent();
+        }
+    }
+
+    fn print_fn(&mut self, func: &Fn) {
+        self.print_attributes(&func.attributes);
+        self.write_pub(func.public);
+        self.write(&format!("fn {}(", func.name));
+
+        for (i, param) in func.params.iter().enumerate() {
+            if i > 0 {
+                self.write(", ");
+            }
+            if param.is_rest {
+                self.write("...");
+            }
+
+            let name = param.ident.literal();
+            if name == "self" {
+                self.write(&name);
+            } else {
+                self.write(&format!("{}: {}", name, param.type_annotation));
+            }
+        }
+        self.write(")");
+
+        if let Some(return_type) = &func.return_type {
+            self.write(&format!(" -> {}", return_type));
+        }
+
+        self.write(" ");
+        self.print_block(&func.body);
+    }
+
+    /// Prints an operand of a binary expression, wrapping it in parentheses if it binds weaker
+    /// than the operator, or as tight on the side the operator doesn't group from.
+    fn print_operand(&mut self, expr: &Expr, parent_precedence: u8, is_opposite_side: bool) {
+        let needs_parens = match expr {
+            Expr::Binary(binary) => {
+                let precedence = binary.operator.precedence();
+                precedence < parent_precedence
+                    || (precedence == parent_precedence && is_opposite_side)
+            }
+            Expr::Assign(_) | Expr::ThenElse(_) => true,
+            _ => false,
+        };
+
+        self.print_wrapped(expr, needs_parens);
+    }
+
+    fn print_wrapped(&mut self, expr: &Expr, parens: bool) {
+        if parens {
+            self.write("(");
+        }
+        self.print_expr(expr);
+        if parens {
+            self.write(")");
+        }
+    }
+
+    fn print_list(&mut self, exprs: &[Expr]) {
+        for (i, expr) in exprs.iter().enumerate() {
+            if i > 0 {
+                self.write(", ");
+            }
+            self.print_expr(expr);
+        }
+    }
+
+    fn write_pub(&mut self, public: bool) {
+        if public {
+            self.write("pub ");
+        }
+    }
+
+    /// Writes a string literal, escaping the characters the lexer unescapes.
+    fn write_string(&mut self, string: &str) {
+        self.output.push('"');
+        self.write_escaped(string, false);
+        self.output.push('"');
+    }
+
+    fn write_interpolated(&mut self, segments: &[Segment]) {
+        self.output.push_str("f\"");
+        for segment in segments {
+            match segment {
+                Segment::Text(text) => self.write_escaped(text, true),
+                Segment::Expr(expr) => {
+                    self.output.push('{');
+                    self.print_expr(expr);
+                    self.output.push('}');
+                }
+            }
+        }
+        self.output.push('"');
+    }
+
+    /// Writes the content of a string literal, braces are doubled inside interpolated strings.
+    fn write_escaped(&mut self, string: &str, interpolated: bool) {
+        for c in string.chars() {
+            match c {
+                '\n' => self.output.push_str("\\n"),
+                '\r' => self.output.push_str("\\r"),
+                '\t' => self.output.push_str("\\t"),
+                '\\' => self.output.push_str("\\\\"),
+                '"' => self.output.push_str("\\\""),
+                '{' if interpolated => self.output.push_str("{{"),
+                '}' if interpolated => self.output.push_str("}}"),
+                _ => self.output.push(c),
+            }
+        }
+    }
+
+    fn write_indent(&mut self) {
+        self.output
+            .push_str(&" ".repeat(self.indent * self.indent_width));
+    }
+
+    fn write(&mut self, s: &str) {
+        self.output.push_str(s);
+    }
+}
+
+/// Returns `true` for statements that are separated from their neighbours by a blank line.
+fn is_declaration(stmt: &Stmt) -> bool {
+    matches!(
+        stmt,
+        Stmt::Fn(_)
+            | Stmt::Struct(_)
+            | Stmt::TraitDef(_)
+            | Stmt::StructImpl(_)
+            | Stmt::TraitImpl(_)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{source::Source, Lexer, Parser};
+
+    fn parse(src: &str) -> Ast {
+        let tokens = Lexer::new(Source::from_string(src.to_string()))
+            .lex(false)
+            .unwrap();
+        Parser::new(tokens).parse().unwrap()
+    }
+
+    fn print(ast: &Ast) -> String {
+        let mut printer = AstPrinter::new();
+        printer.print_ast(ast);
+        printer.finish()
+    }
+
+    /// Formats the AST without source positions, so trees parsed from differently formatted
+    /// sources can be compared.
+    fn structure(ast: &Ast) -> String {
+        let debug = format!("{:?}", ast.stmts);
+        let mut result = String::new();
+        let mut rest = debug.as_str();
+
+        // Spans are written as `"literal" (line:column)`
+        while let Some(start) = rest.find(" (") {
+            let end = rest[start..].find(')').map(|end| start + end + 1);
+            match end {
+                Some(end)
+                    if rest[start + 2..end - 1]
+                        .chars()
+                        .all(|c| c.is_ascii_digit() || c == ':') =>
+                {
+                    result.push_str(&rest[..start]);
+                    rest = &rest[end..];
+                }
+                _ => {
+                    result.push_str(&rest[..start + 2]);
+                    rest = &rest[start + 2..];
+                }
+            }
+        }
+        result.push_str(rest);
+        result
+    }
+
+    fn assert_round_trip(src: &str) {
+        let ast = parse(src);
+        let printed = print(&ast);
+        let reparsed = parse(&printed);
+
+        assert_eq!(structure(&ast), structure(&reparsed), "{}", printed);
+        assert_eq!(print(&reparsed), printed);
+    }
+
+    #[test]
+    fn test_round_trip_statements() {
+        assert_round_trip(
+            r#"
+            use { add, sub } from "std::math";
+            @doc("limit") pub const MAX = 10;
+            let x: vec<int>? = [1, 2, ...rest];
+            let obj = { "a": 1, "b\n": "c\"d" };
+            if x > 1 { print(x); } else if x < 0 { throw "negative"; } else {}
+            while x < 10 { x += 1; continue; }
+            loop { break; }
+            for c in "abc" { print(c); }
+            try { risky(); } catch err { print(err); }
+            try { risky(); } catch err {} finally { close(); }
+            try { risky(); } catch err { throw; }
+            { let scoped = 'a'; }
+            "#,
+        );
+    }
+
+    #[test]
+    fn test_round_trip_declarations() {
+        assert_round_trip(
+            r#"
+            pub struct Point { x: int, y: float }
+            @deprecated("use Point") @internal struct Empty {}
+            pub trait Shape { fn area(self) -> float { return 1.5; } }
+            impl Point {
+                pub fn new(x: int, y: float) -> Point { return Point { x: x, y: y }; }
+                @inline fn sum(self, ...rest: int) -> int { return; }
+            }
+            impl Shape for Point { fn area(self) -> float { return self.x * self.y; } }
+            fn main() { let p = Point::new(1, 2.5); p.area(); p.x = items[1]; }
+            "#,
+        );
+    }
+
+    #[test]
+    fn test_round_trip_expressions() {
+        assert_round_trip(
+            "let a = (1 + 2) * 3 - -4 ** 2 ** 3 % 5;\n\
+             let b = !(a == 1) && a != 2 || a <= 3 & 4 | 5 ^ 6 << 7 >> 8;\n\
+             let c = a > 1 then \"yes\" else null;\n\
+             let d = 1.5 + 2.0;\n\
+             let e = f\"a {x + 1} {{b}}\";",
+        );
+    }
+
+    #[test]
+    fn test_print_formatting() {
+        let ast = parse("fn add(a:int,b:int)->int{if a>b{return a;}return a+b;} add(1,2);");
+        let mut printer = AstPrinter::with_indent_width(2);
+        printer.print_ast(&ast);
+
+        assert_eq!(
+            printer.finish(),
+            "fn add(a: int, b: int) -> int {\n  \
+               if a > b {\n    \
+                 return a;\n  \
+               }\n  \
+               return a + b;\n\
+             }\n\
+             \n\
+             add(1, 2);\n"
+        );
+    }
+
+    #[test]
+    fn test_print_adds_needed_parens() {
+        // `(a - b) - (c - d)` where the right operand was built without parentheses
+        let ast = parse("a - b - c;");
+        let Stmt::Expr(expr) = &ast.stmts[0] else {
+            panic!("Expected expression statement");
+        };
+        let Expr::Binary(binary) = expr.as_ref() else {
+            panic!("Expected binary expression");
+        };
+        let swapped = Expr::Binary(crate::Binary {
+            left: binary.right.clone(),
+            operator: binary.operator,
+            right: binary.left.clone(),
+        });
+
+        let mut printer = AstPrinter::new();
+        printer.print_expr(&swapped);
+        assert_eq!(printer.finish(), "c - (a - b)");
+    }
+}
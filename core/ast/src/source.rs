@@ -1,4 +1,7 @@
+use anyhow::Result;
+use roan_error::{position::floor_char_boundary, Position, TextSpan};
 use std::{
+    fs,
     fs::File,
     io::{self, BufReader, Read},
     path::PathBuf,
@@ -11,25 +14,44 @@ use tracing::debug;
 pub struct Source {
     content: String,
     path: Option<PathBuf>,
+    lines: LineIndex,
+}
+
+/// Byte offsets of the start of every line of a source.
+#[derive(Clone, Debug)]
+struct LineIndex {
+    starts: Vec<usize>,
+}
+
+impl LineIndex {
+    fn new(content: &str) -> Self {
+        let starts = std::iter::once(0)
+            .chain(content.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+
+        Self { starts }
+    }
 }
 
 impl Source {
+    fn new(content: String, path: Option<PathBuf>) -> Self {
+        Self {
+            lines: LineIndex::new(&content),
+            content,
+            path,
+        }
+    }
+
     /// Creates a new `Source` from a `String`.
     pub fn from_string(string: String) -> Self {
         debug!("Creating source from string");
-        Self {
-            content: string,
-            path: None,
-        }
+        Self::new(string, None)
     }
 
     /// Creates a new `Source` from a byte slice.
     pub fn from_bytes<T: AsRef<[u8]> + ?Sized>(source: &T) -> Self {
         debug!("Creating source from bytes");
-        Self {
-            content: source.as_ref().iter().map(|&b| b as char).collect(),
-            path: None,
-        }
+        Self::new(source.as_ref().iter().map(|&b| b as char).collect(), None)
     }
 
     /// Creates a new `Source` from a file path.
@@ -37,20 +59,29 @@ impl Source {
         debug!("Creating source from path: {:?}", path);
         let file = File::open(&path)?;
         let reader = BufReader::new(file);
-        Ok(Self {
-            content: reader
-                .bytes()
-                .filter_map(|b| b.ok().map(|b| b as char))
-                .collect(),
-            path: Some(path),
-        })
+        let content = reader
+            .bytes()
+            .filter_map(|b| b.ok().map(|b| b as char))
+            .collect();
+
+        Ok(Self::new(content, Some(path)))
+    }
+
+    /// Creates a new `Source` from the file at the given path.
+    ///
+    /// The file is read as UTF-8 and the path is associated with the source.
+    pub fn from_file(path: PathBuf) -> Result<Self> {
+        debug!("Creating source from file: {:?}", path);
+        let content = fs::read_to_string(&path)?;
+
+        Ok(Self::new(content, Some(path)))
     }
 
     /// Sets or updates the path of this `Source`.
     pub fn with_path(self, new_path: PathBuf) -> Self {
         Self {
-            content: self.content,
             path: Some(new_path),
+            ..self
         }
     }
 
@@ -69,14 +100,96 @@ impl Source {
         self.content.len()
     }
 
+    /// Returns the line with the given number, starting at 1 like [roan_error::Position::line],
+    /// without its line ending.
+    ///
+    /// Returns `None` if the source has fewer lines.
+    pub fn line_at(&self, line: usize) -> Option<&str> {
+        if line == 0 || line > self.line_count() {
+            return None;
+        }
+
+        let start = self.lines.starts[line - 1];
+        let end = self
+            .lines
+            .starts
+            .get(line)
+            .copied()
+            .unwrap_or(self.content.len());
+        let line = &self.content[start..end];
+        let line = line.strip_suffix('\n').unwrap_or(line);
+
+        Some(line.strip_suffix('\r').unwrap_or(line))
+    }
+
+    /// Returns the number of lines of this `Source`, a trailing newline doesn't start a new line.
+    pub fn line_count(&self) -> usize {
+        match self.lines.starts.last() {
+            Some(&start) if start == self.content.len() => self.lines.starts.len() - 1,
+            _ => self.lines.starts.len(),
+        }
+    }
+
+    /// Returns the position of the byte `offset`, like [Position::from_offset] but the line is
+    /// found with a binary search of the line index.
+    pub fn position_at(&self, offset: usize) -> Position {
+        let offset = floor_char_boundary(&self.content, offset);
+        let line = self.lines.starts.partition_point(|&start| start <= offset);
+        let line_start = self.lines.starts[line - 1];
+
+        Position::new(
+            line as u32,
+            self.content[line_start..offset].chars().count() as u32,
+            self.content[..offset].chars().count(),
+        )
+    }
+
+    /// Returns the lines of this `Source`, without their line endings.
+    pub fn lines(&self) -> impl Iterator<Item = &str> {
+        (1..=self.line_count()).filter_map(|line| self.line_at(line))
+    }
+
     /// Returns the content of this `Source` as a char iterator.
     pub fn chars(&self) -> Chars {
         self.content.chars()
     }
 
-    /// Returns the content of this `Source` between the specified indices.
+    /// Returns the content of this `Source` between the specified char indices.
     pub fn get_between(&self, start: usize, end: usize) -> String {
-        self.content[start..end].to_string()
+        self.content
+            .chars()
+            .skip(start)
+            .take(end.saturating_sub(start))
+            .collect()
+    }
+
+    /// Returns a 64-bit FNV-1a hash of the content of this `Source`.
+    ///
+    /// Used to detect whether a cached module has to be parsed again.
+    pub fn checksum(&self) -> u64 {
+        const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+        const FNV_PRIME: u64 = 0x100000001b3;
+
+        self.content.bytes().fold(FNV_OFFSET_BASIS, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(FNV_PRIME)
+        })
+    }
+
+    /// Renders the line the span starts on with a `^` underline under the span and the label.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use roan_ast::source::Source;
+    /// use roan_error::{Position, TextSpan};
+    /// let source = Source::from_string("let x = 5;".to_string());
+    /// let span = TextSpan::new(Position::new(1, 4, 4), Position::new(1, 5, 5), "x".to_string());
+    /// assert_eq!(source.highlight(span, "declared here"), "1 | let x = 5;\n  |     ^ declared here");
+    /// ```
+    pub fn highlight(&self, span: TextSpan, label: &str) -> String {
+        let line = self.line_at(span.start.line.max(1) as usize).unwrap_or("");
+
+        span.highlight_line(line, label)
     }
 }
 
@@ -109,6 +222,47 @@ mod tests {
         assert_eq!(source.path(), Some(PathBuf::from("tests/test.roan")));
     }
 
+    #[test]
+    fn test_source_from_file() {
+        let path = std::env::temp_dir().join("roan_test_source_from_file.roan");
+        fs::write(&path, "fn main() {\n    let s = \"ñ€\";\n}\n").unwrap();
+
+        let source = Source::from_file(path.clone()).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(source.content(), "fn main() {\n    let s = \"ñ€\";\n}\n");
+        assert_eq!(source.path(), Some(path));
+    }
+
+    #[test]
+    fn test_source_from_missing_file() {
+        let path = std::env::temp_dir().join("roan_test_missing_file.roan");
+
+        assert!(Source::from_file(path).is_err());
+    }
+
+    #[test]
+    fn test_source_checksum() {
+        let source = Source::from_string("fn main() {}".to_string());
+
+        assert_eq!(
+            Source::from_string("".to_string()).checksum(),
+            0xcbf29ce484222325
+        );
+        assert_eq!(
+            Source::from_string("a".to_string()).checksum(),
+            0xaf63dc4c8601ec8c
+        );
+        assert_eq!(
+            source.checksum(),
+            Source::from_string("fn main() {}".to_string()).checksum()
+        );
+        assert_ne!(
+            source.checksum(),
+            Source::from_string("fn main() { }".to_string()).checksum()
+        );
+    }
+
     #[test]
     fn test_source_len() {
         let source = Source::from_string("fn main() {}".to_string());
@@ -129,4 +283,65 @@ mod tests {
 
         assert_eq!(source.get_between(3, 7), "main");
     }
+
+    #[test]
+    fn test_source_get_between_multibyte() {
+        let source = Source::from_string("let s = \"ñ€\";".to_string());
+
+        assert_eq!(source.get_between(8, 12), "\"ñ€\"");
+    }
+
+    #[test]
+    fn test_source_highlight() {
+        let source = Source::from_string("fn main() {\n    let x = y;\n}".to_string());
+        let span = TextSpan::new(
+            roan_error::Position::new(2, 12, 24),
+            roan_error::Position::new(2, 13, 25),
+            "y".to_string(),
+        );
+
+        assert_eq!(
+            source.highlight(span, "not found in this scope"),
+            "2 |     let x = y;\n  |             ^ not found in this scope"
+        );
+    }
+
+    #[test]
+    fn test_source_lines() {
+        let source = Source::from_string("fn main() {\r\n    let x = 1;\n\n}\n".to_string());
+
+        assert_eq!(source.line_count(), 4);
+        assert_eq!(source.line_at(1), Some("fn main() {"));
+        assert_eq!(source.line_at(2), Some("    let x = 1;"));
+        assert_eq!(source.line_at(3), Some(""));
+        assert_eq!(source.line_at(4), Some("}"));
+        assert_eq!(source.line_at(0), None);
+        assert_eq!(source.line_at(5), None);
+        assert!(source.lines().eq(source.content().lines()));
+
+        let source = Source::from_string("".to_string());
+        assert_eq!(source.line_count(), 0);
+        assert_eq!(source.line_at(1), None);
+
+        let source = Source::from_string("let s = \"ñ\";\nlet x = s;".to_string());
+        assert_eq!(source.line_count(), 2);
+        assert_eq!(source.line_at(2), Some("let x = s;"));
+    }
+
+    #[test]
+    fn test_source_position_at() {
+        let content = "fn main() {\n    let s = \"ñ\";\n}\n";
+        let source = Source::from_string(content.to_string());
+
+        for offset in [0, 11, 12, 20, 26, 27, 30, 31, 32, 100] {
+            assert_eq!(
+                source.position_at(offset),
+                Position::from_offset(content, offset),
+                "{}",
+                offset
+            );
+        }
+        assert_eq!(source.position_at(12), Position::new(2, 0, 12));
+        assert_eq!(source.position_at(32), Position::new(4, 0, 31));
+    }
 }
@@ -1,11 +1,30 @@
 use crate::{
-    module::{loaders::ModuleLoader, Module},
+    module::{
+        cache::ModuleCache,
+        loaders::{remove_surrounding_quotes, virtual_scheme, ModuleLoader},
+        symbols::Symbol,
+        Module,
+    },
+    natives::prelude::Prelude,
+    source::Source,
     vm::VM,
 };
 use anyhow::Result;
 use bon::bon;
-use roan_error::print_diagnostic;
-use std::{cell::RefCell, fmt::Debug, path::PathBuf, rc::Rc};
+use indexmap::IndexMap;
+use roan_error::{
+    error::RoanError::{ModuleError, ModuleNotFound},
+    Diagnostic, TextSpan,
+};
+use std::{
+    cell::RefCell,
+    collections::HashSet,
+    fmt::{self, Debug, Formatter},
+    io::{self, Write},
+    path::PathBuf,
+    rc::Rc,
+    time::{Duration, Instant},
+};
 use tracing::debug;
 
 /// Struct to interact with the runtime.
@@ -47,6 +66,175 @@ use tracing::debug;
 pub struct Context {
     pub module_loader: Rc<RefCell<dyn ModuleLoader>>,
     pub cwd: PathBuf,
+    /// Names of the passes that are skipped when a module is parsed, see [Pass::name].
+    ///
+    /// [Pass::name]: crate::interpreter::passes::Pass::name
+    pub disabled_passes: HashSet<String>,
+    /// Functions in scope in every module parsed with the context.
+    pub prelude: Prelude,
+    /// What the natives called by scripts are allowed to access.
+    pub capabilities: Capabilities,
+    /// Parsed modules reused when their files didn't change. Contexts built with the same cache,
+    /// e.g. for every run of a watch mode, only parse the files that changed.
+    pub module_cache: Rc<RefCell<ModuleCache>>,
+    /// Time spent lexing, parsing, running passes and interpreting.
+    pub timings: Timings,
+    /// Where `__print` writes, stdout unless replaced with [Context::set_output].
+    pub(crate) output: OutputSink,
+    /// Where `__eprint` writes, stderr unless replaced with [Context::set_error_output].
+    pub(crate) error_output: OutputSink,
+}
+
+/// Shared writer the output natives write to.
+///
+/// Clones of a [Context] share their sinks, so the output of imported modules ends up in the same
+/// place as the output of the module importing them.
+#[derive(Clone)]
+pub struct OutputSink(Rc<RefCell<Box<dyn Write>>>);
+
+impl OutputSink {
+    pub fn new(writer: Box<dyn Write>) -> Self {
+        Self(Rc::new(RefCell::new(writer)))
+    }
+
+    pub fn write_str(&self, s: &str) -> io::Result<()> {
+        self.0.borrow_mut().write_all(s.as_bytes())
+    }
+
+    pub fn flush(&self) -> io::Result<()> {
+        self.0.borrow_mut().flush()
+    }
+}
+
+impl Debug for OutputSink {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("OutputSink")
+    }
+}
+
+/// Access a native can need, see [NativeFunction::requires].
+///
+/// [NativeFunction::requires]: crate::vm::native_fn::NativeFunction::requires
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Capability {
+    Fs,
+    Process,
+    Env,
+    Net,
+}
+
+impl fmt::Display for Capability {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Capability::Fs => "fs",
+            Capability::Process => "process",
+            Capability::Env => "env",
+            Capability::Net => "net",
+        })
+    }
+}
+
+/// The capabilities allowed in a [Context], all of them by default.
+///
+/// Calling a native that needs a disabled capability raises [CapabilityDenied], which scripts can
+/// catch, instead of running it.
+///
+/// [CapabilityDenied]: roan_error::error::RoanError::CapabilityDenied
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Capabilities {
+    pub allow_fs: bool,
+    pub allow_process: bool,
+    pub allow_env: bool,
+    pub allow_net: bool,
+}
+
+impl Capabilities {
+    /// Allows every capability.
+    pub fn all() -> Self {
+        Self {
+            allow_fs: true,
+            allow_process: true,
+            allow_env: true,
+            allow_net: true,
+        }
+    }
+
+    /// Denies every capability, to run untrusted scripts.
+    pub fn none() -> Self {
+        Self {
+            allow_fs: false,
+            allow_process: false,
+            allow_env: false,
+            allow_net: false,
+        }
+    }
+
+    /// Returns `true` if natives needing `capability` can be called.
+    pub fn allows(&self, capability: Capability) -> bool {
+        match capability {
+            Capability::Fs => self.allow_fs,
+            Capability::Process => self.allow_process,
+            Capability::Env => self.allow_env,
+            Capability::Net => self.allow_net,
+        }
+    }
+}
+
+impl Default for Capabilities {
+    fn default() -> Self {
+        Self::all()
+    }
+}
+
+/// Time spent in each phase of running code, e.g. lexing, a pass or interpretation, summed over
+/// every module using the [Context].
+///
+/// The time of a phase doesn't include the phases run during it, like the lexing and parsing of
+/// the modules loaded by `ImportPass`, so the times add up to the total.
+#[derive(Clone, Debug, Default)]
+pub struct Timings {
+    /// Time and number of runs of every phase, in the order they first ran.
+    phases: IndexMap<String, (Duration, usize)>,
+    /// Time of the phases run during each of the phases being measured.
+    nested: Vec<Duration>,
+}
+
+impl Timings {
+    /// Starts measuring a phase, every call must be followed by [Timings::stop].
+    pub fn start(&mut self) -> Instant {
+        self.nested.push(Duration::ZERO);
+        Instant::now()
+    }
+
+    /// Stops measuring the phase started at `start`, adding its time to `phase`.
+    pub fn stop(&mut self, phase: &str, start: Instant) {
+        let elapsed = start.elapsed();
+        let nested = self.nested.pop().unwrap_or_default();
+        if let Some(parent) = self.nested.last_mut() {
+            *parent += elapsed;
+        }
+
+        let (total, runs) = self.phases.entry(phase.to_string()).or_default();
+        *total += elapsed.saturating_sub(nested);
+        *runs += 1;
+    }
+
+    /// Returns the total time of `phase` and how many times it ran.
+    pub fn get(&self, phase: &str) -> Option<(Duration, usize)> {
+        self.phases.get(phase).copied()
+    }
+
+    /// Returns the phases with their total time and number of runs, in the order they first ran.
+    pub fn phases(&self) -> impl Iterator<Item = (&str, Duration, usize)> {
+        self.phases
+            .iter()
+            .map(|(phase, (total, runs))| (phase.as_str(), *total, *runs))
+    }
+
+    /// Returns the time of every phase together.
+    pub fn total(&self) -> Duration {
+        self.phases.values().map(|(total, _)| *total).sum()
+    }
 }
 
 #[bon]
@@ -56,8 +244,22 @@ impl Context {
     pub fn new(
         #[builder] module_loader: Rc<RefCell<dyn ModuleLoader>>,
         #[builder(default = std::env::current_dir().unwrap())] cwd: PathBuf,
+        #[builder(default)] disabled_passes: HashSet<String>,
+        #[builder(default)] prelude: Prelude,
+        #[builder(default)] capabilities: Capabilities,
+        #[builder(default)] module_cache: Rc<RefCell<ModuleCache>>,
     ) -> Self {
-        Self { module_loader, cwd }
+        Self {
+            module_loader,
+            cwd,
+            disabled_passes,
+            prelude,
+            capabilities,
+            module_cache,
+            timings: Timings::default(),
+            output: OutputSink::new(Box::new(io::stdout())),
+            error_output: OutputSink::new(Box::new(io::stderr())),
+        }
     }
 }
 
@@ -71,13 +273,13 @@ impl Context {
     ///
     /// # Returns
     ///
-    /// The result of the evaluation.
-    pub fn eval(&mut self, module: &mut Module, vm: &mut VM) -> Result<()> {
-        self.parse(module, vm)?;
+    /// The warnings reported for the module if successful, otherwise returns an error.
+    pub fn eval(&mut self, module: &mut Module, vm: &mut VM) -> Result<Vec<Diagnostic>> {
+        let warnings = self.parse(module, vm)?;
 
         self.interpret(module, vm)?;
 
-        Ok(())
+        Ok(warnings)
     }
 
     /// Parse a module to prepare it for interpretation.
@@ -88,15 +290,10 @@ impl Context {
     ///
     /// # Returns
     ///
-    /// An empty result if successful, otherwise returns an error.
-    pub fn parse(&mut self, module: &mut Module, vm: &mut VM) -> Result<()> {
-        match module.parse(self, vm) {
-            Ok(_) => Ok(()),
-            Err(e) => {
-                print_diagnostic(&e, Some(module.source().content()), module.path());
-                std::process::exit(1);
-            }
-        }
+    /// The warnings reported for the module if successful, otherwise returns an error, see
+    /// [Module::parse].
+    pub fn parse(&mut self, module: &mut Module, vm: &mut VM) -> Result<Vec<Diagnostic>> {
+        module.parse(self, vm)
     }
 
     /// Interpret a parsed module in the virtual machine.
@@ -108,15 +305,9 @@ impl Context {
     ///
     /// # Returns
     ///
-    /// An empty result if successful, otherwise returns an error.
+    /// An empty result if successful, otherwise returns an error, see [Module::interpret].
     pub fn interpret(&mut self, module: &mut Module, vm: &mut VM) -> Result<()> {
-        match module.interpret(self, vm) {
-            Ok(_) => Ok(()),
-            Err(e) => {
-                print_diagnostic(&e, Some(module.source().content()), module.path());
-                std::process::exit(1);
-            }
-        }
+        module.interpret(self, vm)
     }
 
     /// Insert a module into the context.
@@ -137,6 +328,16 @@ impl Context {
         self.module_loader.borrow().get(name)
     }
 
+    /// Query a module from the context, returning an error if it doesn't exist.
+    ///
+    /// # Arguments
+    /// - `name` - The name of the module to query.
+    /// - `span` - The span the error should point to if the module is not found.
+    pub fn try_query_module(&self, name: &str, span: TextSpan) -> Result<Module> {
+        self.query_module(name)
+            .ok_or_else(|| ModuleNotFound(name.to_string(), span).into())
+    }
+
     /// Load a module from the context.
     ///
     /// This function is different from `query_module` in that it will attempt to load the module from the cache
@@ -146,9 +347,68 @@ impl Context {
     /// - `referrer` - The module that is requesting the module.
     /// - `spec` - The name of the module to load.
     pub fn load_module(&mut self, referrer: &Module, spec: &str) -> Result<Module> {
+        if virtual_scheme(spec).is_some() {
+            let name = remove_surrounding_quotes(spec);
+
+            return self.query_module(name).ok_or_else(|| {
+                ModuleError(format!("No virtual module is registered as {}", name)).into()
+            });
+        }
+
         Ok(self.module_loader.borrow_mut().load(referrer, spec, self)?)
     }
 
+    /// Registers a module that isn't backed by a file, so `use { x } from "std:math"` imports
+    /// from `source`.
+    ///
+    /// The name must have a scheme, see [virtual_scheme]. The module is cached by the module
+    /// loader like a loaded module, so registering a name that is already taken is an error.
+    ///
+    /// # Arguments
+    /// - `name` - The name the module is imported by, e.g. `std:math`.
+    /// - `source` - The source of the module.
+    pub fn register_virtual_module(
+        &mut self,
+        name: impl Into<String>,
+        source: Source,
+    ) -> Result<()> {
+        let name = name.into();
+        if virtual_scheme(&name).is_none() {
+            return Err(ModuleError(format!(
+                "Virtual module {} has no scheme, name it like `std:{}`",
+                name, name
+            ))
+            .into());
+        }
+
+        if self.query_module(&name).is_some() {
+            return Err(ModuleError(format!("Module {} is already registered", name)).into());
+        }
+
+        // Diagnostics name the file of the module
+        let source = match source.path() {
+            Some(_) => source,
+            None => source.with_path(PathBuf::from(&name)),
+        };
+
+        debug!("Registering virtual module: {}", name);
+        self.insert_module(name, Module::new(source));
+
+        Ok(())
+    }
+
+    /// Returns the functions, structs, traits and consts visible in a module, including the
+    /// imported ones.
+    ///
+    /// # Arguments
+    /// - `name` - The name of the module to query.
+    pub fn module_symbols(&self, name: &str) -> Option<Vec<Symbol>> {
+        self.module_loader
+            .borrow()
+            .get(name)
+            .map(|module| module.all_symbols())
+    }
+
     pub fn module_keys(&self) -> Vec<String> {
         self.module_loader.borrow().keys()
     }
@@ -158,4 +418,115 @@ impl Context {
         debug!("Upserting module: {}", name);
         self.module_loader.borrow_mut().insert(name, module);
     }
+
+    /// Skips the pass called `name` when a module is parsed.
+    ///
+    /// # Arguments
+    /// - `name` - The name of the pass, e.g. `TypePass`.
+    pub fn disable_pass(&mut self, name: impl Into<String>) {
+        self.disabled_passes.insert(name.into());
+    }
+
+    /// Returns `true` if the pass called `name` was disabled with [Context::disable_pass].
+    pub fn is_pass_disabled(&self, name: &str) -> bool {
+        self.disabled_passes.contains(name)
+    }
+
+    /// Removes every function from the prelude, modules parsed afterwards can only call the
+    /// functions they declare or import. Imported modules don't get the prelude either, so the
+    /// parts of the standard library built on natives can't be used.
+    pub fn disable_prelude(&mut self) {
+        self.prelude = Prelude::empty();
+    }
+
+    /// Replaces the writer `__print` writes to, e.g. with an in-memory buffer to capture the
+    /// output of a script.
+    pub fn set_output(&mut self, writer: Box<dyn Write>) {
+        self.output = OutputSink::new(writer);
+    }
+
+    /// Replaces the writer `__eprint` writes to.
+    pub fn set_error_output(&mut self, writer: Box<dyn Write>) {
+        self.error_output = OutputSink::new(writer);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{context, parsed, SharedBuffer};
+
+    #[test]
+    fn test_virtual_module() {
+        let mut ctx = context();
+        let output = SharedBuffer::default();
+        ctx.set_output(Box::new(output.clone()));
+
+        ctx.register_virtual_module(
+            "std:math",
+            Source::from_string("pub fn square(x: int) -> int { return x * x; }".to_string()),
+        )
+        .unwrap();
+
+        let mut module = parsed(
+            "use { square } from \"std:math\";\n\
+             __print(__format(square(3)));\n",
+            &mut ctx,
+        );
+        module.interpret(&mut ctx, &mut VM::new()).unwrap();
+        assert_eq!(String::from_utf8(output.0.take()).unwrap(), "9");
+
+        let math = ctx.query_module("std:math").unwrap();
+        assert_eq!(math.path(), Some(PathBuf::from("std:math")));
+
+        let source = || Source::from_string(String::new());
+        assert!(ctx.register_virtual_module("std:math", source()).is_err());
+        assert!(ctx.register_virtual_module("math", source()).is_err());
+
+        let mut module = Module::new(Source::from_string(
+            "use { cube } from \"std:cube\";".to_string(),
+        ));
+        let diagnostics = module.check(&mut ctx, &mut VM::new()).unwrap();
+        assert!(diagnostics
+            .iter()
+            .any(|diagnostic| diagnostic.title.contains("No virtual module")));
+    }
+
+    #[test]
+    fn test_timings() {
+        let mut ctx = context();
+        ctx.set_output(Box::new(SharedBuffer::default()));
+        ctx.register_virtual_module(
+            "std:math",
+            Source::from_string("pub fn square(x: int) -> int { return x * x; }".to_string()),
+        )
+        .unwrap();
+
+        let mut module = parsed(
+            "use { square } from \"std:math\";\n__print(__format(square(3)));\n",
+            &mut ctx,
+        );
+        module.interpret(&mut ctx, &mut VM::new()).unwrap();
+
+        let timings = &ctx.timings;
+        assert_eq!(timings.get("lex").map(|(_, runs)| runs), Some(2));
+        assert_eq!(timings.get("parse").map(|(_, runs)| runs), Some(2));
+        assert_eq!(timings.get("ImportPass").map(|(_, runs)| runs), Some(2));
+        assert_eq!(timings.get("interpret").map(|(_, runs)| runs), Some(1));
+        assert!(timings.get("unknown").is_none());
+
+        // Phases run during another one don't count towards it
+        let mut timings = Timings::default();
+        let outer = timings.start();
+        let inner = timings.start();
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        timings.stop("inner", inner);
+        timings.stop("outer", outer);
+
+        let (inner, _) = timings.get("inner").unwrap();
+        let (outer, _) = timings.get("outer").unwrap();
+        assert!(inner >= std::time::Duration::from_millis(20));
+        assert!(outer < inner);
+        assert_eq!(timings.total(), inner + outer);
+    }
 }
@@ -0,0 +1,194 @@
+use crate::{
+    context::Context,
+    module::{loaders::basic::BasicModuleLoader, Module},
+    source::Source,
+    value::Value,
+    vm::{native_fn::NativeFunction, VM},
+};
+use anyhow::Result;
+use roan_ast::{Lexer, Parser, TokenKind};
+use roan_error::{error::RoanError::UnexpectedToken, Diagnostic};
+use std::{cell::RefCell, mem, path::Path, rc::Rc};
+
+/// Entry point for Rust programs running Roan code.
+///
+/// The engine owns a [Context], a [VM] and the current module. Files loaded with
+/// [RoanEngine::load_file] become the current module, so their functions and top-level variables
+/// can be used by [RoanEngine::eval_expr] and [RoanEngine::call_fn].
+///
+/// # Example
+/// ```rust
+/// use roan_engine::{value::Value, RoanEngine};
+///
+/// let mut engine = RoanEngine::new();
+/// assert_eq!(engine.eval_expr("1 + 2 * 3").unwrap(), Value::Int(7));
+/// ```
+pub struct RoanEngine {
+    ctx: Context,
+    vm: VM,
+    module: Module,
+}
+
+impl RoanEngine {
+    /// Creates an engine with the default context, loading imported files relative to the file
+    /// importing them.
+    pub fn new() -> Self {
+        let ctx = Context::builder()
+            .module_loader(Rc::new(RefCell::new(BasicModuleLoader::new())))
+            .build();
+
+        Self::with_context(ctx)
+    }
+
+    /// Creates an engine running code with `ctx`, e.g. one with a custom module loader or
+    /// capabilities.
+    pub fn with_context(ctx: Context) -> Self {
+        let mut module = Module::new(Source::from_string(String::new()));
+        module.set_prelude(&ctx.prelude);
+
+        Self {
+            ctx,
+            vm: VM::new(),
+            module,
+        }
+    }
+
+    /// Parses and runs the file at `path`, which becomes the current module, and returns the
+    /// warnings reported for it.
+    ///
+    /// Nothing is written to stderr. Errors reported as diagnostics are returned in
+    /// [AbortedDueToPreviousErrors](roan_error::error::RoanError::AbortedDueToPreviousErrors),
+    /// other errors, like an uncaught `throw`, as they are.
+    pub fn load_file(&mut self, path: &Path) -> Result<Vec<Diagnostic>> {
+        let mut module = Module::new(Source::from_file(path.to_path_buf())?);
+        let warnings = module.parse(&mut self.ctx, &mut self.vm)?;
+        module.interpret(&mut self.ctx, &mut self.vm)?;
+
+        self.module = module;
+
+        Ok(warnings)
+    }
+
+    /// Evaluates a single expression in the scope of the current module and returns its value.
+    pub fn eval_expr(&mut self, src: &str) -> Result<Value> {
+        let tokens = Lexer::new(Source::from_string(src.to_string())).lex(false)?;
+        let mut parser = Parser::new(tokens);
+        let expr = parser.parse_expr()?;
+
+        parser.possible_check(TokenKind::Semicolon);
+        if !parser.is_eof() {
+            let token = parser.peek();
+            return Err(UnexpectedToken(token.literal(), token.span).into());
+        }
+
+        self.module
+            .interpret_expr(&expr, &mut self.ctx, &mut self.vm)?;
+
+        Ok(self.vm.pop().unwrap_or(Value::Void))
+    }
+
+    /// Calls the function called `name` of the current module, or of the prelude.
+    pub fn call_fn(&mut self, name: &str, args: Vec<Value>) -> Result<Value> {
+        self.module
+            .call_function(name, args, &mut self.ctx, &mut self.vm)
+    }
+
+    /// Adds `f` to the prelude as `name`, it can be called by the current module and every module
+    /// loaded after it.
+    pub fn register_native(&mut self, name: &str, mut f: NativeFunction) {
+        f.name = name.to_string();
+
+        self.ctx.prelude = mem::take(&mut self.ctx.prelude).with(f);
+        self.module.set_prelude(&self.ctx.prelude);
+    }
+
+    /// Returns the context code runs with.
+    pub fn context(&mut self) -> &mut Context {
+        &mut self.ctx
+    }
+}
+
+impl Default for RoanEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::native_function;
+    use roan_error::error::RoanError;
+    use std::fs;
+
+    native_function!(
+        fn host_version() {
+            Value::String("1.2.3".to_string())
+        }
+    );
+
+    #[test]
+    fn test_embedding() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("greet.roan");
+        fs::write(
+            &path,
+            "let greeting = \"Hello\";\n\
+             fn greet(name: string) -> string { return \"Hello \" + name; }\n",
+        )
+        .unwrap();
+
+        let mut engine = RoanEngine::new();
+        engine.load_file(&path).unwrap();
+
+        assert_eq!(
+            engine
+                .call_fn("greet", vec![Value::String("roan".to_string())])
+                .unwrap(),
+            Value::String("Hello roan".to_string())
+        );
+        assert_eq!(
+            engine.eval_expr("greet(\"you\") + \"!\"").unwrap(),
+            Value::String("Hello you!".to_string())
+        );
+        assert_eq!(
+            engine.eval_expr("greeting").unwrap(),
+            Value::String("Hello".to_string())
+        );
+        assert!(engine.eval_expr("1 + 1 2").is_err());
+        assert!(engine.call_fn("missing", vec![]).is_err());
+
+        engine.register_native("version", host_version());
+        assert_eq!(
+            engine.eval_expr("\"v\" + version()").unwrap(),
+            Value::String("v1.2.3".to_string())
+        );
+        assert_eq!(
+            engine.call_fn("version", vec![]).unwrap(),
+            Value::String("1.2.3".to_string())
+        );
+    }
+
+    #[test]
+    fn test_load_file_returns_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut engine = RoanEngine::new();
+
+        let path = dir.path().join("invalid.roan");
+        fs::write(&path, "fn f(a: int) {}\nf(\"a\");\nmissing();\n").unwrap();
+        let err = engine.load_file(&path).unwrap_err();
+        let Some(RoanError::AbortedDueToPreviousErrors(diagnostics)) = err.downcast_ref() else {
+            panic!("Expected diagnostics, got {err}");
+        };
+        assert_eq!(diagnostics.len(), 2);
+        assert!(diagnostics.iter().all(|d| d.file.as_ref() == Some(&path)));
+
+        let path = dir.path().join("throws.roan");
+        fs::write(&path, "throw \"failed\";\n").unwrap();
+        let err = engine.load_file(&path).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref(),
+            Some(RoanError::Throw(msg, _)) if msg == "failed"
+        ));
+    }
+}
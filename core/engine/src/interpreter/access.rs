@@ -1,9 +1,19 @@
-use crate::{context::Context, module::Module, value::Value, vm::VM};
+use crate::{
+    context::Context,
+    module::{Module, StoredFunction},
+    value::Value,
+    vm::VM,
+};
 use anyhow::Result;
-use roan_ast::{AccessExpr, AccessKind, Expr, GetSpan};
-use roan_error::error::RoanError::{
-    PropertyNotFoundError, StaticContext, StaticMemberAccess, UndefinedFunctionError,
+use roan_ast::{AccessExpr, AccessKind, CallExpr, Expr, GetSpan};
+use roan_error::error::{
+    RoanError,
+    RoanError::{
+        MissingParameter, PropertyNotFoundError, StaticContext, StaticMemberAccess, TypeMismatch,
+        UndefinedFunctionError,
+    },
 };
+use std::cmp::Ordering;
 
 impl Module {
     /// Interpret an access expression.
@@ -17,40 +27,42 @@ impl Module {
     /// The result of the access expression.
     pub fn interpret_access(
         &mut self,
-        access: AccessExpr,
+        access: &AccessExpr,
         ctx: &mut Context,
         vm: &mut VM,
     ) -> Result<Value> {
-        match access.access.clone() {
+        match &access.access {
             AccessKind::Field(field_expr) => {
-                let base = access.base.clone();
-
-                self.interpret_expr(&base, ctx, vm)?;
+                self.interpret_expr(&access.base, ctx, vm)?;
                 let base = vm.pop().unwrap();
 
-                Ok(self.access_field(base, &field_expr, ctx, vm)?)
+                Ok(self.access_field(base, field_expr, ctx, vm)?)
             }
             AccessKind::Index(index_expr) => {
-                self.interpret_expr(&index_expr, ctx, vm)?;
+                self.interpret_expr(index_expr, ctx, vm)?;
                 let index = vm.pop().unwrap();
 
                 self.interpret_expr(&access.base, ctx, vm)?;
                 let base = vm.pop().unwrap();
 
+                // Unlike objects, structs have a fixed set of fields
+                if let (Value::Struct(_, fields), Value::String(key)) = (&base, &index) {
+                    if !fields.contains_key(key) {
+                        return Err(PropertyNotFoundError(key.clone(), index_expr.span()).into());
+                    }
+                }
+
                 Ok(base.access_index(index))
             }
             AccessKind::StaticMethod(expr) => {
-                let base = access.base.as_ref().clone();
-
-                let (struct_name, span) = match base {
+                let (struct_name, span) = match access.base.as_ref() {
                     Expr::Variable(v) => (v.ident.clone(), v.token.span.clone()),
                     _ => return Err(StaticMemberAccess(access.span()).into()),
                 };
 
                 let struct_def = self.get_struct(&struct_name, span)?;
 
-                let expr = expr.as_ref().clone();
-                match expr {
+                match expr.as_ref() {
                     Expr::Call(call) => {
                         let method_name = call.callee.clone();
                         let method = struct_def.find_static_method(&method_name);
@@ -74,15 +86,18 @@ impl Module {
                             })
                             .collect::<Result<Vec<_>>>()?;
 
-                        let mut def_module = ctx.query_module(&struct_def.defining_module).unwrap();
+                        let mut def_module = ctx.try_query_module(
+                            &struct_def.defining_module,
+                            call.token.span.clone(),
+                        )?;
 
                         self.execute_user_defined_function(
-                            method.clone(),
+                            method,
                             &mut def_module,
                             args,
                             ctx,
                             vm,
-                            &call,
+                            call,
                         )?;
 
                         Ok(vm.pop().unwrap())
@@ -126,10 +141,11 @@ impl Module {
                         args.push(vm.pop().expect("Expected value on stack"));
                     }
 
-                    let mut def_module = ctx.query_module(&struct_def.defining_module).unwrap();
+                    let mut def_module =
+                        ctx.try_query_module(&struct_def.defining_module, expr.span())?;
 
                     self.execute_user_defined_function(
-                        field.clone(),
+                        field,
                         &mut def_module,
                         args,
                         ctx,
@@ -140,6 +156,12 @@ impl Module {
                     return Ok(vm.pop().expect("Expected value on stack"));
                 }
 
+                if let Value::Vec(items) = &value {
+                    if call.callee == "sort_by" {
+                        return self.sort_vec_by(items.clone(), call, ctx, vm);
+                    }
+                }
+
                 let methods = value.builtin_methods();
                 if let Some(method) = methods.get(&call.callee) {
                     let mut args = vec![value.clone()];
@@ -148,7 +170,7 @@ impl Module {
                         args.push(vm.pop().expect("Expected value on stack"));
                     }
 
-                    self.execute_native_function(method.clone(), args, vm)?;
+                    self.execute_native_function(method.clone(), args, ctx, vm)?;
 
                     Ok(vm.pop().expect("Expected value on stack"))
                 } else {
@@ -184,4 +206,228 @@ impl Module {
             }
         }
     }
+
+    /// Sorts a vector using the comparator function passed to `sort_by`.
+    ///
+    /// The comparator is called with two elements and must return an int that is negative,
+    /// zero or positive if the first element is less than, equal to or greater than the second.
+    /// The sort is stable, elements that compare equal keep their original order.
+    ///
+    /// # Arguments
+    /// * `items` - The elements of the vector to sort.
+    /// * `call` - The `sort_by` call expression.
+    /// * `ctx` - The context in which to call the comparator.
+    /// * `vm` - The virtual machine to use.
+    ///
+    /// # Returns
+    /// The sorted vector.
+    pub fn sort_vec_by(
+        &mut self,
+        items: Vec<Value>,
+        call: &CallExpr,
+        ctx: &mut Context,
+        vm: &mut VM,
+    ) -> Result<Value> {
+        let comparator = match call.args.first() {
+            Some(Expr::Variable(var)) => self
+                .find_function(&var.ident)
+                .ok_or_else(|| UndefinedFunctionError(var.ident.clone(), var.token.span.clone()))?
+                .clone(),
+            Some(expr) => {
+                return Err(TypeMismatch(
+                    "sort_by expects the name of a comparator function".to_string(),
+                    expr.span(),
+                )
+                .into())
+            }
+            None => {
+                return Err(
+                    MissingParameter("comparator".to_string(), call.token.span.clone()).into(),
+                )
+            }
+        };
+
+        let items = merge_sort_by(items, &mut |a, b| {
+            self.call_comparator(&comparator, a.clone(), b.clone(), call, ctx, vm)
+        })?;
+
+        Ok(Value::Vec(items))
+    }
+
+    /// Calls a comparator function and converts its result to an [Ordering].
+    fn call_comparator(
+        &mut self,
+        comparator: &StoredFunction,
+        a: Value,
+        b: Value,
+        call: &CallExpr,
+        ctx: &mut Context,
+        vm: &mut VM,
+    ) -> Result<Ordering> {
+        match comparator.clone() {
+            StoredFunction::Native(native) => {
+                self.execute_native_function(native, vec![a, b], ctx, vm)?;
+            }
+            StoredFunction::Function {
+                function,
+                defining_module,
+            } => {
+                let mut def_module =
+                    ctx.try_query_module(&defining_module, call.token.span.clone())?;
+
+                self.execute_user_defined_function(
+                    &function,
+                    &mut def_module,
+                    vec![a, b],
+                    ctx,
+                    vm,
+                    call,
+                )?;
+            }
+        }
+
+        match vm.pop().unwrap_or(Value::Void) {
+            Value::Int(i) => Ok(i.cmp(&0)),
+            // Thrown so that scripts can catch it like an error of the comparator itself
+            other => Err(RoanError::Throw(
+                format!(
+                    "Comparator passed to sort_by must return an int, got {}",
+                    other.type_name()
+                ),
+                Vec::from(vm.frames()),
+            )
+            .into()),
+        }
+    }
+}
+
+/// Sorts `items` with a stable merge sort, stopping at the first error returned by `compare`.
+///
+/// Unlike [slice::sort_by], it never panics when `compare` isn't a total order, the order of the
+/// result is then unspecified.
+fn merge_sort_by<T>(
+    mut items: Vec<T>,
+    compare: &mut impl FnMut(&T, &T) -> Result<Ordering>,
+) -> Result<Vec<T>> {
+    if items.len() <= 1 {
+        return Ok(items);
+    }
+
+    let right = items.split_off(items.len() / 2);
+    let left = merge_sort_by(items, compare)?;
+    let right = merge_sort_by(right, compare)?;
+
+    let mut merged = Vec::with_capacity(left.len() + right.len());
+    let mut left = left.into_iter().peekable();
+    let mut right = right.into_iter().peekable();
+    while let (Some(a), Some(b)) = (left.peek(), right.peek()) {
+        // Equal elements are taken from the left first to keep the sort stable
+        let next = if compare(a, b)? == Ordering::Greater {
+            right.next()
+        } else {
+            left.next()
+        };
+        merged.extend(next);
+    }
+    merged.extend(left);
+    merged.extend(right);
+
+    Ok(merged)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{context, parsed, SharedBuffer};
+
+    #[test]
+    fn test_index_struct() {
+        let mut ctx = context();
+        let output = SharedBuffer::default();
+        ctx.set_output(Box::new(output.clone()));
+
+        let mut module = parsed(
+            "struct Point { x: int, y: int }\n\
+             let p = Point { x: 1, y: 2 };\n\
+             let key = \"x\";\n\
+             __print(__format(p[\"y\"] + p[key]));\n",
+            &mut ctx,
+        );
+        module.interpret(&mut ctx, &mut VM::new()).unwrap();
+        assert_eq!(String::from_utf8(output.0.take()).unwrap(), "3");
+
+        let point = module.find_variable("p").unwrap();
+        let object = point.struct_to_object().unwrap();
+        assert_eq!(point.to_string(), "Point {x: 1, y: 2}");
+        assert_eq!(object.to_string(), "{x: 1, y: 2}");
+        assert_eq!(
+            object.access_index(Value::String("x".to_string())),
+            Value::Int(1)
+        );
+        assert!(Value::Int(1).struct_to_object().is_none());
+
+        let mut module = parsed(
+            "struct Point { x: int }\n\
+             let p = Point { x: 1 };\n\
+             let key = \"z\";\n\
+             let z = p[key];\n",
+            &mut ctx,
+        );
+        let mut vm = VM::new();
+        let mut stmts = module.ast.stmts.clone();
+        let last = stmts.pop().unwrap();
+        for stmt in stmts.iter() {
+            module.interpret_stmt(stmt, &mut ctx, &mut vm).unwrap();
+        }
+
+        let err = module.interpret_stmt(&last, &mut ctx, &mut vm).unwrap_err();
+        match err.downcast_ref::<RoanError>() {
+            Some(RoanError::PropertyNotFoundError(name, span)) => {
+                assert_eq!(name, "z");
+                assert_eq!(span.literal, "key");
+            }
+            err => panic!("Expected a missing property, got {:?}", err),
+        }
+    }
+
+    #[test]
+    fn test_sort_by_inconsistent_comparator() {
+        let mut ctx = context();
+        let output = SharedBuffer::default();
+        ctx.set_output(Box::new(output.clone()));
+
+        let items: Vec<String> = (0..50).map(|i| i.to_string()).collect();
+        let mut module = parsed(
+            &format!(
+                "fn cmp(a: int, b: int) -> int {{ return (a + b) % 3 - 1; }}\n\
+                 __print(__format([{}].sort_by(cmp).len()));\n",
+                items.join(", ")
+            ),
+            &mut ctx,
+        );
+        module.interpret(&mut ctx, &mut VM::new()).unwrap();
+
+        assert_eq!(String::from_utf8_lossy(&output.0.borrow()), "50");
+    }
+
+    #[test]
+    fn test_sort_by_comparator_errors_are_caught() {
+        let mut ctx = context();
+        let output = SharedBuffer::default();
+        ctx.set_output(Box::new(output.clone()));
+
+        let mut module = parsed(
+            "fn failing(a: int, b: int) -> int { throw \"failed\"; }\n\
+             fn not_int(a: int, b: int) { return \"a\"; }\n\
+             try { [3, 2, 1, 4].sort_by(failing); } catch e { __print(e + \" \"); }\n\
+             try { [3, 2, 1].sort_by(not_int); } catch e { __print(e); }\n",
+            &mut ctx,
+        );
+        module.interpret(&mut ctx, &mut VM::new()).unwrap();
+
+        assert_eq!(
+            String::from_utf8_lossy(&output.0.borrow()),
+            "failed Comparator passed to sort_by must return an int, got string"
+        );
+    }
 }
@@ -17,7 +17,7 @@ impl Module {
     /// The result of the then-else expression.
     pub fn interpret_then_else(
         &mut self,
-        then_else: ThenElse,
+        then_else: &ThenElse,
         ctx: &mut Context,
         vm: &mut VM,
     ) -> Result<Value> {
@@ -45,7 +45,7 @@ impl Module {
     /// # Arguments
     /// * `if_stmt` - [`If`] - The if statement to interpret.
     /// * `ctx` - [`Context`] - The context in which to interpret the statement.
-    pub fn interpret_if(&mut self, if_stmt: If, ctx: &mut Context, vm: &mut VM) -> Result<()> {
+    pub fn interpret_if(&mut self, if_stmt: &If, ctx: &mut Context, vm: &mut VM) -> Result<()> {
         debug!("Interpreting if statement");
 
         self.interpret_expr(&if_stmt.condition, ctx, vm)?;
@@ -57,18 +57,21 @@ impl Module {
             _ => {
                 return Err(NonBooleanCondition(
                     "If condition".into(),
-                    TextSpan::combine(vec![if_stmt.if_token.span, if_stmt.condition.span()])
-                        .unwrap(),
+                    TextSpan::combine(vec![
+                        if_stmt.if_token.span.clone(),
+                        if_stmt.condition.span(),
+                    ])
+                    .unwrap(),
                 )
                 .into())
             }
         };
 
         if condition {
-            self.execute_block(if_stmt.then_block, ctx, vm)?;
+            self.execute_block(&if_stmt.then_block, ctx, vm)?;
         } else {
             let mut executed = false;
-            for else_if in if_stmt.else_ifs {
+            for else_if in if_stmt.else_ifs.iter() {
                 self.interpret_expr(&else_if.condition, ctx, vm)?;
                 let else_if_condition = vm.pop().expect("Expected value on stack");
 
@@ -85,15 +88,15 @@ impl Module {
                 };
 
                 if else_if_result {
-                    self.execute_block(else_if.block, ctx, vm)?;
+                    self.execute_block(&else_if.block, ctx, vm)?;
                     executed = true;
                     break;
                 }
             }
 
             if !executed {
-                if let Some(else_block) = if_stmt.else_block {
-                    self.execute_block(else_block.block, ctx, vm)?;
+                if let Some(else_block) = &if_stmt.else_block {
+                    self.execute_block(&else_block.block, ctx, vm)?;
                 }
             }
         }
@@ -0,0 +1,219 @@
+use crate::value::Value;
+use indexmap::IndexMap;
+use roan_ast::{BinOpKind, Expr, Segment, UnOpKind};
+
+/// Evaluates an expression built only from literals and operators without running the interpreter.
+///
+/// Returns `None` if the expression refers to variables, calls functions, accesses fields or
+/// can't be folded safely (e.g. division by zero or integer overflow).
+///
+/// # Arguments
+/// * `expr` - The [Expr] to evaluate.
+///
+/// # Returns
+/// The folded [Value] or `None` if the expression is not constant.
+pub fn const_eval(expr: &Expr) -> Option<Value> {
+    const_eval_with(expr, &|_| None)
+}
+
+/// Same as [const_eval], but variables are looked up using `resolve`.
+///
+/// This is used for const initializers which may refer to previously declared consts.
+///
+/// # Arguments
+/// * `expr` - The [Expr] to evaluate.
+/// * `resolve` - Returns the value of a variable or `None` if it is not constant.
+pub fn const_eval_with(expr: &Expr, resolve: &dyn Fn(&str) -> Option<Value>) -> Option<Value> {
+    match expr {
+        Expr::Literal(lit) => Some(Value::from_literal(lit.clone())),
+        Expr::Null(_) => Some(Value::Null),
+        Expr::Parenthesized(p) => const_eval_with(&p.expr, resolve),
+        Expr::Variable(v) => resolve(&v.ident),
+        Expr::Unary(u) => fold_unary(u.operator.kind, const_eval_with(&u.expr, resolve)?),
+        Expr::Binary(b) => fold_binary(
+            const_eval_with(&b.left, resolve)?,
+            b.operator,
+            const_eval_with(&b.right, resolve)?,
+        ),
+        Expr::Vec(v) => v
+            .exprs
+            .iter()
+            .map(|e| const_eval_with(e, resolve))
+            .collect::<Option<Vec<_>>>()
+            .map(Value::Vec),
+        Expr::Object(o) => o
+            .fields
+            .iter()
+            .map(|(k, e)| Some((k.clone(), const_eval_with(e, resolve)?)))
+            .collect::<Option<IndexMap<_, _>>>()
+            .map(Value::Object),
+        Expr::Interpolated(i) => i
+            .segments
+            .iter()
+            .map(|segment| match segment {
+                Segment::Text(text) => Some(text.clone()),
+                Segment::Expr(e) => Some(const_eval_with(e, resolve)?.to_string()),
+            })
+            .collect::<Option<String>>()
+            .map(Value::String),
+        Expr::ThenElse(t) => {
+            let condition = const_eval_with(&t.condition, resolve)?;
+
+            if condition.is_truthy() {
+                const_eval_with(&t.then_expr, resolve)
+            } else {
+                const_eval_with(&t.else_expr, resolve)
+            }
+        }
+        _ => None,
+    }
+}
+
+fn fold_unary(operator: UnOpKind, val: Value) -> Option<Value> {
+    match (operator, val) {
+        (UnOpKind::Minus, Value::Int(i)) => i.checked_neg().map(Value::Int),
+        (UnOpKind::Minus, Value::Float(f)) => Some(Value::Float(-f)),
+        (UnOpKind::BitwiseNot, Value::Int(i)) => Some(Value::Int(!i)),
+        (UnOpKind::LogicalNot, val) => Some(Value::Bool(!val.is_truthy())),
+        _ => None,
+    }
+}
+
+fn fold_binary(left: Value, operator: BinOpKind, right: Value) -> Option<Value> {
+    match (left, operator, right) {
+        (Value::Int(a), op, Value::Int(b)) if op.is_number_operator() => match op {
+            BinOpKind::Plus => a.checked_add(b),
+            BinOpKind::Minus => a.checked_sub(b),
+            BinOpKind::Multiply => a.checked_mul(b),
+            BinOpKind::Divide => a.checked_div(b),
+            BinOpKind::Modulo => a.checked_rem(b),
+            BinOpKind::Power => u32::try_from(b).ok().and_then(|b| a.checked_pow(b)),
+            BinOpKind::BitwiseAnd => Some(a & b),
+            BinOpKind::BitwiseOr => Some(a | b),
+            BinOpKind::BitwiseXor => Some(a ^ b),
+            BinOpKind::ShiftLeft => u32::try_from(b).ok().and_then(|b| a.checked_shl(b)),
+            BinOpKind::ShiftRight => u32::try_from(b).ok().and_then(|b| a.checked_shr(b)),
+            _ => None,
+        }
+        .map(Value::Int),
+        (
+            left @ (Value::Int(_) | Value::Float(_)),
+            op,
+            right @ (Value::Int(_) | Value::Float(_)),
+        ) if op.is_number_operator() => {
+            let (a, b) = (as_float(&left), as_float(&right));
+
+            match op {
+                BinOpKind::Plus => Some(a + b),
+                BinOpKind::Minus => Some(a - b),
+                BinOpKind::Multiply => Some(a * b),
+                BinOpKind::Divide => Some(a / b),
+                BinOpKind::Modulo => Some(a % b),
+                BinOpKind::Power => Some(a.powf(b)),
+                _ => None,
+            }
+            .map(Value::Float)
+        }
+        (
+            left @ (Value::String(_) | Value::Char(_)),
+            BinOpKind::Plus,
+            right @ (Value::String(_) | Value::Char(_)),
+        ) => Some(left + right),
+        (left, BinOpKind::Equals | BinOpKind::EqualsEquals, right) => {
+            Some(Value::Bool(left == right))
+        }
+        (left, BinOpKind::BangEquals, right) => Some(Value::Bool(left != right)),
+        (left, op, right) if is_comparison(op) => {
+            let ordering = left.partial_cmp(&right)?;
+
+            Some(Value::Bool(match op {
+                BinOpKind::LessThan => ordering.is_lt(),
+                BinOpKind::LessThanOrEqual => ordering.is_le(),
+                BinOpKind::GreaterThan => ordering.is_gt(),
+                _ => ordering.is_ge(),
+            }))
+        }
+        (Value::Bool(a), BinOpKind::And, Value::Bool(b)) => Some(Value::Bool(a && b)),
+        (Value::Bool(a), BinOpKind::Or, Value::Bool(b)) => Some(Value::Bool(a || b)),
+        _ => None,
+    }
+}
+
+fn is_comparison(op: BinOpKind) -> bool {
+    matches!(
+        op,
+        BinOpKind::LessThan
+            | BinOpKind::LessThanOrEqual
+            | BinOpKind::GreaterThan
+            | BinOpKind::GreaterThanOrEqual
+    )
+}
+
+fn as_float(val: &Value) -> f64 {
+    match val {
+        Value::Int(i) => *i as f64,
+        Value::Float(f) => *f,
+        _ => unreachable!("only called with numbers"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use roan_ast::{source::Source, Lexer, Parser, Stmt};
+
+    fn eval(src: &str) -> Option<Value> {
+        let tokens = Lexer::new(Source::from_string(format!("let x = {};", src)))
+            .lex(false)
+            .unwrap();
+        let ast = Parser::new(tokens).parse().unwrap();
+
+        match &ast.stmts[0] {
+            Stmt::Let(let_stmt) => const_eval(&let_stmt.initializer),
+            stmt => panic!("Expected let statement, got {:?}", stmt),
+        }
+    }
+
+    #[test]
+    fn test_const_eval_arithmetic() {
+        assert_eq!(eval("(1 + 2) * 3"), Some(Value::Int(9)));
+        assert_eq!(eval("2 + 3 * 4"), Some(Value::Int(14)));
+        assert_eq!(eval("2 ** 10"), Some(Value::Int(1024)));
+        assert_eq!(eval("-(7 % 4)"), Some(Value::Int(-3)));
+        assert_eq!(eval("1 + 1.5"), Some(Value::Float(2.5)));
+        assert_eq!(eval("1 << 4 | 1"), Some(Value::Int(17)));
+    }
+
+    #[test]
+    fn test_const_eval_boolean() {
+        assert_eq!(eval("1 < 2 && 3 >= 3"), Some(Value::Bool(true)));
+        assert_eq!(eval("\"a\" == \"b\""), Some(Value::Bool(false)));
+        assert_eq!(eval("!null"), Some(Value::Bool(true)));
+    }
+
+    #[test]
+    fn test_const_eval_collections() {
+        assert_eq!(
+            eval("[1, 2 + 3, \"a\" + 'b']"),
+            Some(Value::Vec(vec![
+                Value::Int(1),
+                Value::Int(5),
+                Value::String("ab".to_string())
+            ]))
+        );
+        match eval("{ \"a\": 1 * 2 }") {
+            Some(Value::Object(fields)) => assert_eq!(fields.get("a"), Some(&Value::Int(2))),
+            val => panic!("Expected object, got {:?}", val),
+        }
+    }
+
+    #[test]
+    fn test_const_eval_non_constant() {
+        assert_eq!(eval("x + 1"), None);
+        assert_eq!(eval("len([1])"), None);
+        assert_eq!(eval("[1, ...rest]"), None);
+        assert_eq!(eval("1 / 0"), None);
+        assert_eq!(eval("1 + \"a\""), None);
+        assert_eq!(eval("9223372036854775807 + 1"), None);
+    }
+}
@@ -1,4 +1,9 @@
-use crate::{context::Context, module::Module, value::Value, vm::VM};
+use crate::{
+    context::Context,
+    module::Module,
+    value::Value,
+    vm::{CaughtError, VM},
+};
 use anyhow::Result;
 use roan_ast::{Throw, Try};
 use roan_error::error::RoanError;
@@ -7,51 +12,193 @@ use tracing::debug;
 impl Module {
     /// Interpret TryCatch expression.
     ///
+    /// The `finally` block, if any, runs after the `try` and `catch` blocks even when they throw.
+    /// An error thrown by the `finally` block replaces the one of the `try` or `catch` block.
+    ///
     /// # Arguments
     /// * `try_catch` - TryCatch expression to interpret.
     /// * `ctx` - The context in which to interpret the TryCatch expression.
     ///
     /// # Returns
     /// The result of the TryCatch expression.
-    pub fn interpret_try(&mut self, try_stmt: Try, ctx: &mut Context, vm: &mut VM) -> Result<()> {
+    pub fn interpret_try(&mut self, try_stmt: &Try, ctx: &mut Context, vm: &mut VM) -> Result<()> {
         debug!("Interpreting try");
 
-        let try_result = self.execute_block(try_stmt.try_block.clone(), ctx, vm);
-
-        match try_result {
-            Ok(_) => return Ok(()),
+        let result = match self.execute_block(&try_stmt.try_block, ctx, vm) {
+            Ok(_) => Ok(()),
             Err(e) => match e.downcast_ref::<RoanError>() {
-                Some(RoanError::Throw(msg, _)) => {
+                // Denied capabilities are caught like errors thrown by natives
+                Some(
+                    err @ (RoanError::Throw(_, frames) | RoanError::CapabilityDenied(.., frames)),
+                ) => {
+                    let msg = err.to_string();
+                    // Natives throw messages, so their errors are caught as strings
+                    let value = vm
+                        .take_thrown()
+                        .unwrap_or_else(|| Value::String(msg.clone()));
+
                     self.enter_scope();
+                    vm.push_caught(CaughtError {
+                        value: value.clone(),
+                        message: msg.clone(),
+                        frames: frames.clone(),
+                    });
 
                     let var_name = try_stmt.error_ident.literal();
-                    self.declare_variable(var_name, Value::String(msg.clone()));
-                    let result = self.execute_block(try_stmt.catch_block, ctx, vm);
+                    self.declare_variable(var_name, value);
+                    let result = self.execute_block(&try_stmt.catch_block, ctx, vm);
+
+                    vm.pop_caught();
                     self.exit_scope();
 
-                    result?
+                    result
                 }
-                _ => return Err(e),
+                _ => Err(e),
             },
+        };
+
+        if let Some(finally_block) = &try_stmt.finally_block {
+            // Values left by the finally block would shadow one returned by try or catch
+            let depth = vm.stack().len();
+            self.execute_block(finally_block, ctx, vm)?;
+            while vm.stack().len() > depth {
+                vm.pop();
+            }
         }
 
-        Ok(())
+        result
     }
 
     /// Interpret a throw statement.
     ///
+    /// A throw without a value raises the error of the enclosing `catch` block again, with the
+    /// backtrace of the original `throw`.
+    ///
     /// # Arguments
     /// * `throw_stmt` - The throw statement to interpret.
     /// * `ctx` - The context in which to interpret the statement.
     ///
     /// # Returns
     /// The result of the throw statement.
-    pub fn interpret_throw(&mut self, throw: Throw, ctx: &mut Context, vm: &mut VM) -> Result<()> {
+    pub fn interpret_throw(&mut self, throw: &Throw, ctx: &mut Context, vm: &mut VM) -> Result<()> {
         debug!("Interpreting throw");
 
-        self.interpret_expr(&throw.value, ctx, vm)?;
+        let Some(value) = &throw.value else {
+            let caught = vm
+                .caught()
+                .cloned()
+                .ok_or_else(|| RoanError::RethrowOutsideCatch(throw.token.span.clone()))?;
+            vm.set_thrown(caught.value);
+
+            return Err(RoanError::Throw(caught.message, caught.frames).into());
+        };
+
+        self.interpret_expr(value, ctx, vm)?;
         let val = vm.pop().unwrap();
+        let msg = val.to_string();
+        vm.set_thrown(val);
+
+        Err(RoanError::Throw(msg, Vec::from(vm.frames())).into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{context, parsed, SharedBuffer};
+    use roan_ast::{CallExpr, Token, TokenKind};
+    use roan_error::TextSpan;
+
+    #[test]
+    fn test_catch_binds_thrown_value() {
+        let mut ctx = context();
+        let output = SharedBuffer::default();
+        ctx.set_output(Box::new(output.clone()));
+
+        let mut module = parsed(
+            "fn fail() { throw { \"code\": 42 }; }\n\
+             try { fail(); } catch e { __print(__format(e[\"code\"] + 1)); }\n\
+             try { from_hex(\"zz\"); } catch e { __print(\" \" + e); }\n",
+            &mut ctx,
+        );
+        module.interpret(&mut ctx, &mut VM::new()).unwrap();
 
-        return Err(RoanError::Throw(val.to_string(), Vec::from(vm.frames())).into());
+        assert_eq!(
+            String::from_utf8(output.0.take()).unwrap(),
+            "43 Invalid hex string: zz"
+        );
+    }
+
+    #[test]
+    fn test_finally_runs_last() {
+        let mut ctx = context();
+        let output = SharedBuffer::default();
+        ctx.set_output(Box::new(output.clone()));
+
+        let mut module = parsed(
+            "try { __print(\"try \"); throw \"x\"; } catch e { __print(\"catch \"); } finally { __print(\"finally\\n\"); }\n\
+             try { __print(\"try \"); } catch e { __print(\"catch \"); } finally { __print(\"finally\\n\"); }\n\
+             fn one() { try { return 1; } catch e {} finally { __print(\"returning \"); } }\n\
+             __print(__format(one()));\n",
+            &mut ctx,
+        );
+        module.interpret(&mut ctx, &mut VM::new()).unwrap();
+
+        assert_eq!(
+            String::from_utf8(output.0.take()).unwrap(),
+            "try catch finally\ntry finally\nreturning 1"
+        );
+    }
+
+    #[test]
+    fn test_finally_runs_when_catch_throws() {
+        let mut ctx = context();
+        let output = SharedBuffer::default();
+        ctx.set_output(Box::new(output.clone()));
+
+        let mut module = parsed(
+            "try {\n\
+               try { throw \"first\"; } catch e { throw \"second\"; } finally { __print(\"finally \"); }\n\
+             } catch e { __print(e); }\n",
+            &mut ctx,
+        );
+        module.interpret(&mut ctx, &mut VM::new()).unwrap();
+
+        assert_eq!(
+            String::from_utf8(output.0.take()).unwrap(),
+            "finally second"
+        );
+    }
+
+    #[test]
+    fn test_rethrow_keeps_original_error() {
+        let mut ctx = context();
+        let output = SharedBuffer::default();
+        ctx.set_output(Box::new(output.clone()));
+
+        let mut module = parsed(
+            "fn fail() { throw { \"code\": 42 }; }\n\
+             fn rethrow() { try { fail(); } catch e { __print(\"caught \"); throw; } }\n\
+             try { rethrow(); } catch e { __print(__format(e[\"code\"])); }\n",
+            &mut ctx,
+        );
+        module.interpret(&mut ctx, &mut VM::new()).unwrap();
+        assert_eq!(String::from_utf8(output.0.take()).unwrap(), "caught 42");
+
+        let call = CallExpr {
+            callee: "rethrow".to_string(),
+            args: vec![],
+            token: Token::new(TokenKind::Identifier, TextSpan::default()),
+        };
+        let err = module
+            .interpret_call(&call, &mut ctx, &mut VM::new())
+            .unwrap_err();
+        match err.downcast_ref::<roan_error::error::RoanError>() {
+            Some(roan_error::error::RoanError::Throw(_, frames)) => {
+                let names: Vec<&str> = frames.iter().map(|f| f.name.as_str()).collect();
+                assert_eq!(names, ["rethrow", "fail"]);
+            }
+            err => panic!("Expected a thrown error, got {:?}", err),
+        }
     }
 }
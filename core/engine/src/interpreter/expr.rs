@@ -1,14 +1,25 @@
-use crate::{context::Context, module::Module, value::Value, vm::VM};
+use crate::{
+    context::Context,
+    module::Module,
+    value::{resolve_index, Value},
+    vm::VM,
+};
 use anyhow::Result;
 use indexmap::IndexMap;
 use log::debug;
 use roan_ast::{
-    AccessKind, Assign, AssignOperator, BinOpKind, Binary, Expr, GetSpan, LiteralType, Spread,
-    UnOpKind, Unary, VecExpr,
+    AccessKind, Assign, AssignOperator, BinOpKind, Binary, Expr, GetSpan, LiteralType, Segment,
+    Spread, UnOpKind, Unary, VecExpr,
 };
-use roan_error::error::{
-    RoanError,
-    RoanError::{InvalidSpread, StaticMemberAssignment, VariableNotFoundError},
+use roan_error::{
+    error::{
+        RoanError,
+        RoanError::{
+            IntegerOverflow, InvalidSpread, StaticMemberAssignment, TypeMismatch,
+            VariableNotFoundError,
+        },
+    },
+    TextSpan,
 };
 
 impl Module {
@@ -55,18 +66,18 @@ impl Module {
 
                 Ok(vm.pop().unwrap())
             }
-            Expr::Access(access) => self.interpret_access(access.clone(), ctx, vm),
+            Expr::Access(access) => self.interpret_access(access, ctx, vm),
             Expr::StructConstructor(constructor) => {
-                self.interpret_struct_constructor(constructor.clone(), ctx, vm)
+                self.interpret_struct_constructor(constructor, ctx, vm)
             }
-            Expr::Assign(assign) => self.interpret_assignment(assign.clone(), ctx, vm),
-            Expr::Vec(vec) => self.interpret_vec(vec.clone(), ctx, vm),
-            Expr::Binary(b) => self.interpret_binary(b.clone(), ctx, vm),
+            Expr::Assign(assign) => self.interpret_assignment(assign, ctx, vm),
+            Expr::Vec(vec) => self.interpret_vec(vec, ctx, vm),
+            Expr::Binary(b) => self.interpret_binary(b, ctx, vm),
             // Spread operator are only supposed to be used in vectors and function calls
             Expr::Spread(s) => Err(InvalidSpread(s.expr.span()).into()),
             Expr::Null(_) => Ok(Value::Null),
-            Expr::Unary(u) => self.interpret_unary(u.clone(), ctx, vm),
-            Expr::ThenElse(then_else) => self.interpret_then_else(then_else.clone(), ctx, vm),
+            Expr::Unary(u) => self.interpret_unary(u, ctx, vm),
+            Expr::ThenElse(then_else) => self.interpret_then_else(then_else, ctx, vm),
             Expr::Object(obj) => {
                 let mut fields = IndexMap::new();
 
@@ -77,6 +88,21 @@ impl Module {
 
                 Ok(Value::Object(fields))
             }
+            Expr::Interpolated(interpolated) => {
+                let mut string = String::new();
+
+                for segment in interpolated.segments.iter() {
+                    match segment {
+                        Segment::Text(text) => string.push_str(text),
+                        Segment::Expr(expr) => {
+                            self.interpret_expr(expr, ctx, vm)?;
+                            string.push_str(&vm.pop().unwrap().to_string());
+                        }
+                    }
+                }
+
+                Ok(Value::String(string))
+            }
         };
 
         Ok(vm.push(val?))
@@ -91,11 +117,11 @@ impl Module {
     ///
     /// # Returns
     /// The result of the unary expression.
-    pub fn interpret_unary(&mut self, u: Unary, ctx: &mut Context, vm: &mut VM) -> Result<Value> {
+    pub fn interpret_unary(&mut self, u: &Unary, ctx: &mut Context, vm: &mut VM) -> Result<Value> {
         self.interpret_expr(&u.expr, ctx, vm)?;
         let val = vm.pop().unwrap();
 
-        let val = match (u.operator.clone().kind, val.clone()) {
+        let val = match (&u.operator.kind, val.clone()) {
             (UnOpKind::Minus, Value::Int(i)) => Value::Int(-i),
             (UnOpKind::Minus, Value::Float(f)) => Value::Float(-f),
             (UnOpKind::LogicalNot, Value::Bool(b)) => Value::Bool(!b),
@@ -124,11 +150,16 @@ impl Module {
     ///
     /// # Returns
     /// The result of the vector expression.
-    pub fn interpret_vec(&mut self, vec: VecExpr, ctx: &mut Context, vm: &mut VM) -> Result<Value> {
+    pub fn interpret_vec(
+        &mut self,
+        vec: &VecExpr,
+        ctx: &mut Context,
+        vm: &mut VM,
+    ) -> Result<Value> {
         debug!("Interpreting vec: {:?}", vec);
 
         Ok(Value::Vec(
-            self.interpret_possible_spread(vec.exprs, ctx, vm)?,
+            self.interpret_possible_spread(&vec.exprs, ctx, vm)?,
         ))
     }
 
@@ -142,7 +173,7 @@ impl Module {
     /// The result of the binary expression.
     pub fn interpret_binary(
         &mut self,
-        binary_expr: Binary,
+        binary_expr: &Binary,
         ctx: &mut Context,
         vm: &mut VM,
     ) -> Result<Value> {
@@ -153,7 +184,7 @@ impl Module {
         self.interpret_expr(&binary_expr.right, ctx, vm)?;
         let right = vm.pop().unwrap();
 
-        let val = match (left.clone(), binary_expr.operator, right.clone()) {
+        let val = match (&left, binary_expr.operator, &right) {
             (_, BinOpKind::Plus, _) => left + right,
             (_, BinOpKind::Minus, _) => left - right,
             (_, BinOpKind::Multiply, _) => left * right,
@@ -161,15 +192,19 @@ impl Module {
             (_, BinOpKind::Modulo, _) => left % right,
             (_, BinOpKind::Equals, _) => Value::Bool(left == right),
             (_, BinOpKind::BangEquals, _) => Value::Bool(left != right),
-            (_, BinOpKind::Power, _) => left.pow(right),
+            (_, BinOpKind::Power, _) => pow_at(left, right, binary_expr.span())?,
 
-            (_, BinOpKind::GreaterThan, _) => Value::Bool(left > right),
-            (_, BinOpKind::LessThan, _) => Value::Bool(left < right),
-            (_, BinOpKind::GreaterThanOrEqual, _) => Value::Bool(left >= right),
-            (_, BinOpKind::LessThanOrEqual, _) => Value::Bool(left <= right),
+            (
+                _,
+                BinOpKind::GreaterThan
+                | BinOpKind::LessThan
+                | BinOpKind::GreaterThanOrEqual
+                | BinOpKind::LessThanOrEqual,
+                _,
+            ) => self.compare(&left, binary_expr.operator, &right, binary_expr.span(), ctx)?,
 
-            (Value::Bool(a), BinOpKind::And, Value::Bool(b)) => Value::Bool(a && b),
-            (Value::Bool(a), BinOpKind::Or, Value::Bool(b)) => Value::Bool(a || b),
+            (Value::Bool(a), BinOpKind::And, Value::Bool(b)) => Value::Bool(*a && *b),
+            (Value::Bool(a), BinOpKind::Or, Value::Bool(b)) => Value::Bool(*a || *b),
 
             (Value::Int(a), BinOpKind::BitwiseAnd, Value::Int(b)) => Value::Int(a & b),
             (Value::Int(a), BinOpKind::BitwiseOr, Value::Int(b)) => Value::Int(a | b),
@@ -196,7 +231,7 @@ impl Module {
     /// The result of the expression.
     pub fn interpret_spread(
         &mut self,
-        s: Spread,
+        s: &Spread,
         ctx: &mut Context,
         vm: &mut VM,
         values: &mut Vec<Value>,
@@ -221,7 +256,7 @@ impl Module {
     /// * `vm` - The virtual machine to use.
     pub fn interpret_possible_spread(
         &mut self,
-        exprs: Vec<Expr>,
+        exprs: &[Expr],
         ctx: &mut Context,
         vm: &mut VM,
     ) -> Result<Vec<Value>> {
@@ -229,7 +264,7 @@ impl Module {
 
         for expr in exprs.iter() {
             match expr {
-                Expr::Spread(s) => self.interpret_spread(s.clone(), ctx, vm, &mut values)?,
+                Expr::Spread(s) => self.interpret_spread(s, ctx, vm, &mut values)?,
                 _ => {
                     self.interpret_expr(expr, ctx, vm)?;
                     values.push(vm.pop().unwrap());
@@ -250,7 +285,7 @@ impl Module {
     /// The result of the assignment expression.
     pub fn interpret_assignment(
         &mut self,
-        assign: Assign,
+        assign: &Assign,
         ctx: &mut Context,
         vm: &mut VM,
     ) -> Result<Value> {
@@ -279,30 +314,34 @@ impl Module {
                     AssignOperator::DivideEquals => {
                         self.update_variable(&ident, val, |a, b| a / b)?
                     }
+                    AssignOperator::ModuloEquals => {
+                        self.update_variable(&ident, val, |a, b| a % b)?
+                    }
+                    AssignOperator::PowerEquals => {
+                        let current = self.find_variable(&ident).cloned().ok_or_else(|| {
+                            VariableNotFoundError(ident.clone(), v.token.span.clone())
+                        })?;
+                        self.set_variable(&ident, pow_at(current, val, assign.span())?)?
+                    }
                 }
                 Ok(final_val)
             }
             Expr::Access(access) => match &access.access {
                 AccessKind::Field(field) => {
-                    let base = access.base.as_ref().clone();
-
                     self.interpret_expr(right, ctx, vm)?;
                     let new_val = vm.pop().unwrap();
 
-                    self.interpret_expr(&base, ctx, vm)?;
+                    self.interpret_expr(&access.base, ctx, vm)?;
                     let base_val = vm.pop().unwrap();
 
-                    let field_name = {
-                        let field = field.as_ref().clone();
-                        match field {
-                            Expr::Variable(v) => v.ident.clone(),
-                            Expr::Literal(l) => match l.value.clone() {
-                                LiteralType::String(s) => s,
-                                _ => panic!("Invalid field name"),
-                            },
-                            // TODO: error
+                    let field_name = match field.as_ref() {
+                        Expr::Variable(v) => v.ident.clone(),
+                        Expr::Literal(l) => match &l.value {
+                            LiteralType::String(s) => s.clone(),
                             _ => panic!("Invalid field name"),
-                        }
+                        },
+                        // TODO: error
+                        _ => panic!("Invalid field name"),
                     };
 
                     let var_name = Self::extract_variable_name(&access.base);
@@ -355,33 +394,47 @@ impl Module {
                     self.interpret_expr(right, ctx, vm)?;
                     let new_val = vm.pop().unwrap();
 
-                    if let (Value::Vec(mut vec), Value::Int(index)) = (base_val.clone(), index_val)
-                    {
-                        let idx = index as usize;
-                        if idx >= vec.len() {
-                            return Err(RoanError::IndexOutOfBounds(
-                                idx,
-                                vec.len(),
+                    let updated = match (base_val, index_val) {
+                        (Value::Vec(mut vec), Value::Int(index)) => {
+                            let Some(idx) = resolve_index(vec.len(), index) else {
+                                return Err(RoanError::IndexOutOfBounds(
+                                    index,
+                                    vec.len(),
+                                    index_expr.span(),
+                                )
+                                .into());
+                            };
+
+                            vec[idx] = new_val.clone();
+                            Value::Vec(vec)
+                        }
+                        (Value::Object(mut fields), Value::String(key)) => {
+                            fields.insert(key, new_val.clone());
+                            Value::Object(fields)
+                        }
+                        (Value::Object(_), _) => {
+                            return Err(RoanError::TypeMismatch(
+                                "Objects can only be indexed with string keys".into(),
                                 index_expr.span(),
                             )
-                            .into());
+                            .into())
                         }
-
-                        vec[idx] = new_val.clone();
-
-                        if let Some(var_name) = Self::extract_variable_name(&access.base) {
-                            self.set_variable(&var_name, Value::Vec(vec))?;
-                            Ok(new_val)
-                        } else {
-                            Err(RoanError::InvalidAssignment(
-                                "Unable to determine variable for assignment".into(),
+                        _ => {
+                            return Err(RoanError::TypeMismatch(
+                                "Left side of assignment must be a vector with integer index"
+                                    .into(),
                                 access.base.span(),
                             )
                             .into())
                         }
+                    };
+
+                    if let Some(var_name) = Self::extract_variable_name(&access.base) {
+                        self.set_variable(&var_name, updated)?;
+                        Ok(new_val)
                     } else {
-                        Err(RoanError::TypeMismatch(
-                            "Left side of assignment must be a vector with integer index".into(),
+                        Err(RoanError::InvalidAssignment(
+                            "Unable to determine variable for assignment".into(),
                             access.base.span(),
                         )
                         .into())
@@ -393,3 +446,197 @@ impl Module {
         }
     }
 }
+
+impl Module {
+    /// Compares `left` and `right` with the comparison operator `op`.
+    ///
+    /// Values that can't be ordered, like `NaN` or a string and an int, compare as `false`. It's
+    /// most likely a mistake, so a [TypeMismatch] warning is written to the error output of `ctx`.
+    fn compare(
+        &self,
+        left: &Value,
+        op: BinOpKind,
+        right: &Value,
+        span: TextSpan,
+        ctx: &Context,
+    ) -> Result<Value> {
+        let Some(ordering) = left.partial_cmp(right) else {
+            let msg = format!(
+                "{} {} {} is always false, {} and {} can't be ordered",
+                left,
+                op,
+                right,
+                left.type_name(),
+                right.type_name()
+            );
+            self.warn(&TypeMismatch(msg, span).into(), ctx)?;
+
+            return Ok(Value::Bool(false));
+        };
+
+        Ok(Value::Bool(match op {
+            BinOpKind::GreaterThan => ordering.is_gt(),
+            BinOpKind::LessThan => ordering.is_lt(),
+            BinOpKind::GreaterThanOrEqual => ordering.is_ge(),
+            _ => ordering.is_le(),
+        }))
+    }
+}
+
+/// Raises `base` to the power of `exp` with the errors pointing at `span`, values don't know
+/// where they come from.
+fn pow_at(base: Value, exp: Value, span: TextSpan) -> Result<Value> {
+    base.checked_pow(exp)
+        .map_err(|err| match err.downcast::<RoanError>() {
+            Ok(IntegerOverflow(op, _)) => IntegerOverflow(op, span).into(),
+            Ok(TypeMismatch(msg, _)) => TypeMismatch(msg, span).into(),
+            Ok(err) => err.into(),
+            Err(err) => err,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{context, parsed, SharedBuffer};
+
+    #[test]
+    fn test_power_overflow() {
+        let mut ctx = context();
+        let output = SharedBuffer::default();
+        ctx.set_output(Box::new(output.clone()));
+
+        let mut module = parsed(
+            "let a = 2 ** -1;\n\
+             __print(__format(a));\n\
+             let b = 2;\n\
+             b **= 63;\n",
+            &mut ctx,
+        );
+        let mut vm = VM::new();
+        let stmts = module.ast.stmts.clone();
+        for stmt in stmts[..3].iter() {
+            module.interpret_stmt(stmt, &mut ctx, &mut vm).unwrap();
+        }
+        assert_eq!(String::from_utf8(output.0.take()).unwrap(), "0.5");
+
+        let err = module
+            .interpret_stmt(&stmts[3], &mut ctx, &mut vm)
+            .unwrap_err();
+        let Some(RoanError::IntegerOverflow(op, span)) = err.downcast_ref::<RoanError>() else {
+            panic!("Expected an overflow, got {}", err);
+        };
+        assert_eq!(op, "2 ** 63");
+        assert_eq!(span.start.line, 4);
+    }
+
+    #[test]
+    fn test_comparing_unordered_values() {
+        let mut ctx = context();
+        let output = SharedBuffer::default();
+        let errors = SharedBuffer::default();
+        ctx.set_output(Box::new(output.clone()));
+        ctx.set_error_output(Box::new(errors.clone()));
+
+        let mut module = parsed(
+            "let nan = 0.0 / 0.0;\n\
+             __print(__format(nan) + \" \" + __format(1.0 / 0.0) + \" \" + __format(-1.0 / 0.0));\n\
+             __print(\" \" + __format(1 < 2) + \" \" + __format(nan < 1));\n\
+             __print(\" \" + __format(!nan));\n",
+            &mut ctx,
+        );
+        module.interpret(&mut ctx, &mut VM::new()).unwrap();
+
+        assert_eq!(
+            String::from_utf8(output.0.take()).unwrap(),
+            "NaN Inf -Inf true false true"
+        );
+        let errors = String::from_utf8(errors.0.take()).unwrap();
+        assert!(errors.starts_with(
+            "warning[E0019]: Type mismatch: NaN < 1 is always false, float and int can't be ordered"
+        ));
+        assert_eq!(errors.matches("warning").count(), 1);
+    }
+
+    #[test]
+    fn test_negative_index_assignment() {
+        let mut ctx = context();
+        let output = SharedBuffer::default();
+        ctx.set_output(Box::new(output.clone()));
+
+        let mut module = parsed(
+            "let v = [1, 2, 3];\n\
+             v[-1] = 9;\n\
+             v[-3] = 7;\n\
+             __print(__format(v));\n\
+             v[-4] = 0;\n",
+            &mut ctx,
+        );
+        let mut vm = VM::new();
+        let stmts = module.ast.stmts.clone();
+        for stmt in stmts[..4].iter() {
+            module.interpret_stmt(stmt, &mut ctx, &mut vm).unwrap();
+        }
+        assert_eq!(String::from_utf8(output.0.take()).unwrap(), "[7, 2, 9]");
+
+        let err = module
+            .interpret_stmt(&stmts[4], &mut ctx, &mut vm)
+            .unwrap_err();
+        assert_eq!(err.to_string(), "Index out of bounds: -4 for a length of 3");
+    }
+
+    #[test]
+    fn test_object_index_assignment() {
+        let mut ctx = context();
+        // Keys the type pass can't reject are checked while running
+        ctx.disable_pass("TypePass");
+
+        let mut module = parsed(
+            "let o = {\"a\": 1};\n\
+             o[\"b\"] = 2;\n\
+             o[\"a\"] = 3;\n\
+             o[1] = 4;\n",
+            &mut ctx,
+        );
+        let mut vm = VM::new();
+        let stmts = module.ast.stmts.clone();
+        for stmt in stmts[..3].iter() {
+            module.interpret_stmt(stmt, &mut ctx, &mut vm).unwrap();
+        }
+
+        let Some(Value::Object(fields)) = module.find_variable("o") else {
+            panic!("Expected an object");
+        };
+        assert_eq!(fields.get("a"), Some(&Value::Int(3)));
+        assert_eq!(fields.get("b"), Some(&Value::Int(2)));
+
+        let err = module
+            .interpret_stmt(&stmts[3], &mut ctx, &mut vm)
+            .unwrap_err();
+        let Some(RoanError::TypeMismatch(msg, span)) = err.downcast_ref::<RoanError>() else {
+            panic!("Expected a type mismatch, got {}", err);
+        };
+        assert_eq!(msg, "Objects can only be indexed with string keys");
+        assert_eq!(span.literal, "1");
+    }
+
+    #[test]
+    fn test_interpolated_string() {
+        let mut ctx = context();
+        let output = SharedBuffer::default();
+        ctx.set_output(Box::new(output.clone()));
+
+        let mut module = parsed(
+            "let name = \"roan\";\n\
+             let items = [1, 2];\n\
+             __print(f\"Hello {name}, {1 + 2} {items} {{x}}\");\n",
+            &mut ctx,
+        );
+        module.interpret(&mut ctx, &mut VM::new()).unwrap();
+
+        assert_eq!(
+            String::from_utf8(output.0.take()).unwrap(),
+            "Hello roan, 3 [1, 2] {x}"
+        );
+    }
+}
@@ -1,23 +1,62 @@
 use crate::{
     context::Context,
     module::{Module, StoredFunction},
+    natives::debug::with_output,
     value::Value,
     vm::{native_fn::NativeFunction, VM},
 };
 use anyhow::Result;
-use roan_ast::CallExpr;
-use roan_error::{error::RoanError::UndefinedFunctionError, frame::Frame, print_diagnostic};
+use roan_ast::{CallExpr, Token, TokenKind};
+use roan_error::{
+    error::{RoanError, RoanError::UndefinedFunctionError},
+    frame::Frame,
+    TextSpan,
+};
 use tracing::debug;
 
+/// Stack space a call needs to be left with to run its body without growing the stack.
+const RED_ZONE: usize = 256 * 1024;
+
+/// Size of the stack segments allocated when the stack has to grow.
+const STACK_SEGMENT_SIZE: usize = 4 * 1024 * 1024;
+
 impl Module {
-    /// Executes a native function with the provided arguments.
+    /// Executes a native function with the provided arguments, its output goes to the sinks of
+    /// `ctx`. Natives needing a capability `ctx` doesn't allow aren't run, [CapabilityDenied] is
+    /// returned instead.
+    ///
+    /// [CapabilityDenied]: RoanError::CapabilityDenied
     pub fn execute_native_function(
         &mut self,
         mut native: NativeFunction,
         args: Vec<Value>,
+        ctx: &Context,
         vm: &mut VM,
     ) -> Result<()> {
-        let result = native.call(args)?;
+        if let Some(capability) = native.capability {
+            if !ctx.capabilities.allows(capability) {
+                vm.take_thrown();
+                return Err(RoanError::CapabilityDenied(
+                    capability.to_string(),
+                    native.name,
+                    Vec::from(vm.frames()),
+                )
+                .into());
+            }
+        }
+
+        let result = with_output(ctx, || native.call(args)).map_err(|err| {
+            match err.downcast::<RoanError>() {
+                // Natives don't know the call stack, errors thrown by them get the current one
+                Ok(RoanError::Throw(value, frames)) if frames.is_empty() => {
+                    // Don't let `catch` bind the value of an earlier, uncaught `throw`
+                    vm.take_thrown();
+                    RoanError::Throw(value, Vec::from(vm.frames())).into()
+                }
+                Ok(err) => err.into(),
+                Err(err) => err,
+            }
+        })?;
         vm.push(result);
 
         Ok(())
@@ -26,15 +65,22 @@ impl Module {
     /// Executes a user-defined function with the provided arguments.
     pub fn execute_user_defined_function(
         &mut self,
-        function: roan_ast::Fn,
+        function: &roan_ast::Fn,
         def_module: &mut Module,
         args: Vec<Value>,
         ctx: &mut Context,
         vm: &mut VM,
-        _: &CallExpr,
+        call: &CallExpr,
     ) -> Result<()> {
         debug!("Executing user-defined function: {}", function.name);
 
+        let frame = Frame::new(
+            function.name.clone(),
+            function.fn_token.span.clone(),
+            Frame::path_or_unknown(def_module.path()),
+        );
+        vm.enter_call(frame, call.token.span.clone())?;
+
         self.enter_scope();
 
         let mut offset = 0;
@@ -69,21 +115,21 @@ impl Module {
             }
         }
 
-        let frame = Frame::new(
-            function.name.clone(),
-            function.fn_token.span.clone(),
-            Frame::path_or_unknown(def_module.path()),
-        );
-        vm.push_frame(frame);
-
-        for stmt in function.body.stmts {
-            def_module.interpret_stmt(stmt, ctx, vm)?;
-        }
+        // Deep recursion continues on stack segments allocated on the heap instead of overflowing
+        // the stack of the thread, it's only limited by the maximum depth of the VM
+        let result = stacker::maybe_grow(RED_ZONE, STACK_SEGMENT_SIZE, || {
+            function
+                .body
+                .stmts
+                .iter()
+                .try_for_each(|stmt| def_module.interpret_stmt(stmt, ctx, vm))
+        });
 
+        // Also when the body failed, the error may be caught by the caller
         vm.pop_frame();
         self.exit_scope();
 
-        Ok(())
+        result
     }
 
     /// Interpret a call expression.
@@ -102,8 +148,37 @@ impl Module {
     ) -> Result<Value> {
         log::debug!("Interpreting call");
 
-        let args = self.interpret_possible_spread(call.args.clone(), ctx, vm)?;
+        let args = self.interpret_possible_spread(&call.args, ctx, vm)?;
+
+        self.call_with_args(call, args, ctx, vm)
+    }
 
+    /// Calls the function called `name` with already evaluated arguments, e.g. from Rust code
+    /// embedding the interpreter.
+    pub fn call_function(
+        &mut self,
+        name: &str,
+        args: Vec<Value>,
+        ctx: &mut Context,
+        vm: &mut VM,
+    ) -> Result<Value> {
+        let call = CallExpr {
+            callee: name.to_string(),
+            args: vec![],
+            token: Token::new(TokenKind::Identifier, TextSpan::default()),
+        };
+
+        self.call_with_args(&call, args, ctx, vm)
+    }
+
+    /// Calls the function `call` refers to with `args`, the arguments of the call evaluated.
+    fn call_with_args(
+        &mut self,
+        call: &CallExpr,
+        args: Vec<Value>,
+        ctx: &mut Context,
+        vm: &mut VM,
+    ) -> Result<Value> {
         let stored_function = self
             .find_function(&call.callee)
             .ok_or_else(|| UndefinedFunctionError(call.callee.clone(), call.token.span.clone()))?
@@ -111,7 +186,7 @@ impl Module {
 
         match stored_function {
             StoredFunction::Native(n) => {
-                self.execute_native_function(n, args, vm)?;
+                self.execute_native_function(n, args, ctx, vm)?;
 
                 Ok(vm.pop().unwrap())
             }
@@ -119,10 +194,11 @@ impl Module {
                 function,
                 defining_module,
             } => {
-                let mut def_module = ctx.query_module(&defining_module).unwrap();
+                let mut def_module =
+                    ctx.try_query_module(&defining_module, call.token.span.clone())?;
 
                 match self.execute_user_defined_function(
-                    function,
+                    &function,
                     &mut def_module,
                     args,
                     ctx,
@@ -130,12 +206,75 @@ impl Module {
                     call,
                 ) {
                     Ok(_) => Ok(vm.pop().unwrap_or(Value::Void)),
-                    Err(e) => {
-                        print_diagnostic(&e, Some(def_module.source.content()), def_module.path());
-                        std::process::exit(1);
-                    }
+                    Err(e) => match e.downcast_ref::<RoanError>() {
+                        // Thrown values and denied capabilities can still be caught by the
+                        // caller, aborts were already turned into diagnostics by a nested
+                        // call and exits are handled by the host.
+                        Some(
+                            RoanError::Throw(..)
+                            | RoanError::CapabilityDenied(..)
+                            | RoanError::AbortedDueToPreviousErrors(_)
+                            | RoanError::Exit(_),
+                        ) => Err(e),
+                        // The error is turned into a diagnostic here, as only the defining
+                        // module has the source its span points into.
+                        _ => {
+                            let mut diagnostics = vec![];
+                            def_module.report(e, &mut diagnostics)?;
+                            Err(RoanError::AbortedDueToPreviousErrors(diagnostics).into())
+                        }
+                    },
                 }
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        context::Capabilities,
+        test_utils::{context, parsed, SharedBuffer},
+    };
+
+    #[test]
+    fn test_capability_denied() {
+        let mut ctx = context();
+        ctx.capabilities = Capabilities::none();
+        let output = SharedBuffer::default();
+        ctx.set_output(Box::new(output.clone()));
+
+        let mut module = parsed(
+            "fn quit() { __exit(1); }\n\
+             try { quit(); } catch e { __print(e); }\n\
+             try { read_file(\"roan.toml\"); } catch e { __print(\"\\n\" + e); }\n",
+            &mut ctx,
+        );
+        module.interpret(&mut ctx, &mut VM::new()).unwrap();
+        assert_eq!(
+            String::from_utf8(output.0.take()).unwrap(),
+            "__exit requires the process capability, which is disabled\n\
+             read_file requires the fs capability, which is disabled"
+        );
+
+        let mut module = parsed("fn quit() { __abort(); }\nquit();\n", &mut ctx);
+        let stmt = module.ast.stmts[1].clone();
+        let err = module
+            .interpret_stmt(&stmt, &mut ctx, &mut VM::new())
+            .unwrap_err();
+        match err.downcast_ref::<RoanError>() {
+            Some(RoanError::CapabilityDenied(capability, native, frames)) => {
+                assert_eq!(capability, "process");
+                assert_eq!(native, "__abort");
+                assert_eq!(frames.len(), 1);
+            }
+            _ => panic!("Expected a denied capability, got {:?}", err),
+        }
+
+        ctx.capabilities.allow_env = true;
+        let mut module = parsed("__print(__format(env(\"ROAN_UNSET_VARIABLE\")));", &mut ctx);
+        module.interpret(&mut ctx, &mut VM::new()).unwrap();
+        assert_eq!(String::from_utf8(output.0.take()).unwrap(), "null");
+    }
+}
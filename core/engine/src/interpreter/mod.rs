@@ -1,5 +1,6 @@
 mod access;
 mod conditions;
+pub mod const_eval;
 mod errors;
 mod expr;
 mod functions;
@@ -0,0 +1,299 @@
+use crate::{
+    context::Context,
+    interpreter::{const_eval::const_eval_with, passes::Pass},
+    module::{Module, StoredFunction},
+    value::Value,
+    vm::VM,
+};
+use anyhow::Result;
+use roan_ast::{
+    Block, Expr, Fn, GetSpan, Let, Literal, LiteralType, Stmt, Token, TokenKind, Visitor,
+};
+use roan_error::{Diagnostic, TextSpan};
+use std::collections::{HashMap, HashSet};
+
+/// Replaces binary expressions whose operands are literals or consts with the literal they
+/// evaluate to, e.g. `PI * 2.0` becomes `6.28318`.
+///
+/// Only expression statements and `let` initializers are folded, including the ones nested in
+/// function bodies and blocks. The pass runs after the
+/// [ResolverPass](crate::interpreter::passes::resolver::ResolverPass), which evaluates the
+/// consts.
+#[derive(Clone, Default)]
+pub struct ConstantFoldingPass {
+    /// Values of the consts visible in the module.
+    constants: HashMap<String, Value>,
+    /// Names of the variables and parameters declared anywhere in the module. A const with the
+    /// same name may be shadowed, so it's never folded.
+    locals: HashSet<String>,
+}
+
+impl ConstantFoldingPass {
+    fn fold_block(&self, block: &mut Block) {
+        for stmt in block.stmts.iter_mut() {
+            self.fold_stmt(stmt);
+        }
+    }
+
+    fn fold_fn(&self, func: &mut Fn) {
+        self.fold_block(&mut func.body);
+    }
+
+    fn fold_stmt(&self, stmt: &mut Stmt) {
+        match stmt {
+            Stmt::Expr(expr) => self.fold_expr(expr),
+            Stmt::Let(Let { initializer, .. }) => self.fold_expr(initializer),
+            Stmt::Fn(func) => self.fold_fn(func),
+            Stmt::Block(block) => self.fold_block(block),
+            Stmt::If(if_stmt) => {
+                self.fold_block(&mut if_stmt.then_block);
+                for else_if in if_stmt.else_ifs.iter_mut() {
+                    self.fold_block(&mut else_if.block);
+                }
+                if let Some(else_block) = if_stmt.else_block.as_mut() {
+                    self.fold_block(&mut else_block.block);
+                }
+            }
+            Stmt::Loop(loop_stmt) => self.fold_block(&mut loop_stmt.block),
+            Stmt::While(while_stmt) => self.fold_block(&mut while_stmt.block),
+            Stmt::For(for_stmt) => self.fold_block(&mut for_stmt.block),
+            Stmt::Try(try_stmt) => {
+                self.fold_block(&mut try_stmt.try_block);
+                self.fold_block(&mut try_stmt.catch_block);
+                if let Some(finally_block) = try_stmt.finally_block.as_mut() {
+                    self.fold_block(finally_block);
+                }
+            }
+            Stmt::StructImpl(impl_stmt) => {
+                impl_stmt.methods.iter_mut().for_each(|m| self.fold_fn(m))
+            }
+            Stmt::TraitImpl(impl_stmt) => {
+                impl_stmt.methods.iter_mut().for_each(|m| self.fold_fn(m))
+            }
+            _ => {}
+        }
+    }
+
+    /// Folds `expr` if it's a constant binary expression, otherwise folds its operands.
+    fn fold_expr(&self, expr: &mut Expr) {
+        if let Expr::Binary(_) = expr {
+            let folded = const_eval_with(expr, &|name| self.resolve(name))
+                .and_then(|value| literal_expr(value, expr.span()));
+
+            if let Some(literal) = folded {
+                *expr = literal;
+                return;
+            }
+        }
+
+        match expr {
+            Expr::Binary(binary) => {
+                self.fold_expr(&mut binary.left);
+                self.fold_expr(&mut binary.right);
+            }
+            Expr::Parenthesized(parenthesized) => self.fold_expr(&mut parenthesized.expr),
+            _ => {}
+        }
+    }
+
+    fn resolve(&self, name: &str) -> Option<Value> {
+        if self.locals.contains(name) {
+            return None;
+        }
+
+        self.constants.get(name).cloned()
+    }
+}
+
+/// Returns a literal expression for `value` spanning `span`, or `None` if the value can't be
+/// written as a literal.
+fn literal_expr(value: Value, span: TextSpan) -> Option<Expr> {
+    let (kind, value) = match value {
+        Value::Int(i) => (TokenKind::Integer(i), LiteralType::Int(i)),
+        Value::Float(f) => (TokenKind::Float(f), LiteralType::Float(f)),
+        Value::Bool(true) => (TokenKind::True, LiteralType::Bool(true)),
+        Value::Bool(false) => (TokenKind::False, LiteralType::Bool(false)),
+        Value::String(s) => (TokenKind::String(s.clone()), LiteralType::String(s)),
+        Value::Char(c) => (TokenKind::Char(c), LiteralType::Char(c)),
+        _ => return None,
+    };
+
+    Some(Expr::Literal(Literal::new(Token::new(kind, span), value)))
+}
+
+impl Pass for ConstantFoldingPass {
+    fn name(&self) -> &'static str {
+        "ConstantFoldingPass"
+    }
+
+    fn run(
+        &mut self,
+        module: &mut Module,
+        ctx: &mut Context,
+        _: &mut VM,
+        _: &mut Vec<Diagnostic>,
+    ) -> Result<()> {
+        self.constants = module
+            .consts
+            .iter()
+            .map(|c| (c.ident.literal(), c.value.clone()))
+            .collect();
+
+        let mut collector = LocalCollector::default();
+        module.ast.walk(&mut collector);
+        self.locals = collector.names;
+
+        for stmt in module.ast.stmts.iter_mut() {
+            self.fold_stmt(stmt);
+        }
+
+        // Functions were stored by the resolver before folding, they are what gets called
+        let id = module.id();
+        for func in module.functions.iter_mut() {
+            if let StoredFunction::Function {
+                function,
+                defining_module,
+            } = func
+            {
+                if *defining_module == id {
+                    self.fold_fn(function);
+                }
+            }
+        }
+
+        ctx.upsert_module(id, module.clone());
+
+        Ok(())
+    }
+
+    /// Unused, [ConstantFoldingPass::run] replaces the statements of the module in place.
+    fn pass_stmt(&mut self, _: Stmt, _: &mut Module, _: &mut Context, _: &mut VM) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Collects the names of every variable and parameter declared in the module.
+#[derive(Default)]
+struct LocalCollector {
+    names: HashSet<String>,
+}
+
+impl Visitor for LocalCollector {
+    fn visit_stmt(&mut self, stmt: &Stmt) {
+        match stmt {
+            Stmt::For(for_stmt) => {
+                self.names.insert(for_stmt.ident.literal());
+                if let Some(value_ident) = &for_stmt.value_ident {
+                    self.names.insert(value_ident.literal());
+                }
+            }
+            Stmt::Try(try_stmt) => {
+                self.names.insert(try_stmt.error_ident.literal());
+            }
+            _ => {}
+        }
+    }
+
+    fn visit_fn(&mut self, func: &Fn) {
+        self.names
+            .extend(func.params.iter().map(|param| param.ident.literal()));
+    }
+
+    fn visit_let(&mut self, let_stmt: &Let) {
+        self.names.insert(let_stmt.ident.literal());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::module::loaders::ModuleLoader;
+    use roan_ast::{source::Source, Lexer, Parser};
+    use std::{cell::RefCell, rc::Rc};
+
+    #[derive(Debug)]
+    struct NoopLoader;
+
+    impl ModuleLoader for NoopLoader {
+        fn load(&mut self, _: &Module, spec: &str, _: &Context) -> Result<Module> {
+            anyhow::bail!("Cannot load {}", spec)
+        }
+    }
+
+    fn folded(src: &str) -> Module {
+        let mut module = Module::new(Source::from_string(src.to_string()));
+        module.tokens = Lexer::new(module.source().clone()).lex(false).unwrap();
+        module.ast = Parser::new(module.tokens.clone()).parse().unwrap();
+
+        let mut ctx = Context::builder()
+            .module_loader(Rc::new(RefCell::new(NoopLoader)))
+            .build();
+        let vm = &mut VM::new();
+        let mut diagnostics = vec![];
+
+        crate::interpreter::passes::resolver::ResolverPass
+            .run(&mut module, &mut ctx, vm, &mut diagnostics)
+            .unwrap();
+        ConstantFoldingPass::default()
+            .run(&mut module, &mut ctx, vm, &mut diagnostics)
+            .unwrap();
+        module
+    }
+
+    fn initializer(stmt: &Stmt) -> &Expr {
+        match stmt {
+            Stmt::Let(let_stmt) => &let_stmt.initializer,
+            _ => panic!("Expected let, got {:?}", stmt),
+        }
+    }
+
+    #[test]
+    fn test_fold_let_initializer() {
+        let module = folded("const TWO = 2;\nlet x = TWO * 21 + 1;");
+
+        match initializer(&module.ast.stmts[1]) {
+            Expr::Literal(literal) => {
+                assert_eq!(literal.value, LiteralType::Int(43));
+                let span = &literal.token.span;
+                assert_eq!(
+                    module
+                        .source()
+                        .get_between(span.start.index, span.end.index),
+                    "TWO * 21 + 1"
+                );
+            }
+            expr => panic!("Expected literal, got {:?}", expr),
+        }
+    }
+
+    #[test]
+    fn test_fold_in_function_body() {
+        let module = folded(
+            "const SIZE = 4;\n\
+             fn area() -> int { let a = SIZE * SIZE; return a; }",
+        );
+
+        let body = match module.find_function("area") {
+            Some(StoredFunction::Function { function, .. }) => &function.body,
+            _ => panic!("Expected function area"),
+        };
+        assert!(matches!(initializer(&body.stmts[0]), Expr::Literal(_)));
+    }
+
+    #[test]
+    fn test_shadowed_and_non_constant_are_not_folded() {
+        let module = folded(
+            "const N = 1;\n\
+             fn f(N: int) -> int { let a = N + 1; return a; }\n\
+             let y = 2;\n\
+             let z = y + 1;",
+        );
+
+        let body = match &module.ast.stmts[1] {
+            Stmt::Fn(func) => &func.body,
+            _ => unreachable!(),
+        };
+        assert!(matches!(initializer(&body.stmts[0]), Expr::Binary(_)));
+        assert!(matches!(initializer(&module.ast.stmts[3]), Expr::Binary(_)));
+    }
+}
@@ -1,21 +1,41 @@
 use crate::{
     context::Context,
-    interpreter::passes::Pass,
+    interpreter::passes::{run_stmts, Pass},
     module::{loaders::remove_surrounding_quotes, ExportType, Module, StoredFunction},
     vm::VM,
 };
 use anyhow::Result;
 use roan_ast::{Stmt, Token};
 use roan_error::{
-    error::RoanError::{FailedToImportModule, ImportError},
-    print_diagnostic,
+    error::RoanError::{FailedToImportModule, ImportError, ItemNotExported},
+    Diagnostic,
 };
 use tracing::debug;
 
-#[derive(Clone)]
-pub struct ImportPass;
+#[derive(Clone, Default)]
+pub struct ImportPass {
+    /// Warnings reported by the imported modules, reported with the diagnostics of the pass.
+    warnings: Vec<Diagnostic>,
+}
 
 impl Pass for ImportPass {
+    fn name(&self) -> &'static str {
+        "ImportPass"
+    }
+
+    fn run(
+        &mut self,
+        module: &mut Module,
+        ctx: &mut Context,
+        vm: &mut VM,
+        diagnostics: &mut Vec<Diagnostic>,
+    ) -> Result<()> {
+        let result = run_stmts(self, module, ctx, vm, diagnostics);
+        diagnostics.append(&mut self.warnings);
+
+        result
+    }
+
     fn pass_stmt(
         &mut self,
         stmt: Stmt,
@@ -40,30 +60,30 @@ impl Pass for ImportPass {
                         )
                     })?;
 
-                match loaded_module.parse(ctx, vm) {
-                    Ok(_) => {}
-                    Err(e) => {
-                        print_diagnostic(&e, Some(loaded_module.source().content()), module.path());
-                        std::process::exit(1);
-                    }
+                if let Some(path) = loaded_module.path() {
+                    module.imports.push(path);
                 }
 
+                let warnings = loaded_module.parse(ctx, vm)?;
+                self.warnings.extend(warnings);
+
                 // Collect the items to import
                 let imported_items: Vec<(String, &Token)> =
                     u.items.iter().map(|i| (i.literal(), i)).collect();
 
                 Ok(for (name, item) in imported_items {
-                    let export = loaded_module.exports.iter().find(|(n, _)| n == &name);
-
-                    if let Some((name, value)) = export {
+                    if let Some(export) = loaded_module.find_export(&name) {
                         debug!("Importing {} from {}", name, u.from.literal());
-                        match value {
-                            ExportType::Function(f) => {
+                        match export {
+                            // Calls resolve to the first function with the name, a second
+                            // one would never be called
+                            ExportType::Function(f) if !module.has_function(&name) => {
                                 module.functions.push(StoredFunction::Function {
                                     function: f.clone(),
                                     defining_module: loaded_module.id(),
                                 });
                             }
+                            ExportType::Function(_) => {}
                             ExportType::Struct(s) => {
                                 module.structs.push(s.clone());
                             }
@@ -74,6 +94,8 @@ impl Pass for ImportPass {
                                 module.consts.push(c.clone());
                             }
                         }
+                    } else if loaded_module.has_private_item(&name) {
+                        return Err(ItemNotExported(name, item.span.clone()).into());
                     } else {
                         return Err(ImportError(name, item.span.clone()).into());
                     }
@@ -83,3 +105,45 @@ impl Pass for ImportPass {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{context, parsed, SharedBuffer};
+    use roan_ast::source::Source;
+
+    #[test]
+    fn test_private_items_are_not_exported() {
+        let mut ctx = context();
+        let output = SharedBuffer::default();
+        ctx.set_output(Box::new(output.clone()));
+
+        let src = "pub fn greet(name: string) -> string { return prefix() + name; }\n\
+                   fn prefix() -> string { return \"Hello \"; }\n";
+        ctx.register_virtual_module("std:greet", Source::from_string(src.to_string()))
+            .unwrap();
+
+        let mut module = parsed(
+            "use { greet } from \"std:greet\";\n\
+             __print(greet(\"roan\"));\n",
+            &mut ctx,
+        );
+        module.interpret(&mut ctx, &mut VM::new()).unwrap();
+        assert_eq!(String::from_utf8(output.0.take()).unwrap(), "Hello roan");
+
+        let greet = parsed(src, &mut ctx);
+        assert!(greet.find_export("greet").is_some());
+        assert!(greet.find_export("prefix").is_none());
+        assert!(greet.has_private_item("prefix"));
+        assert!(!greet.has_private_item("greet"));
+
+        for (item, code) in [("prefix", 53), ("missing", 8)] {
+            let mut module = Module::new(Source::from_string(format!(
+                "use {{ {} }} from \"std:greet\";",
+                item
+            )));
+            let diagnostics = module.check(&mut ctx, &mut VM::new()).unwrap();
+            assert_eq!(diagnostics[0].code, Some(code), "{:?}", diagnostics);
+        }
+    }
+}
@@ -0,0 +1,496 @@
+use crate::{context::Context, interpreter::passes::Pass, module::Module, vm::VM};
+use anyhow::Result;
+use log::Level;
+use roan_ast::{
+    walk_expr, AssignOperator, Block, Expr, Fn, GetSpan, Let, Stmt, Struct, StructImpl, Token,
+    TraitImpl, TypeAnnotation, TypeKind, Visitor,
+};
+use roan_error::{Diagnostic, TextSpan};
+use std::{
+    collections::{HashMap, HashSet},
+    slice,
+};
+
+/// Reports code that is valid but most likely a mistake. Everything it finds is a warning:
+///
+/// - imported items that are never used,
+/// - variables shadowing a variable of an outer scope,
+/// - `let` bindings whose value is overwritten before it is read,
+/// - functions shadowing a function of the prelude.
+#[derive(Clone, Default)]
+pub struct LintPass {
+    scopes: Vec<HashMap<String, Binding>>,
+    imports: Vec<Token>,
+    warnings: Vec<Diagnostic>,
+}
+
+/// A variable declared in a scope.
+#[derive(Clone)]
+struct Binding {
+    /// Span of the name in the declaration.
+    span: TextSpan,
+    /// Whether the variable was declared with `let` and the value hasn't been read yet.
+    unread: bool,
+}
+
+impl LintPass {
+    fn warn(&mut self, title: String, span: TextSpan, module: &Module) -> &mut Diagnostic {
+        self.warnings.push(Diagnostic {
+            title,
+            text: None,
+            level: Level::Warn,
+            code: None,
+            location: Some(span),
+            hint: None,
+            content: Some(module.source().content()),
+            file: module.path(),
+            secondary_spans: vec![],
+            fix: None,
+        });
+        self.warnings.last_mut().unwrap()
+    }
+
+    /// Declares a variable in the current scope, warning if it shadows a variable of an outer
+    /// scope.
+    fn declare(&mut self, ident: &Token, unread: bool, module: &Module) {
+        let name = ident.literal();
+        let outer = self.scopes[..self.scopes.len() - 1]
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(&name))
+            .map(|binding| binding.span.clone());
+
+        if let Some(outer) = outer {
+            let warning = self.warn(
+                format!("Variable {} shadows a variable of an outer scope", name),
+                ident.span.clone(),
+                module,
+            );
+            warning
+                .secondary_spans
+                .push((outer, "shadowed variable is declared here".to_string()));
+        }
+
+        self.scopes.last_mut().unwrap().insert(
+            name,
+            Binding {
+                span: ident.span.clone(),
+                unread,
+            },
+        );
+    }
+
+    fn lint_stmt(&mut self, stmt: &Stmt, module: &Module) {
+        match stmt {
+            Stmt::Expr(expr) => self.lint_expr(expr, module),
+            Stmt::Use(use_stmt) => self.imports.extend(use_stmt.items.iter().cloned()),
+            Stmt::Block(block) => self.lint_block(block, &[], module),
+            Stmt::If(if_stmt) => {
+                self.lint_expr(&if_stmt.condition, module);
+                self.lint_block(&if_stmt.then_block, &[], module);
+                for else_if in if_stmt.else_ifs.iter() {
+                    self.lint_expr(&else_if.condition, module);
+                    self.lint_block(&else_if.block, &[], module);
+                }
+                if let Some(else_block) = &if_stmt.else_block {
+                    self.lint_block(&else_block.block, &[], module);
+                }
+            }
+            Stmt::Return(return_stmt) => {
+                if let Some(expr) = &return_stmt.expr {
+                    self.lint_expr(expr, module);
+                }
+            }
+            Stmt::Fn(func) => self.lint_fn(func, module),
+            Stmt::Let(let_stmt) => {
+                self.lint_expr(&let_stmt.initializer, module);
+                self.declare(&let_stmt.ident, true, module);
+            }
+            Stmt::Throw(throw) => {
+                if let Some(value) = &throw.value {
+                    self.lint_expr(value, module);
+                }
+            }
+            Stmt::Try(try_stmt) => {
+                self.lint_block(&try_stmt.try_block, &[], module);
+                self.lint_block(
+                    &try_stmt.catch_block,
+                    slice::from_ref(&try_stmt.error_ident),
+                    module,
+                );
+                if let Some(finally_block) = &try_stmt.finally_block {
+                    self.lint_block(finally_block, &[], module);
+                }
+            }
+            Stmt::Break(_) | Stmt::Continue(_) | Stmt::Struct(_) => {}
+            Stmt::Loop(loop_stmt) => self.lint_block(&loop_stmt.block, &[], module),
+            Stmt::While(while_stmt) => {
+                self.lint_expr(&while_stmt.condition, module);
+                self.lint_block(&while_stmt.block, &[], module);
+            }
+            Stmt::For(for_stmt) => {
+                self.lint_expr(&for_stmt.iterable, module);
+                let mut bindings = vec![for_stmt.ident.clone()];
+                bindings.extend(for_stmt.value_ident.clone());
+                self.lint_block(&for_stmt.block, &bindings, module);
+            }
+            Stmt::TraitDef(trait_def) => {
+                for method in trait_def.methods.iter() {
+                    self.lint_fn(method, module);
+                }
+            }
+            Stmt::StructImpl(impl_stmt) => {
+                for method in impl_stmt.methods.iter() {
+                    self.lint_fn(method, module);
+                }
+            }
+            Stmt::TraitImpl(impl_stmt) => {
+                for method in impl_stmt.methods.iter() {
+                    self.lint_fn(method, module);
+                }
+            }
+            Stmt::Const(const_stmt) => self.lint_expr(&const_stmt.expr, module),
+        }
+    }
+
+    /// Lints the parameters and body of a function, which share a scope.
+    fn lint_fn(&mut self, func: &Fn, module: &Module) {
+        self.scopes.push(HashMap::new());
+        for param in func.params.iter() {
+            if param.ident.literal() != "self" {
+                self.declare(&param.ident, false, module);
+            }
+        }
+        for stmt in func.body.stmts.iter() {
+            self.lint_stmt(stmt, module);
+        }
+        self.scopes.pop();
+    }
+
+    /// Lints a block in a new scope, in which `bindings` are declared first.
+    fn lint_block(&mut self, block: &Block, bindings: &[Token], module: &Module) {
+        self.scopes.push(HashMap::new());
+        for ident in bindings.iter() {
+            self.declare(ident, false, module);
+        }
+        for stmt in block.stmts.iter() {
+            self.lint_stmt(stmt, module);
+        }
+        self.scopes.pop();
+    }
+
+    /// Marks the variables read by `expr` and warns about assignments overwriting a value that
+    /// was never read.
+    fn lint_expr(&mut self, expr: &Expr, module: &Module) {
+        let mut accesses = VariableAccesses::default();
+        walk_expr(&mut accesses, expr);
+
+        // The right side of an assignment is evaluated before the variable is overwritten
+        for name in accesses.reads.iter() {
+            if let Some(binding) = self.find_binding(name) {
+                binding.unread = false;
+            }
+        }
+
+        let current_scope = self.scopes.len() - 1;
+        for (name, span) in accesses.assignments {
+            let depth = match self.scopes.iter().rposition(|s| s.contains_key(&name)) {
+                Some(depth) => depth,
+                None => continue,
+            };
+            let binding = self.scopes[depth].get_mut(&name).unwrap();
+            let unread = std::mem::replace(&mut binding.unread, false);
+            let declaration = binding.span.clone();
+
+            // An assignment in a nested block might not run, the value could still be read
+            if unread && depth == current_scope {
+                let warning = self.warn(
+                    format!("Value of {} is overwritten before it is read", name),
+                    declaration,
+                    module,
+                );
+                warning
+                    .secondary_spans
+                    .push((span, "value is overwritten here".to_string()));
+            }
+        }
+    }
+
+    fn find_binding(&mut self, name: &str) -> Option<&mut Binding> {
+        self.scopes
+            .iter_mut()
+            .rev()
+            .find_map(|scope| scope.get_mut(name))
+    }
+}
+
+impl Pass for LintPass {
+    fn name(&self) -> &'static str {
+        "LintPass"
+    }
+
+    fn run(
+        &mut self,
+        module: &mut Module,
+        ctx: &mut Context,
+        vm: &mut VM,
+        diagnostics: &mut Vec<Diagnostic>,
+    ) -> Result<()> {
+        self.scopes = vec![HashMap::new()];
+        self.imports.clear();
+
+        for stmt in module.ast.stmts.clone() {
+            self.pass_stmt(stmt, module, ctx, vm)?;
+        }
+
+        let mut references = References::default();
+        module.ast.walk(&mut references);
+
+        for item in std::mem::take(&mut self.imports) {
+            let name = item.literal();
+            if !references.names.contains(&name) {
+                self.warn(format!("Unused import: {}", name), item.span, module);
+            }
+        }
+
+        for stmt in module.ast.stmts.iter() {
+            if let Stmt::Fn(func) = stmt {
+                if ctx.prelude.contains(&func.name) {
+                    self.warn(
+                        format!("Function {} shadows a function of the prelude", func.name),
+                        func.fn_token.span.clone(),
+                        module,
+                    );
+                }
+            }
+        }
+
+        diagnostics.append(&mut self.warnings);
+        Ok(())
+    }
+
+    fn pass_stmt(
+        &mut self,
+        stmt: Stmt,
+        module: &mut Module,
+        _: &mut Context,
+        _: &mut VM,
+    ) -> Result<()> {
+        if self.scopes.is_empty() {
+            self.scopes.push(HashMap::new());
+        }
+
+        self.lint_stmt(&stmt, module);
+        Ok(())
+    }
+}
+
+/// Collects the variables read and assigned by an expression.
+#[derive(Default)]
+struct VariableAccesses {
+    reads: Vec<String>,
+    /// Variables overwritten with `=`, with the span of the assignment.
+    assignments: Vec<(String, TextSpan)>,
+    /// Start of the variables on the left side of an assignment, they aren't reads.
+    targets: HashSet<usize>,
+}
+
+impl Visitor for VariableAccesses {
+    fn visit_expr(&mut self, expr: &Expr) {
+        match expr {
+            Expr::Assign(assign) if matches!(assign.op, AssignOperator::Assign) => {
+                if let Expr::Variable(var) = assign.left.as_ref() {
+                    self.targets.insert(var.token.span.start.index);
+                    self.assignments.push((var.ident.clone(), assign.span()));
+                }
+            }
+            Expr::Variable(var) if !self.targets.contains(&var.token.span.start.index) => {
+                self.reads.push(var.ident.clone());
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Collects every name the module refers to: variables, called functions and the types used in
+/// annotations, constructors and impl blocks.
+#[derive(Default)]
+struct References {
+    names: HashSet<String>,
+}
+
+impl References {
+    fn add_type(&mut self, typ: &TypeAnnotation) {
+        if let TypeKind::Custom(name) = &typ.kind {
+            self.names.insert(name.clone());
+        }
+        for generic in typ.generics.iter() {
+            self.add_type(generic);
+        }
+    }
+}
+
+impl Visitor for References {
+    fn visit_expr(&mut self, expr: &Expr) {
+        match expr {
+            Expr::Variable(var) => {
+                self.names.insert(var.ident.clone());
+            }
+            Expr::Call(call) => {
+                self.names.insert(call.callee.clone());
+            }
+            Expr::StructConstructor(constructor) => {
+                self.names.insert(constructor.name.clone());
+            }
+            _ => {}
+        }
+    }
+
+    fn visit_fn(&mut self, func: &Fn) {
+        for param in func.params.iter() {
+            self.add_type(&param.type_annotation);
+        }
+        if let Some(return_type) = &func.return_type {
+            self.add_type(return_type);
+        }
+    }
+
+    fn visit_struct(&mut self, struct_def: &Struct) {
+        for field in struct_def.fields.values() {
+            self.add_type(&field.type_annotation);
+        }
+    }
+
+    fn visit_struct_impl(&mut self, impl_stmt: &StructImpl) {
+        self.names.insert(impl_stmt.struct_name.literal());
+    }
+
+    fn visit_trait_impl(&mut self, impl_stmt: &TraitImpl) {
+        self.names.insert(impl_stmt.trait_name.literal());
+        self.names.insert(impl_stmt.struct_name.literal());
+    }
+
+    fn visit_let(&mut self, let_stmt: &Let) {
+        if let Some(typ) = &let_stmt.type_annotation {
+            self.add_type(typ);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::module::loaders::ModuleLoader;
+    use roan_ast::{source::Source, Lexer, Parser};
+    use std::{cell::RefCell, rc::Rc};
+
+    #[derive(Debug)]
+    struct NoopLoader;
+
+    impl ModuleLoader for NoopLoader {
+        fn load(&mut self, _: &Module, spec: &str, _: &Context) -> Result<Module> {
+            anyhow::bail!("Cannot load {}", spec)
+        }
+    }
+
+    fn lint(src: &str) -> Vec<Diagnostic> {
+        let mut module = Module::new(Source::from_string(src.to_string()));
+        module.tokens = Lexer::new(module.source().clone()).lex(false).unwrap();
+        module.ast = Parser::new(module.tokens.clone()).parse().unwrap();
+
+        let mut ctx = Context::builder()
+            .module_loader(Rc::new(RefCell::new(NoopLoader)))
+            .build();
+
+        let mut diagnostics = vec![];
+        LintPass::default()
+            .run(&mut module, &mut ctx, &mut VM::new(), &mut diagnostics)
+            .unwrap();
+        diagnostics
+    }
+
+    #[test]
+    fn test_unused_import() {
+        let diagnostics = lint(
+            "use { add, sub, Point } from \"math\";\n\
+             fn main(p: Point) { add(1, 2); }\n",
+        );
+
+        assert_eq!(diagnostics.len(), 1, "{:?}", diagnostics);
+        assert_eq!(diagnostics[0].level, Level::Warn);
+        assert_eq!(diagnostics[0].title, "Unused import: sub");
+        assert_eq!(diagnostics[0].location.as_ref().unwrap().literal, "sub");
+    }
+
+    #[test]
+    fn test_used_imports() {
+        let diagnostics = lint(
+            "use { add, Point, PI } from \"math\";\n\
+             let p = Point { x: add(1, 2) };\n\
+             let area = PI * 2;\n",
+        );
+
+        assert!(diagnostics.is_empty(), "{:?}", diagnostics);
+    }
+
+    #[test]
+    fn test_shadowed_variable() {
+        let diagnostics = lint(
+            "let x = 1;\n\
+             fn main(y: int) {\n\
+                 let x = 2;\n\
+                 for y in [1, 2] {}\n\
+                 let z = x + y;\n\
+                 let z = z + 1;\n\
+             }\n",
+        );
+
+        assert_eq!(diagnostics.len(), 2, "{:?}", diagnostics);
+        assert_eq!(
+            diagnostics[0].title,
+            "Variable x shadows a variable of an outer scope"
+        );
+        assert_eq!(diagnostics[0].location.as_ref().unwrap().start.line, 3);
+        assert_eq!(diagnostics[0].secondary_spans[0].0.start.line, 1);
+        assert_eq!(diagnostics[1].location.as_ref().unwrap().start.line, 4);
+    }
+
+    #[test]
+    fn test_value_overwritten_before_read() {
+        let diagnostics = lint(
+            "fn main(c: bool) {\n\
+                 let a = 1;\n\
+                 a = 2;\n\
+                 let b = 1;\n\
+                 b = b + 1;\n\
+                 let d = 1;\n\
+                 if c { d = 2; }\n\
+                 d = 3;\n\
+             }\n",
+        );
+
+        assert_eq!(diagnostics.len(), 1, "{:?}", diagnostics);
+        assert_eq!(
+            diagnostics[0].title,
+            "Value of a is overwritten before it is read"
+        );
+        assert_eq!(diagnostics[0].location.as_ref().unwrap().start.line, 2);
+        assert_eq!(diagnostics[0].secondary_spans[0].0.start.line, 3);
+    }
+
+    #[test]
+    fn test_shadowed_prelude_function() {
+        let diagnostics = lint(
+            "fn len(v: int) -> int { return 0; }
+fn length() {}
+",
+        );
+
+        assert_eq!(diagnostics.len(), 1, "{:?}", diagnostics);
+        assert_eq!(
+            diagnostics[0].title,
+            "Function len shadows a function of the prelude"
+        );
+        assert_eq!(diagnostics[0].location.as_ref().unwrap().start.line, 1);
+    }
+}
@@ -1,18 +1,36 @@
+pub mod constant_folding;
 pub mod imports;
+pub mod lint;
 pub mod resolver;
 pub mod types;
 
 use crate::{context::Context, module::Module, vm::VM};
 use dyn_clone::{clone_trait_object, DynClone};
 use roan_ast::Stmt;
+use roan_error::Diagnostic;
 
 pub trait Pass: DynClone {
-    fn run(&mut self, module: &mut Module, ctx: &mut Context, vm: &mut VM) -> anyhow::Result<()> {
-        for stmt in module.ast.stmts.clone() {
-            self.pass_stmt(stmt, module, ctx, vm)?;
-        }
+    /// Name of the pass, used in logs and to disable it with [`Context::disable_pass`].
+    fn name(&self) -> &'static str;
+
+    /// Returns `false` if the pass should be skipped.
+    fn is_enabled(&self, _ctx: &Context) -> bool {
+        true
+    }
 
-        Ok(())
+    /// Runs the pass over every top-level statement of the module.
+    ///
+    /// Errors that can be reported as a [`Diagnostic`] don't stop the pass, they are pushed to
+    /// `diagnostics` so that all of them can be reported at once, along with any warnings. Any
+    /// other error aborts the pass and is returned.
+    fn run(
+        &mut self,
+        module: &mut Module,
+        ctx: &mut Context,
+        vm: &mut VM,
+        diagnostics: &mut Vec<Diagnostic>,
+    ) -> anyhow::Result<()> {
+        run_stmts(self, module, ctx, vm, diagnostics)
     }
 
     fn pass_stmt(
@@ -25,3 +43,20 @@ pub trait Pass: DynClone {
 }
 
 clone_trait_object!(Pass);
+
+/// Runs `pass` over every top-level statement of the module, see [`Pass::run`].
+pub(crate) fn run_stmts<P: Pass + ?Sized>(
+    pass: &mut P,
+    module: &mut Module,
+    ctx: &mut Context,
+    vm: &mut VM,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> anyhow::Result<()> {
+    for stmt in module.ast.stmts.clone() {
+        if let Err(err) = pass.pass_stmt(stmt, module, ctx, vm) {
+            module.report(err, diagnostics)?;
+        }
+    }
+
+    Ok(())
+}
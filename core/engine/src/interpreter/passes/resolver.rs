@@ -1,13 +1,13 @@
 use crate::{
     context::Context,
-    interpreter::passes::Pass,
+    interpreter::{const_eval::const_eval_with, passes::Pass},
     module::{
         ExportType, Module, StoredConst, StoredFunction, StoredImpl, StoredStruct, StoredTraitImpl,
     },
     vm::VM,
 };
 use anyhow::Result;
-use roan_ast::{Const, Stmt, Struct, StructImpl, TraitDef, TraitImpl};
+use roan_ast::{Const, GetSpan, Stmt, Struct, StructImpl, TraitDef, TraitImpl};
 use roan_error::error::RoanError;
 use tracing::debug;
 
@@ -15,12 +15,16 @@ use tracing::debug;
 pub struct ResolverPass;
 
 impl Pass for ResolverPass {
+    fn name(&self) -> &'static str {
+        "ResolverPass"
+    }
+
     fn pass_stmt(
         &mut self,
         stmt: Stmt,
         module: &mut Module,
         ctx: &mut Context,
-        vm: &mut VM,
+        _: &mut VM,
     ) -> Result<()> {
         match stmt {
             Stmt::Fn(f) => self.interpret_function(module, f, ctx)?,
@@ -28,7 +32,7 @@ impl Pass for ResolverPass {
             Stmt::TraitDef(trait_stmt) => self.interpret_trait(module, trait_stmt, ctx)?,
             Stmt::StructImpl(impl_stmt) => self.interpret_struct_impl(module, impl_stmt, ctx)?,
             Stmt::TraitImpl(impl_stmt) => self.interpret_trait_impl(module, impl_stmt, ctx)?,
-            Stmt::Const(const_stmt) => self.interpret_const(module, const_stmt, ctx, vm)?,
+            Stmt::Const(const_stmt) => self.interpret_const(module, const_stmt, ctx)?,
             _ => {}
         }
 
@@ -120,11 +124,7 @@ impl ResolverPass {
 
         let mut struct_def = module.get_struct(&for_name, impl_stmt.struct_name.span.clone())?;
         let trait_def = module.get_trait(&trait_name, impl_stmt.trait_name.span.clone())?;
-        if struct_def
-            .trait_impls
-            .iter()
-            .any(|t| t.def.trait_name.literal() == trait_name)
-        {
+        if struct_def.has_trait(&trait_name) {
             return Err(RoanError::StructAlreadyImplementsTrait(
                 for_name,
                 trait_name,
@@ -247,7 +247,6 @@ impl ResolverPass {
     /// # Arguments
     /// * `const_stmt` - [`Const`] - The const to interpret.
     /// * `ctx` - [`Context`] - The context in which to interpret the const.
-    /// * `vm` - [`VM`] - The virtual machine to use for interpretation.
     ///
     /// # Returns
     /// The result of interpreting the const declaration.
@@ -256,15 +255,16 @@ impl ResolverPass {
         module: &mut Module,
         c: Const,
         ctx: &mut Context,
-        vm: &mut VM,
     ) -> Result<()> {
         let def_expr = c.expr.clone();
         let ident_literal = c.ident.literal();
         let is_public = c.public;
 
-        module.interpret_expr(&def_expr, ctx, vm)?;
-
-        let val = vm.pop().expect("Expected value on stack");
+        // Consts are folded without running the interpreter, they may only refer to other consts
+        let val = const_eval_with(&def_expr, &|name| {
+            module.find_const(name).map(|c| c.value.clone())
+        })
+        .ok_or_else(|| RoanError::NonConstantExpression(ident_literal.clone(), def_expr.span()))?;
 
         let stored_val = StoredConst {
             ident: c.ident.clone(),
@@ -12,13 +12,14 @@ use anyhow::Result;
 use colored::Colorize;
 use indexmap::IndexMap;
 use roan_ast::{
-    AccessKind, AssignOperator, BinOpKind, Expr, GetSpan, LiteralType, Stmt, TypeAnnotation,
-    TypeKind, UnOpKind,
+    walk_stmt, AccessKind, AssignOperator, BinOpKind, CallExpr, Expr, GetSpan, Literal,
+    LiteralType, Segment, Stmt, TypeAnnotation, TypeKind, UnOpKind, Visitor,
 };
 use roan_error::{
     error::RoanError::{
-        MissingField, MissingParameter, PropertyNotFoundError, StaticContext, StaticMemberAccess,
-        TypeMismatch, UndefinedFunctionError, VariableNotFoundError,
+        InvalidType, MissingField, MissingParameter, MissingReturn, PropertyNotFoundError,
+        StaticContext, StaticMemberAccess, TooManyArguments, TypeMismatch,
+        TypeMismatchWithDeclaration, UndefinedFunctionError, VariableNotFoundError, VoidValue,
     },
     TextSpan,
 };
@@ -30,15 +31,36 @@ use std::{
 #[derive(Clone)]
 pub struct TypePass {
     pub scopes: Vec<HashMap<String, ResolvedType>>,
+    /// Types of the values thrown in each `try` block being validated, innermost last.
+    thrown: Vec<Vec<ResolvedType>>,
+    /// Type of every variable declared or read, by the span of its name.
+    pub variable_types: Vec<(TextSpan, ResolvedType)>,
+    /// Return types of the functions being validated, innermost last. `None` for functions
+    /// without one, which may return a value of any type.
+    return_types: Vec<Option<TypeAnnotation>>,
 }
 
 impl TypePass {
     pub fn new() -> Self {
         Self {
             scopes: vec![HashMap::new()],
+            thrown: vec![],
+            variable_types: vec![],
+            return_types: vec![],
         }
     }
 
+    /// Returns the type of the variable whose name is at `line` and `column`, both as counted
+    /// in [TextSpan]s.
+    pub fn variable_type_at(&self, line: u32, column: u32) -> Option<&ResolvedType> {
+        self.variable_types
+            .iter()
+            .find(|(span, _)| {
+                span.start.line == line && span.start.column <= column && column < span.end.column
+            })
+            .map(|(_, typ)| typ)
+    }
+
     pub fn enter_scope(&mut self) {
         self.scopes.push(HashMap::new());
     }
@@ -71,9 +93,69 @@ impl TypePass {
         }
         None
     }
+
+    /// Returns the variable in scope or const of the module spelled the most like `name`, if
+    /// one is close enough to be a typo.
+    fn similar_name(&self, name: &str, module: &Module) -> Option<String> {
+        let max_distance = (name.chars().count() / 3).max(1);
+
+        self.scopes
+            .iter()
+            .flat_map(|scope| scope.keys().cloned())
+            .chain(module.consts.iter().map(|c| c.ident.literal()))
+            .map(|candidate| (edit_distance(name, &candidate), candidate))
+            .filter(|(distance, _)| *distance <= max_distance)
+            .min()
+            .map(|(_, candidate)| candidate)
+    }
+
+    /// Returns `expr` converted from `actual` to the `expected` type, if the language can
+    /// convert between them.
+    fn cast_suggestion(
+        expr: &Expr,
+        actual: &ResolvedType,
+        expected: &ResolvedType,
+        module: &Module,
+    ) -> Option<String> {
+        let span = expr.span();
+        let source = module
+            .source()
+            .get_between(span.start.index, span.end.index);
+
+        match (actual, expected) {
+            (
+                ResolvedType::Int | ResolvedType::Float | ResolvedType::Bool,
+                ResolvedType::String,
+            ) => Some(format!("__format({})", source)),
+            _ => None,
+        }
+    }
+
+    /// Creates a type mismatch error, pointing to the declaration of the expected type if it's
+    /// known and suggesting `cast` as a replacement of the source at `span`.
+    fn type_mismatch(
+        message: String,
+        span: TextSpan,
+        declaration: Option<TextSpan>,
+        cast: Option<String>,
+    ) -> anyhow::Error {
+        let err = match declaration {
+            Some(declaration) => TypeMismatchWithDeclaration(message, span.clone(), declaration),
+            None => TypeMismatch(message, span.clone()),
+        };
+
+        match cast {
+            Some(cast) => err.with_fix(span, cast).into(),
+            None => err.into(),
+        }
+    }
 }
 
 impl Pass for TypePass {
+    fn name(&self) -> &'static str {
+        "TypePass"
+    }
+
     fn pass_stmt(
         &mut self,
         stmt: Stmt,
@@ -81,7 +163,14 @@ impl Pass for TypePass {
         ctx: &mut Context,
         _: &mut VM,
     ) -> Result<()> {
-        self.validate_stmt(&stmt, module, ctx)?;
+        let depth = self.scopes.len();
+
+        if let Err(err) = self.validate_stmt(&stmt, module, ctx) {
+            // Drop scopes left behind by the failed statement so later statements are
+            // checked against the correct scope.
+            self.scopes.truncate(depth);
+            return Err(err);
+        }
 
         Ok(())
     }
@@ -205,7 +294,7 @@ impl ResolvedType {
         ResolvedType::matches(type1, to.clone()) && ResolvedType::matches(type2, to)
     }
 
-    pub fn built_in(&self) -> HashMap<String, NativeFunction> {
+    pub fn built_in(&self) -> &'static HashMap<String, NativeFunction> {
         let value = match self {
             ResolvedType::Int => Value::Int(0),
             ResolvedType::Float => Value::Float(0.0),
@@ -247,6 +336,7 @@ impl ResolvedType {
             ))),
             Value::Struct(name, _) => ResolvedType::Struct(name.name.literal(), mod_id),
             Value::Void => ResolvedType::Void,
+            Value::Iterator(_) | Value::Bytes(_) => ResolvedType::Any,
         }
     }
 }
@@ -260,6 +350,27 @@ impl TypePass {
     ) -> Result<()> {
         typ.module_id = Some(module.id().clone());
 
+        // Annotations written in the source can name any identifier, make sure it's a known type.
+        if let (TypeKind::Custom(name), Some(token)) = (&typ.kind, &typ.token_name) {
+            let is_known = name == "null"
+                || module.structs.iter().any(|s| s.name.literal() == *name)
+                || module.find_trait(name).is_some();
+
+            if !is_known {
+                let available = [
+                    "bool", "int", "float", "string", "char", "vec", "object", "anytype", "void",
+                ]
+                .into_iter()
+                .map(String::from)
+                .chain(module.structs.iter().map(|s| s.name.literal()))
+                .chain(module.traits.iter().map(|t| t.name.literal()))
+                .collect::<Vec<_>>()
+                .join(", ");
+
+                return Err(InvalidType(name.clone(), available, token.span.clone()).into());
+            }
+        }
+
         if typ.is_generic() {
             for generic in typ.generics.iter_mut() {
                 self.check_type_annotation(generic, module, ctx)?;
@@ -280,13 +391,96 @@ impl TypePass {
             self.check_type_annotation(typ, module, ctx)?;
         }
 
-        for stmt in &func.body.stmts {
-            self.validate_stmt(stmt, module, ctx)?;
+        self.return_types.push(func.return_type.clone());
+        let result = func
+            .body
+            .stmts
+            .iter()
+            .try_for_each(|stmt| self.validate_stmt(stmt, module, ctx));
+        self.return_types.pop();
+        result?;
+
+        if let Some(typ) = &func.return_type {
+            if ResolvedType::from_type_annotation(typ) != ResolvedType::Void
+                && !always_returns(&func.body.stmts)
+            {
+                return Err(MissingReturn(Self::return_type_span(func)).into());
+            }
         }
 
         Ok(())
     }
 
+    /// Returns the span of the return type of `func`, or of its `fn` keyword if it has none.
+    fn return_type_span(func: &roan_ast::Fn) -> TextSpan {
+        func.return_type
+            .as_ref()
+            .and_then(|typ| typ.token_name.as_ref())
+            .map_or(func.fn_token.span.clone(), |token| token.span.clone())
+    }
+
+    /// Validates the value returned by `ret` against the return type of the function it's in.
+    fn validate_return(
+        &mut self,
+        ret: &roan_ast::Return,
+        module: &mut Module,
+        ctx: &mut Context,
+    ) -> Result<()> {
+        let expected = self.return_types.last().cloned().flatten();
+
+        match (&ret.expr, expected) {
+            (Some(expr), Some(expected)) => {
+                let actual = self.validate_value_expr(expr, module, ctx, Some(expected.clone()))?;
+                let expected_type = ResolvedType::from_type_annotation(&expected);
+
+                let is_null = expected.is_nullable && actual == ResolvedType::Null;
+                if !is_null && !ResolvedType::matches(expected_type.clone(), actual.clone()) {
+                    return Err(Self::type_mismatch(
+                        format!(
+                            "Expected {} to be returned but got {}",
+                            expected_type.to_string().bright_magenta(),
+                            actual.to_string().bright_magenta()
+                        ),
+                        expr.span(),
+                        expected.token_name.map(|token| token.span),
+                        Self::cast_suggestion(expr, &actual, &expected_type, module),
+                    ));
+                }
+            }
+            // A function without a return type returns `anytype` when it returns a value, like
+            // calls to it are typed, so only the expression itself is validated
+            (Some(expr), None) => {
+                self.validate_value_expr(expr, module, ctx, None)?;
+            }
+            (None, Some(expected))
+                if ResolvedType::from_type_annotation(&expected) != ResolvedType::Void =>
+            {
+                return Err(TypeMismatch(
+                    format!(
+                        "Expected {} to be returned but got nothing",
+                        expected.kind.to_string().bright_magenta()
+                    ),
+                    ret.return_token.span.clone(),
+                )
+                .into());
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    /// Replaces `Self` in a type annotation used inside an `impl` block with the name of the struct.
+    pub fn replace_self_type(typ: &mut TypeAnnotation, struct_name: &str) {
+        if typ.kind == TypeKind::Custom("Self".to_string()) {
+            typ.kind = TypeKind::Custom(struct_name.to_string());
+        }
+
+        for generic in typ.generics.iter_mut() {
+            Self::replace_self_type(generic, struct_name);
+        }
+    }
+
     pub fn annotation_from_native_param(param: NativeFunctionParam) -> TypeAnnotation {
         TypeAnnotation {
             separator: None,
@@ -298,8 +492,176 @@ impl TypePass {
         }
     }
 
+    /// Annotation of values whose type is only known at runtime, like the results of natives.
+    fn anytype_annotation() -> TypeAnnotation {
+        TypeAnnotation {
+            separator: None,
+            token_name: None,
+            kind: TypeKind::Anytype,
+            is_nullable: true,
+            module_id: None,
+            generics: vec![],
+        }
+    }
+
+    /// Validates `expr` where a value is expected, the result of a call to a function that
+    /// doesn't return anything can't be used there.
+    pub fn validate_value_expr(
+        &mut self,
+        expr: &Expr,
+        module: &mut Module,
+        ctx: &mut Context,
+        global_type: Option<TypeAnnotation>,
+    ) -> Result<ResolvedType> {
+        let typ = self.validate_and_get_type_expr(expr, module, ctx, global_type)?;
+
+        if typ == ResolvedType::Void {
+            if let Expr::Call(call) = expr {
+                return Err(VoidValue(call.callee.clone(), expr.span()).into());
+            }
+        }
+
+        Ok(typ)
+    }
+
+    /// Validates the arguments of a call to `stored_function` and returns the type of its result.
+    pub fn validate_call(
+        &mut self,
+        call: &CallExpr,
+        stored_function: StoredFunction,
+        module: &mut Module,
+        ctx: &mut Context,
+        global_type: Option<TypeAnnotation>,
+    ) -> Result<ResolvedType> {
+        if !stored_function.is_variadic() && call.args.len() > stored_function.arity() {
+            return Err(TooManyArguments(
+                stored_function.arity(),
+                stored_function.name().bright_magenta().to_string(),
+                call.args.len(),
+                call.token.span.clone(),
+            )
+            .into());
+        }
+
+        let mut arg_types = vec![];
+
+        for arg in &call.args {
+            arg_types.push(self.validate_value_expr(arg, module, ctx, global_type.clone())?);
+        }
+
+        // Type, nullable, rest and the declaration if it's in this module
+        let mut param_types: Vec<(ResolvedType, bool, bool, Option<TextSpan>)> = vec![];
+        let mut typ: Option<TypeAnnotation>;
+
+        match stored_function {
+            StoredFunction::Native(native) => {
+                for param in &native.params {
+                    // Rest parameters of natives collect arguments of any type
+                    let param_type = if param.is_rest {
+                        ResolvedType::Any
+                    } else {
+                        ResolvedType::from_type_annotation(&Self::annotation_from_native_param(
+                            param.clone(),
+                        ))
+                    };
+                    param_types.push((param_type, true, param.is_rest, None));
+                }
+                typ = Some(Self::anytype_annotation());
+            }
+            StoredFunction::Function {
+                function,
+                defining_module,
+            } => {
+                let is_local = defining_module == module.id();
+                for param in &function.params {
+                    param_types.push((
+                        ResolvedType::from_type_annotation(&param.type_annotation),
+                        param.type_annotation.is_nullable,
+                        param.is_rest,
+                        is_local.then(|| param.ident.span.clone()),
+                    ));
+                }
+                typ = function.return_type.clone();
+                // Without a return type, only the functions that never return a value
+                // are void
+                if typ.is_none() && returns_value(&function) {
+                    typ = Some(Self::anytype_annotation());
+                }
+            }
+        }
+
+        let mut arg_index = 0;
+
+        for (param_type, nullable, is_rest, declaration) in param_types.iter() {
+            if *is_rest {
+                // Handle rest parameter: All remaining arguments must match the `param_type`
+                while let Some(arg_type) = arg_types.get(arg_index) {
+                    if !ResolvedType::matches(param_type.clone(), arg_type.clone()) {
+                        return Err(Self::type_mismatch(
+                            format!(
+                                "Expected type {} for rest arguments but got {}",
+                                param_type.to_string().bright_magenta(),
+                                arg_type.to_string().bright_magenta()
+                            ),
+                            call.args[arg_index].span().clone(),
+                            declaration.clone(),
+                            Self::cast_suggestion(
+                                &call.args[arg_index],
+                                arg_type,
+                                param_type,
+                                module,
+                            ),
+                        ));
+                    }
+                    arg_index += 1;
+                }
+                break; // All remaining arguments processed as rest
+            } else {
+                // Non-rest parameter
+                if let Some(arg_type) = arg_types.get(arg_index) {
+                    if !ResolvedType::matches(param_type.clone(), arg_type.clone()) {
+                        return Err(Self::type_mismatch(
+                            format!(
+                                "Expected type {} but got {}",
+                                param_type.to_string().bright_magenta(),
+                                arg_type.to_string().bright_magenta()
+                            ),
+                            call.args[arg_index].span().clone(),
+                            declaration.clone(),
+                            Self::cast_suggestion(
+                                &call.args[arg_index],
+                                arg_type,
+                                param_type,
+                                module,
+                            ),
+                        ));
+                    }
+                    arg_index += 1;
+                } else if !nullable {
+                    return Err(MissingParameter(
+                        call.callee.clone().bright_magenta().to_string(),
+                        call.token.span.clone(),
+                    )
+                    .into());
+                }
+            }
+        }
+        let typ = &mut typ.unwrap_or_else(|| TypeAnnotation {
+            separator: None,
+            token_name: None,
+            kind: TypeKind::Void,
+            is_nullable: true,
+            module_id: None,
+            generics: vec![],
+        });
+
+        typ.module_id = Some(module.id().clone());
+
+        Ok(ResolvedType::from_type_annotation(typ))
+    }
+
     pub fn validate_and_get_type_expr(
-        &self,
+        &mut self,
         expr: &Expr,
         module: &mut Module,
         ctx: &mut Context,
@@ -321,10 +683,19 @@ impl TypePass {
                     .map(|gt| gt.match_generic(TypeKind::Object, vec![TypeKind::Anytype]))
                     .unwrap_or(false);
 
+                // Values are checked against the generic of the annotation, e.g. `vec<int>` in `object<vec<int>>`
+                let value_annotation = global_type
+                    .as_ref()
+                    .and_then(|gt| gt.generics.first().cloned());
+
                 let mut obj_type = ResolvedType::Null;
                 for (_, value) in &obj.fields {
-                    let value_type =
-                        self.validate_and_get_type_expr(value, module, ctx, global_type.clone())?;
+                    let value_type = self.validate_and_get_type_expr(
+                        value,
+                        module,
+                        ctx,
+                        value_annotation.clone(),
+                    )?;
                     if obj_type == ResolvedType::Null {
                         obj_type = value_type;
                     } else if !ResolvedType::matches(obj_type.clone(), value_type) && !accepts_any {
@@ -386,17 +757,26 @@ impl TypePass {
                     .map(|gt| gt.match_generic(TypeKind::Vec, vec![TypeKind::Anytype]))
                     .unwrap_or(false);
 
+                // Elements are checked against the generic of the annotation, e.g. `vec<int>` in `vec<vec<int>>`
+                let element_annotation = global_type
+                    .as_ref()
+                    .and_then(|gt| gt.generics.first().cloned());
+
                 let mut vec_type = ResolvedType::Null;
                 for expr in &vec.exprs {
-                    let expr_type =
-                        self.validate_and_get_type_expr(expr, module, ctx, global_type.clone())?;
+                    let expr_type = self.validate_and_get_type_expr(
+                        expr,
+                        module,
+                        ctx,
+                        element_annotation.clone(),
+                    )?;
                     if vec_type == ResolvedType::Null {
                         vec_type = expr_type;
-                    } else if let Some(ref typ) = global_type {
+                    } else if let (Some(typ), Some(element)) = (&global_type, &element_annotation) {
                         if !ResolvedType::matches_to(
                             vec_type.clone(),
                             expr_type.clone(),
-                            ResolvedType::from_type_annotation(&typ.generics[0]),
+                            ResolvedType::from_type_annotation(element),
                         ) {
                             return Err(TypeMismatch(
                                 format!(
@@ -423,7 +803,23 @@ impl TypePass {
                 Ok(ResolvedType::Vector(Box::new(vec_type)))
             }
             Expr::Unary(unary) => match unary.operator.kind {
-                UnOpKind::Minus | UnOpKind::BitwiseNot => {
+                UnOpKind::BitwiseNot => {
+                    let expr_type =
+                        self.validate_and_get_type_expr(&unary.expr, module, ctx, global_type)?;
+                    if expr_type == ResolvedType::Int {
+                        Ok(expr_type)
+                    } else {
+                        Err(TypeMismatch(
+                            format!(
+                                "Unary operator {} can only be applied to int",
+                                unary.operator.kind
+                            ),
+                            unary.span().clone(),
+                        )
+                        .into())
+                    }
+                }
+                UnOpKind::Minus => {
                     let expr_type =
                         self.validate_and_get_type_expr(&unary.expr, module, ctx, global_type)?;
                     if expr_type == ResolvedType::Int || expr_type == ResolvedType::Float {
@@ -462,12 +858,10 @@ impl TypePass {
                             ctx,
                             Some(field.type_annotation.clone()),
                         )?;
+                        let field_type = ResolvedType::from_type_annotation(&field.type_annotation);
 
-                        if !ResolvedType::matches(
-                            expr_type,
-                            ResolvedType::from_type_annotation(&field.type_annotation),
-                        ) {
-                            return Err(TypeMismatch(
+                        if !ResolvedType::matches(expr_type.clone(), field_type.clone()) {
+                            return Err(Self::type_mismatch(
                                 format!(
                                     "Field {} of struct {} must be of type {}",
                                     name.bright_magenta(),
@@ -475,8 +869,10 @@ impl TypePass {
                                     field.type_annotation.kind.to_string().bright_magenta()
                                 ),
                                 expr.span().clone(),
-                            )
-                            .into());
+                                (struct_type.defining_module == module.id())
+                                    .then(|| field.ident.span.clone()),
+                                Self::cast_suggestion(expr, &expr_type, &field_type, module),
+                            ));
                         }
                     } else if !field.type_annotation.is_nullable {
                         return Err(MissingField(
@@ -508,6 +904,24 @@ impl TypePass {
                 )?;
 
                 match binary.operator {
+                    _ if binary.operator.is_bitwise_operator() => {
+                        match (left_type.clone(), right_type.clone()) {
+                            (ResolvedType::Int, ResolvedType::Int) => Ok(ResolvedType::Int),
+                            // Checked when the expression is evaluated
+                            (ResolvedType::Any, _) | (_, ResolvedType::Any) => {
+                                Ok(ResolvedType::Any)
+                            }
+                            _ => Err(TypeMismatch(
+                                format!(
+                                    "bitwise operators require int operands, got {} and {}",
+                                    left_type.to_string().bright_magenta(),
+                                    right_type.to_string().bright_magenta()
+                                ),
+                                binary.span().clone(),
+                            )
+                            .into()),
+                        }
+                    }
                     _ if binary.operator.is_number_operator() => {
                         match (left_type.clone(), binary.operator, right_type.clone()) {
                             (
@@ -523,6 +937,10 @@ impl TypePass {
                             | (ResolvedType::Float, _, ResolvedType::Int) => {
                                 Ok(ResolvedType::Float)
                             }
+                            // Checked when the expression is evaluated
+                            (ResolvedType::Any, _, _) | (_, _, ResolvedType::Any) => {
+                                Ok(ResolvedType::Any)
+                            }
                             _ => Err(TypeMismatch(
                                 format!(
                                     "Invalid binary operation between {} and {}",
@@ -550,9 +968,17 @@ impl TypePass {
                             (ResolvedType::Vector(_), _, ResolvedType::Vector(_)) => {
                                 Ok(ResolvedType::Bool)
                             }
+                            (ResolvedType::Any, _, _) | (_, _, ResolvedType::Any) => {
+                                Ok(ResolvedType::Bool)
+                            }
                             _ => Err(TypeMismatch(
                                 format!(
-                                    "Invalid boolean operation between {} and {}",
+                                    "Invalid {} operation between {} and {}",
+                                    if binary.operator.is_logical_operator() {
+                                        "logical"
+                                    } else {
+                                        "comparison"
+                                    },
                                     left_type.to_string().bright_magenta(),
                                     right_type.to_string().bright_magenta()
                                 ),
@@ -573,12 +999,8 @@ impl TypePass {
                     ctx,
                     global_type.clone(),
                 )?;
-                let right_type = self.validate_and_get_type_expr(
-                    &assign.right,
-                    module,
-                    ctx,
-                    global_type.clone(),
-                )?;
+                let right_type =
+                    self.validate_value_expr(&assign.right, module, ctx, global_type.clone())?;
 
                 match (left_type.clone(), assign.op.clone(), right_type.clone()) {
                     (_, AssignOperator::Assign, _) => {
@@ -600,7 +1022,9 @@ impl TypePass {
                         ResolvedType::Int | ResolvedType::Float | ResolvedType::Any,
                         AssignOperator::MultiplyEquals
                         | AssignOperator::DivideEquals
-                        | AssignOperator::MinusEquals,
+                        | AssignOperator::MinusEquals
+                        | AssignOperator::ModuloEquals
+                        | AssignOperator::PowerEquals,
                         ResolvedType::Int | ResolvedType::Float | ResolvedType::Any,
                     ) => Ok(left_type.clone()),
                     (
@@ -631,8 +1055,10 @@ impl TypePass {
                 }
             }
             Expr::Variable(var) => {
-                if let Some(typ) = self.find_variable(&var.ident) {
-                    Ok(typ.clone())
+                if let Some(typ) = self.find_variable(&var.ident).cloned() {
+                    self.variable_types
+                        .push((var.token.span.clone(), typ.clone()));
+                    Ok(typ)
                 } else {
                     if let Some(cnst) = module.find_const(&var.ident) {
                         Ok(ResolvedType::from_value(
@@ -640,7 +1066,12 @@ impl TypePass {
                             cnst.defining_module.clone(),
                         ))
                     } else {
-                        Err(VariableNotFoundError(var.ident.clone(), var.token.span.clone()).into())
+                        let err = VariableNotFoundError(var.ident.clone(), var.token.span.clone());
+
+                        match self.similar_name(&var.ident, module) {
+                            Some(name) => Err(err.with_fix(var.token.span.clone(), name).into()),
+                            None => Err(err.into()),
+                        }
                     }
                 }
             }
@@ -652,108 +1083,7 @@ impl TypePass {
                     })?
                     .clone();
 
-                let mut arg_types = vec![];
-
-                for arg in &call.args {
-                    arg_types.push(self.validate_and_get_type_expr(
-                        arg,
-                        module,
-                        ctx,
-                        global_type.clone(),
-                    )?);
-                }
-
-                let mut param_types: Vec<(ResolvedType, bool, bool)> = vec![];
-                let mut typ: Option<TypeAnnotation> = None;
-
-                match stored_function {
-                    StoredFunction::Native(native) => {
-                        for param in &native.params {
-                            param_types.push((
-                                ResolvedType::from_type_annotation(
-                                    &Self::annotation_from_native_param(param.clone()),
-                                ),
-                                true,
-                                param.is_rest,
-                            ));
-                        }
-                        typ = Some(TypeAnnotation {
-                            separator: None,
-                            token_name: None,
-                            kind: TypeKind::Anytype,
-                            is_nullable: true,
-                            module_id: None,
-                            generics: vec![],
-                        });
-                    }
-                    StoredFunction::Function { function, .. } => {
-                        for param in &function.params {
-                            param_types.push((
-                                ResolvedType::from_type_annotation(&param.type_annotation),
-                                param.type_annotation.is_nullable,
-                                param.is_rest,
-                            ));
-                        }
-                        typ = function.return_type.clone();
-                    }
-                }
-
-                let mut arg_index = 0;
-
-                for (param_type, nullable, is_rest) in param_types.iter() {
-                    if *is_rest {
-                        // Handle rest parameter: All remaining arguments must match the `param_type`
-                        while let Some(arg_type) = arg_types.get(arg_index) {
-                            if !ResolvedType::matches(param_type.clone(), arg_type.clone()) {
-                                return Err(TypeMismatch(
-                                    format!(
-                                        "Expected type {} for rest arguments but got {}",
-                                        param_type.to_string().bright_magenta(),
-                                        arg_type.to_string().bright_magenta()
-                                    ),
-                                    call.args[arg_index].span().clone(),
-                                )
-                                .into());
-                            }
-                            arg_index += 1;
-                        }
-                        break; // All remaining arguments processed as rest
-                    } else {
-                        // Non-rest parameter
-                        if let Some(arg_type) = arg_types.get(arg_index) {
-                            if !ResolvedType::matches(param_type.clone(), arg_type.clone()) {
-                                return Err(TypeMismatch(
-                                    format!(
-                                        "Expected type {} but got {}",
-                                        param_type.to_string().bright_magenta(),
-                                        arg_type.to_string().bright_magenta()
-                                    ),
-                                    call.args[arg_index].span().clone(),
-                                )
-                                .into());
-                            }
-                            arg_index += 1;
-                        } else if !nullable {
-                            return Err(MissingParameter(
-                                call.callee.clone().bright_magenta().to_string(),
-                                call.token.span.clone(),
-                            )
-                            .into());
-                        }
-                    }
-                }
-                let typ = &mut typ.unwrap_or_else(|| TypeAnnotation {
-                    separator: None,
-                    token_name: None,
-                    kind: TypeKind::Void,
-                    is_nullable: true,
-                    module_id: None,
-                    generics: vec![],
-                });
-
-                typ.module_id = Some(module.id().clone());
-
-                Ok(ResolvedType::from_type_annotation(typ))
+                self.validate_call(call, stored_function, module, ctx, global_type)
             }
             Expr::Access(access) => match access.access.clone() {
                 AccessKind::Index(expr) => {
@@ -776,6 +1106,31 @@ impl TypePass {
                             expr.span().clone(),
                         )
                         .into()),
+                        (ResolvedType::Struct(name, id), ResolvedType::String) => {
+                            // The field is only known here when the key is a literal
+                            let key = match expr.as_ref() {
+                                Expr::Literal(lit) => match &lit.value {
+                                    LiteralType::String(key) => key.clone(),
+                                    _ => return Ok(ResolvedType::Any),
+                                },
+                                _ => return Ok(ResolvedType::Any),
+                            };
+
+                            let module = ctx.try_query_module(&id, expr.span())?;
+                            let struct_def = module.get_struct(&name, expr.span())?;
+
+                            match struct_def.find_field(&key) {
+                                Some(field) => {
+                                    Ok(ResolvedType::from_type_annotation(&field.type_annotation))
+                                }
+                                None => Err(PropertyNotFoundError(key, expr.span()).into()),
+                            }
+                        }
+                        (ResolvedType::Struct(..), _) => Err(TypeMismatch(
+                            "Structs can only be indexed with strings".to_string(),
+                            expr.span().clone(),
+                        )
+                        .into()),
                         (ResolvedType::Vector(_), _) => Err(TypeMismatch(
                             "Vectors can only be indexed with integers".to_string(),
                             expr.span().clone(),
@@ -810,7 +1165,7 @@ impl TypePass {
                         Expr::Call(call) => {
                             match base {
                                 ResolvedType::Struct(name, id) => {
-                                    let module = ctx.query_module(&id).unwrap();
+                                    let module = ctx.try_query_module(&id, expr.span())?;
                                     let struct_def = module.get_struct(&name, expr.span())?;
 
                                     let field = struct_def.find_method(&call.callee);
@@ -823,8 +1178,8 @@ impl TypePass {
                                         .into());
                                     }
 
-                                    Ok(ResolvedType::from_type_annotation(
-                                        &field.unwrap().return_type.clone().unwrap_or(
+                                    let mut return_type =
+                                        field.unwrap().return_type.clone().unwrap_or(
                                             TypeAnnotation {
                                                 separator: None,
                                                 token_name: None,
@@ -833,9 +1188,18 @@ impl TypePass {
                                                 module_id: None,
                                                 generics: vec![],
                                             },
-                                        ),
-                                    ))
+                                        );
+                                    Self::replace_self_type(&mut return_type, &name);
+                                    return_type.module_id = Some(id);
+
+                                    Ok(ResolvedType::from_type_annotation(&return_type))
+                                }
+                                // `sort_by` calls back into the interpreter, so it's not a native method
+                                ResolvedType::Vector(_) if call.callee == "sort_by" => {
+                                    Ok(ResolvedType::Vector(Box::new(ResolvedType::Any)))
                                 }
+                                // The value is only known at runtime, e.g. an iterator returned by `iter`
+                                ResolvedType::Any => Ok(ResolvedType::Any),
                                 _ => {
                                     if let Some(_) = base.built_in().get(&call.callee) {
                                         Ok(ResolvedType::from_type_annotation(&TypeAnnotation {
@@ -860,7 +1224,7 @@ impl TypePass {
                             // We could possibly check if the field exists in the object here
                             ResolvedType::Object(typ) => Ok(*typ),
                             ResolvedType::Struct(name, id) => {
-                                let module = ctx.query_module(&id).unwrap();
+                                let module = ctx.try_query_module(&id, expr.span())?;
                                 let struct_def = module.get_struct(&name, expr.span())?;
 
                                 let field = struct_def.find_field(&lit.ident);
@@ -900,42 +1264,61 @@ impl TypePass {
                         _ => return Err(StaticMemberAccess(access.span()).into()),
                     };
 
-                    let struct_def = module.get_struct(&struct_name, span)?;
+                    // Imported structs are copies, the methods are looked up in the module
+                    // declaring the struct
+                    let defining_module_id = module
+                        .get_struct(&struct_name, span.clone())?
+                        .defining_module;
+                    let defining_module =
+                        ctx.try_query_module(&defining_module_id, access.span())?;
+                    let struct_def = defining_module.get_struct(&struct_name, span)?;
 
-                    match expr.as_ref().clone() {
+                    match expr.as_ref() {
                         Expr::Call(call) => {
-                            let method_name = call.callee.clone();
-                            let method = struct_def.find_static_method(&method_name);
-
-                            if method.is_none() {
-                                return Err(UndefinedFunctionError(
-                                    method_name,
-                                    call.token.span.clone(),
-                                )
-                                .into());
+                            let mut function = struct_def
+                                .find_static_method(&call.callee)
+                                .ok_or_else(|| {
+                                    UndefinedFunctionError(
+                                        call.callee.clone(),
+                                        call.token.span.clone(),
+                                    )
+                                })?
+                                .clone();
+                            for param in function.params.iter_mut() {
+                                Self::replace_self_type(&mut param.type_annotation, &struct_name);
+                            }
+                            if let Some(return_type) = &mut function.return_type {
+                                Self::replace_self_type(return_type, &struct_name);
                             }
 
-                            let function = method.unwrap().clone();
-                            module.functions.push(StoredFunction::Function {
-                                function: function.clone(),
-                                defining_module: module.id().clone(),
-                            });
-
-                            let typ =
-                                self.validate_and_get_type_expr(expr.as_ref(), module, ctx, None)?;
-
-                            module.functions.remove(
-                                module.functions.iter().position(|f| {
-                                    matches!(f, StoredFunction::Function { function, .. } if function.name == method_name)
-                                }).unwrap()
-                            );
-
-                            Ok(typ)
+                            let stored_function = StoredFunction::Function {
+                                function,
+                                defining_module: defining_module.id(),
+                            };
+                            self.validate_call(call, stored_function, module, ctx, global_type)
                         }
                         _ => Err(StaticContext(expr.span()).into()),
                     }
                 }
             },
+            Expr::Interpolated(interpolated) => {
+                for segment in interpolated.segments.iter() {
+                    let Segment::Expr(expr) = segment else {
+                        continue;
+                    };
+
+                    let typ = self.validate_and_get_type_expr(expr, module, ctx, None)?;
+                    if matches!(typ, ResolvedType::Void) {
+                        return Err(TypeMismatch(
+                            "Cannot interpolate a value of type void".to_string(),
+                            expr.span(),
+                        )
+                        .into());
+                    }
+                }
+
+                Ok(ResolvedType::String)
+            }
             _ => Ok(ResolvedType::Null),
         }
     }
@@ -947,7 +1330,7 @@ impl TypePass {
         ctx: &mut Context,
     ) -> Result<()> {
         match stmt.clone() {
-            Stmt::Return(ret) if ret.expr.is_some() => {}
+            Stmt::Return(ret) => self.validate_return(&ret, module, ctx)?,
             Stmt::Block(block) => {
                 self.validate_block(&block.stmts, module, ctx)?;
             }
@@ -970,6 +1353,97 @@ impl TypePass {
             Stmt::Loop(loop_stmt) => {
                 self.validate_block(&loop_stmt.block.stmts, module, ctx)?;
             }
+            Stmt::For(for_stmt) => {
+                let iterable =
+                    self.validate_and_get_type_expr(&for_stmt.iterable, module, ctx, None)?;
+
+                let element = match iterable.clone() {
+                    ResolvedType::Vector(element) => *element,
+                    ResolvedType::String => ResolvedType::Char,
+                    // Objects yield `[key, value]` pairs
+                    ResolvedType::Object(_) => ResolvedType::Vector(Box::new(ResolvedType::Any)),
+                    ResolvedType::Any => ResolvedType::Any,
+                    typ => {
+                        return Err(TypeMismatch(
+                            format!("Cannot iterate over a value of type {}", typ),
+                            for_stmt.iterable.span(),
+                        )
+                        .into())
+                    }
+                };
+
+                let bindings = match &for_stmt.value_ident {
+                    // Objects are destructured into their keys and values, other pairs into
+                    // their two elements
+                    Some(value_ident) => {
+                        let (key, value) = match (iterable, element) {
+                            (ResolvedType::Object(value), _) => (ResolvedType::String, *value),
+                            (_, ResolvedType::Vector(element)) => (*element.clone(), *element),
+                            (_, ResolvedType::Any) => (ResolvedType::Any, ResolvedType::Any),
+                            (_, typ) => {
+                                return Err(TypeMismatch(
+                                    format!("Cannot destructure {} into a key and a value", typ),
+                                    for_stmt.iterable.span(),
+                                )
+                                .into())
+                            }
+                        };
+
+                        vec![(&for_stmt.ident, key), (value_ident, value)]
+                    }
+                    None => vec![(&for_stmt.ident, element)],
+                };
+
+                self.enter_scope();
+                for (ident, typ) in bindings {
+                    self.declare_variable(ident.literal(), typ);
+                }
+                let result = self.validate_block(&for_stmt.block.stmts, module, ctx);
+                self.exit_scope();
+
+                result?;
+            }
+            Stmt::Throw(throw) => {
+                // The type of a rethrown error isn't tracked
+                let typ = match &throw.value {
+                    Some(value) => self.validate_and_get_type_expr(value, module, ctx, None)?,
+                    None => ResolvedType::Any,
+                };
+
+                if let Some(thrown) = self.thrown.last_mut() {
+                    thrown.push(typ);
+                }
+            }
+            Stmt::Try(try_stmt) => {
+                self.thrown.push(vec![]);
+                let result = self.validate_block(&try_stmt.try_block.stmts, module, ctx);
+                let thrown = self.thrown.pop().unwrap_or_default();
+                result?;
+
+                // A call may throw a value of any type
+                let mut calls = CallFinder::default();
+                for stmt in try_stmt.try_block.stmts.iter() {
+                    walk_stmt(&mut calls, stmt);
+                }
+
+                let error_type = match thrown.split_first() {
+                    Some((first, rest)) if !calls.found && rest.iter().all(|t| t == first) => {
+                        first.clone()
+                    }
+                    _ => ResolvedType::Any,
+                };
+
+                self.enter_scope();
+                self.declare_variable(try_stmt.error_ident.literal(), error_type);
+                let result = self.validate_block(&try_stmt.catch_block.stmts, module, ctx);
+                self.exit_scope();
+
+                result?;
+
+                if let Some(finally_block) = &try_stmt.finally_block {
+                    self.validate_block(&finally_block.stmts, module, ctx)?;
+                }
+            }
             // We just validate all types of expressions
             Stmt::Expr(expr) => {
                 self.validate_and_get_type_expr(expr.as_ref(), module, ctx, None)?;
@@ -981,18 +1455,13 @@ impl TypePass {
                     self.check_type_annotation(&mut typ, module, ctx)?;
 
                     let mut expr_type = self
-                        .validate_and_get_type_expr(
-                            let_stmt.initializer.as_ref(),
-                            module,
-                            ctx,
-                            Some(typ),
-                        )?
+                        .validate_value_expr(let_stmt.initializer.as_ref(), module, ctx, Some(typ))?
                         .to_type_annotation();
 
                     self.check_type_annotation(&mut expr_type, module, ctx)?;
                 } else {
                     let typ = self
-                        .validate_and_get_type_expr(
+                        .validate_value_expr(
                             let_stmt.initializer.as_ref(),
                             module,
                             ctx,
@@ -1006,20 +1475,21 @@ impl TypePass {
                     let_stmt.type_annotation = Some(typ_clone);
                 }
 
-                self.declare_variable(
-                    let_stmt.ident.literal().clone(),
-                    ResolvedType::from_type_annotation(let_stmt.type_annotation.as_ref().unwrap()),
-                );
+                let typ =
+                    ResolvedType::from_type_annotation(let_stmt.type_annotation.as_ref().unwrap());
+                self.variable_types
+                    .push((let_stmt.ident.span.clone(), typ.clone()));
+                self.declare_variable(let_stmt.ident.literal().clone(), typ);
             }
             Stmt::Fn(mut func) => {
                 self.enter_scope();
                 for param in func.params.iter_mut() {
                     self.check_type_annotation(&mut param.type_annotation, module, ctx)?;
 
-                    self.declare_variable(
-                        param.ident.clone().literal(),
-                        ResolvedType::from_type_annotation(&param.type_annotation),
-                    );
+                    let typ = ResolvedType::from_type_annotation(&param.type_annotation);
+                    self.variable_types
+                        .push((param.ident.span.clone(), typ.clone()));
+                    self.declare_variable(param.ident.clone().literal(), typ);
                 }
                 self.validate_function(&mut func, module, ctx)?;
                 self.exit_scope()
@@ -1045,3 +1515,773 @@ impl TypePass {
         Ok(())
     }
 }
+
+/// Returns `true` if `func` has a `return` statement with a value.
+fn returns_value(func: &roan_ast::Fn) -> bool {
+    let mut returns = ReturnFinder::default();
+    for stmt in func.body.stmts.iter() {
+        walk_stmt(&mut returns, stmt);
+    }
+
+    returns.found
+}
+
+/// Returns `true` if every path through `stmts` ends with a `return` or a `throw`, so running
+/// them never continues with the statement after them.
+fn always_returns(stmts: &[Stmt]) -> bool {
+    stmts.iter().any(stmt_always_returns)
+}
+
+fn stmt_always_returns(stmt: &Stmt) -> bool {
+    match stmt {
+        Stmt::Return(_) | Stmt::Throw(_) => true,
+        Stmt::Block(block) => always_returns(&block.stmts),
+        // Without an `else`, no branch runs when none of the conditions hold
+        Stmt::If(if_stmt) => {
+            always_returns(&if_stmt.then_block.stmts)
+                && if_stmt
+                    .else_ifs
+                    .iter()
+                    .all(|else_if| always_returns(&else_if.block.stmts))
+                && if_stmt
+                    .else_block
+                    .as_ref()
+                    .is_some_and(|else_block| always_returns(&else_block.block.stmts))
+        }
+        Stmt::Try(try_stmt) => {
+            (always_returns(&try_stmt.try_block.stmts)
+                && always_returns(&try_stmt.catch_block.stmts))
+                || try_stmt
+                    .finally_block
+                    .as_ref()
+                    .is_some_and(|finally| always_returns(&finally.stmts))
+        }
+        // A loop is only left with a `break`, or by returning
+        Stmt::Loop(loop_stmt) => !breaks(&loop_stmt.block.stmts),
+        // The body of other loops may not run at all, unless the condition is always true
+        Stmt::While(while_stmt) => {
+            matches!(
+                while_stmt.condition.as_ref(),
+                Expr::Literal(Literal {
+                    value: LiteralType::Bool(true),
+                    ..
+                })
+            ) && !breaks(&while_stmt.block.stmts)
+        }
+        _ => false,
+    }
+}
+
+/// Returns `true` if `stmts`, the body of a loop, contain a `break` leaving that loop. A `break`
+/// in a nested loop leaves the nested loop instead.
+fn breaks(stmts: &[Stmt]) -> bool {
+    stmts.iter().any(|stmt| match stmt {
+        Stmt::Break(_) => true,
+        Stmt::Block(block) => breaks(&block.stmts),
+        Stmt::If(if_stmt) => {
+            breaks(&if_stmt.then_block.stmts)
+                || if_stmt
+                    .else_ifs
+                    .iter()
+                    .chain(&if_stmt.else_block)
+                    .any(|else_block| breaks(&else_block.block.stmts))
+        }
+        Stmt::Try(try_stmt) => {
+            breaks(&try_stmt.try_block.stmts)
+                || breaks(&try_stmt.catch_block.stmts)
+                || try_stmt
+                    .finally_block
+                    .as_ref()
+                    .is_some_and(|finally| breaks(&finally.stmts))
+        }
+        _ => false,
+    })
+}
+
+/// Finds out whether statements contain a `return` with a value.
+#[derive(Default)]
+struct ReturnFinder {
+    found: bool,
+}
+
+impl Visitor for ReturnFinder {
+    fn visit_stmt(&mut self, stmt: &Stmt) {
+        if let Stmt::Return(return_stmt) = stmt {
+            self.found |= return_stmt.expr.is_some();
+        }
+    }
+}
+
+/// Finds out whether statements contain a call.
+#[derive(Default)]
+struct CallFinder {
+    found: bool,
+}
+
+impl Visitor for CallFinder {
+    fn visit_expr(&mut self, expr: &Expr) {
+        if let Expr::Call(_) = expr {
+            self.found = true;
+        }
+    }
+}
+
+/// Returns the number of single character insertions, deletions, substitutions and swaps of
+/// adjacent characters needed to turn `a` into `b`.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut distances = vec![vec![0; b.len() + 1]; a.len() + 1];
+
+    for (i, row) in distances.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, distance) in distances[0].iter_mut().enumerate() {
+        *distance = j;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut distance = (distances[i - 1][j] + 1)
+                .min(distances[i][j - 1] + 1)
+                .min(distances[i - 1][j - 1] + cost);
+
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(distances[i - 2][j - 2] + 1);
+            }
+            distances[i][j] = distance;
+        }
+    }
+
+    distances[a.len()][b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        interpreter::passes::{imports::ImportPass, resolver::ResolverPass},
+        module::loaders::ModuleLoader,
+    };
+    use roan_ast::{source::Source, Lexer, Parser};
+    use std::{cell::RefCell, rc::Rc};
+
+    #[derive(Debug)]
+    struct NoopLoader;
+
+    impl ModuleLoader for NoopLoader {
+        fn load(&mut self, _: &Module, spec: &str, _: &Context) -> Result<Module> {
+            anyhow::bail!("Cannot load {}", spec)
+        }
+    }
+
+    /// Loader that can't load anything, but caches the modules inserted into it.
+    #[derive(Debug, Default)]
+    struct CachingLoader {
+        modules: HashMap<String, Module>,
+    }
+
+    impl ModuleLoader for CachingLoader {
+        fn load(&mut self, _: &Module, spec: &str, _: &Context) -> Result<Module> {
+            anyhow::bail!("Cannot load {}", spec)
+        }
+
+        fn insert(&mut self, name: String, module: Module) {
+            self.modules.insert(name, module);
+        }
+
+        fn get(&self, name: &str) -> Option<Module> {
+            self.modules.get(name).cloned()
+        }
+    }
+
+    fn setup(src: &str) -> (Module, Context, VM) {
+        setup_with_loader(src, NoopLoader)
+    }
+
+    fn setup_with_loader(src: &str, loader: impl ModuleLoader + 'static) -> (Module, Context, VM) {
+        let ctx = Context::builder()
+            .module_loader(Rc::new(RefCell::new(loader)))
+            .build();
+        let mut module = Module::new(Source::from_string(src.to_string()));
+        module.tokens = Lexer::new(module.source().clone()).lex(false).unwrap();
+        module.ast = Parser::new(module.tokens.clone()).parse().unwrap();
+
+        (module, ctx, VM::new())
+    }
+
+    #[test]
+    fn test_collects_independent_type_errors() {
+        let (mut module, mut ctx, mut vm) = setup("let a = 1 + \"a\";\nlet b = true + 1;\n");
+
+        let mut diagnostics = vec![];
+        let result = TypePass::new().run(&mut module, &mut ctx, &mut vm, &mut diagnostics);
+
+        assert!(result.is_ok());
+        assert_eq!(diagnostics.len(), 2);
+        assert!(diagnostics[0].title.contains("Invalid binary operation"));
+        assert_eq!(diagnostics[0].location.as_ref().unwrap().start.line, 1);
+        assert!(diagnostics[1].title.contains("Invalid binary operation"));
+        assert_eq!(diagnostics[1].location.as_ref().unwrap().start.line, 2);
+    }
+
+    #[test]
+    fn test_argument_mismatch_points_to_parameter() {
+        let (mut module, mut ctx, mut vm) =
+            setup("fn add(a: int, b: int) -> int { return a + b; }\nadd(1, \"2\");\n");
+
+        let mut diagnostics = vec![];
+        let result = ResolverPass.run(&mut module, &mut ctx, &mut vm, &mut diagnostics);
+        assert!(result.is_ok() && diagnostics.is_empty());
+        let mut diagnostics = vec![];
+        let result = TypePass::new().run(&mut module, &mut ctx, &mut vm, &mut diagnostics);
+
+        assert!(result.is_ok());
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].code, Some(19));
+        assert_eq!(diagnostics[0].location.as_ref().unwrap().start.line, 2);
+
+        let (declaration, _) = &diagnostics[0].secondary_spans[0];
+        assert_eq!(declaration.start.line, 1);
+        assert_eq!(declaration.literal, "b");
+    }
+
+    #[test]
+    fn test_too_many_arguments_to_native() {
+        let (mut module, mut ctx, mut vm) = setup("__exit(1, 2);\n");
+
+        let mut diagnostics = vec![];
+        let result = TypePass::new().run(&mut module, &mut ctx, &mut vm, &mut diagnostics);
+
+        assert!(result.is_ok());
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].code, Some(41));
+    }
+
+    #[test]
+    fn test_too_many_arguments_to_function() {
+        let (mut module, mut ctx, mut vm) =
+            setup("fn f(a: int, ...rest: int) {}\nfn g(a: int) {}\nf(1, 2, 3);\ng(1, 2);\n");
+
+        let mut diagnostics = vec![];
+        let result = ResolverPass.run(&mut module, &mut ctx, &mut vm, &mut diagnostics);
+        assert!(result.is_ok() && diagnostics.is_empty());
+        let result = TypePass::new().run(&mut module, &mut ctx, &mut vm, &mut diagnostics);
+
+        assert!(result.is_ok());
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].code, Some(41));
+        assert_eq!(diagnostics[0].location.as_ref().unwrap().start.line, 4);
+    }
+
+    #[test]
+    fn test_module_parse_reports_all_errors() {
+        let (mut module, mut ctx, mut vm) = setup("let a = 1 + \"a\";\nlet b = true + 1;\n");
+
+        let err = module.parse(&mut ctx, &mut vm).unwrap_err();
+
+        assert_eq!(err.to_string(), "Aborting due to 2 previous error(s)");
+    }
+
+    #[test]
+    fn test_missing_defining_module_is_reported() {
+        let (mut module, mut ctx, mut vm) = setup(
+            "struct Point { x: int }\nimpl Point { fn new() -> Point { return Point { x: 1 }; } }\nlet p = Point::new();\n",
+        );
+
+        // The module is never inserted into the loader, so the struct's defining module can't be found.
+        let mut diagnostics = vec![];
+        let result = ResolverPass.run(&mut module, &mut ctx, &mut vm, &mut diagnostics);
+        assert!(result.is_ok() && diagnostics.is_empty());
+        let mut diagnostics = vec![];
+        let result = TypePass::new().run(&mut module, &mut ctx, &mut vm, &mut diagnostics);
+
+        assert!(result.is_ok());
+        assert_eq!(diagnostics.len(), 1);
+        assert!(diagnostics[0].title.starts_with("Module not found"));
+        assert_eq!(diagnostics[0].code, Some(44));
+    }
+
+    #[test]
+    fn test_self_return_type_allows_chaining() {
+        let (mut module, mut ctx, mut vm) = setup_with_loader(
+            "struct Point { x: int, y: int }\n\
+             impl Point {\n\
+                 fn new() -> Self { return Point { x: 0, y: 0 }; }\n\
+                 fn with_x(self, x: int) -> Self { self.x = x; return self; }\n\
+                 fn with_y(self, y: int) -> Self { self.y = y; return self; }\n\
+             }\n\
+             let origin = Point::new();\n\
+             let p = origin.with_x(1).with_y(2);\n",
+            CachingLoader::default(),
+        );
+
+        let mut diagnostics = vec![];
+        let result = ResolverPass.run(&mut module, &mut ctx, &mut vm, &mut diagnostics);
+        assert!(result.is_ok() && diagnostics.is_empty());
+
+        let mut pass = TypePass::new();
+        let mut diagnostics = vec![];
+        let result = pass.run(&mut module, &mut ctx, &mut vm, &mut diagnostics);
+
+        assert!(result.is_ok());
+        assert!(diagnostics.is_empty(), "{:?}", diagnostics);
+        assert_eq!(
+            pass.find_variable("p"),
+            Some(&ResolvedType::Struct("Point".to_string(), module.id()))
+        );
+    }
+
+    #[test]
+    fn test_static_method_of_imported_struct() {
+        let (mut module, mut ctx, mut vm) = setup_with_loader(
+            "use { Person } from \"std:people\";\n\
+             fn new() -> int { return 1; }\n\
+             let p = Person::new(\"roan\");\n\
+             let greeting = p.greet();\n\
+             Person::new(1);\n",
+            CachingLoader::default(),
+        );
+        ctx.register_virtual_module(
+            "std:people",
+            Source::from_string(
+                "pub struct Person { name: string }\n\
+                 impl Person {\n\
+                     fn new(name: string) -> Self { return Person { name: name }; }\n\
+                     fn greet(self) -> string { return \"Hi \" + self.name; }\n\
+                 }\n"
+                .to_string(),
+            ),
+        )
+        .unwrap();
+
+        let mut diagnostics = vec![];
+        for mut pass in [
+            Box::new(ImportPass::default()) as Box<dyn Pass>,
+            Box::new(ResolverPass),
+        ] {
+            assert!(pass
+                .run(&mut module, &mut ctx, &mut vm, &mut diagnostics)
+                .is_ok());
+        }
+        assert!(diagnostics.is_empty(), "{:?}", diagnostics);
+
+        let mut pass = TypePass::new();
+        let result = pass.run(&mut module, &mut ctx, &mut vm, &mut diagnostics);
+
+        assert!(result.is_ok());
+        assert_eq!(diagnostics.len(), 1, "{:?}", diagnostics);
+        assert_eq!(diagnostics[0].code, Some(19));
+        assert_eq!(diagnostics[0].location.as_ref().unwrap().start.line, 5);
+        assert_eq!(
+            pass.find_variable("p"),
+            Some(&ResolvedType::Struct("Person".to_string(), module.id()))
+        );
+        assert_eq!(pass.find_variable("greeting"), Some(&ResolvedType::String));
+    }
+
+    #[test]
+    fn test_struct_typed_parameter() {
+        let (mut module, mut ctx, mut vm) = setup_with_loader(
+            "struct Person { name: string }\n\
+             fn greet(p: Person) -> string { return \"Hello \" + p.name; }\n",
+            CachingLoader::default(),
+        );
+
+        let mut diagnostics = vec![];
+        let result = ResolverPass.run(&mut module, &mut ctx, &mut vm, &mut diagnostics);
+        assert!(result.is_ok() && diagnostics.is_empty());
+        let mut diagnostics = vec![];
+        let result = TypePass::new().run(&mut module, &mut ctx, &mut vm, &mut diagnostics);
+
+        assert!(result.is_ok());
+        assert!(diagnostics.is_empty(), "{:?}", diagnostics);
+    }
+
+    #[test]
+    fn test_unknown_type_name() {
+        let (mut module, mut ctx, mut vm) = setup(
+            "struct Person { name: string }\n\
+             fn greet(p: Persn) -> string { return \"Hello\"; }\n",
+        );
+
+        let mut diagnostics = vec![];
+        let result = ResolverPass.run(&mut module, &mut ctx, &mut vm, &mut diagnostics);
+        assert!(result.is_ok() && diagnostics.is_empty());
+        let mut diagnostics = vec![];
+        let result = TypePass::new().run(&mut module, &mut ctx, &mut vm, &mut diagnostics);
+
+        assert!(result.is_ok());
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].code, Some(39));
+        assert!(diagnostics[0]
+            .title
+            .starts_with("Invalid type provided: Persn."));
+        assert!(diagnostics[0].title.ends_with("void, Person"));
+        assert_eq!(diagnostics[0].location.as_ref().unwrap().start.line, 2);
+    }
+
+    #[test]
+    fn test_nested_vector_annotation() {
+        let (mut module, mut ctx, mut vm) = setup(
+            "let a: vec<vec<int>> = [[1, 2], [3]];\n\
+             let b: int[][] = [[1], [\"a\"]];\n\
+             let c: object<int[]> = { \"x\": [1], \"y\": [2, 3] };\n",
+        );
+
+        let mut diagnostics = vec![];
+        let result = TypePass::new().run(&mut module, &mut ctx, &mut vm, &mut diagnostics);
+
+        assert!(result.is_ok());
+        assert_eq!(diagnostics.len(), 1, "{:?}", diagnostics);
+        assert_eq!(diagnostics[0].location.as_ref().unwrap().start.line, 2);
+    }
+
+    #[test]
+    fn test_interpolation_types() {
+        let (mut module, mut ctx, mut vm) = setup(
+            "fn nothing() {}\n\
+             let a: string = f\"{1} {true} {[1.5]}\";\n\
+             let b = f\"{nothing()}\";\n\
+             let c = f\"{a} {f\"{1}\"}\";\n",
+        );
+
+        let mut diagnostics = vec![];
+        let result = ResolverPass.run(&mut module, &mut ctx, &mut vm, &mut diagnostics);
+        assert!(result.is_ok() && diagnostics.is_empty());
+        let result = TypePass::new().run(&mut module, &mut ctx, &mut vm, &mut diagnostics);
+
+        assert!(result.is_ok());
+        assert_eq!(diagnostics.len(), 1, "{:?}", diagnostics);
+        assert!(diagnostics[0].title.contains("type void"));
+        assert_eq!(diagnostics[0].location.as_ref().unwrap().literal, "nothing");
+    }
+
+    #[test]
+    fn test_variable_types() {
+        let (mut module, mut ctx, mut vm) =
+            setup("fn f(name: string) { let n = 1.5; n; }\nlet items = [1];\nitems;\n");
+
+        let mut types = TypePass::new();
+        types
+            .run(&mut module, &mut ctx, &mut vm, &mut vec![])
+            .unwrap();
+
+        assert_eq!(types.variable_type_at(1, 5), Some(&ResolvedType::String));
+        assert_eq!(types.variable_type_at(1, 25), Some(&ResolvedType::Float));
+        assert_eq!(types.variable_type_at(1, 34), Some(&ResolvedType::Float));
+        let items = ResolvedType::Vector(Box::new(ResolvedType::Int));
+        assert_eq!(types.variable_type_at(2, 4), Some(&items));
+        assert_eq!(types.variable_type_at(3, 2), Some(&items));
+        assert_eq!(types.variable_type_at(3, 5), None);
+    }
+
+    #[test]
+    fn test_for_loop_element_type() {
+        let (mut module, mut ctx, mut vm) = setup(
+            "for c in \"ab\" { c.is_alphabetic(); }\n\
+             for x in [1, 2] { x + true; }\n\
+             for n in 5 {}\n",
+        );
+
+        let mut diagnostics = vec![];
+        let result = TypePass::new().run(&mut module, &mut ctx, &mut vm, &mut diagnostics);
+
+        assert!(result.is_ok());
+        assert_eq!(diagnostics.len(), 2, "{:?}", diagnostics);
+        assert_eq!(diagnostics[0].location.as_ref().unwrap().start.line, 2);
+        assert!(diagnostics[1]
+            .title
+            .ends_with("Cannot iterate over a value of type int"));
+    }
+
+    #[test]
+    fn test_for_loop_destructuring_types() {
+        let (mut module, mut ctx, mut vm) = setup(
+            "fn int_only(i: int) {}\n\
+             for k, v in {\"a\": 1} { int_only(v); }\n\
+             for k, v in {\"a\": 1} { int_only(k); }\n\
+             for a, b in [[1, 2]] { int_only(a); int_only(b); }\n\
+             for a, b in [1, 2] {}\n",
+        );
+
+        let mut diagnostics = vec![];
+        ResolverPass
+            .run(&mut module, &mut ctx, &mut vm, &mut diagnostics)
+            .unwrap();
+        let result = TypePass::new().run(&mut module, &mut ctx, &mut vm, &mut diagnostics);
+
+        assert!(result.is_ok());
+        assert_eq!(diagnostics.len(), 2, "{:?}", diagnostics);
+        assert_eq!(diagnostics[0].location.as_ref().unwrap().start.line, 3);
+        assert!(diagnostics[1]
+            .title
+            .ends_with("Cannot destructure int into a key and a value"));
+    }
+
+    #[test]
+    fn test_caught_error_type() {
+        let (mut module, mut ctx, mut vm) = setup(
+            "try { throw { \"code\": 42 }; } catch e { e[\"code\"] + 1; e + true; }\n\
+             try { let x = 1; throw x; } catch e { e + true; }\n\
+             try { __pid(); throw 1; } catch e { e + true; }\n\
+             try { throw 1; throw \"a\"; } catch e { e + true; }\n",
+        );
+
+        let mut diagnostics = vec![];
+        let result = TypePass::new().run(&mut module, &mut ctx, &mut vm, &mut diagnostics);
+
+        assert!(result.is_ok());
+        let lines: Vec<u32> = diagnostics
+            .iter()
+            .map(|d| d.location.as_ref().unwrap().start.line)
+            .collect();
+        assert_eq!(lines, [1, 2], "{:?}", diagnostics);
+    }
+
+    #[test]
+    fn test_return_types() {
+        let (mut module, mut ctx, mut vm) = setup(
+            "fn a() -> int { return \"x\"; }\n\
+             fn b() -> int { let x = 1; }\n\
+             fn c() -> int { return; }\n\
+             fn d(x: int) -> int? { if x > 1 { return null; } return x; }\n\
+             fn e() -> float { return 1; }\n\
+             fn f() -> void { return; }\n\
+             fn g() { return 1 + true; }\n\
+             fn h() { return 1; }\n\
+             fn i() -> void { return 1; }\n",
+        );
+
+        let mut diagnostics = vec![];
+        let result = TypePass::new().run(&mut module, &mut ctx, &mut vm, &mut diagnostics);
+
+        assert!(result.is_ok());
+        let lines: Vec<u32> = diagnostics
+            .iter()
+            .map(|d| d.location.as_ref().unwrap().start.line)
+            .collect();
+        assert_eq!(lines, [1, 2, 3, 7, 9], "{:?}", diagnostics);
+        assert_eq!(diagnostics[0].code, Some(19));
+        assert!(diagnostics[0]
+            .title
+            .contains("Expected int to be returned but got string"));
+        assert_eq!(diagnostics[0].secondary_spans[0].0.literal, "int");
+        assert_eq!(diagnostics[1].code, Some(57));
+    }
+
+    #[test]
+    fn test_missing_return() {
+        let (mut module, mut ctx, mut vm) = setup(
+            "fn a(c: bool) -> int { if c { return 1; } }\n\
+             fn b(c: bool) -> int { if c { return 1; } else { return 2; } }\n\
+             fn c(x: int) -> int { if x > 0 { return 1; } else if x < 0 { return -1; } }\n\
+             fn d(x: int) -> int { if x > 0 { return 1; } else if x < 0 { return -1; } else { throw \"zero\"; } }\n\
+             fn e() -> int { loop { return 1; } }\n\
+             fn f() -> int { loop { if true { break; } } }\n\
+             fn g() -> int { loop { for i in [1] { break; } return 1; } }\n\
+             fn h(c: bool) -> int { while c { return 1; } }\n\
+             fn i() -> int { while true { return 1; } }\n\
+             fn j() -> int { try { return 1; } catch e { return 2; } }\n\
+             fn k() -> int { { return 1; } }\n\
+             fn l(c: bool) { if c { return; } }\n",
+        );
+
+        let mut diagnostics = vec![];
+        let result = TypePass::new().run(&mut module, &mut ctx, &mut vm, &mut diagnostics);
+
+        assert!(result.is_ok());
+        let lines: Vec<u32> = diagnostics
+            .iter()
+            .map(|d| d.location.as_ref().unwrap().start.line)
+            .collect();
+        assert_eq!(lines, [1, 3, 6, 8], "{:?}", diagnostics);
+        assert!(diagnostics.iter().all(|d| d.code == Some(57)));
+        assert_eq!(diagnostics[0].location.as_ref().unwrap().literal, "int");
+    }
+
+    #[test]
+    fn test_modulo_and_power_assign() {
+        let (mut module, mut ctx, mut vm) = setup(
+            "let x = 7;
+x %= 3;
+x **= 2;
+let y = 2.5;
+y %= 2;
+y **= 2.0;
+             let s = \"a\";
+s %= 2;
+s **= 2;
+",
+        );
+
+        let mut diagnostics = vec![];
+        let result = TypePass::new().run(&mut module, &mut ctx, &mut vm, &mut diagnostics);
+
+        assert!(result.is_ok());
+        assert_eq!(diagnostics.len(), 2, "{:?}", diagnostics);
+        assert!(diagnostics[0].title.contains("Invalid"));
+        assert_eq!(diagnostics[0].location.as_ref().unwrap().start.line, 8);
+        assert_eq!(diagnostics[1].location.as_ref().unwrap().start.line, 9);
+    }
+
+    #[test]
+    fn test_bitwise_not() {
+        let (mut module, mut ctx, mut vm) = setup("let a = ~42;\nlet b = ~3.14;\n");
+
+        let mut diagnostics = vec![];
+        let result = TypePass::new().run(&mut module, &mut ctx, &mut vm, &mut diagnostics);
+
+        assert!(result.is_ok());
+        assert_eq!(diagnostics.len(), 1, "{:?}", diagnostics);
+        assert_eq!(
+            diagnostics[0].title,
+            "Type mismatch: Unary operator ~ can only be applied to int"
+        );
+        assert_eq!(diagnostics[0].location.as_ref().unwrap().start.line, 2);
+
+        let Stmt::Let(let_stmt) = module.ast.stmts[0].clone() else {
+            panic!("Expected a let statement");
+        };
+        module
+            .interpret_expr(&let_stmt.initializer, &mut ctx, &mut vm)
+            .unwrap();
+        assert_eq!(vm.pop(), Some(Value::Int(-43)));
+    }
+
+    #[test]
+    fn test_number_suffixes() {
+        let (mut module, mut ctx, mut vm) = setup("let a: float = 5f / 2;\nlet b: int = 10i;\n");
+
+        let mut diagnostics = vec![];
+        let result = TypePass::new().run(&mut module, &mut ctx, &mut vm, &mut diagnostics);
+
+        assert!(result.is_ok());
+        assert!(diagnostics.is_empty(), "{:?}", diagnostics);
+
+        let Stmt::Let(let_stmt) = module.ast.stmts[0].clone() else {
+            panic!("Expected a let statement");
+        };
+        module
+            .interpret_expr(&let_stmt.initializer, &mut ctx, &mut vm)
+            .unwrap();
+        assert_eq!(vm.pop(), Some(Value::Float(2.5)));
+    }
+
+    #[test]
+    fn test_bitwise_operator_types() {
+        let (mut module, mut ctx, mut vm) =
+            setup("let a = 6 & 3;\nlet b = 1.5 | 2;\nlet c = 1 << true;\nlet d = 1 < \"a\";\n");
+
+        let mut diagnostics = vec![];
+        let result = TypePass::new().run(&mut module, &mut ctx, &mut vm, &mut diagnostics);
+
+        assert!(result.is_ok());
+        assert_eq!(diagnostics.len(), 3, "{:?}", diagnostics);
+        assert_eq!(
+            diagnostics[0].title,
+            "Type mismatch: bitwise operators require int operands, got float and int"
+        );
+        assert_eq!(diagnostics[0].location.as_ref().unwrap().start.line, 2);
+        assert_eq!(
+            diagnostics[1].title,
+            "Type mismatch: bitwise operators require int operands, got int and bool"
+        );
+        assert_eq!(
+            diagnostics[2].title,
+            "Type mismatch: Invalid comparison operation between int and string"
+        );
+    }
+
+    #[test]
+    fn test_void_result_used_as_value() {
+        let (mut module, mut ctx, mut vm) = setup(
+            "fn log(msg: string) { __print(msg); }\n\
+             fn one() { return 1; }\n\
+             fn nothing() -> void {}\n\
+             log(\"a\");\n\
+             let a = log(\"b\");\n\
+             let b = one();\n\
+             __print(__format(nothing()));\n\
+             let c = null;\n\
+             let d = 1; d = log(\"x\");\n",
+        );
+        ResolverPass
+            .run(&mut module, &mut ctx, &mut vm, &mut vec![])
+            .unwrap();
+
+        let mut diagnostics = vec![];
+        let result = TypePass::new().run(&mut module, &mut ctx, &mut vm, &mut diagnostics);
+
+        assert!(result.is_ok());
+        assert_eq!(diagnostics.len(), 3, "{:?}", diagnostics);
+        assert_eq!(
+            diagnostics[0].title,
+            "log doesn't return a value, its result can't be used"
+        );
+        let lines: Vec<u32> = diagnostics
+            .iter()
+            .map(|d| d.location.as_ref().unwrap().start.line)
+            .collect();
+        assert_eq!(lines, [5, 7, 9]);
+    }
+
+    #[test]
+    fn test_argument_mismatch_suggests_cast() {
+        let (mut module, mut ctx, mut vm) =
+            setup("fn greet(name: string) {}\nlet age = 1;\ngreet(age + 1);\n");
+
+        let mut diagnostics = vec![];
+        let result = ResolverPass.run(&mut module, &mut ctx, &mut vm, &mut diagnostics);
+        assert!(result.is_ok() && diagnostics.is_empty());
+        let mut diagnostics = vec![];
+        let result = TypePass::new().run(&mut module, &mut ctx, &mut vm, &mut diagnostics);
+
+        assert!(result.is_ok());
+        assert_eq!(diagnostics.len(), 1, "{:?}", diagnostics);
+        let (span, replacement) = diagnostics[0].fix.as_ref().unwrap();
+        assert_eq!(replacement, "__format(age + 1)");
+        assert_eq!((span.start.line, span.start.column), (3, 6));
+    }
+
+    #[test]
+    fn test_unknown_variable_suggests_similar_name() {
+        let (mut module, mut ctx, mut vm) =
+            setup("let count = 1;\nlet total = cuont + 1;\nlet other = xyz;\n");
+
+        let mut diagnostics = vec![];
+        let result = TypePass::new().run(&mut module, &mut ctx, &mut vm, &mut diagnostics);
+
+        assert!(result.is_ok());
+        assert_eq!(diagnostics.len(), 2, "{:?}", diagnostics);
+        let (span, replacement) = diagnostics[0].fix.as_ref().unwrap();
+        assert_eq!(replacement, "count");
+        assert_eq!(span.literal, "cuont");
+        assert!(diagnostics[1].fix.is_none());
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("count", "count"), 0);
+        assert_eq!(edit_distance("cuont", "count"), 1);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("coun", "count"), 1);
+        assert_eq!(edit_distance("", "abc"), 3);
+    }
+
+    #[test]
+    fn test_disabled_type_pass_is_skipped() {
+        let src = "fn f(a: int) {}\nf(\"a\");\n";
+        let mut ctx = Context::builder()
+            .module_loader(Rc::new(RefCell::new(NoopLoader)))
+            .build();
+
+        let mut module = Module::new(Source::from_string(src.to_string()));
+        assert!(module.parse(&mut ctx, &mut VM::new()).is_err());
+
+        ctx.disable_pass(TypePass::new().name());
+        let mut module = Module::new(Source::from_string(src.to_string()));
+        assert!(module.parse(&mut ctx, &mut VM::new()).is_ok());
+    }
+}
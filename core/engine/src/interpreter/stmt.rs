@@ -1,7 +1,10 @@
 use crate::{context::Context, module::Module, value::Value, vm::VM};
 use anyhow::Result;
-use roan_ast::{Block, GetSpan, Let, Loop, Stmt, While};
-use roan_error::error::{RoanError, RoanError::NonBooleanCondition};
+use roan_ast::{Block, For, GetSpan, Let, Loop, Stmt, While};
+use roan_error::error::{
+    RoanError,
+    RoanError::{NonBooleanCondition, TypeMismatch},
+};
 use tracing::debug;
 
 impl Module {
@@ -10,19 +13,20 @@ impl Module {
     /// # Arguments
     /// * `stmt` - [`Stmt`] - The statement to interpret.
     /// * `ctx` - [`Context`] - The context in which to interpret the statement.
-    pub fn interpret_stmt(&mut self, stmt: Stmt, ctx: &mut Context, vm: &mut VM) -> Result<()> {
+    pub fn interpret_stmt(&mut self, stmt: &Stmt, ctx: &mut Context, vm: &mut VM) -> Result<()> {
         match stmt {
             Stmt::While(while_stmt) => self.interpret_while(while_stmt, ctx, vm)?,
+            Stmt::For(for_stmt) => self.interpret_for(for_stmt, ctx, vm)?,
             Stmt::Loop(loop_stmt) => self.interpret_loop(loop_stmt, ctx, vm)?,
             Stmt::Block(block) => self.execute_block(block, ctx, vm)?,
             Stmt::If(if_stmt) => self.interpret_if(if_stmt, ctx, vm)?,
             Stmt::Break(token) => {
                 debug!("Interpreting break statement");
-                return Err(RoanError::LoopBreak(token.span).into());
+                return Err(RoanError::LoopBreak(token.span.clone()).into());
             }
             Stmt::Continue(token) => {
                 debug!("Interpreting continue statement");
-                return Err(RoanError::LoopContinue(token.span).into());
+                return Err(RoanError::LoopContinue(token.span.clone()).into());
             }
             Stmt::Throw(throw) => self.interpret_throw(throw, ctx, vm)?,
             Stmt::Try(try_stmt) => self.interpret_try(try_stmt, ctx, vm)?,
@@ -31,7 +35,7 @@ impl Module {
             Stmt::Return(r) => {
                 debug!("Interpreting return: {:?}", r);
 
-                if let Some(expr) = r.expr {
+                if let Some(expr) = &r.expr {
                     self.interpret_expr(expr.as_ref(), ctx, vm)?;
                 } else {
                     vm.push(Value::Void);
@@ -49,14 +53,12 @@ impl Module {
     /// * `let_stmt` - [`Let`] - The let statement to interpret.
     /// * `vm` - [`VM`] - The virtual machine to use for interpretation.
     /// * `ctx` - [`Context`] - The context in which to interpret the statement.
-    pub fn interpret_let(&mut self, l: Let, vm: &mut VM, ctx: &mut Context) -> Result<()> {
+    pub fn interpret_let(&mut self, l: &Let, vm: &mut VM, ctx: &mut Context) -> Result<()> {
         debug!("Interpreting let: {:?}", l.ident);
         self.interpret_expr(l.initializer.as_ref(), ctx, vm)?;
 
         let val = vm.pop().unwrap();
-        let ident = l.ident.literal();
-
-        self.declare_variable(ident.clone(), val);
+        self.declare_variable(l.ident.literal(), val);
 
         Ok(())
     }
@@ -88,14 +90,16 @@ impl Module {
     /// * `ctx` - [`Context`] - The context in which to interpret the loop.
     pub fn interpret_loop(
         &mut self,
-        loop_stmt: Loop,
+        loop_stmt: &Loop,
         ctx: &mut Context,
         vm: &mut VM,
     ) -> Result<()> {
         debug!("Interpreting infinite loop");
         loop {
+            vm.check_deadline()?;
+
             self.enter_scope();
-            let result = self.execute_block(loop_stmt.block.clone(), ctx, vm);
+            let result = self.execute_block(&loop_stmt.block, ctx, vm);
             self.exit_scope();
 
             self.handle_loop_result(result)?
@@ -109,13 +113,14 @@ impl Module {
     /// * `ctx` - [`Context`] - The context in which to interpret the while loop.
     pub fn interpret_while(
         &mut self,
-        while_stmt: While,
+        while_stmt: &While,
         ctx: &mut Context,
         vm: &mut VM,
     ) -> Result<()> {
         debug!("Interpreting while loop");
 
         loop {
+            vm.check_deadline()?;
             self.interpret_expr(&while_stmt.condition, ctx, vm)?;
             let condition_value = vm.pop().expect("Expected value on stack");
 
@@ -135,7 +140,7 @@ impl Module {
             }
 
             self.enter_scope();
-            let result = self.execute_block(while_stmt.block.clone(), ctx, vm);
+            let result = self.execute_block(&while_stmt.block, ctx, vm);
             self.exit_scope();
 
             self.handle_loop_result(result)?
@@ -144,18 +149,126 @@ impl Module {
         Ok(())
     }
 
+    /// Interpret a for loop.
+    ///
+    /// The iterable is converted to an iterator using [Value::iter] and the block is executed
+    /// for every element it yields.
+    ///
+    /// # Arguments
+    /// * `for_stmt` - [`For`] - The for loop to interpret.
+    /// * `ctx` - [`Context`] - The context in which to interpret the for loop.
+    pub fn interpret_for(&mut self, for_stmt: &For, ctx: &mut Context, vm: &mut VM) -> Result<()> {
+        debug!("Interpreting for loop");
+
+        self.interpret_expr(&for_stmt.iterable, ctx, vm)?;
+        let iterable = vm.pop().expect("Expected value on stack");
+
+        let iter = match iterable.iter() {
+            Some(Value::Iterator(iter)) => iter,
+            _ => {
+                return Err(TypeMismatch(
+                    format!(
+                        "Cannot iterate over a value of type {}",
+                        iterable.type_name()
+                    ),
+                    for_stmt.iterable.span(),
+                )
+                .into())
+            }
+        };
+
+        loop {
+            vm.check_deadline()?;
+
+            let next = iter.borrow_mut().next();
+            let Some(item) = next else {
+                break;
+            };
+
+            self.enter_scope();
+            let result = self
+                .declare_for_variables(for_stmt, item)
+                .and_then(|_| self.execute_block(&for_stmt.block, ctx, vm));
+            self.exit_scope();
+
+            match result {
+                Ok(_) => {}
+                Err(e) => match e.downcast::<RoanError>() {
+                    Ok(RoanError::LoopBreak(_)) => break,
+                    Ok(RoanError::LoopContinue(_)) => continue,
+                    Ok(other) => return Err(other.into()),
+                    Err(e) => return Err(e),
+                },
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Declares the variables of `for_stmt` for the element `item`. In `for key, value in` the
+    /// element has to be a pair, like the `[key, value]` elements of objects.
+    fn declare_for_variables(&mut self, for_stmt: &For, item: Value) -> Result<()> {
+        let Some(value_ident) = &for_stmt.value_ident else {
+            self.declare_variable(for_stmt.ident.literal(), item);
+            return Ok(());
+        };
+
+        match item {
+            Value::Vec(pair) if pair.len() == 2 => {
+                let mut pair = pair.into_iter();
+                self.declare_variable(for_stmt.ident.literal(), pair.next().unwrap());
+                self.declare_variable(value_ident.literal(), pair.next().unwrap());
+
+                Ok(())
+            }
+            _ => Err(TypeMismatch(
+                format!(
+                    "Cannot destructure {} into a key and a value",
+                    item.type_name()
+                ),
+                for_stmt.iterable.span(),
+            )
+            .into()),
+        }
+    }
+
     /// Execute a block of statements within a new scope.
     ///
     /// # Arguments
     /// * `block` - [`Block`] - The block of statements to execute.
-    pub fn execute_block(&mut self, block: Block, ctx: &mut Context, vm: &mut VM) -> Result<()> {
+    pub fn execute_block(&mut self, block: &Block, ctx: &mut Context, vm: &mut VM) -> Result<()> {
         debug!("Interpreting block statement");
 
         self.enter_scope();
-        for stmt in block.stmts {
-            self.interpret_stmt(stmt, ctx, vm)?;
-        }
+        let result = block
+            .stmts
+            .iter()
+            .try_for_each(|stmt| self.interpret_stmt(stmt, ctx, vm));
         self.exit_scope();
-        Ok(())
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{context, parsed, SharedBuffer};
+
+    #[test]
+    fn test_for_over_object() {
+        let mut ctx = context();
+        let output = SharedBuffer::default();
+        ctx.set_output(Box::new(output.clone()));
+
+        let mut module = parsed(
+            "let o = {\"b\": 1, \"a\": 2};\n\
+             for pair in o { __print(__format(pair[0])); }\n\
+             for k, v in o { __print(\" \" + k + \"=\" + __format(v)); }\n",
+            &mut ctx,
+        );
+        module.interpret(&mut ctx, &mut VM::new()).unwrap();
+
+        assert_eq!(String::from_utf8(output.0.take()).unwrap(), "ba b=1 a=2");
     }
 }
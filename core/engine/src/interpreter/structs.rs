@@ -13,9 +13,7 @@ use roan_error::{error::RoanError, TextSpan};
 impl Module {
     pub fn get_trait(&self, name: &str, span: TextSpan) -> Result<TraitDef> {
         Ok(self
-            .traits
-            .iter()
-            .find(|t| t.name.literal() == name)
+            .find_trait(name)
             .cloned()
             .ok_or_else(|| RoanError::TraitNotFoundError(name.into(), span))?)
     }
@@ -38,7 +36,7 @@ impl Module {
     /// The result of the struct constructor expression.
     pub fn interpret_struct_constructor(
         &mut self,
-        constructor: StructConstructor,
+        constructor: &StructConstructor,
         ctx: &mut Context,
         vm: &mut VM,
     ) -> Result<Value> {
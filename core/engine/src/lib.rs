@@ -3,13 +3,17 @@
 extern crate core;
 
 pub mod context;
+mod engine;
 pub mod interpreter;
 mod macros;
 pub mod module;
 pub mod natives;
 pub mod path;
+#[cfg(test)]
+mod test_utils;
 pub mod value;
 pub mod vm;
 
+pub use engine::RoanEngine;
 pub use roan_ast::*;
-pub use roan_error::{diagnostic::*, error::RoanError::*, span::*};
+pub use roan_error::{diagnostic::*, error, error::RoanError::*, span::*};
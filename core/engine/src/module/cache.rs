@@ -0,0 +1,296 @@
+use crate::module::Module;
+use roan_ast::source::Source;
+use roan_error::Diagnostic;
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+};
+use tracing::debug;
+
+/// Parsed modules kept between runs, so a file is only lexed, parsed and checked again if it or
+/// one of the modules it imports changed.
+///
+/// Modules are cached by path with the [checksum] of their source. An entry is only used if the
+/// module and every module it imports, directly or not, still have the content they had when it
+/// was parsed. Dropping the entry of a changed module drops the entries of the modules importing
+/// it, as what they import from it may have changed.
+///
+/// Only modules read from a file are cached, so modules importing a virtual module are always
+/// parsed again.
+///
+/// [checksum]: Source::checksum
+#[derive(Debug, Default)]
+pub struct ModuleCache {
+    entries: HashMap<PathBuf, CachedModule>,
+}
+
+#[derive(Debug)]
+struct CachedModule {
+    /// Checksum of the source the module was parsed from.
+    checksum: u64,
+    /// The module after its passes ran.
+    module: Module,
+    /// Warnings reported while parsing the module, reported again when it's reused.
+    diagnostics: Vec<Diagnostic>,
+    /// Paths of the modules imported by the module, with the checksum they had.
+    imports: Vec<(PathBuf, u64)>,
+}
+
+impl ModuleCache {
+    /// Creates an empty cache.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the module parsed from the file at `path` and the warnings reported parsing it, if
+    /// `checksum` is still the checksum of its source and none of the modules it imports changed.
+    ///
+    /// An outdated entry is dropped, along with the entries of the modules importing it.
+    pub fn get(&mut self, path: &Path, checksum: u64) -> Option<(Module, Vec<Diagnostic>)> {
+        if !self.is_fresh(path, checksum, &mut HashSet::new()) {
+            self.invalidate(path);
+            return None;
+        }
+
+        let entry = &self.entries[path];
+        Some((entry.module.clone(), entry.diagnostics.clone()))
+    }
+
+    /// Returns `true` if the module at `path` is cached for `checksum` and the files of the
+    /// modules it imports still have the content they were parsed from.
+    fn is_fresh(&self, path: &Path, checksum: u64, visited: &mut HashSet<PathBuf>) -> bool {
+        let Some(entry) = self.entries.get(path) else {
+            return false;
+        };
+        if entry.checksum != checksum {
+            return false;
+        }
+        if !visited.insert(path.to_path_buf()) {
+            return true;
+        }
+
+        entry.imports.iter().all(|(import, checksum)| {
+            file_checksum(import) == Some(*checksum) && self.is_fresh(import, *checksum, visited)
+        })
+    }
+
+    /// Returns the modules imported by the module at `path`, directly or not. They have to be
+    /// known by the context again when a cached module is reused.
+    pub fn imported_modules(&self, path: &Path) -> Vec<Module> {
+        let mut visited = HashSet::from([path.to_path_buf()]);
+        let mut pending = vec![path.to_path_buf()];
+        let mut modules = vec![];
+
+        while let Some(path) = pending.pop() {
+            let Some(entry) = self.entries.get(&path) else {
+                continue;
+            };
+
+            for (import, _) in &entry.imports {
+                if visited.insert(import.clone()) {
+                    if let Some(imported) = self.entries.get(import) {
+                        modules.push(imported.module.clone());
+                    }
+                    pending.push(import.clone());
+                }
+            }
+        }
+
+        modules
+    }
+
+    /// Caches `module` after it was parsed without errors, `diagnostics` are the warnings
+    /// reported while parsing it.
+    ///
+    /// Modules that aren't read from a file or import a module that isn't cached are skipped.
+    pub fn insert(&mut self, module: &Module, diagnostics: Vec<Diagnostic>) {
+        let Some(path) = module.path() else {
+            return;
+        };
+        if !path.is_file() {
+            return;
+        }
+
+        let imports: Option<Vec<(PathBuf, u64)>> = module
+            .imports
+            .iter()
+            .map(|import| {
+                let entry = self.entries.get(import)?;
+                Some((import.clone(), entry.checksum))
+            })
+            .collect();
+        let Some(imports) = imports else {
+            return;
+        };
+
+        let checksum = module.source.checksum();
+        if self
+            .entries
+            .get(&path)
+            .is_some_and(|entry| entry.checksum != checksum)
+        {
+            self.invalidate(&path);
+        }
+
+        debug!("Caching module: {}", path.display());
+        self.entries.insert(
+            path,
+            CachedModule {
+                checksum,
+                module: module.clone(),
+                diagnostics,
+                imports,
+            },
+        );
+    }
+
+    /// Drops the module at `path` and every module importing it, directly or not.
+    pub fn invalidate(&mut self, path: &Path) {
+        let mut stale = vec![path.to_path_buf()];
+
+        while let Some(path) = stale.pop() {
+            if self.entries.remove(&path).is_some() {
+                debug!("Dropping cached module: {}", path.display());
+            }
+
+            stale.extend(
+                self.entries
+                    .iter()
+                    .filter(|(_, entry)| entry.imports.iter().any(|(import, _)| *import == path))
+                    .map(|(importer, _)| importer.clone()),
+            );
+        }
+    }
+
+    /// Returns `true` if the module at `path` is cached, outdated or not.
+    pub fn contains(&self, path: &Path) -> bool {
+        self.entries.contains_key(path)
+    }
+
+    /// Returns the number of cached modules.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if no module is cached.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// Returns the checksum of the current content of the file at `path`.
+fn file_checksum(path: &Path) -> Option<u64> {
+    Source::from_file(path.to_path_buf())
+        .ok()
+        .map(|source| source.checksum())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        context::Context, module::loaders::basic::BasicModuleLoader, path::canonicalize_path,
+        vm::VM,
+    };
+    use std::{cell::RefCell, fs, rc::Rc};
+
+    /// Parses and runs the file at `path` with a new context using `cache`, like a watch mode
+    /// running it again. Returns how many files were lexed and what the program printed.
+    fn run(path: &Path, cache: &Rc<RefCell<ModuleCache>>) -> (usize, String) {
+        let output = Rc::new(RefCell::new(Vec::new()));
+        let mut ctx = Context::builder()
+            .module_loader(Rc::new(RefCell::new(BasicModuleLoader::new())))
+            .module_cache(cache.clone())
+            .build();
+        ctx.set_output(Box::new(SharedWriter(output.clone())));
+
+        let mut vm = VM::new();
+        let mut module = Module::new(Source::from_file(path.to_path_buf()).unwrap());
+        module.parse(&mut ctx, &mut vm).unwrap();
+        module.interpret(&mut ctx, &mut vm).unwrap();
+
+        let lexed = ctx.timings.get("lex").map_or(0, |(_, runs)| runs);
+        let printed = String::from_utf8(output.take()).unwrap();
+        (lexed, printed)
+    }
+
+    struct SharedWriter(Rc<RefCell<Vec<u8>>>);
+
+    impl std::io::Write for SharedWriter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.borrow_mut().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_only_changed_modules_are_parsed_again() {
+        let dir = tempfile::tempdir().unwrap();
+        let dir = canonicalize_path(dir.path().to_path_buf()).unwrap();
+        let main = dir.join("main.roan");
+        let math = dir.join("math.roan");
+        let text = dir.join("text.roan");
+        fs::write(
+            &main,
+            "use { double } from \"./math.roan\";\n\
+             use { shout } from \"./text.roan\";\n\
+             __print(shout(__format(double(2))));\n",
+        )
+        .unwrap();
+        fs::write(&math, "pub fn double(x: int) -> int { return x * 2; }\n").unwrap();
+        fs::write(
+            &text,
+            "pub fn shout(s: string) -> string { return s + \"!\"; }\n",
+        )
+        .unwrap();
+
+        let cache = Rc::new(RefCell::new(ModuleCache::new()));
+        assert_eq!(run(&main, &cache), (3, "4!".to_string()));
+        assert_eq!(cache.borrow().len(), 3);
+        // Nothing changed, the cached modules are used and still work
+        assert_eq!(run(&main, &cache), (0, "4!".to_string()));
+
+        // Only the changed module and the module importing it are parsed
+        fs::write(
+            &math,
+            "pub fn double(x: int) -> int { return x + x + 1; }\n",
+        )
+        .unwrap();
+        assert_eq!(run(&main, &cache), (2, "5!".to_string()));
+        assert_eq!(run(&main, &cache), (0, "5!".to_string()));
+
+        fs::write(
+            &main,
+            "use { shout } from \"./text.roan\";\n__print(shout(\"hi\"));\n",
+        )
+        .unwrap();
+        assert_eq!(run(&main, &cache), (1, "hi!".to_string()));
+    }
+
+    #[test]
+    fn test_invalidate() {
+        let dir = tempfile::tempdir().unwrap();
+        let dir = canonicalize_path(dir.path().to_path_buf()).unwrap();
+        let main = dir.join("main.roan");
+        let lib = dir.join("lib.roan");
+        fs::write(
+            &main,
+            "use { one } from \"./lib.roan\";\n__print(__format(one()));\n",
+        )
+        .unwrap();
+        fs::write(&lib, "pub fn one() -> int { return 1; }\n").unwrap();
+
+        let cache = Rc::new(RefCell::new(ModuleCache::new()));
+        run(&main, &cache);
+        assert!(cache.borrow().contains(&main));
+        assert_eq!(cache.borrow().imported_modules(&main).len(), 1);
+
+        // Dropping an imported module drops the module importing it
+        cache.borrow_mut().invalidate(&lib);
+        assert!(cache.borrow().is_empty());
+        assert_eq!(run(&main, &cache), (2, "1".to_string()));
+    }
+}
@@ -0,0 +1,60 @@
+use crate::{
+    context::Context,
+    module::{
+        loaders::{remove_surrounding_quotes, ModuleLoader},
+        Module,
+    },
+    path::canonicalize_path,
+    source::Source,
+};
+use std::collections::HashMap;
+use tracing::debug;
+
+/// Loader resolving modules relative to the file importing them, and caching them by path.
+///
+/// Packages installed in `build/deps` can't be loaded by it, only files and virtual modules.
+#[derive(Debug, Default)]
+pub struct BasicModuleLoader {
+    modules: HashMap<String, Module>,
+}
+
+impl BasicModuleLoader {
+    /// Creates a loader with an empty cache.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl ModuleLoader for BasicModuleLoader {
+    fn load(&mut self, referrer: &Module, spec: &str, _: &Context) -> anyhow::Result<Module> {
+        debug!("Loading module: {}", spec);
+
+        let cache_key = remove_surrounding_quotes(spec).to_string();
+        if let Some(module) = self.modules.get(&cache_key) {
+            return Ok(module.clone());
+        }
+
+        let path = canonicalize_path(self.resolve_referrer(referrer, spec)?)?;
+        let cache_key = path.to_string_lossy().to_string();
+        if let Some(module) = self.modules.get(&cache_key) {
+            return Ok(module.clone());
+        }
+
+        let module = Module::new(Source::from_file(path)?);
+        self.modules.insert(cache_key, module.clone());
+
+        Ok(module)
+    }
+
+    fn insert(&mut self, name: String, module: Module) {
+        self.modules.insert(name, module);
+    }
+
+    fn get(&self, name: &str) -> Option<Module> {
+        self.modules.get(remove_surrounding_quotes(name)).cloned()
+    }
+
+    fn keys(&self) -> Vec<String> {
+        self.modules.keys().cloned().collect()
+    }
+}
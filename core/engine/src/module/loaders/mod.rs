@@ -2,6 +2,7 @@ use crate::{context::Context, module::Module};
 use std::{fmt::Debug, path::PathBuf};
 use tracing::debug;
 
+pub mod basic;
 pub mod ident;
 
 /// Trait that defines the interface for a module loader.
@@ -88,3 +89,36 @@ pub fn remove_surrounding_quotes(s: &str) -> &str {
         s
     }
 }
+
+/// Returns the scheme of a module specification like `std:math`, `std` in this case.
+///
+/// Modules with a scheme are registered with [Context::register_virtual_module] and never
+/// resolved on the filesystem. A scheme is at least two characters long, so Windows drive letters
+/// aren't mistaken for one, and module identifiers like `std::io` don't have one.
+pub fn virtual_scheme(spec: &str) -> Option<&str> {
+    let (scheme, rest) = remove_surrounding_quotes(spec).split_once(':')?;
+
+    let is_scheme = scheme.len() >= 2
+        && scheme
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        && !rest.is_empty()
+        && !rest.starts_with(':');
+
+    is_scheme.then_some(scheme)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_virtual_scheme() {
+        assert_eq!(virtual_scheme("std:math"), Some("std"));
+        assert_eq!(virtual_scheme("\"test-utils:assert\""), Some("test-utils"));
+        assert_eq!(virtual_scheme("std::io"), None);
+        assert_eq!(virtual_scheme("C:\\lib.roan"), None);
+        assert_eq!(virtual_scheme("./lib.roan"), None);
+        assert_eq!(virtual_scheme("std:"), None);
+    }
+}
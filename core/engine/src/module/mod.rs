@@ -1,26 +1,46 @@
 use crate::{
     context::Context,
-    interpreter::passes::{imports::ImportPass, resolver::ResolverPass, types::TypePass, Pass},
-    natives::get_stored_function,
+    interpreter::passes::{
+        constant_folding::ConstantFoldingPass, imports::ImportPass, lint::LintPass,
+        resolver::ResolverPass, types::TypePass, Pass,
+    },
+    module::symbols::ScopedDeclaration,
+    natives::{get_stored_function, prelude::Prelude},
     value::Value,
     vm::{native_fn::NativeFunction, VM},
 };
+use anstream::ColorChoice;
 use anyhow::Result;
 use indexmap::IndexMap;
+use log::Level;
 use roan_ast::{
-    source::Source, Ast, Expr, Fn, Lexer, Parser, StructField, StructImpl, Token, TraitDef,
-    TraitImpl,
+    source::Source, Ast, Expr, Fn, Lexer, Parser, Stmt, StructField, StructImpl, Token, TokenKind,
+    TraitDef, TraitImpl,
+};
+use roan_error::{
+    error::{
+        RoanError,
+        RoanError::{AbortedDueToPreviousErrors, DuplicateDefinition, VariableNotFoundError},
+    },
+    Diagnostic, DiagnosticBuffer, Position, TextSpan,
 };
-use roan_error::{error::RoanError::VariableNotFoundError, print_diagnostic, TextSpan};
+use roan_shell::Shell;
 use std::{
+    cell::OnceCell,
     collections::HashMap,
     fmt::Debug,
+    io::Write,
     path::{Path, PathBuf},
 };
 use tracing::debug;
 use uuid::Uuid;
 
+pub mod cache;
 pub mod loaders;
+pub mod snapshot;
+pub mod symbols;
+
+pub use snapshot::ModuleSnapshot;
 
 #[derive(Clone, Debug)]
 pub struct StoredStruct {
@@ -34,7 +54,31 @@ pub struct StoredStruct {
 }
 
 impl StoredStruct {
-    fn find_method_internal(&self, name: &str, is_static: bool) -> Option<&Fn> {
+    /// Creates a struct called `name` without fields, methods or defining module, e.g. for a
+    /// struct value built outside of a script.
+    pub fn named(name: &str) -> Self {
+        let token = |literal: &str| {
+            let position = Position::new(1, 0, 0);
+            Token::new(
+                TokenKind::Identifier,
+                TextSpan::new(position, position, literal.to_string()),
+            )
+        };
+
+        Self {
+            defining_module: String::new(),
+            struct_token: token("struct"),
+            name: token(name),
+            fields: IndexMap::new(),
+            public: false,
+            impls: vec![],
+            trait_impls: vec![],
+        }
+    }
+
+    /// Returns the methods of every `impl` block, followed by the methods of every trait
+    /// implementation, in the order they were declared.
+    pub fn all_methods(&self) -> impl Iterator<Item = &Fn> {
         self.impls
             .iter()
             .flat_map(|impl_stmt| impl_stmt.def.methods.iter())
@@ -43,6 +87,17 @@ impl StoredStruct {
                     .iter()
                     .flat_map(|impl_stmt| impl_stmt.def.methods.iter()),
             )
+    }
+
+    /// Returns `true` if the struct implements the trait called `name`.
+    pub fn has_trait(&self, name: &str) -> bool {
+        self.trait_impls
+            .iter()
+            .any(|impl_stmt| impl_stmt.def.trait_name.literal() == name)
+    }
+
+    fn find_method_internal(&self, name: &str, is_static: bool) -> Option<&Fn> {
+        self.all_methods()
             .find(|method| method.name == name && method.is_static == is_static)
     }
 
@@ -96,6 +151,34 @@ pub enum StoredFunction {
     },
 }
 
+impl StoredFunction {
+    /// Returns the name the function is called by.
+    pub fn name(&self) -> &str {
+        match self {
+            StoredFunction::Native(native) => &native.name,
+            StoredFunction::Function { function, .. } => &function.name,
+        }
+    }
+
+    /// Returns the number of parameters, not counting the rest parameter.
+    pub fn arity(&self) -> usize {
+        match self {
+            StoredFunction::Native(native) => native.arity(),
+            StoredFunction::Function { function, .. } => {
+                function.params.iter().filter(|p| !p.is_rest).count()
+            }
+        }
+    }
+
+    /// Returns `true` if the function takes a rest parameter.
+    pub fn is_variadic(&self) -> bool {
+        match self {
+            StoredFunction::Native(native) => native.is_variadic(),
+            StoredFunction::Function { function, .. } => function.params.iter().any(|p| p.is_rest),
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct Module {
     pub source: Source,
@@ -108,9 +191,14 @@ pub struct Module {
     pub structs: Vec<StoredStruct>,
     pub traits: Vec<TraitDef>,
     pub consts: Vec<StoredConst>,
+    /// Paths of the modules imported by this one, recorded by `ImportPass` before they're parsed.
+    pub imports: Vec<PathBuf>,
     pub id: String,
     pub lex_comments: bool,
     pub passes: Vec<Box<dyn Pass>>,
+    /// Variables and parameters of the AST with their scopes, built on the first call to
+    /// [Module::symbols_in_scope] and cleared when the module is parsed again.
+    scoped_declarations: OnceCell<Vec<ScopedDeclaration>>,
 }
 
 impl Debug for Module {
@@ -152,13 +240,17 @@ impl Module {
             structs: vec![],
             traits: vec![],
             consts: vec![],
+            imports: vec![],
             id: Uuid::new_v4().to_string(),
             lex_comments: false,
             passes: vec![
-                Box::new(ImportPass {}),
+                Box::new(ImportPass::default()),
                 Box::new(ResolverPass {}),
+                Box::new(ConstantFoldingPass::default()),
                 Box::new(TypePass::new()),
+                Box::new(LintPass::default()),
             ],
+            scoped_declarations: OnceCell::new(),
         }
     }
 
@@ -189,37 +281,192 @@ impl Module {
     /// Parses the module.
     ///
     /// First, the module is lexed into tokens. Then, the tokens are parsed into an AST.
-    pub fn parse(&mut self, ctx: &mut Context, vm: &mut VM) -> Result<()> {
+    /// Finally, every pass is run over the AST. Nothing is written to stderr: the warnings
+    /// reported along the way are returned, and if any error was reported,
+    /// [AbortedDueToPreviousErrors] is returned with all diagnostics instead.
+    pub fn parse(&mut self, ctx: &mut Context, vm: &mut VM) -> Result<Vec<Diagnostic>> {
+        let diagnostics = self.check(ctx, vm)?;
+
+        if diagnostics.iter().any(|d| d.level == Level::Error) {
+            return Err(AbortedDueToPreviousErrors(diagnostics).into());
+        }
+
+        Ok(diagnostics)
+    }
+
+    /// Parses the module like [Module::parse], returning the diagnostics whether or not there
+    /// are errors among them. Errors that can't be reported as a diagnostic are returned.
+    pub fn check(&mut self, ctx: &mut Context, vm: &mut VM) -> Result<Vec<Diagnostic>> {
+        let mut diagnostics = DiagnosticBuffer::new();
+
+        if let Err(err) = self.parse_into(ctx, vm, &mut diagnostics) {
+            self.report(err, &mut diagnostics)?;
+        }
+
+        Ok(diagnostics.into_iter().collect())
+    }
+
+    /// Lexes and parses the module and runs the passes over it, pushing what they report to
+    /// `diagnostics`.
+    fn parse_into(
+        &mut self,
+        ctx: &mut Context,
+        vm: &mut VM,
+        diagnostics: &mut DiagnosticBuffer,
+    ) -> Result<()> {
+        if self.use_cached(ctx, diagnostics) {
+            return Ok(());
+        }
+
         debug!("Parsing module from source");
         let mut lexer = Lexer::new(self.source.clone());
 
-        let tokens = lexer.lex(self.lex_comments)?;
+        let start = ctx.timings.start();
+        let tokens = lexer.lex(self.lex_comments);
+        ctx.timings.stop("lex", start);
+        let tokens = tokens?;
         debug!("Parsed {} tokens", tokens.len());
-        self.tokens = tokens;
 
-        let mut parser = Parser::new(self.tokens.clone());
+        let mut parser = Parser::new(tokens);
 
         debug!("Parsing tokens into AST");
-        let ast = parser.parse()?;
+        let start = ctx.timings.start();
+        let (ast, errors) = parser.parse_with_recovery();
+        ctx.timings.stop("parse", start);
         self.ast = ast;
+        self.tokens = parser.into_tokens();
+        self.scoped_declarations.take();
+
+        if !errors.is_empty() {
+            for err in errors {
+                self.report(err, diagnostics)?;
+            }
+
+            return Ok(());
+        }
+
+        self.set_prelude(&ctx.prelude);
 
         let mut passes = self.passes.clone();
         for pass in passes.iter_mut() {
-            pass.run(self, ctx, vm)?;
+            if !pass.is_enabled(ctx) || ctx.is_pass_disabled(pass.name()) {
+                debug!("Skipping pass: {}", pass.name());
+                continue;
+            }
+
+            debug!("Running pass: {}", pass.name());
+            let mut pass_diagnostics = vec![];
+            let start = ctx.timings.start();
+            let result = pass.run(self, ctx, vm, &mut pass_diagnostics);
+            ctx.timings.stop(pass.name(), start);
+            diagnostics.extend(pass_diagnostics);
+            result?;
+        }
+
+        if !diagnostics.has_errors() {
+            ctx.module_cache
+                .borrow_mut()
+                .insert(self, diagnostics.iter().cloned().collect());
         }
 
         Ok(())
     }
 
-    pub fn interpret(&mut self, ctx: &mut Context, vm: &mut VM) -> Result<()> {
-        for stmt in self.ast.stmts.clone() {
-            match self.interpret_stmt(stmt, ctx, vm) {
-                Ok(_) => {}
-                Err(e) => {
-                    print_diagnostic(&e, Some(self.source.content()), self.path());
-                    std::process::exit(1);
-                }
+    /// Replaces the module with the module parsed from the same source before, if it's in the
+    /// module cache of `ctx` and none of the modules it imports changed since. Returns `true` if
+    /// the cached module was used.
+    ///
+    /// The warnings reported when it was parsed are pushed to `diagnostics` again.
+    fn use_cached(&mut self, ctx: &mut Context, diagnostics: &mut DiagnosticBuffer) -> bool {
+        let Some(path) = self.path() else {
+            return false;
+        };
+        let Some((module, warnings)) = ctx
+            .module_cache
+            .borrow_mut()
+            .get(&path, self.source.checksum())
+        else {
+            return false;
+        };
+
+        debug!("Using cached module: {}", path.display());
+        *self = module;
+        diagnostics.extend(warnings);
+
+        // Functions and structs are looked up by the id of the module defining them
+        let imported = ctx.module_cache.borrow().imported_modules(&path);
+        for module in imported.into_iter().chain([self.clone()]) {
+            ctx.upsert_module(module.id(), module);
+        }
+
+        true
+    }
+
+    /// Builds a diagnostic from `err`, pointing into the source of the module.
+    pub(crate) fn to_diagnostic(&self, err: &anyhow::Error) -> Option<Diagnostic> {
+        let mut diagnostic = Diagnostic::from_error(err, Some(self.source.content()))?;
+        diagnostic.file = self.path();
+
+        Some(diagnostic)
+    }
+
+    /// Pushes `err` to `diagnostics`. When another module failed, the diagnostics it reported
+    /// are pushed instead. Returns `err` if it can't be reported as a diagnostic.
+    pub(crate) fn report<D: Extend<Diagnostic>>(
+        &self,
+        err: anyhow::Error,
+        diagnostics: &mut D,
+    ) -> Result<()> {
+        if let Some(AbortedDueToPreviousErrors(reported)) = err.downcast_ref::<RoanError>() {
+            diagnostics.extend(reported.iter().cloned());
+            return Ok(());
+        }
+
+        match self.to_diagnostic(&err) {
+            Some(diagnostic) => {
+                diagnostics.extend([diagnostic]);
+                Ok(())
             }
+            None => Err(err),
+        }
+    }
+
+    /// Writes `err` as a warning to the error output of `ctx`, for mistakes found while running
+    /// that don't stop the program.
+    pub(crate) fn warn(&self, err: &anyhow::Error, ctx: &Context) -> Result<()> {
+        let Some(mut diagnostic) = self.to_diagnostic(err) else {
+            return Ok(());
+        };
+        diagnostic.level = Level::Warn;
+
+        let mut buff = Vec::new();
+        writeln!(buff, "{}: {}", diagnostic.label(), diagnostic.title)?;
+        diagnostic.log_details(&mut buff, self.path(), &mut Shell::new(ColorChoice::Auto))?;
+
+        Ok(ctx
+            .error_output
+            .write_str(&String::from_utf8_lossy(&buff))?)
+    }
+
+    /// Interprets the statements of the module.
+    ///
+    /// An uncaught error is turned into a diagnostic here, as only this module has the source
+    /// its span points into, and returned in [AbortedDueToPreviousErrors]. Errors without a
+    /// diagnostic, like an uncaught `throw`, are returned as they are.
+    pub fn interpret(&mut self, ctx: &mut Context, vm: &mut VM) -> Result<()> {
+        let start = ctx.timings.start();
+        let result = self
+            .ast
+            .stmts
+            .clone()
+            .iter()
+            .try_for_each(|stmt| self.interpret_stmt(stmt, ctx, vm));
+        ctx.timings.stop("interpret", start);
+
+        if let Err(e) = result {
+            let mut diagnostics = vec![];
+            self.report(e, &mut diagnostics)?;
+            return Err(AbortedDueToPreviousErrors(diagnostics).into());
         }
 
         Ok(())
@@ -275,13 +522,84 @@ impl Module {
         self.consts.iter().find(|c| c.ident.literal() == name)
     }
 
+    /// Returns the consts whose value has the type `type_name`, e.g. `int` or `string[]`.
+    pub fn find_const_by_type(&self, type_name: &str) -> Vec<&StoredConst> {
+        self.consts
+            .iter()
+            .filter(|c| c.value.type_name() == type_name)
+            .collect()
+    }
+
+    /// Finds a trait declared in or imported into the module by name.
+    pub fn find_trait(&self, name: &str) -> Option<&TraitDef> {
+        self.traits.iter().find(|t| t.name.literal() == name)
+    }
+
+    /// Finds the export called `name`, only `pub` items declared in the module are exported.
+    pub fn find_export(&self, name: &str) -> Option<&ExportType> {
+        self.exports
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, export)| export)
+    }
+
+    /// Returns `true` if the module declares an item called `name` that isn't `pub`.
+    pub fn has_private_item(&self, name: &str) -> bool {
+        self.ast.stmts.iter().any(|stmt| match stmt {
+            Stmt::Fn(f) => !f.public && f.name == name,
+            Stmt::Struct(s) => !s.public && s.name.literal() == name,
+            Stmt::TraitDef(t) => !t.public && t.name.literal() == name,
+            Stmt::Const(c) => !c.public && c.ident.literal() == name,
+            _ => false,
+        })
+    }
+
+    /// Brings every export of `other` into the module, as if each of them was imported with a
+    /// `use` statement.
+    ///
+    /// Nothing is merged if an export has the same name as an item of the module, the error
+    /// points to the export in `other`.
+    pub fn merge_module(&mut self, other: &Module) -> Result<()> {
+        for (name, export) in other.exports.iter() {
+            let (exists, span) = match export {
+                ExportType::Function(f) => (self.has_function(name), f.fn_token.span.clone()),
+                ExportType::Struct(s) => (
+                    self.structs.iter().any(|st| st.name.literal() == *name),
+                    s.name.span.clone(),
+                ),
+                ExportType::Trait(t) => (self.find_trait(name).is_some(), t.name.span.clone()),
+                ExportType::Const(c) => (self.find_const(name).is_some(), c.ident.span.clone()),
+            };
+
+            if exists {
+                return Err(DuplicateDefinition(name.clone(), span).into());
+            }
+        }
+
+        for (_, export) in other.exports.iter() {
+            match export {
+                ExportType::Function(f) => self.functions.push(StoredFunction::Function {
+                    function: f.clone(),
+                    defining_module: other.id(),
+                }),
+                ExportType::Struct(s) => self.structs.push(s.clone()),
+                ExportType::Trait(t) => self.traits.push(t.clone()),
+                ExportType::Const(c) => self.consts.push(c.clone()),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the file name of the module without the extension, `unknown` if it has no path.
     pub fn name(&self) -> String {
         self.path()
-            .unwrap()
-            .file_stem()
-            .unwrap()
-            .to_string_lossy()
-            .to_string()
+            .as_deref()
+            .and_then(Path::file_stem)
+            .map_or_else(
+                || "unknown".to_string(),
+                |stem| stem.to_string_lossy().to_string(),
+            )
     }
 
     pub fn extract_variable_name(expr: &Expr) -> Option<String> {
@@ -296,10 +614,30 @@ impl Module {
     pub fn find_function(&self, name: &str) -> Option<&StoredFunction> {
         debug!("Looking for function: {}", name);
 
-        self.functions.iter().find(|f| match f {
-            StoredFunction::Native(n) => n.name == name,
-            StoredFunction::Function { function, .. } => function.name == name,
-        })
+        // Functions declared in or imported into the module shadow the prelude
+        self.functions
+            .iter()
+            .find(|f| f.name() == name && !matches!(f, StoredFunction::Native(_)))
+            .or_else(|| self.functions.iter().find(|f| f.name() == name))
+    }
+
+    /// Replaces the natives in scope in the module with the functions of `prelude`.
+    pub fn set_prelude(&mut self, prelude: &Prelude) {
+        self.functions
+            .retain(|f| !matches!(f, StoredFunction::Native(_)));
+        self.functions.splice(
+            0..0,
+            prelude
+                .functions()
+                .iter()
+                .cloned()
+                .map(StoredFunction::Native),
+        );
+    }
+
+    /// Returns `true` if a function called `name` can be called from the module.
+    pub fn has_function(&self, name: &str) -> bool {
+        self.find_function(name).is_some()
     }
 
     pub fn update_variable(
@@ -317,3 +655,107 @@ impl Module {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{context, parsed};
+    use roan_ast::CallExpr;
+
+    #[test]
+    fn test_struct_traits_and_methods() {
+        let module = parsed(
+            "struct Point { x: int }\n\
+             trait Show { fn show(self) -> string { return \"\"; } }\n\
+             trait Reset { fn reset(self) {} }\n\
+             trait Unused {}\n\
+             impl Point { fn new() -> Point { return Point { x: 0 }; } }\n\
+             impl Show for Point { fn show(self) -> string { return \"p\"; } }\n\
+             impl Reset for Point { fn reset(self) {} }\n",
+            &mut context(),
+        );
+
+        let point = module.get_struct("Point", TextSpan::default()).unwrap();
+        let methods: Vec<&str> = point.all_methods().map(|m| m.name.as_str()).collect();
+        assert_eq!(methods, ["new", "show", "reset"]);
+
+        assert!(point.has_trait("Show"));
+        assert!(point.has_trait("Reset"));
+        assert!(!point.has_trait("Unused"));
+    }
+
+    #[test]
+    fn test_find_items() {
+        let module = parsed(
+            "const A = 1;\n\
+             const B = \"b\";\n\
+             const C = 3;\n\
+             trait Named {}\n\
+             fn f() {}\n",
+            &mut context(),
+        );
+
+        let ints: Vec<String> = module
+            .find_const_by_type("int")
+            .iter()
+            .map(|c| c.ident.literal())
+            .collect();
+        assert_eq!(ints, ["A", "C"]);
+        assert!(module.find_const_by_type("float").is_empty());
+
+        assert!(module.find_trait("Named").is_some());
+        assert!(module.find_trait("Missing").is_none());
+
+        assert!(module.has_function("f"));
+        assert!(module.has_function("__print"));
+        assert!(!module.has_function("g"));
+    }
+
+    #[test]
+    fn test_merge_module() {
+        let mut ctx = context();
+        let math = parsed(
+            "pub const TWO = 2;\n\
+             pub struct Pair { a: int, b: int }\n\
+             pub fn double(x: int) -> int { return x * TWO; }\n",
+            &mut ctx,
+        );
+        let mut module = parsed("fn triple(x: int) -> int { return x * 3; }\n", &mut ctx);
+
+        module.merge_module(&math).unwrap();
+
+        assert!(module.has_function("double"));
+        assert!(module.has_function("triple"));
+        assert!(module.find_const("TWO").is_some());
+        assert!(module.structs.iter().any(|s| s.name.literal() == "Pair"));
+
+        let call = CallExpr {
+            callee: "double".to_string(),
+            args: vec![Expr::new_integer(
+                Token::new(TokenKind::Integer(21), TextSpan::default()),
+                21,
+            )],
+            token: Token::new(TokenKind::Identifier, TextSpan::default()),
+        };
+        let result = module
+            .interpret_call(&call, &mut ctx, &mut VM::new())
+            .unwrap();
+        assert_eq!(result, Value::Int(42));
+    }
+
+    #[test]
+    fn test_merge_module_conflict() {
+        let mut ctx = context();
+        let other = parsed("pub fn helper() {}\npub const LIMIT = 1;\n", &mut ctx);
+        let mut module = parsed("fn helper() {}\n", &mut ctx);
+        let functions = module.functions.len();
+
+        let err = module.merge_module(&other).unwrap_err();
+        match err.downcast_ref::<roan_error::error::RoanError>() {
+            Some(DuplicateDefinition(name, _)) => assert_eq!(name, "helper"),
+            err => panic!("Expected duplicate definition, got {:?}", err),
+        }
+        assert_eq!(module.functions.len(), functions);
+        assert!(module.find_const("LIMIT").is_none());
+    }
+}
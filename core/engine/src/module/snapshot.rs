@@ -0,0 +1,154 @@
+use crate::{
+    module::{ExportType, Module, StoredStruct},
+    value::Value,
+};
+use std::collections::HashMap;
+
+/// State of a [Module] saved with [Module::snapshot], to roll back the variables declared and the
+/// items registered since with [Module::restore].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModuleSnapshot {
+    pub scopes: Vec<HashMap<String, Value>>,
+    pub functions_len: usize,
+    pub exports_len: usize,
+    pub structs_len: usize,
+    pub traits_len: usize,
+    pub consts_len: usize,
+    pub imports_len: usize,
+    /// Number of impls and trait impls of each struct, in the order of `structs`.
+    pub impls_len: Vec<(usize, usize)>,
+    /// Number of impls and trait impls of each exported struct, in the order of `exports`.
+    pub export_impls_len: Vec<Option<(usize, usize)>>,
+}
+
+impl Module {
+    /// Saves the variables of the module and how many items it has, e.g. before running untrusted
+    /// code in it.
+    ///
+    /// Items, and the impls of structs, are only ever added to the module, so only their number
+    /// is kept.
+    pub fn snapshot(&self) -> ModuleSnapshot {
+        ModuleSnapshot {
+            scopes: self.scopes.clone(),
+            functions_len: self.functions.len(),
+            exports_len: self.exports.len(),
+            structs_len: self.structs.len(),
+            traits_len: self.traits.len(),
+            consts_len: self.consts.len(),
+            imports_len: self.imports.len(),
+            impls_len: self.structs.iter().map(impls_len).collect(),
+            export_impls_len: self
+                .exports
+                .iter()
+                .map(|(_, export)| match export {
+                    ExportType::Struct(s) => Some(impls_len(s)),
+                    _ => None,
+                })
+                .collect(),
+        }
+    }
+
+    /// Rolls the module back to `snapshot`: the variables get the values they had and the items
+    /// and impls registered since are removed.
+    ///
+    /// The copy of the module known by the context isn't changed, call
+    /// [Context::upsert_module](crate::context::Context::upsert_module) with the module to roll it
+    /// back too.
+    pub fn restore(&mut self, snapshot: ModuleSnapshot) {
+        self.scopes = snapshot.scopes;
+        self.functions.truncate(snapshot.functions_len);
+        self.exports.truncate(snapshot.exports_len);
+        self.structs.truncate(snapshot.structs_len);
+        self.traits.truncate(snapshot.traits_len);
+        self.consts.truncate(snapshot.consts_len);
+        self.imports.truncate(snapshot.imports_len);
+
+        for (s, len) in self.structs.iter_mut().zip(snapshot.impls_len) {
+            truncate_impls(s, len);
+        }
+        for ((_, export), len) in self.exports.iter_mut().zip(snapshot.export_impls_len) {
+            if let (ExportType::Struct(s), Some(len)) = (export, len) {
+                truncate_impls(s, len);
+            }
+        }
+    }
+}
+
+fn impls_len(s: &StoredStruct) -> (usize, usize) {
+    (s.impls.len(), s.trait_impls.len())
+}
+
+fn truncate_impls(s: &mut StoredStruct, (impls, trait_impls): (usize, usize)) {
+    s.impls.truncate(impls);
+    s.trait_impls.truncate(trait_impls);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        interpreter::passes::{resolver::ResolverPass, Pass},
+        test_utils::{context, parsed},
+        vm::VM,
+    };
+    use roan_ast::{source::Source, Lexer, Parser};
+    use roan_error::TextSpan;
+
+    #[test]
+    fn test_snapshot_and_restore() {
+        let mut ctx = context();
+        let mut vm = VM::new();
+        let mut module = parsed(
+            "let count = 1;\npub fn safe() -> int { return 1; }\npub struct Point { x: int }\n",
+            &mut ctx,
+        );
+        module.interpret(&mut ctx, &mut vm).unwrap();
+        let snapshot = module.snapshot();
+
+        // Untrusted code changing a variable, declaring one, registering items and adding
+        // methods to an existing struct
+        let untrusted = "count = 5;\n\
+                         let secret = \"s\";\n\
+                         pub fn evil() {}\n\
+                         struct Loot { x: int }\n\
+                         const LIMIT = 3;\n\
+                         trait Steal { fn steal(self) -> int { return 0; } }\n\
+                         impl Point { fn leak(self) -> int { return 1; } }\n\
+                         impl Steal for Point { fn steal(self) -> int { return 2; } }\n";
+        let tokens = Lexer::new(Source::from_string(untrusted.to_string()))
+            .lex(false)
+            .unwrap();
+        for stmt in Parser::new(tokens).parse().unwrap().stmts {
+            ResolverPass
+                .pass_stmt(stmt.clone(), &mut module, &mut ctx, &mut vm)
+                .unwrap();
+            module.interpret_stmt(&stmt, &mut ctx, &mut vm).unwrap();
+        }
+        assert_eq!(module.find_variable("count"), Some(&Value::Int(5)));
+        assert!(module.find_function("evil").is_some());
+        assert!(module.find_trait("Steal").is_some());
+        let point = module.get_struct("Point", TextSpan::default()).unwrap();
+        assert_eq!((point.impls.len(), point.trait_impls.len()), (1, 1));
+        assert_ne!(module.snapshot(), snapshot);
+
+        module.restore(snapshot.clone());
+        assert_eq!(module.snapshot(), snapshot);
+        assert_eq!(module.find_variable("count"), Some(&Value::Int(1)));
+        assert!(module.find_variable("secret").is_none());
+        assert!(module.find_function("evil").is_none());
+        assert!(module.find_export("evil").is_none());
+        assert!(module.get_struct("Loot", TextSpan::default()).is_err());
+        assert!(module.find_const("LIMIT").is_none());
+        assert!(module.find_trait("Steal").is_none());
+        let point = module.get_struct("Point", TextSpan::default()).unwrap();
+        assert!(point.impls.is_empty());
+        assert!(point.trait_impls.is_empty());
+        let Some(ExportType::Struct(point)) = module.find_export("Point") else {
+            panic!("Point isn't exported");
+        };
+        assert!(point.impls.is_empty());
+        assert!(point.trait_impls.is_empty());
+        assert!(module.find_function("safe").is_some());
+        assert!(module.find_export("safe").is_some());
+    }
+}
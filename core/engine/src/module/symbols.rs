@@ -0,0 +1,370 @@
+use crate::module::{Module, StoredFunction};
+use roan_ast::{Block, Fn, Let, Stmt, Token, TypeAnnotation, Visitor};
+use roan_error::TextSpan;
+
+/// Kind of a [Symbol].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SymbolKind {
+    Function,
+    NativeFunction,
+    Struct,
+    Trait,
+    Const,
+    Variable,
+    Parameter,
+}
+
+/// A named item that can be referenced from a module, used by tooling such as autocomplete.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Symbol {
+    pub name: String,
+    pub kind: SymbolKind,
+    /// Type of the symbol, e.g. the return type of a function or the annotation of a variable.
+    /// `None` if the type isn't written in the source.
+    pub typ: Option<String>,
+    /// Id of the module the symbol is declared in, `None` for natives and local symbols.
+    pub defining_module: Option<String>,
+}
+
+impl Symbol {
+    fn new(name: String, kind: SymbolKind, typ: Option<String>, module: Option<String>) -> Self {
+        Self {
+            name,
+            kind,
+            typ,
+            defining_module: module,
+        }
+    }
+}
+
+/// A variable or parameter declared somewhere in the module, with the scope it's visible in.
+#[derive(Debug, Clone)]
+pub(crate) struct ScopedDeclaration {
+    symbol: Symbol,
+    /// Index of the identifier in the source.
+    index: usize,
+    /// Start and exclusive end of the block the symbol belongs to, the whole module for top-level
+    /// variables.
+    scope: (usize, usize),
+}
+
+impl ScopedDeclaration {
+    /// Whether the symbol can be referenced at `index`: after both its declaration and the start
+    /// of its scope, e.g. the opening brace of the body for a parameter.
+    fn is_visible_at(&self, index: usize) -> bool {
+        self.index.max(self.scope.0) < index && index < self.scope.1
+    }
+}
+
+impl Module {
+    /// Returns every function that can be called from the module, including imported and native
+    /// functions.
+    pub fn all_functions(&self) -> Vec<Symbol> {
+        self.functions
+            .iter()
+            .map(|func| match func {
+                StoredFunction::Native(native) => {
+                    Symbol::new(native.name.clone(), SymbolKind::NativeFunction, None, None)
+                }
+                StoredFunction::Function {
+                    function,
+                    defining_module,
+                } => Symbol::new(
+                    function.name.clone(),
+                    SymbolKind::Function,
+                    function.return_type.as_ref().map(TypeAnnotation::to_string),
+                    Some(defining_module.clone()),
+                ),
+            })
+            .collect()
+    }
+
+    /// Returns every struct declared in or imported into the module.
+    pub fn all_structs(&self) -> Vec<Symbol> {
+        self.structs
+            .iter()
+            .map(|s| {
+                Symbol::new(
+                    s.name.literal(),
+                    SymbolKind::Struct,
+                    None,
+                    Some(s.defining_module.clone()),
+                )
+            })
+            .collect()
+    }
+
+    /// Returns every trait declared in or imported into the module.
+    pub fn all_traits(&self) -> Vec<Symbol> {
+        self.traits
+            .iter()
+            .map(|t| Symbol::new(t.name.literal(), SymbolKind::Trait, None, None))
+            .collect()
+    }
+
+    /// Returns every const declared in or imported into the module.
+    pub fn all_consts(&self) -> Vec<Symbol> {
+        self.consts
+            .iter()
+            .map(|c| {
+                Symbol::new(
+                    c.ident.literal(),
+                    SymbolKind::Const,
+                    Some(c.value.type_name()),
+                    Some(c.defining_module.clone()),
+                )
+            })
+            .collect()
+    }
+
+    /// Returns the functions, structs, traits and consts of the module.
+    pub fn all_symbols(&self) -> Vec<Symbol> {
+        let mut symbols = self.all_functions();
+        symbols.extend(self.all_structs());
+        symbols.extend(self.all_traits());
+        symbols.extend(self.all_consts());
+        symbols
+    }
+
+    /// Returns the variables and parameters visible at the start of `span`.
+    ///
+    /// Only declarations written before the position are returned. If a name is declared more
+    /// than once, the innermost declaration is returned. The module has to be parsed first.
+    pub fn symbols_in_scope(&self, span: &TextSpan) -> Vec<Symbol> {
+        let mut visible: Vec<&ScopedDeclaration> = self
+            .scoped_declarations()
+            .iter()
+            .filter(|declaration| declaration.is_visible_at(span.start.index))
+            .collect();
+        // The scopes visible at a position are nested, so inner scopes start later
+        visible.sort_by_key(|declaration| (declaration.scope.0, declaration.index));
+
+        let mut symbols: Vec<Symbol> = vec![];
+        for declaration in visible {
+            symbols.retain(|s| s.name != declaration.symbol.name);
+            symbols.push(declaration.symbol.clone());
+        }
+        symbols
+    }
+
+    /// Returns the variables and parameters declared in the module, collected from the AST the
+    /// first time they're needed.
+    fn scoped_declarations(&self) -> &[ScopedDeclaration] {
+        self.scoped_declarations.get_or_init(|| {
+            let mut collector = DeclarationCollector::default();
+            self.ast.walk(&mut collector);
+            collector.finish()
+        })
+    }
+}
+
+/// Collects every variable and parameter declared in the module with its scope.
+#[derive(Default)]
+struct DeclarationCollector {
+    declarations: Vec<ScopedDeclaration>,
+    /// Variables declared with `let`, their scope is only known once every block is.
+    lets: Vec<(usize, Symbol)>,
+    /// Start and end of every block of the module.
+    blocks: Vec<(usize, usize)>,
+}
+
+impl DeclarationCollector {
+    /// Declares a symbol visible in `block`, e.g. a parameter in the body of its function.
+    fn declare(
+        &mut self,
+        ident: &Token,
+        kind: SymbolKind,
+        typ: Option<&TypeAnnotation>,
+        block: &Block,
+    ) {
+        self.declarations.push(ScopedDeclaration {
+            symbol: symbol(ident, kind, typ),
+            index: ident.span.start.index,
+            scope: (block.span.start.index, block.span.end.index),
+        });
+    }
+
+    /// Scopes the `let` variables to the innermost block they're declared in.
+    fn finish(mut self) -> Vec<ScopedDeclaration> {
+        for (index, symbol) in self.lets {
+            let scope = self
+                .blocks
+                .iter()
+                .filter(|(start, end)| (*start..*end).contains(&index))
+                .max_by_key(|(start, _)| *start)
+                .copied()
+                .unwrap_or((0, usize::MAX));
+
+            self.declarations.push(ScopedDeclaration {
+                symbol,
+                index,
+                scope,
+            });
+        }
+
+        self.declarations
+    }
+}
+
+fn symbol(ident: &Token, kind: SymbolKind, typ: Option<&TypeAnnotation>) -> Symbol {
+    Symbol::new(
+        ident.literal(),
+        kind,
+        typ.map(TypeAnnotation::to_string),
+        None,
+    )
+}
+
+impl Visitor for DeclarationCollector {
+    fn visit_stmt(&mut self, stmt: &Stmt) {
+        match stmt {
+            Stmt::For(for_stmt) => {
+                let block = &for_stmt.block;
+                self.declare(&for_stmt.ident, SymbolKind::Variable, None, block);
+                if let Some(value_ident) = &for_stmt.value_ident {
+                    self.declare(value_ident, SymbolKind::Variable, None, block);
+                }
+            }
+            Stmt::Try(try_stmt) => {
+                self.declare(
+                    &try_stmt.error_ident,
+                    SymbolKind::Variable,
+                    None,
+                    &try_stmt.catch_block,
+                );
+            }
+            _ => {}
+        }
+    }
+
+    fn visit_fn(&mut self, func: &Fn) {
+        for param in func.params.iter() {
+            self.declare(
+                &param.ident,
+                SymbolKind::Parameter,
+                Some(&param.type_annotation),
+                &func.body,
+            );
+        }
+    }
+
+    fn visit_block(&mut self, block: &Block) {
+        self.blocks
+            .push((block.span.start.index, block.span.end.index));
+    }
+
+    fn visit_let(&mut self, let_stmt: &Let) {
+        let symbol = symbol(
+            &let_stmt.ident,
+            SymbolKind::Variable,
+            let_stmt.type_annotation.as_ref(),
+        );
+        self.lets.push((let_stmt.ident.span.start.index, symbol));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use roan_ast::{source::Source, Lexer, Parser};
+    use roan_error::Position;
+
+    fn module(src: &str) -> Module {
+        let mut module = Module::new(Source::from_string(src.to_string()));
+        module.tokens = Lexer::new(module.source.clone()).lex(false).unwrap();
+        module.ast = Parser::new(module.tokens.clone()).parse().unwrap();
+        module
+    }
+
+    /// Returns the names of the symbols visible at the first occurrence of `marker`.
+    fn names_at(module: &Module, marker: &str) -> Vec<String> {
+        let index = module.source.content().find(marker).unwrap();
+        let position = Position::new(0, 0, index);
+        let span = TextSpan::new(position, position, String::new());
+
+        module
+            .symbols_in_scope(&span)
+            .into_iter()
+            .map(|s| s.name)
+            .collect()
+    }
+
+    #[test]
+    fn test_symbols_in_scope() {
+        let module = module(
+            "let top: int = 1;\n\
+             fn add(a: int, b: int) -> int {\n\
+                 let sum = a + b;\n\
+                 if sum > 0 {\n\
+                     let inner = 1;\n\
+                 }\n\
+                 return sum; // here\n\
+             }\n\
+             fn other(c: string) {\n\
+                 return c; // other\n\
+             }\n",
+        );
+
+        assert_eq!(names_at(&module, "// here"), ["top", "a", "b", "sum"]);
+        assert_eq!(names_at(&module, "// other"), ["top", "c"]);
+        assert_eq!(names_at(&module, "let top"), Vec::<String>::new());
+
+        let symbols = module.symbols_in_scope(&TextSpan::new(
+            Position::new(0, 0, module.source.content().find("// here").unwrap()),
+            Position::new(0, 0, 0),
+            String::new(),
+        ));
+        assert_eq!(symbols[1].kind, SymbolKind::Parameter);
+        assert_eq!(symbols[1].typ.as_deref(), Some("int"));
+        assert_eq!(symbols[3].kind, SymbolKind::Variable);
+    }
+
+    #[test]
+    fn test_symbols_in_for_loop() {
+        let module = module(
+            "for item in [1, 2] {\n\
+                 let x = item; // body\n\
+             }\n\
+             let after = 1; // after\n",
+        );
+
+        assert_eq!(names_at(&module, "// body"), ["item", "x"]);
+        assert_eq!(names_at(&module, "// after"), ["after"]);
+    }
+
+    #[test]
+    fn test_symbols_in_nested_scopes() {
+        let module = module(
+            "let x = 1;\n\
+             fn f(y: int) {\n\
+                 try {\n\
+                     let x = \"inner\"; // try\n\
+                 } catch err {\n\
+                     let y = 2; // catch\n\
+                 }\n\
+                 return y; // end\n\
+             }\n",
+        );
+
+        assert_eq!(names_at(&module, "// try"), ["y", "x"]);
+        assert_eq!(names_at(&module, "// catch"), ["x", "err", "y"]);
+        assert_eq!(names_at(&module, "// end"), ["x", "y"]);
+
+        let symbols = module.symbols_in_scope(&TextSpan::new(
+            Position::new(0, 0, module.source.content().find("// catch").unwrap()),
+            Position::new(0, 0, 0),
+            String::new(),
+        ));
+        assert_eq!(symbols[2].kind, SymbolKind::Variable);
+    }
+
+    #[test]
+    fn test_all_functions_include_natives() {
+        let module = module("fn add(a: int, b: int) -> int { return a + b; }");
+
+        assert!(module
+            .all_functions()
+            .iter()
+            .any(|f| f.kind == SymbolKind::NativeFunction));
+    }
+}
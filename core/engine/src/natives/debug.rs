@@ -1,15 +1,50 @@
 use crate::{
-    as_cast, native_function,
+    as_cast,
+    context::{Context, OutputSink},
+    native_function,
     value::Value,
     vm::native_fn::{NativeFunction, NativeFunctionParam},
 };
+use anyhow::Result;
 use roan_ast::TypeKind;
+use std::{
+    cell::RefCell,
+    io::{self, Write},
+};
+
+thread_local! {
+    /// Output and error output of the context calling the current native, set by [with_output].
+    static OUTPUT: RefCell<Option<(OutputSink, OutputSink)>> = const { RefCell::new(None) };
+}
+
+/// Runs `f` with the output natives writing to the sinks of `ctx`.
+///
+/// Natives called outside of it, e.g. directly in tests, write to stdout and stderr.
+pub fn with_output<T>(ctx: &Context, f: impl FnOnce() -> T) -> T {
+    let previous =
+        OUTPUT.with(|output| output.replace(Some((ctx.output.clone(), ctx.error_output.clone()))));
+    let result = f();
+    OUTPUT.with(|output| output.replace(previous));
+
+    result
+}
+
+fn write_output(msg: &str, error: bool) -> Result<()> {
+    OUTPUT.with(|output| match &*output.borrow() {
+        Some((_, sink)) if error => sink.write_str(msg),
+        Some((sink, _)) => sink.write_str(msg),
+        None if error => io::stderr().write_all(msg.as_bytes()),
+        None => io::stdout().write_all(msg.as_bytes()),
+    })?;
+
+    Ok(())
+}
 
 native_function!(fn __print(
     msg
 ) {
     let msg = as_cast!(msg, String);
-    print!("{}", msg);
+    write_output(&msg, false)?;
 
     Value::Void
 });
@@ -18,7 +53,7 @@ native_function!(fn __eprint(
     msg
 ) {
     let msg = as_cast!(msg, String);
-    eprint!("{}", msg);
+    write_output(&msg, true)?;
     Value::Void
 });
 
@@ -27,3 +62,34 @@ native_function!(fn __format(
 ) {
   Value::String(format!("{}", msg.to_string()))
 });
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        test_utils::{context, parsed, SharedBuffer},
+        vm::VM,
+    };
+
+    #[test]
+    fn test_capture_output() {
+        let mut ctx = context();
+        let (output, error_output) = (SharedBuffer::default(), SharedBuffer::default());
+        ctx.set_output(Box::new(output.clone()));
+        ctx.set_error_output(Box::new(error_output.clone()));
+
+        let mut module = parsed(
+            "fn greet(name: string) { __print(\"hello \" + name + \"\\n\"); }\n\
+             greet(\"roan\");\n\
+             __eprint(\"oops\");\n\
+             __print(__format([1, 2]));\n",
+            &mut ctx,
+        );
+        module.interpret(&mut ctx, &mut VM::new()).unwrap();
+
+        assert_eq!(
+            String::from_utf8(output.0.take()).unwrap(),
+            "hello roan\n[1, 2]"
+        );
+        assert_eq!(String::from_utf8(error_output.0.take()).unwrap(), "oops");
+    }
+}
@@ -0,0 +1,131 @@
+use crate::{
+    native_function, throw,
+    value::Value,
+    vm::native_fn::{NativeFunction, NativeFunctionParam},
+};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use roan_ast::TypeKind;
+
+native_function!(
+    fn to_hex(bytes) {
+        match bytes {
+            Value::Bytes(bytes) => Value::String(hex::encode(bytes)),
+            other => throw!("to_hex expects bytes, got {}", other.type_name()),
+        }
+    }
+);
+
+native_function!(
+    fn from_hex(s) {
+        let Value::String(s) = s else {
+            throw!("from_hex expects a string, got {}", s.type_name());
+        };
+
+        // Both lowercase and uppercase digits are accepted
+        match hex::decode(&s) {
+            Ok(bytes) => Value::Bytes(bytes),
+            Err(_) => throw!("Invalid hex string: {}", s),
+        }
+    }
+);
+
+native_function!(
+    fn to_base64(bytes) {
+        match bytes {
+            Value::Bytes(bytes) => Value::String(BASE64.encode(bytes)),
+            other => throw!("to_base64 expects bytes, got {}", other.type_name()),
+        }
+    }
+);
+
+native_function!(
+    fn from_base64(s) {
+        let Value::String(s) = s else {
+            throw!("from_base64 expects a string, got {}", s.type_name());
+        };
+
+        // Only padded base64 written with the standard alphabet is accepted
+        match BASE64.decode(&s) {
+            Ok(bytes) => Value::Bytes(bytes),
+            Err(_) => throw!("Invalid base64 string: {}", s),
+        }
+    }
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode(native: NativeFunction, s: &str) -> Option<Vec<u8>> {
+        match (native.func)(vec![Value::String(s.to_string())]) {
+            Ok(Value::Bytes(bytes)) => Some(bytes),
+            _ => None,
+        }
+    }
+
+    fn encode(native: NativeFunction, bytes: &[u8]) -> Value {
+        (native.func)(vec![Value::Bytes(bytes.to_vec())]).unwrap()
+    }
+
+    #[test]
+    fn test_hex() {
+        assert_eq!(decode(from_hex(), "ff"), Some(vec![255]));
+        assert_eq!(decode(from_hex(), "00A1ff"), Some(vec![0, 161, 255]));
+        assert_eq!(decode(from_hex(), ""), Some(vec![]));
+        assert_eq!(decode(from_hex(), "f"), None);
+        assert_eq!(decode(from_hex(), "zz"), None);
+
+        assert_eq!(
+            encode(to_hex(), &[0, 161, 255]),
+            Value::String("00a1ff".to_string())
+        );
+    }
+
+    #[test]
+    fn test_base64() {
+        for (bytes, encoded) in [
+            ("", ""),
+            ("f", "Zg=="),
+            ("fo", "Zm8="),
+            ("foo", "Zm9v"),
+            ("foobar", "Zm9vYmFy"),
+        ] {
+            assert_eq!(
+                encode(to_base64(), bytes.as_bytes()),
+                Value::String(encoded.to_string())
+            );
+            assert_eq!(
+                decode(from_base64(), encoded),
+                Some(bytes.as_bytes().to_vec())
+            );
+        }
+
+        assert_eq!(decode(from_base64(), "Zg="), None);
+        assert_eq!(decode(from_base64(), "Zg==Zm9v"), None);
+        assert_eq!(decode(from_base64(), "Z==="), None);
+        assert_eq!(decode(from_base64(), "Zm9!"), None);
+    }
+
+    #[test]
+    fn test_invalid_input_throws() {
+        let err = (from_hex().func)(vec![Value::String("xyz".to_string())]).unwrap_err();
+
+        assert!(matches!(
+            err.downcast_ref::<crate::error::RoanError>(),
+            Some(crate::error::RoanError::Throw(msg, _)) if msg == "Invalid hex string: xyz"
+        ));
+
+        let err = (to_hex().func)(vec![Value::Int(1)]).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<crate::error::RoanError>(),
+            Some(crate::error::RoanError::Throw(msg, _)) if msg == "to_hex expects bytes, got int"
+        ));
+
+        let err = (to_base64().func)(vec![Value::String("foo".to_string())]).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<crate::error::RoanError>(),
+            Some(crate::error::RoanError::Throw(msg, _))
+                if msg == "to_base64 expects bytes, got string"
+        ));
+    }
+}
@@ -0,0 +1,44 @@
+use crate::{
+    as_cast, native_function, throw,
+    value::Value,
+    vm::native_fn::{NativeFunction, NativeFunctionParam},
+};
+use roan_ast::TypeKind;
+
+native_function!(
+    fn read_file(path) {
+        let path = as_cast!(path, String);
+
+        match std::fs::read_to_string(&path) {
+            Ok(content) => Value::String(content),
+            Err(err) => throw!("Failed to read {}: {}", path, err),
+        }
+    }
+);
+
+native_function!(
+    fn read_file_bytes(path) {
+        let path = as_cast!(path, String);
+
+        match std::fs::read(&path) {
+            Ok(content) => Value::Bytes(content),
+            Err(err) => throw!("Failed to read {}: {}", path, err),
+        }
+    }
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_file_bytes() {
+        let content = (read_file_bytes().func)(vec![Value::String("Cargo.toml".to_string())]);
+
+        assert_eq!(
+            content.unwrap(),
+            Value::Bytes(std::fs::read("Cargo.toml").unwrap())
+        );
+        assert!((read_file_bytes().func)(vec![Value::String("missing.bin".to_string())]).is_err());
+    }
+}
@@ -0,0 +1,101 @@
+use crate::{
+    native_function, throw,
+    value::Value,
+    vm::native_fn::{NativeFunction, NativeFunctionParam},
+};
+use md5::Md5;
+use roan_ast::TypeKind;
+use sha2::{Digest, Sha256};
+
+native_function!(
+    fn sha256(data) {
+        match hashable_bytes(&data) {
+            Some(bytes) => Value::String(sha256_hex(bytes)),
+            None => throw!("sha256 expects a string or bytes, got {}", data.type_name()),
+        }
+    }
+);
+
+native_function!(
+    fn md5(data) {
+        match hashable_bytes(&data) {
+            Some(bytes) => Value::String(hex::encode(Md5::digest(bytes))),
+            None => throw!("md5 expects a string or bytes, got {}", data.type_name()),
+        }
+    }
+);
+
+/// Returns the bytes hashed for `value`, strings are hashed as UTF-8.
+fn hashable_bytes(value: &Value) -> Option<&[u8]> {
+    match value {
+        Value::String(s) => Some(s.as_bytes()),
+        Value::Bytes(bytes) => Some(bytes),
+        _ => None,
+    }
+}
+
+/// Returns the SHA-256 digest of `data` as lowercase hex, the same as the `sha256` native.
+pub fn sha256_hex(data: &[u8]) -> String {
+    hex::encode(Sha256::digest(data))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(native: NativeFunction, value: Value) -> anyhow::Result<Value> {
+        (native.func)(vec![value])
+    }
+
+    #[test]
+    fn test_sha256() {
+        for (input, digest) in [
+            (
+                "",
+                "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+            ),
+            (
+                "abc",
+                "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+            ),
+            (
+                "abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq",
+                "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1",
+            ),
+        ] {
+            assert_eq!(
+                call(sha256(), Value::String(input.to_string())).unwrap(),
+                Value::String(digest.to_string())
+            );
+        }
+    }
+
+    #[test]
+    fn test_md5() {
+        for (input, digest) in [
+            ("", "d41d8cd98f00b204e9800998ecf8427e"),
+            ("abc", "900150983cd24fb0d6963f7d28e17f72"),
+            (
+                "The quick brown fox jumps over the lazy dog",
+                "9e107d9d372bb6826bd81d3542a419d6",
+            ),
+        ] {
+            assert_eq!(
+                call(md5(), Value::String(input.to_string())).unwrap(),
+                Value::String(digest.to_string())
+            );
+        }
+
+        assert_eq!(
+            call(md5(), Value::Bytes(b"abc".to_vec())).unwrap(),
+            Value::String("900150983cd24fb0d6963f7d28e17f72".to_string())
+        );
+    }
+
+    #[test]
+    fn test_unsupported_type() {
+        let err = call(sha256(), Value::Int(1)).unwrap_err();
+
+        assert_eq!(err.to_string(), "sha256 expects a string or bytes, got int");
+    }
+}
@@ -1,21 +1,36 @@
 use crate::{
+    context::Capability,
     module::StoredFunction,
     natives::{
         debug::{__eprint, __format, __print},
-        process::{__abort, __exit, __pid},
+        encoding::{from_base64, from_hex, to_base64, to_hex},
+        fs::{read_file, read_file_bytes},
+        hash::{md5, sha256},
+        prelude::{abs, assert, len, max, min, sqrt},
+        process::{__abort, __exit, __pid, env},
     },
     value::Value,
     vm::native_fn::{NativeFunction, NativeFunctionParam},
 };
 use roan_ast::TypeKind;
-use std::{panic, panic::panic_any};
 
 pub mod debug;
+mod encoding;
+mod fs;
+pub mod hash;
+pub mod prelude;
 mod process;
 
 #[macro_export]
 macro_rules! native_function {
+    // The body never returns a value, e.g. it always throws or exits
+    (fn $name:ident($($arg:ident),* $(, ...$rest:ident)?) -> ! {$($body:tt)*}) => {
+        $crate::native_function!(@define $name($($arg),* $(, ...$rest)?) {$($body)*});
+    };
     (fn $name:ident($($arg:ident),* $(, ...$rest:ident)?) {$($body:tt)*}) => {
+        $crate::native_function!(@define $name($($arg),* $(, ...$rest)?) {Ok({$($body)*})});
+    };
+    (@define $name:ident($($arg:ident),* $(, ...$rest:ident)?) {$($body:tt)*}) => {
         #[allow(unused_mut, unused_variables)]
         pub fn $name() -> NativeFunction {
             NativeFunction {
@@ -51,6 +66,7 @@ macro_rules! native_function {
                         },
                     )?
                 ],
+                capability: None,
             }
         }
     };
@@ -67,6 +83,15 @@ macro_rules! as_cast {
     };
 }
 
+/// Returns from a native function with an error that can be caught with `try`/`catch`, the
+/// arguments are formatted like [format!].
+#[macro_export]
+macro_rules! throw {
+    ($($arg:tt)*) => {
+        return Err($crate::error::RoanError::Throw(format!($($arg)*), vec![]).into())
+    };
+}
+
 native_function!(
     fn type_of(value) {
         Value::String(value.type_name())
@@ -74,40 +99,64 @@ native_function!(
 );
 
 native_function!(
-    fn __panic(msg) {
-        let msg = as_cast!(msg, String);
-
-        // Save panic hook to restore it later
-        let old_hook = std::panic::take_hook();
-
-        panic::set_hook(Box::new(|panic_info| {
-            let payload = panic_info.payload().downcast_ref::<String>().unwrap();
-            eprintln!("program panicked");
-            eprintln!("{}", payload);
-        }));
-
-        panic_any(msg);
-
-        // Restore the original hook afterward if needed.
-        #[allow(unreachable_code)]
-        panic::set_hook(old_hook);
-
-        Value::Void
+    fn __panic(msg) -> ! {
+        // Raised as an error rather than a Rust panic, so scripts can catch it and the panic hook
+        // of the host is left alone
+        throw!("{}", msg)
     }
 );
 
+/// Returns the natives of the default [Prelude](prelude::Prelude).
 pub fn get_stored_function() -> Vec<StoredFunction> {
     vec![
         __print(),
         __format(),
         __eprint(),
-        __exit(),
-        __abort(),
-        __pid(),
+        __exit().requires(Capability::Process),
+        __abort().requires(Capability::Process),
+        __pid().requires(Capability::Process),
+        env().requires(Capability::Env),
+        read_file().requires(Capability::Fs),
+        read_file_bytes().requires(Capability::Fs),
         type_of(),
         __panic(),
+        to_hex(),
+        from_hex(),
+        to_base64(),
+        from_base64(),
+        sha256(),
+        md5(),
+        len(),
+        assert(),
+        abs(),
+        min(),
+        max(),
+        sqrt(),
     ]
     .into_iter()
     .map(|f| StoredFunction::Native(f))
     .collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        test_utils::{context, parsed, SharedBuffer},
+        vm::VM,
+    };
+
+    #[test]
+    fn test_panic_can_be_caught() {
+        let mut ctx = context();
+        let output = SharedBuffer::default();
+        ctx.set_output(Box::new(output.clone()));
+
+        let mut module = parsed(
+            "fn fail() { __panic(\"boom\"); }\n\
+             try { fail(); } catch e { __print(e); }\n",
+            &mut ctx,
+        );
+        module.interpret(&mut ctx, &mut VM::new()).unwrap();
+        assert_eq!(String::from_utf8(output.0.take()).unwrap(), "boom");
+    }
+}
@@ -0,0 +1,228 @@
+use crate::{
+    module::StoredFunction,
+    native_function,
+    natives::get_stored_function,
+    throw,
+    value::Value,
+    vm::native_fn::{NativeFunction, NativeFunctionParam},
+};
+use roan_ast::TypeKind;
+
+/// Functions in scope in every module without being imported.
+///
+/// The default prelude has the natives the standard library is built on, like `__print`, and
+/// common helpers: `len`, `assert`, `abs`, `min`, `max` and `sqrt`. A function declared in a module
+/// with the name of a prelude function shadows it.
+///
+/// The prelude of a [Context] is used for every module parsed with it, embedders can add their own
+/// natives with [Prelude::with] or run scripts in a minimal environment with [Prelude::empty].
+///
+/// [Context]: crate::context::Context
+#[derive(Clone, Debug)]
+pub struct Prelude {
+    functions: Vec<NativeFunction>,
+}
+
+impl Prelude {
+    /// Creates the default prelude.
+    pub fn new() -> Self {
+        let functions = get_stored_function()
+            .into_iter()
+            .filter_map(|f| match f {
+                StoredFunction::Native(native) => Some(native),
+                StoredFunction::Function { .. } => None,
+            })
+            .collect();
+
+        Self { functions }
+    }
+
+    /// Creates a prelude without any function, not even the natives the standard library needs.
+    pub fn empty() -> Self {
+        Self { functions: vec![] }
+    }
+
+    /// Adds `function` to the prelude, replacing the function with the same name if there's one.
+    pub fn with(mut self, function: NativeFunction) -> Self {
+        self.functions.retain(|f| f.name != function.name);
+        self.functions.push(function);
+        self
+    }
+
+    /// Removes the function called `name` from the prelude.
+    pub fn without(mut self, name: &str) -> Self {
+        self.functions.retain(|f| f.name != name);
+        self
+    }
+
+    /// Returns `true` if the prelude has a function called `name`.
+    pub fn contains(&self, name: &str) -> bool {
+        self.functions.iter().any(|f| f.name == name)
+    }
+
+    /// Returns the functions of the prelude.
+    pub fn functions(&self) -> &[NativeFunction] {
+        &self.functions
+    }
+}
+
+impl Default for Prelude {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+native_function!(
+    fn len(value) {
+        match value {
+            Value::String(s) => Value::Int(s.chars().count() as i64),
+            Value::Vec(v) => Value::Int(v.len() as i64),
+            Value::Object(fields) => Value::Int(fields.len() as i64),
+            Value::Bytes(bytes) => Value::Int(bytes.len() as i64),
+            _ => throw!("len expects a string, vec, object or bytes, got {}", value.type_name()),
+        }
+    }
+);
+
+native_function!(
+    fn assert(condition, ...message) {
+        if !condition.is_truthy() {
+            match message.first() {
+                Some(message) => throw!("Assertion failed: {}", message),
+                None => throw!("Assertion failed"),
+            }
+        }
+
+        Value::Void
+    }
+);
+
+native_function!(
+    fn abs(value) {
+        match value {
+            Value::Int(i) => Value::Int(i.abs()),
+            Value::Float(f) => Value::Float(f.abs()),
+            _ => throw!("abs expects a number, got {}", value.type_name()),
+        }
+    }
+);
+
+native_function!(
+    fn min(a, b) {
+        match (&a, &b) {
+            (Value::Int(x), Value::Int(y)) => Value::Int(*x.min(y)),
+            _ => match (as_float(&a), as_float(&b)) {
+                (Some(x), Some(y)) => Value::Float(x.min(y)),
+                _ => throw!("min expects numbers, got {} and {}", a.type_name(), b.type_name()),
+            },
+        }
+    }
+);
+
+native_function!(
+    fn max(a, b) {
+        match (&a, &b) {
+            (Value::Int(x), Value::Int(y)) => Value::Int(*x.max(y)),
+            _ => match (as_float(&a), as_float(&b)) {
+                (Some(x), Some(y)) => Value::Float(x.max(y)),
+                _ => throw!("max expects numbers, got {} and {}", a.type_name(), b.type_name()),
+            },
+        }
+    }
+);
+
+native_function!(
+    fn sqrt(value) {
+        match as_float(&value) {
+            Some(f) => Value::Float(f.sqrt()),
+            None => throw!("sqrt expects a number, got {}", value.type_name()),
+        }
+    }
+);
+
+/// Returns the value of a number as a float, ints are converted.
+fn as_float(value: &Value) -> Option<f64> {
+    match value {
+        Value::Int(i) => Some(*i as f64),
+        Value::Float(f) => Some(*f),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        module::Module,
+        test_utils::{context, parsed, SharedBuffer},
+        vm::VM,
+    };
+    use roan_ast::source::Source;
+
+    #[test]
+    fn test_prelude() {
+        let prelude = Prelude::new();
+        assert!(prelude.contains("__print"));
+        assert!(prelude.contains("len"));
+        assert!(!Prelude::empty().contains("__print"));
+
+        let prelude = Prelude::empty().with(len()).with(len()).without("abs");
+        assert_eq!(prelude.functions().len(), 1);
+        assert!(!prelude.contains("abs"));
+    }
+
+    #[test]
+    fn test_helpers() {
+        assert_eq!(
+            len().call(vec![Value::String("héllo".into())]).unwrap(),
+            Value::Int(5)
+        );
+        assert!(len().call(vec![Value::Int(1)]).is_err());
+        assert!(assert().call(vec![Value::Bool(true)]).is_ok());
+        assert!(assert().call(vec![Value::Bool(false)]).is_err());
+        assert_eq!(
+            min().call(vec![Value::Int(3), Value::Float(1.5)]).unwrap(),
+            Value::Float(1.5)
+        );
+        assert_eq!(
+            max().call(vec![Value::Int(3), Value::Int(7)]).unwrap(),
+            Value::Int(7)
+        );
+        assert_eq!(abs().call(vec![Value::Int(-2)]).unwrap(), Value::Int(2));
+        assert_eq!(sqrt().call(vec![Value::Int(9)]).unwrap(), Value::Float(3.0));
+    }
+
+    #[test]
+    fn test_prelude_in_modules() {
+        let mut ctx = context();
+        let output = SharedBuffer::default();
+        ctx.set_output(Box::new(output.clone()));
+
+        let mut module = parsed(
+            "fn len(v: int[]) -> int { return 7; }\n\
+             __print(__format(len([1]) + max(1, 2)));\n",
+            &mut ctx,
+        );
+        module.interpret(&mut ctx, &mut VM::new()).unwrap();
+        assert_eq!(String::from_utf8(output.0.take()).unwrap(), "9");
+
+        ctx.prelude = Prelude::empty().with(crate::natives::debug::__print());
+        let mut module = Module::new(Source::from_string(
+            "__print(\"sandboxed\");\nlen([1]);\n".to_string(),
+        ));
+        let diagnostics = module.check(&mut ctx, &mut VM::new()).unwrap();
+        assert_eq!(diagnostics.len(), 1, "{:?}", diagnostics);
+        assert_eq!(diagnostics[0].title, "Call to undefined function: len");
+
+        let stmt = module.ast.stmts[0].clone();
+        module
+            .interpret_stmt(&stmt, &mut ctx, &mut VM::new())
+            .unwrap();
+        assert_eq!(String::from_utf8(output.0.take()).unwrap(), "sandboxed");
+
+        ctx.disable_prelude();
+        let module = parsed("fn main() {}", &mut ctx);
+        assert!(!module.has_function("__print"));
+        assert!(module.has_function("main"));
+    }
+}
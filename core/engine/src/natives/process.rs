@@ -4,16 +4,17 @@ use crate::{
     vm::native_fn::{NativeFunction, NativeFunctionParam},
 };
 use roan_ast::TypeKind;
+use roan_error::error::RoanError;
 
 native_function!(
-    fn __exit(status) {
+    fn __exit(status) -> ! {
         let status = as_cast!(status, Int);
-        std::process::exit(status as i32);
+        return Err(RoanError::Exit(status as i32).into());
     }
 );
 
 native_function!(
-    fn __abort() {
+    fn __abort() -> ! {
         std::process::abort();
     }
 );
@@ -23,3 +24,14 @@ native_function!(
         Value::Int(std::process::id() as i64)
     }
 );
+
+native_function!(
+    fn env(name) {
+        let name = as_cast!(name, String);
+
+        match std::env::var(name) {
+            Ok(value) => Value::String(value),
+            Err(_) => Value::Null,
+        }
+    }
+);
@@ -0,0 +1,58 @@
+//! Helpers shared by the tests running Roan code.
+
+use crate::{
+    context::Context,
+    module::{loaders::ModuleLoader, Module},
+    vm::VM,
+};
+use anyhow::Result;
+use roan_ast::source::Source;
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+/// Loader that can't load anything, but caches the modules inserted into it.
+#[derive(Debug, Default)]
+pub struct CachingLoader {
+    modules: HashMap<String, Module>,
+}
+
+impl ModuleLoader for CachingLoader {
+    fn load(&mut self, _: &Module, spec: &str, _: &Context) -> Result<Module> {
+        anyhow::bail!("Cannot load {}", spec)
+    }
+
+    fn insert(&mut self, name: String, module: Module) {
+        self.modules.insert(name, module);
+    }
+
+    fn get(&self, name: &str) -> Option<Module> {
+        self.modules.get(name).cloned()
+    }
+}
+
+/// Returns a context using a [CachingLoader].
+pub fn context() -> Context {
+    Context::builder()
+        .module_loader(Rc::new(RefCell::new(CachingLoader::default())))
+        .build()
+}
+
+/// Parses `src` into a module, panicking if it has errors.
+pub fn parsed(src: &str, ctx: &mut Context) -> Module {
+    let mut module = Module::new(Source::from_string(src.to_string()));
+    module.parse(ctx, &mut VM::new()).unwrap();
+    module
+}
+
+/// Writer appending to a buffer that can still be read after it's given to the context.
+#[derive(Clone, Default)]
+pub struct SharedBuffer(pub Rc<RefCell<Vec<u8>>>);
+
+impl std::io::Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.borrow_mut().write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,103 @@
+use crate::value::Value;
+use std::{cell::RefCell, rc::Rc};
+
+/// State of an iterator created by calling `iter()` on a vector, string or object.
+///
+/// The elements are copied when the iterator is created, so changing the source afterwards
+/// doesn't affect the iterator. Every call to `iter()` creates a new cursor, which means two
+/// iterators over the same value are independent of each other.
+#[derive(Debug, Clone)]
+pub struct ValueIterator {
+    items: Vec<Value>,
+    cursor: usize,
+}
+
+impl ValueIterator {
+    pub fn new(items: Vec<Value>) -> Self {
+        Self { items, cursor: 0 }
+    }
+
+    /// Returns `true` if calling [ValueIterator::next] would return an element.
+    pub fn has_next(&self) -> bool {
+        self.cursor < self.items.len()
+    }
+}
+
+impl Iterator for ValueIterator {
+    type Item = Value;
+
+    /// Returns the next element and advances the cursor, or `None` once all elements were
+    /// returned. Calling it again after the end keeps returning `None`.
+    fn next(&mut self) -> Option<Value> {
+        let item = self.items.get(self.cursor).cloned()?;
+        self.cursor += 1;
+
+        Some(item)
+    }
+}
+
+impl Value {
+    /// Creates an iterator over the value.
+    ///
+    /// Vectors yield their elements, strings yield chars, bytes yield ints and objects yield
+    /// `[key, value]` pairs. An iterator returns itself, so it continues where it stopped.
+    ///
+    /// Returns `None` if the value can't be iterated.
+    pub fn iter(&self) -> Option<Value> {
+        let items = match self {
+            Value::Vec(items) => items.clone(),
+            Value::String(s) => s.chars().map(Value::Char).collect(),
+            Value::Object(fields) => fields
+                .iter()
+                .map(|(key, val)| Value::Vec(vec![Value::String(key.clone()), val.clone()]))
+                .collect(),
+            Value::Bytes(bytes) => bytes.iter().map(|b| Value::Int(*b as i64)).collect(),
+            Value::Iterator(_) => return Some(self.clone()),
+            _ => return None,
+        };
+
+        Some(Value::Iterator(Rc::new(RefCell::new(ValueIterator::new(
+            items,
+        )))))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_iterator_next_past_end() {
+        let mut iter = ValueIterator::new(vec![Value::Int(1)]);
+
+        assert!(iter.has_next());
+        assert_eq!(iter.next(), Some(Value::Int(1)));
+        assert!(!iter.has_next());
+        assert_eq!(iter.next(), None);
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn test_iter_string_and_object() {
+        let Some(Value::Iterator(chars)) = Value::String("ab".to_string()).iter() else {
+            panic!("Expected iterator");
+        };
+        assert_eq!(chars.borrow_mut().next(), Some(Value::Char('a')));
+        assert_eq!(chars.borrow_mut().next(), Some(Value::Char('b')));
+
+        let mut fields = indexmap::IndexMap::new();
+        fields.insert("a".to_string(), Value::Int(1));
+        let Some(Value::Iterator(pairs)) = Value::Object(fields).iter() else {
+            panic!("Expected iterator");
+        };
+        assert_eq!(
+            pairs.borrow_mut().next(),
+            Some(Value::Vec(vec![
+                Value::String("a".to_string()),
+                Value::Int(1)
+            ]))
+        );
+
+        assert!(Value::Int(1).iter().is_none());
+    }
+}
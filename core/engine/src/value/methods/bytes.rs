@@ -0,0 +1,14 @@
+use crate::{
+    as_cast, native_function,
+    value::Value,
+    vm::native_fn::{NativeFunction, NativeFunctionParam},
+};
+use roan_ast::TypeKind;
+
+native_function!(
+    fn __bytes_len(bytes) {
+        let bytes = as_cast!(bytes, Bytes);
+
+        Value::Int(bytes.len() as i64)
+    }
+);
@@ -0,0 +1,90 @@
+use crate::{
+    as_cast, native_function, throw,
+    value::Value,
+    vm::native_fn::{NativeFunction, NativeFunctionParam},
+};
+use roan_ast::TypeKind;
+
+// Creates a new iterator over a vector, string or object. Iterators created from the same value
+// don't share their position.
+native_function!(
+    fn __iter(value) {
+        match value.iter() {
+            Some(iter) => iter,
+            None => throw!("Cannot iterate over a value of type {}", value.type_name()),
+        }
+    }
+);
+
+// Returns the next element of the iterator, or null once it's exhausted.
+native_function!(
+    fn __iterator_next(iter) {
+        let iter = as_cast!(iter, Iterator);
+        let next = iter.borrow_mut().next();
+
+        next.unwrap_or(Value::Null)
+    }
+);
+
+native_function!(
+    fn __iterator_has_next(iter) {
+        let iter = as_cast!(iter, Iterator);
+        let has_next = iter.borrow().has_next();
+
+        Value::Bool(has_next)
+    }
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use roan_error::error::RoanError;
+
+    #[test]
+    fn test_iterator_next() {
+        let vec = Value::Vec(vec![Value::Int(1), Value::Int(2)]);
+        let iter = __iter().call(vec![vec]).unwrap();
+
+        assert_eq!(
+            __iterator_next().call(vec![iter.clone()]).unwrap(),
+            Value::Int(1)
+        );
+        assert_eq!(
+            __iterator_next().call(vec![iter.clone()]).unwrap(),
+            Value::Int(2)
+        );
+        assert_eq!(
+            __iterator_has_next().call(vec![iter.clone()]).unwrap(),
+            Value::Bool(false)
+        );
+        assert_eq!(
+            __iterator_next().call(vec![iter.clone()]).unwrap(),
+            Value::Null
+        );
+        assert_eq!(__iterator_next().call(vec![iter]).unwrap(), Value::Null);
+    }
+
+    #[test]
+    fn test_iterators_are_independent() {
+        let vec = Value::Vec(vec![Value::Int(1), Value::Int(2)]);
+        let first = __iter().call(vec![vec.clone()]).unwrap();
+        let second = __iter().call(vec![vec]).unwrap();
+
+        assert_eq!(
+            __iterator_next().call(vec![first.clone()]).unwrap(),
+            Value::Int(1)
+        );
+        assert_eq!(__iterator_next().call(vec![first]).unwrap(), Value::Int(2));
+        assert_eq!(__iterator_next().call(vec![second]).unwrap(), Value::Int(1));
+    }
+
+    #[test]
+    fn test_iter_non_iterable_throws() {
+        let err = __iter().call(vec![Value::Int(1)]).unwrap_err();
+
+        assert!(matches!(
+            err.downcast_ref::<RoanError>(),
+            Some(RoanError::Throw(msg, _)) if msg == "Cannot iterate over a value of type int"
+        ));
+    }
+}
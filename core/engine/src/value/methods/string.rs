@@ -1,6 +1,6 @@
 use crate::{
     as_cast, native_function,
-    value::Value,
+    value::{resolve_index, resolve_slice, Value},
     vm::native_fn::{NativeFunction, NativeFunctionParam},
 };
 use roan_ast::TypeKind;
@@ -113,6 +113,8 @@ native_function!(
     }
 );
 
+// Reverses the string by Unicode scalar value (`char`), so the result is always valid UTF-8.
+// It is not grapheme-aware: combining marks end up before the character they modified.
 native_function!(
     fn __string_reverse(s) {
         let s = as_cast!(s, String);
@@ -126,17 +128,10 @@ native_function!(
         let s = as_cast!(s, String);
         let index = as_cast!(index, Int);
 
-        let index = if index < 0 {
-            s.len() as i64 + index
-        } else {
-            index
-        };
-
-        if index < 0 || index as usize >= s.len() {
-            return Value::Null;
+        match resolve_index(s.chars().count(), index) {
+            Some(index) => Value::String(s.chars().nth(index).unwrap().to_string()),
+            None => Value::Null,
         }
-
-        Value::String(s.chars().nth(index as usize).unwrap().to_string())
     }
 );
 
@@ -152,7 +147,7 @@ native_function!(
         };
 
         if index < 0 || index as usize >= s.len() {
-            return Value::Null;
+            return Ok(Value::Null);
         }
 
         Value::Int(s.chars().nth(index as usize).unwrap() as i64)
@@ -160,28 +155,14 @@ native_function!(
 );
 
 native_function!(
-    fn __string_slice(s, start, end) {
+    fn __string_slice(s, start, ...rest) {
         let s = as_cast!(s, String);
         let start = as_cast!(start, Int);
-        let end = as_cast!(end, Int);
-
-        let start = if start < 0 {
-            s.len() as i64 + start
-        } else {
-            start
-        };
+        let end = rest.into_iter().next().map(|end| as_cast!(end, Int));
 
-        let end = if end < 0 {
-            s.len() as i64 + end
-        } else {
-            end
-        };
-
-        if start < 0 || end < 0 || start as usize >= s.len() || end as usize >= s.len() {
-            return Value::Null;
-        }
+        let (start, end) = resolve_slice(s.chars().count(), start, end);
 
-        Value::String(s.chars().skip(start as usize).take((end - start) as usize).collect())
+        Value::String(s.chars().skip(start).take(end - start).collect())
     }
 );
 
@@ -356,6 +337,15 @@ mod tests {
         assert_eq!(result, Value::String("olleH".to_string()));
     }
 
+    #[test]
+    fn test_string_reverse_multibyte() {
+        let result = __string_reverse()
+            .call(vec![Value::String("añ€😀".to_string())])
+            .unwrap();
+
+        assert_eq!(result, Value::String("😀€ña".to_string()));
+    }
+
     #[test]
     fn test_string_char_at() {
         let result = __string_char_at()
@@ -387,6 +377,38 @@ mod tests {
         assert_eq!(result, Value::String("el".to_string()));
     }
 
+    #[test]
+    fn test_string_slice_negative() {
+        let result = __string_slice()
+            .call(vec![Value::String("abcd".to_string()), Value::Int(-2)])
+            .unwrap();
+
+        assert_eq!(result, Value::String("cd".to_string()));
+    }
+
+    #[test]
+    fn test_string_slice_clamps_out_of_range() {
+        let result = __string_slice()
+            .call(vec![
+                Value::String("abcd".to_string()),
+                Value::Int(-10),
+                Value::Int(10),
+            ])
+            .unwrap();
+
+        assert_eq!(result, Value::String("abcd".to_string()));
+
+        let result = __string_slice()
+            .call(vec![
+                Value::String("abcd".to_string()),
+                Value::Int(3),
+                Value::Int(1),
+            ])
+            .unwrap();
+
+        assert_eq!(result, Value::String("".to_string()));
+    }
+
     #[test]
     fn test_string_index_of() {
         let result = __string_index_of()
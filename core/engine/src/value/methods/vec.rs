@@ -1,9 +1,9 @@
-use roan_ast::TypeKind;
 use crate::{
-    as_cast, native_function,
-    value::Value,
+    as_cast, native_function, throw,
+    value::{resolve_slice, Value},
     vm::native_fn::{NativeFunction, NativeFunctionParam},
 };
+use roan_ast::TypeKind;
 
 native_function!(
     fn __vec_len(vec) {
@@ -32,10 +32,64 @@ native_function!(
     }
 );
 
+native_function!(
+    fn __vec_slice(vec, start, ...rest) {
+        let vec = as_cast!(vec, Vec);
+        let start = as_cast!(start, Int);
+        let end = rest.into_iter().next().map(|end| as_cast!(end, Int));
+
+        let (start, end) = resolve_slice(vec.len(), start, end);
+
+        Value::Vec(vec[start..end].to_vec())
+    }
+);
+
+native_function!(
+    fn __vec_reverse(vec) {
+        let vec = as_cast!(vec, Vec);
+
+        Value::Vec(vec.into_iter().rev().collect())
+    }
+);
+
+// Sorts a vector of numbers, strings or chars in ascending order. The sort is stable, elements
+// that compare equal keep their original order, and `NaN`s go last. Any other vector has to be
+// sorted with `sort_by`.
+native_function!(
+    fn __vec_sort(vec) {
+        let mut vec = as_cast!(vec, Vec);
+
+        let is_sortable = vec.iter().all(|v| matches!(v, Value::Int(_) | Value::Float(_)))
+            || vec.iter().all(|v| matches!(v, Value::String(_)))
+            || vec.iter().all(|v| matches!(v, Value::Char(_)));
+
+        if !is_sortable {
+            let mut types: Vec<String> = vec![];
+            for typ in vec.iter().map(Value::type_name) {
+                if !types.contains(&typ) {
+                    types.push(typ);
+                }
+            }
+
+            throw!(
+                "Cannot sort a vector of {} without a comparator, use sort_by instead",
+                types.join(", ")
+            );
+        }
+
+        // Numbers are only unordered when one of them is `NaN`
+        let is_nan = |v: &Value| matches!(v, Value::Float(f) if f.is_nan());
+        vec.sort_by(|a, b| a.partial_cmp(b).unwrap_or_else(|| is_nan(a).cmp(&is_nan(b))));
+
+        Value::Vec(vec)
+    }
+);
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::value::Value;
+    use roan_error::error::RoanError;
 
     #[test]
     fn test_vec_len() {
@@ -70,4 +124,134 @@ mod tests {
             ])
         );
     }
+
+    #[test]
+    fn test_vec_slice() {
+        let vec = vec![Value::Int(1), Value::Int(2), Value::Int(3), Value::Int(4)];
+        let result = __vec_slice()
+            .call(vec![Value::Vec(vec.clone()), Value::Int(1), Value::Int(3)])
+            .unwrap();
+
+        assert_eq!(result, Value::Vec(vec![Value::Int(2), Value::Int(3)]));
+    }
+
+    #[test]
+    fn test_vec_slice_negative() {
+        let vec = vec![Value::Int(1), Value::Int(2), Value::Int(3), Value::Int(4)];
+        let result = __vec_slice()
+            .call(vec![Value::Vec(vec.clone()), Value::Int(-2)])
+            .unwrap();
+
+        assert_eq!(result, Value::Vec(vec![Value::Int(3), Value::Int(4)]));
+    }
+
+    #[test]
+    fn test_vec_slice_clamps_out_of_range() {
+        let vec = vec![Value::Int(1), Value::Int(2), Value::Int(3)];
+        let result = __vec_slice()
+            .call(vec![
+                Value::Vec(vec.clone()),
+                Value::Int(1),
+                Value::Int(100),
+            ])
+            .unwrap();
+
+        assert_eq!(result, Value::Vec(vec![Value::Int(2), Value::Int(3)]));
+
+        let result = __vec_slice()
+            .call(vec![Value::Vec(vec.clone()), Value::Int(5), Value::Int(10)])
+            .unwrap();
+
+        assert_eq!(result, Value::Vec(vec![]));
+    }
+
+    #[test]
+    fn test_vec_reverse() {
+        let vec = vec![Value::Int(1), Value::Int(2), Value::Int(3)];
+        let result = __vec_reverse().call(vec![Value::Vec(vec.clone())]).unwrap();
+
+        assert_eq!(
+            result,
+            Value::Vec(vec![Value::Int(3), Value::Int(2), Value::Int(1)])
+        );
+        assert_eq!(vec, vec![Value::Int(1), Value::Int(2), Value::Int(3)]);
+    }
+
+    #[test]
+    fn test_vec_sort() {
+        let vec = vec![Value::Int(3), Value::Int(1), Value::Int(2)];
+        let result = __vec_sort().call(vec![Value::Vec(vec)]).unwrap();
+
+        assert_eq!(
+            result,
+            Value::Vec(vec![Value::Int(1), Value::Int(2), Value::Int(3)])
+        );
+    }
+
+    #[test]
+    fn test_vec_sort_mixed_numbers() {
+        let vec = vec![Value::Float(2.5), Value::Int(3), Value::Int(1)];
+        let result = __vec_sort().call(vec![Value::Vec(vec)]).unwrap();
+
+        assert_eq!(
+            result,
+            Value::Vec(vec![Value::Int(1), Value::Float(2.5), Value::Int(3)])
+        );
+    }
+
+    #[test]
+    fn test_vec_sort_strings() {
+        let vec = vec![
+            Value::String("b".to_string()),
+            Value::String("c".to_string()),
+            Value::String("a".to_string()),
+        ];
+        let result = __vec_sort().call(vec![Value::Vec(vec)]).unwrap();
+
+        assert_eq!(
+            result,
+            Value::Vec(vec![
+                Value::String("a".to_string()),
+                Value::String("b".to_string()),
+                Value::String("c".to_string())
+            ])
+        );
+    }
+
+    #[test]
+    fn test_vec_sort_empty() {
+        let result = __vec_sort().call(vec![Value::Vec(vec![])]).unwrap();
+
+        assert_eq!(result, Value::Vec(vec![]));
+    }
+
+    #[test]
+    fn test_vec_sort_nan() {
+        let vec = vec![
+            Value::Float(2.0),
+            Value::Float(f64::NAN),
+            Value::Int(3),
+            Value::Float(1.0),
+        ];
+        let Value::Vec(result) = __vec_sort().call(vec![Value::Vec(vec)]).unwrap() else {
+            panic!("Expected a vector");
+        };
+
+        assert_eq!(
+            result[..3],
+            [Value::Float(1.0), Value::Float(2.0), Value::Int(3)]
+        );
+        assert!(matches!(result[3], Value::Float(f) if f.is_nan()));
+    }
+
+    #[test]
+    fn test_vec_sort_heterogeneous() {
+        let vec = vec![Value::Int(1), Value::String("a".to_string())];
+        let err = __vec_sort().call(vec![Value::Vec(vec)]).unwrap_err();
+
+        assert!(matches!(
+            err.downcast_ref::<RoanError>(),
+            Some(RoanError::Throw(msg, _)) if msg.contains("without a comparator")
+        ));
+    }
 }
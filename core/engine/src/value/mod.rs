@@ -2,6 +2,7 @@ use crate::{
     entries,
     module::StoredStruct,
     value::methods::{
+        bytes::__bytes_len,
         char::{
             __char_escape_default, __char_escape_unicode, __char_from_digit, __char_is_alphabetic,
             __char_is_alphanumeric, __char_is_ascii, __char_is_ascii_alphabetic,
@@ -13,6 +14,7 @@ use crate::{
             __char_to_ascii_uppercase, __char_to_int, __char_to_lowercase, __char_to_string,
             __char_to_uppercase,
         },
+        iterator::{__iter, __iterator_has_next, __iterator_next},
         string::{
             __string_char_at, __string_char_code_at, __string_chars, __string_contains,
             __string_ends_with, __string_index_of, __string_last_index_of, __string_len,
@@ -20,26 +22,37 @@ use crate::{
             __string_starts_with, __string_to_lowercase, __string_to_uppercase, __string_trim,
             __string_trim_end, __string_trim_start,
         },
-        vec::{__vec_len, __vec_next},
+        vec::{__vec_len, __vec_next, __vec_reverse, __vec_slice, __vec_sort},
     },
     vm::native_fn::NativeFunction,
 };
 use anyhow::Result;
 use indexmap::IndexMap;
 use roan_ast::{Literal, LiteralType};
-use roan_error::{error::RoanError::TypeMismatch, TextSpan};
+use roan_error::{
+    error::RoanError::{IntegerOverflow, TypeMismatch},
+    TextSpan,
+};
 use std::{
+    cell::RefCell,
     collections::HashMap,
     fmt::{Debug, Display},
     ops,
+    rc::Rc,
+    sync::LazyLock,
 };
 
+pub mod iterator;
 pub mod methods {
+    pub mod bytes;
     pub mod char;
+    pub mod iterator;
     pub mod string;
     pub mod vec;
 }
 
+pub use iterator::ValueIterator;
+
 #[derive(Clone)]
 pub enum Value {
     Int(i64),
@@ -50,75 +63,122 @@ pub enum Value {
     Vec(Vec<Value>),
     Struct(StoredStruct, IndexMap<String, Value>),
     Object(IndexMap<String, Value>),
+    Iterator(Rc<RefCell<ValueIterator>>),
+    Bytes(Vec<u8>),
+    /// The `null` literal, a value standing for a missing one. It can be stored in variables and
+    /// passed around like any other value.
     Null,
+    /// The absence of a value, the result of a function that doesn't return anything. The type
+    /// pass rejects binding it to a variable or passing it to a function.
     Void,
 }
 
+// Methods of built-in values, by the kind of value they're called on. The tables are built the
+// first time they're used and shared by every lookup after that.
+
+static VEC_METHODS: LazyLock<HashMap<String, NativeFunction>> = LazyLock::new(|| {
+    entries!(
+        "len" => __vec_len(),
+        "iter" => __iter(),
+        "next" => __vec_next(),
+        "slice" => __vec_slice(),
+        "sort" => __vec_sort(),
+        "reverse" => __vec_reverse()
+    )
+});
+
+static STRING_METHODS: LazyLock<HashMap<String, NativeFunction>> = LazyLock::new(|| {
+    entries!(
+        "len" => __string_len(),
+        "iter" => __iter(),
+        "split" => __string_split(),
+        "chars" => __string_chars(),
+        "contains" => __string_contains(),
+        "starts_with" => __string_starts_with(),
+        "ends_with" => __string_ends_with(),
+        "replace" => __string_replace(),
+        "trim" => __string_trim(),
+        "trim_start" => __string_trim_start(),
+        "trim_end" => __string_trim_end(),
+        "to_uppercase" => __string_to_uppercase(),
+        "to_lowercase" => __string_to_lowercase(),
+        "reverse" => __string_reverse(),
+        "char_at" => __string_char_at(),
+        "char_code_at" => __string_char_code_at(),
+        "slice" => __string_slice(),
+        "index_of" => __string_index_of(),
+        "last_index_of" => __string_last_index_of()
+    )
+});
+
+static CHAR_METHODS: LazyLock<HashMap<String, NativeFunction>> = LazyLock::new(|| {
+    entries!(
+        "is_alphabetic" => __char_is_alphabetic(),
+        "is_alphanumeric" => __char_is_alphanumeric(),
+        "is_ascii" => __char_is_ascii(),
+        "is_ascii_alphabetic" => __char_is_ascii_alphabetic(),
+        "is_ascii_alphanumeric" => __char_is_ascii_alphanumeric(),
+        "is_ascii_control" => __char_is_ascii_control(),
+        "is_ascii_digit" => __char_is_ascii_digit(),
+        "is_ascii_graphic" => __char_is_ascii_graphic(),
+        "is_ascii_lowercase" => __char_is_ascii_lowercase(),
+        "is_ascii_punctuation" => __char_is_ascii_punctuation(),
+        "is_ascii_uppercase" => __char_is_ascii_uppercase(),
+        "is_ascii_whitespace" => __char_is_ascii_whitespace(),
+        "is_control" => __char_is_control(),
+        "is_digit" => __char_is_digit(),
+        "is_lowercase" => __char_is_lowercase(),
+        "is_numeric" => __char_is_numeric(),
+        "is_uppercase" => __char_is_uppercase(),
+        "is_whitespace" => __char_is_whitespace(),
+        "to_ascii_lowercase" => __char_to_ascii_lowercase(),
+        "to_ascii_uppercase" => __char_to_ascii_uppercase(),
+        "to_lowercase" => __char_to_lowercase(),
+        "to_uppercase" => __char_to_uppercase(),
+        "is_digit_in_base" => __char_is_digit_in_base(),
+        "escape_default" => __char_escape_default(),
+        "escape_unicode" => __char_escape_unicode(),
+        "from_digit" => __char_from_digit(),
+        "len_utf8" => __char_len_utf8(),
+        "to_string" => __char_to_string(),
+        "to_int" => __char_to_int()
+    )
+});
+
+static OBJECT_METHODS: LazyLock<HashMap<String, NativeFunction>> = LazyLock::new(|| {
+    entries!(
+        "iter" => __iter()
+    )
+});
+
+static ITERATOR_METHODS: LazyLock<HashMap<String, NativeFunction>> = LazyLock::new(|| {
+    entries!(
+        "next" => __iterator_next(),
+        "has_next" => __iterator_has_next()
+    )
+});
+
+static BYTES_METHODS: LazyLock<HashMap<String, NativeFunction>> = LazyLock::new(|| {
+    entries!(
+        "len" => __bytes_len(),
+        "iter" => __iter()
+    )
+});
+
+/// Table of values without any method.
+static NO_METHODS: LazyLock<HashMap<String, NativeFunction>> = LazyLock::new(HashMap::new);
+
 impl Value {
-    pub fn builtin_methods(&self) -> HashMap<String, NativeFunction> {
+    /// Returns the native methods that can be called on the value, keyed by name.
+    pub fn builtin_methods(&self) -> &'static HashMap<String, NativeFunction> {
         match self {
-            Value::Vec(_) => {
-                entries!(
-                    "len" => __vec_len(),
-                    "next" => __vec_next()
-                )
-            }
-            Value::String(_) => {
-                entries!(
-                    "len" => __string_len(),
-                    "split" => __string_split(),
-                    "chars" => __string_chars(),
-                    "contains" => __string_contains(),
-                    "starts_with" => __string_starts_with(),
-                    "ends_with" => __string_ends_with(),
-                    "replace" => __string_replace(),
-                    "trim" => __string_trim(),
-                    "trim_start" => __string_trim_start(),
-                    "trim_end" => __string_trim_end(),
-                    "to_uppercase" => __string_to_uppercase(),
-                    "to_lowercase" => __string_to_lowercase(),
-                    "reverse" => __string_reverse(),
-                    "char_at" => __string_char_at(),
-                    "char_code_at" => __string_char_code_at(),
-                    "slice" => __string_slice(),
-                    "index_of" => __string_index_of(),
-                    "last_index_of" => __string_last_index_of()
-                )
-            }
-            Value::Char(_) => {
-                entries!(
-                    "is_alphabetic" => __char_is_alphabetic(),
-                    "is_alphanumeric" => __char_is_alphanumeric(),
-                    "is_ascii" => __char_is_ascii(),
-                    "is_ascii_alphabetic" => __char_is_ascii_alphabetic(),
-                    "is_ascii_alphanumeric" => __char_is_ascii_alphanumeric(),
-                    "is_ascii_control" => __char_is_ascii_control(),
-                    "is_ascii_digit" => __char_is_ascii_digit(),
-                    "is_ascii_graphic" => __char_is_ascii_graphic(),
-                    "is_ascii_lowercase" => __char_is_ascii_lowercase(),
-                    "is_ascii_punctuation" => __char_is_ascii_punctuation(),
-                    "is_ascii_uppercase" => __char_is_ascii_uppercase(),
-                    "is_ascii_whitespace" => __char_is_ascii_whitespace(),
-                    "is_control" => __char_is_control(),
-                    "is_digit" => __char_is_digit(),
-                    "is_lowercase" => __char_is_lowercase(),
-                    "is_numeric" => __char_is_numeric(),
-                    "is_uppercase" => __char_is_uppercase(),
-                    "is_whitespace" => __char_is_whitespace(),
-                    "to_ascii_lowercase" => __char_to_ascii_lowercase(),
-                    "to_ascii_uppercase" => __char_to_ascii_uppercase(),
-                    "to_lowercase" => __char_to_lowercase(),
-                    "to_uppercase" => __char_to_uppercase(),
-                    "is_digit_in_base" => __char_is_digit_in_base(),
-                    "escape_default" => __char_escape_default(),
-                    "escape_unicode" => __char_escape_unicode(),
-                    "from_digit" => __char_from_digit(),
-                    "len_utf8" => __char_len_utf8(),
-                    "to_string" => __char_to_string(),
-                    "to_int" => __char_to_int()
-                )
-            }
-            _ => HashMap::new(),
+            Value::Vec(_) => &VEC_METHODS,
+            Value::String(_) => &STRING_METHODS,
+            Value::Char(_) => &CHAR_METHODS,
+            Value::Object(_) => &OBJECT_METHODS,
+            Value::Iterator(_) => &ITERATOR_METHODS,
+            Value::Bytes(_) => &BYTES_METHODS,
+            _ => &NO_METHODS,
         }
     }
 }
@@ -178,14 +238,21 @@ impl Debug for Value {
             Value::Object(fields) => {
                 write!(f, "{:#?}", fields)
             }
+            Value::Iterator(iter) => write!(f, "Iterator({:?})", iter.borrow()),
+            Value::Bytes(bytes) => write!(f, "Bytes({:?})", bytes),
         }
     }
 }
 
+/// Floats that aren't finite display as `NaN`, `Inf` and `-Inf`.
 impl Display for Value {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Value::Int(i) => write!(f, "{}", i),
+            Value::Float(fl) if fl.is_nan() => write!(f, "NaN"),
+            Value::Float(fl) if fl.is_infinite() => {
+                write!(f, "{}", if *fl > 0.0 { "Inf" } else { "-Inf" })
+            }
             Value::Float(fl) => write!(f, "{}", fl),
             Value::Bool(b) => write!(f, "{}", b),
             Value::String(s) => write!(f, "{}", s),
@@ -224,6 +291,14 @@ impl Display for Value {
                 }
                 write!(f, "}}")
             }
+            Value::Iterator(_) => write!(f, "<iterator>"),
+            Value::Bytes(bytes) => {
+                write!(f, "<bytes")?;
+                for byte in bytes {
+                    write!(f, " {:02x}", byte)?;
+                }
+                write!(f, ">")
+            }
         }
     }
 }
@@ -284,6 +359,7 @@ impl ops::Rem for Value {
     }
 }
 
+/// Floats compare like `f64`, so `NaN` isn't equal to anything, not even itself.
 impl PartialEq for Value {
     fn eq(&self, other: &Self) -> bool {
         match (self, other) {
@@ -307,6 +383,8 @@ impl PartialEq for Value {
             (Value::Char(a), Value::Char(b)) => a == b,
             (Value::Char(a), Value::String(b)) => a.to_string() == *b,
             (Value::String(a), Value::Char(b)) => a == &b.to_string(),
+            (Value::Iterator(a), Value::Iterator(b)) => Rc::ptr_eq(a, b),
+            (Value::Bytes(a), Value::Bytes(b)) => a == b,
             _ => false,
         }
     }
@@ -314,6 +392,8 @@ impl PartialEq for Value {
 
 impl Eq for Value {}
 
+/// Numbers, strings and chars can be ordered. Other values, and `NaN` compared with anything,
+/// have no ordering: `partial_cmp` returns `None` and every comparison is `false`.
 impl PartialOrd for Value {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
         match (self, other) {
@@ -321,20 +401,63 @@ impl PartialOrd for Value {
             (Value::Float(a), Value::Float(b)) => a.partial_cmp(b),
             (Value::Int(a), Value::Float(b)) => (*a as f64).partial_cmp(b),
             (Value::Float(a), Value::Int(b)) => a.partial_cmp(&(*b as f64)),
+            (Value::String(a), Value::String(b)) => a.partial_cmp(b),
+            (Value::Char(a), Value::Char(b)) => a.partial_cmp(b),
             _ => None,
         }
     }
 }
 
 impl Value {
-    pub fn pow(self, other: Self) -> Self {
-        match (self, other) {
-            (Value::Int(a), Value::Int(b)) => Value::Int(a.pow(b as u32)),
-            (Value::Float(a), Value::Float(b)) => Value::Float(a.powf(b)),
-            (Value::Int(a), Value::Float(b)) => Value::Float((a as f64).powf(b)),
-            (Value::Float(a), Value::Int(b)) => Value::Float(a.powf(b as f64)),
-            _ => panic!("Cannot apply power operator on values of different or unsupported types"),
-        }
+    /// Raises the value to the power of `exp`.
+    ///
+    /// Ints raised to a negative int are floats, `2 ** -1` is `0.5`. The result of a float
+    /// operand that is `NaN` is `NaN`, even for exponents like `0` that otherwise give `1`.
+    ///
+    /// Returns [IntegerOverflow] if the result of ints doesn't fit in an int.
+    ///
+    /// [IntegerOverflow]: roan_error::error::RoanError::IntegerOverflow
+    pub fn checked_pow(self, exp: Self) -> Result<Self> {
+        let result = match (&self, &exp) {
+            (Value::Int(a), Value::Int(b)) if *b < 0 => {
+                Value::Float((*a as f64).powi((*b).max(i32::MIN as i64) as i32))
+            }
+            (Value::Int(a), Value::Int(b)) => {
+                match u32::try_from(*b).ok().and_then(|b| a.checked_pow(b)) {
+                    Some(result) => Value::Int(result),
+                    None => {
+                        return Err(
+                            IntegerOverflow(format!("{} ** {}", a, b), TextSpan::default()).into(),
+                        )
+                    }
+                }
+            }
+            (Value::Float(a), Value::Float(b)) => Value::Float(checked_powf(*a, *b)),
+            (Value::Int(a), Value::Float(b)) => Value::Float(checked_powf(*a as f64, *b)),
+            (Value::Float(a), Value::Int(b)) => Value::Float(checked_powf(*a, *b as f64)),
+            _ => {
+                return Err(TypeMismatch(
+                    format!(
+                        "Cannot apply power operator on {} and {}",
+                        self.type_name(),
+                        exp.type_name()
+                    ),
+                    TextSpan::default(),
+                )
+                .into())
+            }
+        };
+
+        Ok(result)
+    }
+}
+
+/// `a` to the power of `b`, `NaN` if either of them is.
+fn checked_powf(a: f64, b: f64) -> f64 {
+    if a.is_nan() || b.is_nan() {
+        f64::NAN
+    } else {
+        a.powf(b)
     }
 }
 
@@ -342,30 +465,78 @@ impl Value {
     pub fn access_index(&self, index: Self) -> Self {
         match self {
             Value::Vec(v) => match index {
-                Value::Int(i) => v.get(i as usize).cloned().unwrap_or(Value::Null),
+                Value::Int(i) => resolve_index(v.len(), i)
+                    .and_then(|i| v.get(i).cloned())
+                    .unwrap_or(Value::Null),
                 _ => unreachable!("Index must be an integer"),
             },
             Value::String(s) => match index {
-                Value::Int(i) => {
-                    if i < 0 {
-                        Value::Null
-                    } else {
-                        s.chars()
-                            .nth(i as usize)
-                            .map(Value::Char)
-                            .unwrap_or(Value::Null)
-                    }
-                }
+                Value::Int(i) => resolve_index(s.chars().count(), i)
+                    .and_then(|i| s.chars().nth(i))
+                    .map(Value::Char)
+                    .unwrap_or(Value::Null),
                 _ => unreachable!("Index must be an integer"),
             },
             Value::Object(fields) => match index {
                 Value::String(key) => fields.get(&key).cloned().unwrap_or(Value::Null),
                 _ => unreachable!("Object can be only indexed by a string"),
             },
+            Value::Struct(_, fields) => match index {
+                Value::String(key) => fields.get(&key).cloned().unwrap_or(Value::Null),
+                _ => unreachable!("Struct can be only indexed by a string"),
+            },
+            Value::Bytes(bytes) => match index {
+                Value::Int(i) => resolve_index(bytes.len(), i)
+                    .map(|i| Value::Int(bytes[i] as i64))
+                    .unwrap_or(Value::Null),
+                _ => unreachable!("Index must be an integer"),
+            },
             // TODO: proper error handling
             _ => panic!("Cannot access index of non-indexable value"),
         }
     }
+
+    /// Converts a struct into an object with the same fields, in the order they are declared.
+    ///
+    /// Returns `None` if the value isn't a struct.
+    pub fn struct_to_object(&self) -> Option<Value> {
+        match self {
+            Value::Struct(_, fields) => Some(Value::Object(fields.clone())),
+            _ => None,
+        }
+    }
+}
+
+/// Resolves an index into a sequence of `len` elements.
+///
+/// Negative indices count from the end, so `-1` is the last element. Returns `None` if the
+/// index is out of bounds.
+pub fn resolve_index(len: usize, index: i64) -> Option<usize> {
+    let index = if index < 0 { len as i64 + index } else { index };
+
+    if index < 0 || index >= len as i64 {
+        None
+    } else {
+        Some(index as usize)
+    }
+}
+
+/// Resolves the `start` and `end` bounds of a slice of a sequence of `len` elements.
+///
+/// Negative bounds count from the end and a missing `end` means the end of the sequence.
+/// Out of range bounds are clamped, so the result always describes a valid (possibly empty)
+/// range.
+pub fn resolve_slice(len: usize, start: i64, end: Option<i64>) -> (usize, usize) {
+    let clamp = |bound: i64| {
+        let bound = if bound < 0 { len as i64 + bound } else { bound };
+
+        bound.clamp(0, len as i64) as usize
+    };
+
+    let start = clamp(start);
+    let end = end.map(clamp).unwrap_or(len);
+
+    (start, end.max(start))
 }
 
 impl Value {
@@ -400,6 +571,18 @@ impl Value {
     pub fn is_void(&self) -> bool {
         matches!(self, Value::Void)
     }
+
+    pub fn is_bytes(&self) -> bool {
+        matches!(self, Value::Bytes(_))
+    }
+
+    pub fn is_char(&self) -> bool {
+        matches!(self, Value::Char(_))
+    }
+
+    pub fn is_object(&self) -> bool {
+        matches!(self, Value::Object(_))
+    }
 }
 
 impl Value {
@@ -427,7 +610,21 @@ impl Value {
             "string" => self.is_string(),
             "null" => self.is_null(),
             "void" => self.is_void(),
-            _ => false,
+            "bytes" => self.is_bytes(),
+            "char" => self.is_char(),
+            "object" => self.is_object(),
+            "vec" => self.is_array(),
+            _ => {
+                // Every element of a vector has to be of the element type, `T[]`
+                if let Some(element_type) = type_name.strip_suffix("[]") {
+                    return match self {
+                        Value::Vec(vals) => vals.iter().all(|val| val.is_type(element_type)),
+                        _ => false,
+                    };
+                }
+
+                self.is_struct() && self.type_name() == type_name
+            }
         }
     }
 
@@ -450,15 +647,19 @@ impl Value {
             Value::Void => "void".to_string(),
             Value::Char(_) => "char".to_string(),
             Value::Object(_) => "object".to_string(),
+            Value::Iterator(_) => "iterator".to_string(),
+            Value::Bytes(_) => "bytes".to_string(),
         }
     }
 }
 
 impl Value {
+    /// Returns whether the value counts as `true` in conditions. Zero, `NaN`, empty strings, vecs
+    /// and bytes, `null` and `void` are falsy.
     pub fn is_truthy(&self) -> bool {
         match self {
             Value::Int(i) => *i != 0,
-            Value::Float(f) => *f != 0.0,
+            Value::Float(f) => *f != 0.0 && !f.is_nan(),
             Value::Bool(b) => *b,
             Value::String(s) => !s.is_empty(),
             Value::Vec(v) => !v.is_empty(),
@@ -467,13 +668,156 @@ impl Value {
             Value::Struct(_, _) => true,
             Value::Char(_) => true,
             Value::Object(_) => true,
+            Value::Iterator(_) => true,
+            Value::Bytes(bytes) => !bytes.is_empty(),
         }
     }
 }
 
+/// Name of the field holding the name of a struct in its serialized form.
+#[cfg(feature = "serde")]
+pub const STRUCT_FIELD: &str = "__struct__";
+
+/// Values serialize to the closest serde type: chars become one-char strings, `Void` becomes
+/// `null` like `Null`, and structs become maps with their name in a [STRUCT_FIELD] field.
+/// Iterators can't be serialized.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Value {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        use serde::ser::{Error, SerializeMap};
+
+        match self {
+            Value::Int(i) => serializer.serialize_i64(*i),
+            Value::Float(f) => serializer.serialize_f64(*f),
+            Value::Bool(b) => serializer.serialize_bool(*b),
+            Value::Char(c) => serializer.serialize_char(*c),
+            Value::String(s) => serializer.serialize_str(s),
+            Value::Vec(vals) => serializer.collect_seq(vals),
+            Value::Struct(def, fields) => {
+                let mut map = serializer.serialize_map(Some(fields.len() + 1))?;
+                map.serialize_entry(STRUCT_FIELD, &def.name.literal())?;
+                for (name, val) in fields {
+                    map.serialize_entry(name, val)?;
+                }
+                map.end()
+            }
+            Value::Object(fields) => serializer.collect_map(fields),
+            Value::Iterator(_) => Err(S::Error::custom("iterators can't be serialized")),
+            Value::Bytes(bytes) => serializer.serialize_bytes(bytes),
+            Value::Null | Value::Void => serializer.serialize_unit(),
+        }
+    }
+}
+
+/// Maps with a string [STRUCT_FIELD] field deserialize to structs, every other map to an object.
+/// The struct only knows its name, it has neither fields declarations nor methods.
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Value {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        deserializer.deserialize_any(ValueVisitor)
+    }
+}
+
+#[cfg(feature = "serde")]
+struct ValueVisitor;
+
+#[cfg(feature = "serde")]
+impl<'de> serde::de::Visitor<'de> for ValueVisitor {
+    type Value = Value;
+
+    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str("a roan value")
+    }
+
+    fn visit_bool<E>(self, b: bool) -> std::result::Result<Value, E> {
+        Ok(Value::Bool(b))
+    }
+
+    fn visit_i64<E>(self, i: i64) -> std::result::Result<Value, E> {
+        Ok(Value::Int(i))
+    }
+
+    fn visit_u64<E>(self, u: u64) -> std::result::Result<Value, E> {
+        // Numbers too big for an int lose precision rather than failing
+        Ok(i64::try_from(u).map_or(Value::Float(u as f64), Value::Int))
+    }
+
+    fn visit_f64<E>(self, f: f64) -> std::result::Result<Value, E> {
+        Ok(Value::Float(f))
+    }
+
+    fn visit_char<E>(self, c: char) -> std::result::Result<Value, E> {
+        Ok(Value::Char(c))
+    }
+
+    fn visit_str<E>(self, s: &str) -> std::result::Result<Value, E> {
+        Ok(Value::String(s.to_string()))
+    }
+
+    fn visit_string<E>(self, s: String) -> std::result::Result<Value, E> {
+        Ok(Value::String(s))
+    }
+
+    fn visit_bytes<E>(self, bytes: &[u8]) -> std::result::Result<Value, E> {
+        Ok(Value::Bytes(bytes.to_vec()))
+    }
+
+    fn visit_unit<E>(self) -> std::result::Result<Value, E> {
+        Ok(Value::Null)
+    }
+
+    fn visit_none<E>(self) -> std::result::Result<Value, E> {
+        Ok(Value::Null)
+    }
+
+    fn visit_some<D: serde::Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> std::result::Result<Value, D::Error> {
+        serde::Deserialize::deserialize(deserializer)
+    }
+
+    fn visit_seq<A: serde::de::SeqAccess<'de>>(
+        self,
+        mut seq: A,
+    ) -> std::result::Result<Value, A::Error> {
+        let mut vals = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(val) = seq.next_element()? {
+            vals.push(val);
+        }
+
+        Ok(Value::Vec(vals))
+    }
+
+    fn visit_map<A: serde::de::MapAccess<'de>>(
+        self,
+        mut map: A,
+    ) -> std::result::Result<Value, A::Error> {
+        let mut fields = IndexMap::new();
+        while let Some((name, val)) = map.next_entry::<String, Value>()? {
+            fields.insert(name, val);
+        }
+
+        Ok(match fields.shift_remove(STRUCT_FIELD) {
+            Some(Value::String(name)) => Value::Struct(StoredStruct::named(&name), fields),
+            Some(val) => {
+                fields.insert(STRUCT_FIELD.to_string(), val);
+                Value::Object(fields)
+            }
+            None => Value::Object(fields),
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use roan_error::error::RoanError;
 
     #[test]
     fn test_value_add() {
@@ -520,11 +864,31 @@ mod tests {
     }
 
     #[test]
-    fn test_value_pow() {
-        assert_eq!(Value::Int(2).pow(Value::Int(3)), Value::Int(8));
-        assert_eq!(Value::Float(2.0).pow(Value::Float(3.0)), Value::Float(8.0));
-        assert_eq!(Value::Int(2).pow(Value::Float(3.0)), Value::Float(8.0));
-        assert_eq!(Value::Float(2.0).pow(Value::Int(3)), Value::Float(8.0));
+    fn test_value_checked_pow() {
+        let pow = |a: Value, b: Value| a.checked_pow(b).unwrap();
+
+        assert_eq!(pow(Value::Int(2), Value::Int(3)), Value::Int(8));
+        assert_eq!(pow(Value::Float(2.0), Value::Float(3.0)), Value::Float(8.0));
+        assert_eq!(pow(Value::Int(2), Value::Float(3.0)), Value::Float(8.0));
+        assert_eq!(pow(Value::Float(2.0), Value::Int(3)), Value::Float(8.0));
+        assert_eq!(pow(Value::Int(2), Value::Int(-1)), Value::Float(0.5));
+        assert_eq!(
+            pow(Value::Float(2.0), Value::Int(63)),
+            Value::Float(2f64.powi(63))
+        );
+        assert_eq!(pow(Value::Int(2), Value::Int(62)), Value::Int(1 << 62));
+
+        let Value::Float(nan) = pow(Value::Float(f64::NAN), Value::Int(0)) else {
+            panic!("Expected a float");
+        };
+        assert!(nan.is_nan());
+
+        let err = Value::Int(2).checked_pow(Value::Int(63)).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<RoanError>(),
+            Some(RoanError::IntegerOverflow(..))
+        ));
+        assert!(Value::Int(2).checked_pow(Value::Bool(true)).is_err());
     }
 
     #[test]
@@ -541,6 +905,18 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_value_access_negative_index() {
+        let vec = Value::Vec(vec![Value::Int(1), Value::Int(2), Value::Int(3)]);
+        assert_eq!(vec.access_index(Value::Int(-1)), Value::Int(3));
+        assert_eq!(vec.access_index(Value::Int(-3)), Value::Int(1));
+        assert_eq!(vec.access_index(Value::Int(-4)), Value::Null);
+
+        let string = Value::String("abc".to_string());
+        assert_eq!(string.access_index(Value::Int(-1)), Value::Char('c'));
+        assert_eq!(string.access_index(Value::Int(-4)), Value::Null);
+    }
+
     #[test]
     fn test_value_eq() {
         assert_eq!(Value::Int(1), Value::Int(1));
@@ -556,6 +932,7 @@ mod tests {
         );
         assert_eq!(Value::Null, Value::Null);
         assert_eq!(Value::Void, Value::Void);
+        assert_ne!(Value::Float(f64::NAN), Value::Float(f64::NAN));
     }
 
     #[test]
@@ -576,6 +953,13 @@ mod tests {
             Value::Float(1.0).partial_cmp(&Value::Int(2)),
             Some(std::cmp::Ordering::Less)
         );
+        assert_eq!(Value::Float(f64::NAN).partial_cmp(&Value::Int(1)), None);
+        assert_eq!(
+            Value::Float(f64::NAN).partial_cmp(&Value::Float(f64::NAN)),
+            None
+        );
+        assert_eq!(Value::String("1".into()).partial_cmp(&Value::Int(1)), None);
+        assert!(Value::Float(f64::INFINITY) > Value::Int(i64::MAX));
     }
 
     #[test]
@@ -593,6 +977,41 @@ mod tests {
         );
         assert_eq!(format!("{}", Value::Null), "null");
         assert_eq!(format!("{}", Value::Void), "void");
+        assert_eq!(format!("{}", Value::Float(f64::NAN)), "NaN");
+        assert_eq!(format!("{}", Value::Float(f64::INFINITY)), "Inf");
+        assert_eq!(format!("{}", Value::Float(f64::NEG_INFINITY)), "-Inf");
+        assert_eq!(
+            format!("{}", Value::Vec(vec![Value::Float(f64::NAN)])),
+            "[NaN]"
+        );
+    }
+
+    #[test]
+    fn test_value_is_truthy() {
+        assert!(Value::Float(0.5).is_truthy());
+        assert!(Value::Float(f64::INFINITY).is_truthy());
+        assert!(!Value::Float(0.0).is_truthy());
+        assert!(!Value::Float(f64::NAN).is_truthy());
+        assert!(!Value::Int(0).is_truthy());
+        assert!(!Value::Null.is_truthy());
+    }
+
+    #[test]
+    fn test_builtin_methods() {
+        let methods = Value::Vec(vec![]).builtin_methods();
+        assert!(std::ptr::eq(
+            methods,
+            Value::Vec(vec![Value::Int(1)]).builtin_methods()
+        ));
+        assert!(methods.contains_key("sort"));
+        assert!(Value::String("a".to_string())
+            .builtin_methods()
+            .contains_key("split"));
+        assert!(!std::ptr::eq(
+            methods,
+            Value::String("a".to_string()).builtin_methods()
+        ));
+        assert!(Value::Int(1).builtin_methods().is_empty());
     }
 
     #[test]
@@ -615,12 +1034,19 @@ mod tests {
         assert!(Value::Float(1.0).is_type("float"));
         assert!(Value::Bool(true).is_type("bool"));
         assert!(Value::String("Hello".to_string()).is_type("string"));
-        // We check it outside the is_type method
-        // assert!(
-        //     Value::Vec(vec![Value::Int(1), Value::Int(2), Value::Int(3)]).is_type("int[]")
-        // );
+        assert!(Value::Vec(vec![Value::Int(1), Value::Int(2), Value::Int(3)]).is_type("int[]"));
+        assert!(Value::Vec(vec![Value::Int(1)]).is_type("vec"));
+        assert!(!Value::Vec(vec![Value::Int(1), Value::Float(2.0)]).is_type("int[]"));
         assert!(Value::Null.is_type("null"));
         assert!(Value::Void.is_type("void"));
+        assert!(Value::Char('a').is_type("char"));
+        assert!(Value::Object(IndexMap::new()).is_type("object"));
+        assert!(!Value::String("a".to_string()).is_type("char"));
+
+        let point = Value::Struct(StoredStruct::named("Point"), IndexMap::new());
+        assert!(point.is_type("Point"));
+        assert!(!point.is_type("Line"));
+        assert!(!Value::Int(1).is_type("Point"));
     }
 
     #[test]
@@ -658,4 +1084,93 @@ mod tests {
         assert!(Value::String("Hello".to_string()).is_string());
         assert!(!Value::Int(1).is_string());
     }
+
+    #[test]
+    fn test_value_is_char() {
+        assert!(Value::Char('a').is_char());
+        assert!(!Value::String("a".to_string()).is_char());
+    }
+
+    #[test]
+    fn test_value_is_object() {
+        assert!(Value::Object(IndexMap::new()).is_object());
+        assert!(!Value::Vec(vec![]).is_object());
+    }
+
+    #[cfg(feature = "serde")]
+    fn round_trip(val: &Value) -> Value {
+        let json = serde_json::to_string(val).unwrap();
+        serde_json::from_str(&json).unwrap()
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_value_serde_round_trip() {
+        for val in [
+            Value::Int(-42),
+            Value::Float(1.5),
+            Value::Float(2.0),
+            Value::Bool(true),
+            Value::String("héllo".to_string()),
+            Value::Vec(vec![Value::Int(1), Value::Vec(vec![Value::Null])]),
+            Value::Null,
+        ] {
+            assert_eq!(round_trip(&val), val);
+        }
+
+        // Types serde doesn't know about come back as the closest one
+        assert_eq!(
+            round_trip(&Value::Char('x')),
+            Value::String("x".to_string())
+        );
+        assert_eq!(round_trip(&Value::Void), Value::Null);
+        assert_eq!(
+            round_trip(&Value::Bytes(vec![1, 2])),
+            Value::Vec(vec![Value::Int(1), Value::Int(2)])
+        );
+
+        let fields = IndexMap::from([
+            ("name".to_string(), Value::String("roan".to_string())),
+            ("tags".to_string(), Value::Vec(vec![Value::Char('a')])),
+        ]);
+
+        let Value::Object(object) = round_trip(&Value::Object(fields.clone())) else {
+            panic!("Expected an object");
+        };
+        assert!(object.iter().eq(fields.iter()));
+
+        let val = Value::Struct(StoredStruct::named("Person"), fields.clone());
+        let json = serde_json::to_value(&val).unwrap();
+        assert_eq!(json["__struct__"], "Person");
+        assert_eq!(json["name"], "roan");
+
+        let Value::Struct(def, struct_fields) = round_trip(&val) else {
+            panic!("Expected a struct");
+        };
+        assert_eq!(def.name.literal(), "Person");
+        assert!(struct_fields.iter().eq(fields.iter()));
+
+        let iterator = Value::Vec(vec![]).iter().unwrap();
+        assert!(serde_json::to_string(&iterator).is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_value_from_json() {
+        let json = serde_json::json!({ "id": 1, "score": 9.5, "tags": [], "parent": null });
+        let Value::Object(fields) = serde_json::from_value(json).unwrap() else {
+            panic!("Expected an object");
+        };
+
+        assert_eq!(fields["id"], Value::Int(1));
+        assert_eq!(fields["score"], Value::Float(9.5));
+        assert_eq!(fields["tags"], Value::Vec(vec![]));
+        assert_eq!(fields["parent"], Value::Null);
+
+        let json = serde_json::json!({ "__struct__": 1 });
+        assert!(matches!(
+            serde_json::from_value(json).unwrap(),
+            Value::Object(fields) if fields["__struct__"] == Value::Int(1)
+        ));
+    }
 }
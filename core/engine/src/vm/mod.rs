@@ -1,7 +1,14 @@
 pub mod native_fn;
 
 use crate::value::Value;
-use roan_error::frame::Frame;
+use anyhow::Result;
+use roan_error::{
+    error::RoanError::{MaxDepthExceeded, Timeout},
+    frame::Frame,
+    TextSpan,
+};
+use std::time::{Duration, Instant};
+use tracing::debug;
 
 /// Virtual machine for executing Roan code.
 #[derive(Debug, Clone)]
@@ -10,6 +17,26 @@ pub struct VM {
     frames: Vec<Frame>,
     /// The stack of values.
     stack: Vec<Value>,
+    /// Value of the last `throw`, the error it raises only carries its message.
+    thrown: Option<Value>,
+    /// Errors handled by the `catch` blocks being executed, innermost last.
+    caught: Vec<CaughtError>,
+    /// Name and duration of every finished call, only recorded once profiling is enabled.
+    frame_timings: Option<Vec<(String, Duration)>>,
+    /// Maximum number of frames on the call stack, unlimited if `None`.
+    max_depth: Option<usize>,
+    /// When the code has to stop running and the timeout it was computed from, set by
+    /// [VM::set_timeout].
+    deadline: Option<(Instant, Duration)>,
+}
+
+/// An error caught by a `catch` block, kept so `throw;` can raise it again unchanged.
+#[derive(Debug, Clone)]
+pub struct CaughtError {
+    pub value: Value,
+    pub message: String,
+    /// The call stack at the original `throw`.
+    pub frames: Vec<Frame>,
 }
 
 impl VM {
@@ -17,17 +44,101 @@ impl VM {
         Self {
             frames: vec![],
             stack: vec![],
+            thrown: None,
+            caught: vec![],
+            frame_timings: None,
+            max_depth: None,
+            deadline: None,
+        }
+    }
+
+    /// Creates a VM where calls fail once there are `max_depth` frames on the call stack.
+    pub fn with_max_depth(max_depth: usize) -> Self {
+        Self {
+            max_depth: Some(max_depth),
+            ..Self::new()
         }
     }
 }
 
+impl VM {
+    pub fn set_thrown(&mut self, value: Value) {
+        self.thrown = Some(value);
+    }
+
+    /// Takes the value of the last `throw`, `None` if the error was thrown by a native.
+    pub fn take_thrown(&mut self) -> Option<Value> {
+        self.thrown.take()
+    }
+
+    pub fn push_caught(&mut self, caught: CaughtError) {
+        self.caught.push(caught);
+    }
+
+    pub fn pop_caught(&mut self) -> Option<CaughtError> {
+        self.caught.pop()
+    }
+
+    /// The error handled by the innermost `catch` block being executed.
+    pub fn caught(&self) -> Option<&CaughtError> {
+        self.caught.last()
+    }
+}
+
 impl VM {
     pub fn push_frame(&mut self, frame: Frame) {
         self.frames.push(frame);
     }
 
     pub fn pop_frame(&mut self) -> Option<Frame> {
-        self.frames.pop()
+        let frame = self.frames.pop()?;
+        debug!("Frame '{}' elapsed: {:?}", frame.name, frame.elapsed());
+
+        if let Some(timings) = &mut self.frame_timings {
+            timings.push((frame.name.clone(), frame.elapsed()));
+        }
+
+        Some(frame)
+    }
+
+    /// Pushes the frame of a call, failing with [MaxDepthExceeded] pointing at `call_site` if the
+    /// call stack is already as deep as it's allowed to be, or with [Timeout] once the timeout
+    /// elapsed.
+    pub fn enter_call(&mut self, frame: Frame, call_site: TextSpan) -> Result<()> {
+        if let Some(max_depth) = self.max_depth {
+            if self.frames.len() >= max_depth {
+                return Err(MaxDepthExceeded(max_depth, call_site).into());
+            }
+        }
+        self.check_deadline()?;
+
+        self.push_frame(frame);
+        Ok(())
+    }
+
+    /// Stops the code run from now on once `timeout` elapsed, the interpreter checks it on every
+    /// call and iteration of a loop.
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.deadline = Some((Instant::now() + timeout, timeout));
+    }
+
+    /// Returns [Timeout] if the timeout set with [VM::set_timeout] elapsed.
+    pub fn check_deadline(&self) -> Result<()> {
+        match self.deadline {
+            Some((deadline, timeout)) if Instant::now() >= deadline => Err(Timeout(timeout).into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Starts recording how long each call takes, see [VM::frame_timings].
+    pub fn enable_profiling(&mut self) {
+        self.frame_timings.get_or_insert_with(Vec::new);
+    }
+
+    /// Returns the name and duration of every call that finished since profiling was enabled,
+    /// in the order they returned. The duration includes the calls made by the function.
+    pub fn frame_timings(&self) -> &[(String, Duration)] {
+        self.frame_timings.as_deref().unwrap_or_default()
     }
 
     pub fn frame(&self) -> Option<&Frame> {
@@ -65,7 +176,6 @@ impl VM {
 mod tests {
     use super::*;
     use crate::value::Value;
-    use roan_error::TextSpan;
 
     #[test]
     fn test_vm() {
@@ -93,4 +203,77 @@ mod tests {
         let popped = vm.pop_frame().unwrap();
         assert_eq!(vm.frames().len(), 0);
     }
+
+    #[test]
+    fn test_frame_timings() {
+        let mut vm = VM::new();
+        vm.push_frame(Frame::new("before", TextSpan::default(), "test.roan"));
+        vm.pop_frame();
+        assert!(vm.frame_timings().is_empty());
+
+        vm.enable_profiling();
+        vm.push_frame(Frame::new("outer", TextSpan::default(), "test.roan"));
+        vm.push_frame(Frame::new("inner", TextSpan::default(), "test.roan"));
+        vm.pop_frame();
+        vm.pop_frame();
+
+        let names: Vec<&str> = vm
+            .frame_timings()
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect();
+        assert_eq!(names, ["inner", "outer"]);
+        assert!(vm.frame_timings()[1].1 >= vm.frame_timings()[0].1);
+    }
+
+    #[test]
+    fn test_max_depth() {
+        let mut vm = VM::with_max_depth(2);
+        vm.enter_call(
+            Frame::new("a", TextSpan::default(), "test.roan"),
+            TextSpan::default(),
+        )
+        .unwrap();
+        vm.enter_call(
+            Frame::new("b", TextSpan::default(), "test.roan"),
+            TextSpan::default(),
+        )
+        .unwrap();
+
+        let err = vm
+            .enter_call(
+                Frame::new("c", TextSpan::default(), "test.roan"),
+                TextSpan::default(),
+            )
+            .unwrap_err();
+        assert_eq!(err.to_string(), "Maximum call depth of 2 exceeded");
+        assert_eq!(vm.frames().len(), 2);
+
+        vm.pop_frame();
+        assert!(vm
+            .enter_call(
+                Frame::new("c", TextSpan::default(), "test.roan"),
+                TextSpan::default()
+            )
+            .is_ok());
+    }
+
+    #[test]
+    fn test_timeout() {
+        let mut vm = VM::new();
+        assert!(vm.check_deadline().is_ok());
+
+        vm.set_timeout(Duration::from_millis(10));
+        assert!(vm.check_deadline().is_ok());
+        std::thread::sleep(Duration::from_millis(20));
+
+        let err = vm.check_deadline().unwrap_err();
+        assert_eq!(err.to_string(), "Program timed out after 10ms");
+        assert!(vm
+            .enter_call(
+                Frame::new("f", TextSpan::default(), "test.roan"),
+                TextSpan::default()
+            )
+            .is_err());
+    }
 }
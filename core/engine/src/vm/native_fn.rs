@@ -1,11 +1,11 @@
-use crate::value::Value;
+use crate::{context::Capability, value::Value};
 use anyhow::Result;
+use roan_ast::TypeKind;
 use std::{
     fmt,
     fmt::{Display, Formatter},
 };
 use tracing::debug;
-use roan_ast::TypeKind;
 
 #[derive(Debug, Clone)]
 pub struct NativeFunctionParam {
@@ -17,23 +17,46 @@ pub struct NativeFunctionParam {
 #[derive(Debug, Clone)]
 pub struct NativeFunction {
     pub name: String,
-    pub func: fn(args: Vec<Value>) -> Value,
+    pub func: fn(args: Vec<Value>) -> Result<Value>,
     pub params: Vec<NativeFunctionParam>,
+    /// Capability the context has to allow for the function to be called.
+    pub capability: Option<Capability>,
 }
 
 impl NativeFunction {
     pub fn new(
         name: impl Into<String>,
         params: Vec<NativeFunctionParam>,
-        func: fn(args: Vec<Value>) -> Value,
+        func: fn(args: Vec<Value>) -> Result<Value>,
     ) -> Self {
         Self {
             name: name.into(),
             func,
             params,
+            capability: None,
         }
     }
 
+    /// Makes calls to the function fail with [CapabilityDenied] unless the context allows
+    /// `capability`.
+    ///
+    /// [CapabilityDenied]: roan_error::error::RoanError::CapabilityDenied
+    pub fn requires(mut self, capability: Capability) -> Self {
+        self.capability = Some(capability);
+        self
+    }
+
+    /// Returns the number of parameters, not counting the rest parameter.
+    pub fn arity(&self) -> usize {
+        self.params.iter().filter(|p| !p.is_rest).count()
+    }
+
+    /// Returns `true` if the function takes a rest parameter, so it accepts any number of
+    /// arguments after the fixed ones.
+    pub fn is_variadic(&self) -> bool {
+        self.params.iter().any(|p| p.is_rest)
+    }
+
     pub fn call(&mut self, args: Vec<Value>) -> Result<Value> {
         debug!(
             "Executing native function: {} with {:?} args",
@@ -52,7 +75,13 @@ impl NativeFunction {
             }
         }
 
-        Ok((self.func)(params))
+        (self.func)(params)
+    }
+}
+
+impl Display for NativeFunction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "<native fn {}>", self.name)
     }
 }
 
@@ -116,10 +145,25 @@ mod tests {
             Value::Null
         );
     }
-}
 
-impl Display for NativeFunction {
-    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        write!(f, "<native fn {}>", self.name)
+    #[test]
+    fn test_arity() {
+        let natives = crate::natives::get_stored_function();
+        let native = |name: &str| {
+            natives
+                .iter()
+                .find_map(|f| match f {
+                    crate::module::StoredFunction::Native(n) if n.name == name => Some(n.clone()),
+                    _ => None,
+                })
+                .unwrap()
+        };
+
+        assert_eq!(native("__exit").arity(), 1);
+        assert!(!native("__exit").is_variadic());
+        assert!(!native("__print").is_variadic());
+
+        assert_eq!(__test().arity(), 2);
+        assert!(__test().is_variadic());
     }
 }
@@ -1,11 +1,16 @@
-use crate::{error::RoanError, span::TextSpan};
+use crate::{
+    error::{format_error_code, RoanError},
+    span::TextSpan,
+};
 use anstream::ColorChoice;
 use anyhow::Result;
 use colored::Colorize;
 use log::Level;
-use roan_shell::Shell;
+use roan_shell::{
+    styles::{ERROR, NOTE, WARN},
+    Shell,
+};
 use std::{
-    env,
     io::{BufWriter, Stderr, Write},
     path::PathBuf,
 };
@@ -20,176 +25,53 @@ pub struct Diagnostic {
     pub text: Option<String>,
     /// The severity level of the diagnostic message (e.g., Error, Warning).
     pub level: Level,
+    /// The stable code of the error (displayed as `E####`), if the diagnostic has one.
+    pub code: Option<u16>,
     /// The location in the source code where the error or warning occurred, represented as a `TextSpan`.
     pub location: Option<TextSpan>,
     /// An optional hint that provides additional guidance on resolving the issue.
     pub hint: Option<String>,
     /// The content of the source code related to the diagnostic.
     pub content: Option<String>,
+    /// The file the source code related to the diagnostic was read from, if any.
+    pub file: Option<PathBuf>,
+    /// Other locations related to the diagnostic, each with a label explaining why, e.g. where
+    /// a variable was declared.
+    pub secondary_spans: Vec<(TextSpan, String)>,
+    /// A suggested fix: the span to replace and the text to replace it with.
+    pub fix: Option<(TextSpan, String)>,
 }
 
 impl Diagnostic {
-    /// Logs the diagnostic in a human-readable format to the provided buffer.
+    /// Builds a diagnostic from the provided error.
     ///
-    /// The message is colored according to its severity level, and the source code around
-    /// the error location (if available) is highlighted.
+    /// Returns `None` if the error is not a [`RoanError`] or if it has no diagnostic
+    /// representation (e.g. an uncaught `throw`, which is reported with its call stack instead).
     ///
     /// # Arguments
     ///
-    /// * `buff` - A mutable reference to a `BufWriter` that writes to `stderr`.
-    ///
-    /// # Example
-    ///
-    /// ```rust ignore
-    /// use std::io::BufWriter;
-    /// use log::Level;
-    /// use roan_error::{Diagnostic, Position, TextSpan};
-    /// let diagnostic = Diagnostic {
-    ///     title: "Syntax Error".to_string(),
-    ///     text: None,
-    ///     level: Level::Error,
-    ///     location: Some(TextSpan::new(Position::new(1, 1, 0), Position::new(1, 5, 4), "test".to_string())),
-    ///     hint: None,
-    ///     content: Some("let x = ;".to_string()),
-    /// };
-    ///
-    /// let mut buff = BufWriter::new(std::io::stderr());
-    /// diagnostic.log_pretty(&mut buff);
-    /// ```
-    pub fn log_pretty(
-        &self,
-        buff: &mut BufWriter<Stderr>,
-        file: Option<PathBuf>,
-        shell: &mut Shell,
-    ) -> Result<()> {
-        writeln!(
-            buff,
-            "{}{}{}",
-            self.level.to_string().to_lowercase().bright_red(),
-            ": ".dimmed(),
-            self.title
-        )
-        .expect("Error writing level");
-
-        if let Some(location) = &self.location {
-            if let Some(content) = &self.content {
-                let line_number = location.start.line;
-                let line = content
-                    .lines()
-                    .nth((line_number - 1) as usize)
-                    .unwrap_or("");
-                let column = location.start.column;
-                let line_content = line.trim_end();
-                let decoration =
-                    "^".repeat(location.end.column as usize - location.start.column as usize);
-
-                let (text, link) = if let Some(file) = &file {
-                    let shortened_path = file
-                        .strip_prefix(std::env::current_dir()?)?
-                        .to_string_lossy()
-                        .to_string();
-                    let text = format!("{}:{}:{}", shortened_path, line_number, column);
-                    (
-                        text.clone().bright_magenta().underline().to_string(),
-                        shell.file_link(file.into())?.to_string(),
-                    )
-                } else {
-                    ("".to_string(), "".to_string())
-                };
-                let hyperlink = shell.hyperlink(&link, &text)?;
-
-                writeln!(buff, "{} {}", "--->".cyan(), hyperlink)
-                    .expect("Error writing line number");
-
-                if line_number > 1 {
-                    let line_before = format!("{} |", line_number - 1);
-                    writeln!(buff, "{}", line_before.cyan()).expect("Error writing line number");
-                }
-
-                let line_current = format!("{} |", line_number);
-                write!(buff, "{}", line_current.cyan()).expect("Error writing line number");
-                writeln!(buff, "    {}", line_content).expect("Error writing content");
-
-                let padding_left =
-                    " ".repeat((column + 6 + line_number.to_string().len() as u32) as usize);
-                writeln!(buff, "{}{}", padding_left, decoration.bright_red())
-                    .expect("Error writing decoration");
-
-                if line_number > 1 {
-                    let line_after = format!("{} |", line_number + 1);
-                    writeln!(buff, "{}", line_after.cyan()).expect("Error writing line number");
-                }
-            }
-        }
-
-        if let Some(text) = &self.text {
-            writeln!(buff, "{}", text).expect("Error writing text");
-        }
-
-        self.print_hint(buff);
-
-        Ok(())
+    /// * `err` - An `anyhow::Error` object that encapsulates the actual error.
+    /// * `content` - An optional string containing the source code related to the error.
+    pub fn from_error(err: &anyhow::Error, content: Option<String>) -> Option<Diagnostic> {
+        Self::from_roan_error(err.downcast_ref::<RoanError>()?, content)
     }
 
-    /// Prints a hint message (if available) to the provided buffer.
-    ///
-    /// # Arguments
-    ///
-    /// * `buff` - A mutable reference to a `BufWriter` that writes to `stderr`.
-    pub fn print_hint(&self, buff: &mut BufWriter<Stderr>) {
-        if let Some(hint) = &self.hint {
-            writeln!(buff, "{}{}", "Hint: ".bright_cyan(), hint.bright_cyan())
-                .expect("Error writing hint");
-        }
-    }
-}
-
-/// Prints a diagnostic message based on the provided error. The function matches
-/// the error type with corresponding diagnostics and logs it prettily.
-///
-/// # Arguments
-///
-/// * `err` - An `anyhow::Error` object that encapsulates the actual error.
-/// * `content` - An optional string slice containing the source code related to the error.
-///
-/// # Example
-///
-/// ```rust ignore
-/// use roan_error::error::PulseError;
-/// use roan_error::print_diagnostic;
-/// let err = PulseError::SemanticError("Unexpected token".to_string(), span);
-/// print_diagnostic(anyhow::Error::new(err), Some(source_code));
-/// ```
-pub fn print_diagnostic(
-    err: &anyhow::Error,
-    content: Option<String>,
-    file: Option<PathBuf>,
-) -> Option<()> {
-    let pulse_error = err.downcast_ref::<RoanError>();
-    let mut shell = Shell::new(ColorChoice::Auto);
-
-    if let Some(err) = pulse_error {
+    fn from_roan_error(err: &RoanError, content: Option<String>) -> Option<Diagnostic> {
         let err_str = err.to_string();
-
-        if let RoanError::Throw(content, frames) = err {
-            let mut buff = BufWriter::new(std::io::stderr());
-
-            shell.error(&err_str).expect("Error writing error");
-
-            for frame in frames {
-                writeln!(buff, "{:?}", frame).expect("Error writing text");
-            }
-            return Some(());
-        }
+        let code = Some(err.error_code());
 
         let diagnostic = match err {
             RoanError::Io(_) => Diagnostic {
                 title: "IO error".to_string(),
                 text: Some(err_str),
                 level: Level::Error,
+                code,
                 location: None,
                 hint: None,
                 content: None,
+                file: None,
+                secondary_spans: vec![],
+                fix: None,
             },
             RoanError::RestParameterNotLast(span)
             | RoanError::RestParameterNotLastPosition(span)
@@ -203,9 +85,13 @@ pub fn print_diagnostic(
                 title: err_str,
                 text: None,
                 level: Level::Error,
+                code,
                 location: Some(span.clone()),
                 hint: None,
                 content,
+                file: None,
+                secondary_spans: vec![],
+                fix: None,
             },
             RoanError::InvalidToken(_, span)
             | RoanError::SemanticError(_, span)
@@ -217,9 +103,13 @@ pub fn print_diagnostic(
                 title: err_str,
                 text: None,
                 level: Level::Error,
+                code,
                 location: Some(span.clone()),
                 hint: None,
                 content,
+                file: None,
+                secondary_spans: vec![],
+                fix: None,
             },
             RoanError::TraitMethodNotImplemented(name, methods, span) => Diagnostic {
                 title: format!(
@@ -228,57 +118,85 @@ pub fn print_diagnostic(
                 ),
                 text: None,
                 level: Level::Error,
+                code,
                 location: Some(span.clone()),
                 hint: Some("Method not implemented".to_string()),
                 content,
+                file: None,
+                secondary_spans: vec![],
+                fix: None,
             },
             RoanError::StructAlreadyImplementsTrait(_, _, span) => Diagnostic {
                 title: err_str,
                 text: None,
                 level: Level::Error,
+                code,
                 location: Some(span.clone()),
                 hint: Some("Struct already implements this trait".to_string()),
                 content,
+                file: None,
+                secondary_spans: vec![],
+                fix: None,
             },
             RoanError::ExpectedToken(_, hint, span) => Diagnostic {
                 title: err_str,
                 text: None,
                 level: Level::Error,
+                code,
                 location: Some(span.clone()),
                 hint: Some(hint.clone()),
                 content,
+                file: None,
+                secondary_spans: vec![],
+                fix: None,
             },
             RoanError::FailedToImportModule(_, _, span) => Diagnostic {
                 title: err_str,
                 text: None,
                 level: Level::Error,
+                code,
                 location: Some(span.clone()),
                 hint: None,
                 content,
+                file: None,
+                secondary_spans: vec![],
+                fix: None,
             },
             RoanError::InvalidType(_, _, span) => Diagnostic {
                 title: err_str,
                 text: None,
                 level: Level::Error,
+                code,
                 location: Some(span.clone()),
                 hint: None,
                 content,
+                file: None,
+                secondary_spans: vec![],
+                fix: None,
             },
             RoanError::ResolverError(_) => Diagnostic {
                 title: err_str,
                 text: None,
                 level: Level::Error,
+                code,
                 location: None,
                 hint: None,
                 content: None,
+                file: None,
+                secondary_spans: vec![],
+                fix: None,
             },
             RoanError::ModuleError(_) => Diagnostic {
                 title: err_str,
                 text: None,
                 level: Level::Error,
+                code,
                 location: None,
                 hint: None,
                 content: None,
+                file: None,
+                secondary_spans: vec![],
+                fix: None,
             },
             RoanError::UndefinedFunctionError(_, span)
             | RoanError::VariableNotFoundError(_, span)
@@ -289,78 +207,687 @@ pub fn print_diagnostic(
             | RoanError::InvalidAssignment(_, span)
             | RoanError::MissingParameter(_, span)
             | RoanError::InvalidUnaryOperation(_, span)
-            | RoanError::MissingField(_, _, span) => Diagnostic {
+            | RoanError::MissingField(_, _, span)
+            | RoanError::ModuleNotFound(_, span)
+            | RoanError::NonConstantExpression(_, span)
+            | RoanError::UnexpectedEof(_, span)
+            | RoanError::DuplicateDefinition(_, span) => Diagnostic {
                 title: err_str,
                 text: None,
                 level: Level::Error,
+                code,
                 location: Some(span.clone()),
                 hint: None,
                 content,
+                file: None,
+                secondary_spans: vec![],
+                fix: None,
             },
+            RoanError::TypeMismatchWithDeclaration(_, span, declaration) => Diagnostic {
+                title: err_str,
+                text: None,
+                level: Level::Error,
+                code,
+                location: Some(span.clone()),
+                hint: None,
+                content,
+                file: None,
+                secondary_spans: vec![],
+                fix: None,
+            }
+            .with_secondary_span(
+                declaration.clone(),
+                "expected type is declared here".to_string(),
+            ),
+            RoanError::WithFix(err, span, replacement) => {
+                Self::from_roan_error(err, content)?.with_fix(span.clone(), replacement.clone())
+            }
             RoanError::InvalidBreakOrContinue(span) => Diagnostic {
                 title: err_str,
                 text: None,
                 level: Level::Error,
+                code,
                 location: Some(span.clone()),
                 hint: Some(
                     "Break and continue statements can only be used inside loops".to_string(),
                 ),
                 content,
+                file: None,
+                secondary_spans: vec![],
+                fix: None,
             },
             RoanError::LoopBreak(span) | RoanError::LoopContinue(span) => Diagnostic {
                 title: err_str,
                 text: None,
                 level: Level::Error,
+                code,
                 location: Some(span.clone()),
                 hint: Some(
                     "Break and continue statements can only be used inside loops".to_string(),
                 ),
                 content,
+                file: None,
+                secondary_spans: vec![],
+                fix: None,
+            },
+            RoanError::RethrowOutsideCatch(span) => Diagnostic {
+                title: err_str,
+                text: None,
+                level: Level::Error,
+                code,
+                location: Some(span.clone()),
+                hint: Some(
+                    "A throw without a value rethrows the error caught by the enclosing catch block"
+                        .to_string(),
+                ),
+                content,
+                file: None,
+                secondary_spans: vec![],
+                fix: None,
             },
             RoanError::TooManyArguments(_, _, _, span) => Diagnostic {
                 title: err_str,
                 text: None,
                 level: Level::Error,
+                code,
                 location: Some(span.clone()),
                 hint: None,
                 content,
+                file: None,
+                secondary_spans: vec![],
+                fix: None,
+            },
+            RoanError::UnterminatedString(span) => Diagnostic {
+                title: err_str,
+                text: None,
+                level: Level::Error,
+                code,
+                location: Some(span.clone()),
+                hint: Some("The string starts here and is never closed".to_string()),
+                content,
+                file: None,
+                secondary_spans: vec![],
+                fix: None,
+            },
+            RoanError::ChainedComparison(span) => Diagnostic {
+                title: err_str,
+                text: None,
+                level: Level::Error,
+                code,
+                location: Some(span.clone()),
+                hint: Some(
+                    "`a < b < c` compares the boolean `a < b` with `c`, write `a < b && b < c` instead"
+                        .to_string(),
+                ),
+                content,
+                file: None,
+                secondary_spans: vec![],
+                fix: None,
+            },
+            RoanError::VoidValue(_, span) => Diagnostic {
+                title: err_str,
+                text: None,
+                level: Level::Error,
+                code,
+                location: Some(span.clone()),
+                hint: Some(
+                    "Declare the return type of the function, or return `null` to represent a missing value"
+                        .to_string(),
+                ),
+                content,
+                file: None,
+                secondary_spans: vec![],
+                fix: None,
+            },
+            RoanError::ItemNotExported(name, span) => Diagnostic {
+                title: err_str,
+                text: None,
+                level: Level::Error,
+                code,
+                location: Some(span.clone()),
+                hint: Some(format!(
+                    "Declare {} with `pub` to import it from other modules",
+                    name
+                )),
+                content,
+                file: None,
+                secondary_spans: vec![],
+                fix: None,
+            },
+            RoanError::IntegerOverflow(_, span) => Diagnostic {
+                title: err_str,
+                text: None,
+                level: Level::Error,
+                code,
+                location: Some(span.clone()),
+                hint: Some(
+                    "Ints are 64 bits wide, use a float operand to get a float result".to_string(),
+                ),
+                content,
+                file: None,
+                secondary_spans: vec![],
+                fix: None,
+            },
+            RoanError::MaxDepthExceeded(_, span) => Diagnostic {
+                title: err_str,
+                text: None,
+                level: Level::Error,
+                code,
+                location: Some(span.clone()),
+                hint: Some(
+                    "Make sure the recursion of this function stops, or raise the limit with `--max-depth`"
+                        .to_string(),
+                ),
+                content,
+                file: None,
+                secondary_spans: vec![],
+                fix: None,
+            },
+            RoanError::MissingReturn(span) => Diagnostic {
+                title: err_str,
+                text: None,
+                level: Level::Error,
+                code,
+                location: Some(span.clone()),
+                hint: Some(
+                    "Return a value at the end of the function, or in an `else` branch of the last `if`"
+                        .to_string(),
+                ),
+                content,
+                file: None,
+                secondary_spans: vec![],
+                fix: None,
+            },
+            RoanError::Timeout(_) => Diagnostic {
+                title: err_str,
+                text: None,
+                level: Level::Error,
+                code,
+                location: None,
+                hint: Some(
+                    "Make sure the loops of the program stop, or raise the limit with `--timeout`"
+                        .to_string(),
+                ),
+                content: None,
+                file: None,
+                secondary_spans: vec![],
+                fix: None,
             },
             RoanError::InvalidSpread(span) => Diagnostic {
                 title: err_str,
                 text: None,
                 level: Level::Error,
+                code,
                 location: Some(span.clone()),
                 hint: Some(
                     "Spread operator can only be used in function calls or vectors".to_string(),
                 ),
                 content,
+                file: None,
+                secondary_spans: vec![],
+                fix: None,
             },
             RoanError::InvalidPropertyAccess(span) => Diagnostic {
                 title: err_str,
                 text: None,
                 level: Level::Error,
+                code,
                 location: Some(span.clone()),
                 hint: Some("Only string literals or call expressions are allowed".to_string()),
                 content,
+                file: None,
+                secondary_spans: vec![],
+                fix: None,
             },
             RoanError::IndexOutOfBounds(_, _, span) => Diagnostic {
                 title: err_str,
                 text: None,
                 level: Level::Error,
+                code,
                 location: Some(span.clone()),
                 hint: None,
                 content,
+                file: None,
+                secondary_spans: vec![],
+                fix: None,
+            },
+            RoanError::AbortedDueToPreviousErrors(_) => Diagnostic {
+                title: err_str,
+                text: None,
+                level: Level::Error,
+                code,
+                location: None,
+                hint: None,
+                content: None,
+                file: None,
+                secondary_spans: vec![],
+                fix: None,
             },
             _ => return None,
         };
 
+        Some(diagnostic)
+    }
+
+    /// Adds another location related to the diagnostic, printed below the main one as a note
+    /// with `label`.
+    pub fn with_secondary_span(mut self, span: TextSpan, label: String) -> Self {
+        self.secondary_spans.push((span, label));
+        self
+    }
+
+    /// Sets the suggested fix of the diagnostic, printed as a `help:` with the replacement.
+    ///
+    /// # Arguments
+    ///
+    /// * `span` - The source to replace.
+    /// * `replacement` - The text to replace it with.
+    pub fn with_fix(mut self, span: TextSpan, replacement: String) -> Self {
+        self.fix = Some((span, replacement));
+        self
+    }
+
+    /// Returns the label printed before the title, e.g. `error` or `error[E0019]`.
+    pub fn label(&self) -> String {
+        let level = match self.level {
+            Level::Warn => "warning".to_string(),
+            level => level.to_string().to_lowercase(),
+        };
+
+        match self.code {
+            Some(code) => format!("{}[{}]", level, format_error_code(code)),
+            None => level,
+        }
+    }
+
+    /// Logs the diagnostic in a human-readable format to the provided buffer.
+    ///
+    /// The message is colored according to its severity level, and the source code around
+    /// the error location (if available) is highlighted.
+    ///
+    /// # Arguments
+    ///
+    /// * `buff` - A mutable reference to a `BufWriter` that writes to `stderr`.
+    ///
+    /// # Example
+    ///
+    /// ```rust ignore
+    /// use std::io::BufWriter;
+    /// use log::Level;
+    /// use roan_error::{Diagnostic, Position, TextSpan};
+    /// let diagnostic = Diagnostic {
+    ///     title: "Syntax Error".to_string(),
+    ///     text: None,
+    ///     level: Level::Error,
+    ///     code: None,
+    ///     location: Some(TextSpan::new(Position::new(1, 1, 0), Position::new(1, 5, 4), "test".to_string())),
+    ///     hint: None,
+    ///     content: Some("let x = ;".to_string()),
+    ///     secondary_spans: vec![],
+    ///     fix: None,
+    /// };
+    ///
+    /// let mut buff = BufWriter::new(std::io::stderr());
+    /// diagnostic.log_pretty(&mut buff);
+    /// ```
+    pub fn log_pretty(
+        &self,
+        buff: &mut BufWriter<Stderr>,
+        file: Option<PathBuf>,
+        shell: &mut Shell,
+    ) -> Result<()> {
+        writeln!(
+            buff,
+            "{}{}{}",
+            self.label().bright_red(),
+            ": ".dimmed(),
+            self.title
+        )
+        .expect("Error writing level");
+
+        self.log_details(buff, file, shell)
+    }
+
+    /// Logs everything but the title line of the diagnostic: the highlighted source location,
+    /// the detailed text and the hint.
+    ///
+    /// # Arguments
+    ///
+    /// * `buff` - A mutable reference to a `BufWriter` that writes to `stderr`.
+    /// * `file` - The file the diagnostic originates from, used to print a link to the location.
+    /// * `shell` - The shell used to create hyperlinks.
+    pub fn log_details<W: Write>(
+        &self,
+        buff: &mut W,
+        file: Option<PathBuf>,
+        shell: &mut Shell,
+    ) -> Result<()> {
+        let styled = shell.output.stderr().current_choice() != ColorChoice::Never;
+
+        if let Some(location) = &self.location {
+            if let Some(content) = &self.content {
+                let line_number = location.start.line;
+                let column = location.start.column;
+
+                let (text, link) = if let Some(file) = &file {
+                    // Files outside of the working directory keep their full path
+                    let cwd = std::env::current_dir()?;
+                    let shortened_path = file
+                        .strip_prefix(&cwd)
+                        .unwrap_or(file)
+                        .to_string_lossy()
+                        .to_string();
+                    let text = format!("{}:{}:{}", shortened_path, line_number, column);
+                    (
+                        text.clone().bright_magenta().underline().to_string(),
+                        shell.file_link(file.into())?.to_string(),
+                    )
+                } else {
+                    ("".to_string(), "".to_string())
+                };
+                // The location is enough without hyperlinks, the file URL would only add noise
+                let file_location = if shell.supports_hyperlinks() {
+                    shell.hyperlink(&link, &text)?.to_string()
+                } else {
+                    text
+                };
+
+                writeln!(buff, "{} {}", "--->".cyan(), file_location)
+                    .expect("Error writing line number");
+
+                writeln!(buff, "{}", location.highlight(content, ""))
+                    .expect("Error writing highlighted source");
+
+                self.log_secondary_spans(buff, content, styled)?;
+            }
+        }
+
+        if let Some(text) = &self.text {
+            writeln!(buff, "{}", text).expect("Error writing text");
+        }
+
+        self.print_hint(buff);
+        self.log_fix(buff, styled)?;
+
+        Ok(())
+    }
+
+    /// Prints the suggested fix as a `help:`, followed by the highlighted source it replaces.
+    fn log_fix<W: Write>(&self, buff: &mut W, styled: bool) -> Result<()> {
+        if let Some((span, replacement)) = &self.fix {
+            if styled {
+                writeln!(buff, "{NOTE}help:{NOTE:#} replace this with: {replacement}")?;
+            } else {
+                writeln!(buff, "help: replace this with: {replacement}")?;
+            }
+
+            if let Some(content) = &self.content {
+                writeln!(buff, "{}", span.highlight(content, ""))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Prints every secondary span as a `note:` with its label, followed by the highlighted
+    /// source.
+    fn log_secondary_spans<W: Write>(
+        &self,
+        buff: &mut W,
+        content: &str,
+        styled: bool,
+    ) -> Result<()> {
+        for (span, label) in self.secondary_spans.iter() {
+            if styled {
+                writeln!(buff, "{NOTE}note:{NOTE:#} {label}")?;
+            } else {
+                writeln!(buff, "note: {label}")?;
+            }
+            writeln!(buff, "{}", span.highlight(content, ""))?;
+        }
+
+        Ok(())
+    }
+
+    /// Prints a hint message (if available) to the provided buffer.
+    ///
+    /// # Arguments
+    ///
+    /// * `buff` - A mutable reference to a `BufWriter` that writes to `stderr`.
+    pub fn print_hint<W: Write>(&self, buff: &mut W) {
+        if let Some(hint) = &self.hint {
+            writeln!(buff, "{}{}", "Hint: ".bright_cyan(), hint.bright_cyan())
+                .expect("Error writing hint");
+        }
+    }
+}
+
+/// Accumulates diagnostics so that every error found while parsing and type-checking a module
+/// is reported, instead of stopping at the first one.
+///
+/// Diagnostics are kept in the order they were pushed and written to `stderr` by [`DiagnosticBuffer::flush`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DiagnosticBuffer {
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl DiagnosticBuffer {
+    /// Creates a new, empty `DiagnosticBuffer`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a diagnostic to the end of the buffer.
+    pub fn push(&mut self, diagnostic: Diagnostic) {
+        self.diagnostics.push(diagnostic);
+    }
+
+    /// Returns the number of diagnostics in the buffer.
+    pub fn len(&self) -> usize {
+        self.diagnostics.len()
+    }
+
+    /// Returns `true` if the buffer contains no diagnostics.
+    pub fn is_empty(&self) -> bool {
+        self.diagnostics.is_empty()
+    }
+
+    /// Returns `true` if any diagnostic in the buffer is an error.
+    pub fn has_errors(&self) -> bool {
+        self.diagnostics.iter().any(|d| d.level == Level::Error)
+    }
+
+    /// Returns the number of errors in the buffer, warnings aren't counted.
+    pub fn error_count(&self) -> usize {
+        self.diagnostics
+            .iter()
+            .filter(|d| d.level == Level::Error)
+            .count()
+    }
+
+    /// Returns an iterator over the diagnostics in the buffer.
+    pub fn iter(&self) -> std::slice::Iter<'_, Diagnostic> {
+        self.diagnostics.iter()
+    }
+
+    /// Writes all diagnostics to `stderr` in the order they were added and empties the buffer.
+    ///
+    /// The title of each diagnostic is printed through the shell with the error (or warning)
+    /// style, followed by the highlighted source location and hint.
+    ///
+    /// # Arguments
+    ///
+    /// * `shell` - The shell used to print the diagnostics.
+    pub fn flush(&mut self, shell: &mut Shell) -> Result<()> {
+        for diagnostic in self.diagnostics.drain(..) {
+            let style = match diagnostic.level {
+                Level::Error => ERROR,
+                _ => WARN,
+            };
+            shell.print(&diagnostic.label(), Some(&diagnostic.title), &style, false)?;
+
+            let mut buff = BufWriter::new(std::io::stderr());
+            diagnostic.log_details(&mut buff, diagnostic.file.clone(), shell)?;
+            buff.flush()?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Extend<Diagnostic> for DiagnosticBuffer {
+    fn extend<T: IntoIterator<Item = Diagnostic>>(&mut self, iter: T) {
+        self.diagnostics.extend(iter);
+    }
+}
+
+impl FromIterator<Diagnostic> for DiagnosticBuffer {
+    fn from_iter<T: IntoIterator<Item = Diagnostic>>(iter: T) -> Self {
+        Self {
+            diagnostics: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl IntoIterator for DiagnosticBuffer {
+    type Item = Diagnostic;
+    type IntoIter = std::vec::IntoIter<Diagnostic>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.diagnostics.into_iter()
+    }
+}
+
+/// Prints a diagnostic message based on the provided error. The function matches
+/// the error type with corresponding diagnostics and logs it prettily.
+///
+/// # Arguments
+///
+/// * `err` - An `anyhow::Error` object that encapsulates the actual error.
+/// * `content` - An optional string slice containing the source code related to the error.
+///
+/// # Example
+///
+/// ```rust ignore
+/// use roan_error::error::PulseError;
+/// use roan_error::print_diagnostic;
+/// let err = PulseError::SemanticError("Unexpected token".to_string(), span);
+/// print_diagnostic(anyhow::Error::new(err), Some(source_code));
+/// ```
+pub fn print_diagnostic(
+    err: &anyhow::Error,
+    content: Option<String>,
+    file: Option<PathBuf>,
+) -> Option<()> {
+    let pulse_error = err.downcast_ref::<RoanError>()?;
+    let mut shell = Shell::new(ColorChoice::Auto);
+
+    if let RoanError::Throw(_, frames) | RoanError::CapabilityDenied(_, _, frames) = pulse_error {
         let mut buff = BufWriter::new(std::io::stderr());
+
+        shell
+            .print(
+                &format!("error[{}]", pulse_error.code()),
+                Some(pulse_error),
+                &ERROR,
+                false,
+            )
+            .expect("Error writing error");
+
+        for frame in frames {
+            writeln!(buff, "{:?}", frame).expect("Error writing text");
+        }
+        return Some(());
+    }
+
+    let diagnostic = Diagnostic::from_error(err, content)?;
+
+    let mut buff = BufWriter::new(std::io::stderr());
+    diagnostic
+        .log_pretty(&mut buff, file, &mut shell)
+        .expect("Error writing diagnostic");
+
+    Some(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Position;
+
+    #[test]
+    fn test_secondary_span_golden() {
+        colored::control::set_override(false);
+
+        let content = "fn add(a: int, b: int) -> int {\n    return a + b;\n}\nadd(1, \"2\");\n";
+        let diagnostic = Diagnostic::from_error(
+            &RoanError::TypeMismatchWithDeclaration(
+                "Expected type int but got string".to_string(),
+                TextSpan::new(
+                    Position::new(4, 7, 50),
+                    Position::new(4, 10, 53),
+                    "\"2\"".into(),
+                ),
+                TextSpan::new(
+                    Position::new(1, 15, 15),
+                    Position::new(1, 16, 16),
+                    "b".into(),
+                ),
+            )
+            .into(),
+            Some(content.to_string()),
+        )
+        .unwrap()
+        .with_secondary_span(
+            TextSpan::new(Position::new(1, 3, 3), Position::new(1, 6, 6), "add".into()),
+            "function is declared here".to_string(),
+        );
+
+        let mut output = vec![];
+        diagnostic
+            .log_details(&mut output, None, &mut Shell::new(ColorChoice::Never))
+            .unwrap();
+
+        assert_eq!(diagnostic.label(), "error[E0019]");
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            include_str!("../tests/fixtures/secondary_spans.stderr")
+        );
+    }
+
+    #[test]
+    fn test_fix_is_printed() {
+        colored::control::set_override(false);
+
+        let content = "let count = 1;\ncuont + 1;\n";
+        let span = TextSpan::new(
+            Position::new(2, 0, 15),
+            Position::new(2, 5, 20),
+            "cuont".into(),
+        );
+        let diagnostic = Diagnostic::from_error(
+            &RoanError::VariableNotFoundError("cuont".to_string(), span.clone())
+                .with_fix(span.clone(), "count".to_string())
+                .into(),
+            Some(content.to_string()),
+        )
+        .unwrap();
+
+        assert_eq!(diagnostic.label(), "error[E0010]");
+        assert_eq!(diagnostic.location, Some(span.clone()));
+        assert_eq!(diagnostic.fix, Some((span, "count".to_string())));
+
+        let mut output = vec![];
         diagnostic
-            .log_pretty(&mut buff, file, &mut shell)
-            .expect("Error writing diagnostic");
+            .log_details(&mut output, None, &mut Shell::new(ColorChoice::Never))
+            .unwrap();
 
-        Some(())
-    } else {
-        None
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "---> \n\
+             2 | cuont + 1;\n  | ^^^^^\n\
+             help: replace this with: count\n\
+             2 | cuont + 1;\n  | ^^^^^\n"
+        );
     }
 }
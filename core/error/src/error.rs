@@ -1,4 +1,6 @@
-use crate::{frame::Frame, span::TextSpan};
+use crate::{diagnostic::Diagnostic, frame::Frame, span::TextSpan};
+use log::Level;
+use std::time::Duration;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -37,8 +39,8 @@ pub enum RoanError {
     InvalidEscapeSequence(String, TextSpan),
     #[error("{0} does not evaluate to a boolean.")]
     NonBooleanCondition(String, TextSpan),
-    #[error("Index out of bounds: {0} >= {1}")]
-    IndexOutOfBounds(usize, usize, TextSpan),
+    #[error("Index out of bounds: {0} for a length of {1}")]
+    IndexOutOfBounds(i64, usize, TextSpan),
     #[error("Type mismatch: {0}")]
     TypeMismatch(String, TextSpan),
     #[error("Invalid assignment {0}")]
@@ -87,11 +89,191 @@ pub enum RoanError {
     TooManyArguments(usize, String, usize, TextSpan),
     #[error("Attempted to assign value to non existing struct field {0}")]
     PropertyAssignmentError(String, TextSpan),
+    /// Errors were reported as diagnostics, which are carried along so the caller can decide
+    /// how to show them. Warnings reported with them are included.
+    #[error("Aborting due to {} previous error(s)", error_count(.0))]
+    AbortedDueToPreviousErrors(Vec<Diagnostic>),
+    #[error("Module not found: {0}")]
+    ModuleNotFound(String, TextSpan),
+    #[error("Value of const {0} is not a constant expression")]
+    NonConstantExpression(String, TextSpan),
+    /// A [RoanError::TypeMismatch] where the declaration of the expected type is known. The
+    /// second span points to the declaration.
+    #[error("Type mismatch: {0}")]
+    TypeMismatchWithDeclaration(String, TextSpan, TextSpan),
+    /// Another error together with a suggested fix: the span to replace and the replacement.
+    /// Created with [RoanError::with_fix], it has the code and message of the wrapped error.
+    #[error("{0}")]
+    WithFix(Box<RoanError>, TextSpan, String),
+    /// The input ended before a token or expression, named by the first field, was found. The
+    /// span points at the end of the input.
+    #[error("Unexpected end of file, expected {0}")]
+    UnexpectedEof(String, TextSpan),
+    /// An item was brought into a module that already has an item with the same name.
+    #[error("{0} is defined multiple times")]
+    DuplicateDefinition(String, TextSpan),
+    /// A bare `throw;` was found outside of a `catch` block, so there is no error to rethrow.
+    #[error("Found rethrow outside of catch block.")]
+    RethrowOutsideCatch(TextSpan),
+    /// A raw or multiline string literal isn't closed before the end of the file. The span points
+    /// at its opening quote.
+    #[error("Unterminated string literal.")]
+    UnterminatedString(TextSpan),
+    /// A comparison was chained after another, as in `a < b < c`. The span points at the second
+    /// operator.
+    #[error("Comparison operators cannot be chained.")]
+    ChainedComparison(TextSpan),
+    /// A native needing a capability the context doesn't allow was called. The fields are the
+    /// capability, the name of the native and the call stack, it can be caught like a thrown
+    /// error.
+    #[error("{1} requires the {0} capability, which is disabled")]
+    CapabilityDenied(String, String, Vec<Frame>),
+    /// The result of a call to a function that doesn't return a value was used as a value, e.g.
+    /// bound to a variable. The fields are the name of the function and the span of the call.
+    #[error("{0} doesn't return a value, its result can't be used")]
+    VoidValue(String, TextSpan),
+    /// An item that isn't `pub` was imported from another module. The fields are the name of the
+    /// item and the span of its name in the `use` statement.
+    #[error("{0} is private to the module it's declared in")]
+    ItemNotExported(String, TextSpan),
+    /// The result of an operation on ints doesn't fit in an int. The fields are the operation,
+    /// e.g. `2 ** 63`, and its span.
+    #[error("Integer overflow in {0}")]
+    IntegerOverflow(String, TextSpan),
+    /// A call would make the call stack deeper than allowed. The fields are the maximum depth and
+    /// the span of the called function.
+    #[error("Maximum call depth of {0} exceeded")]
+    MaxDepthExceeded(usize, TextSpan),
+    /// The program ran for longer than it's allowed to, the field is how long.
+    #[error("Program timed out after {0:?}")]
+    Timeout(Duration),
+    /// A path through a function declared to return a value ends without a `return` or a
+    /// `throw`. The span points at the return type of the function.
+    #[error("Not every path of the function returns a value")]
+    MissingReturn(TextSpan),
+    /// The program called `__exit`, the field is the exit code. It unwinds through the
+    /// interpreter without being caught, so the host decides how to exit.
+    #[error("Exited with code {0}")]
+    Exit(i32),
+}
+
+impl RoanError {
+    /// Returns the numeric code of the error, displayed as `E####` in diagnostics.
+    ///
+    /// Codes are stable across versions: an existing code is never renumbered or reused,
+    /// new variants always get the next free code.
+    pub fn error_code(&self) -> u16 {
+        match self {
+            RoanError::Io(..) => 1,
+            RoanError::InvalidToken(..) => 2,
+            RoanError::ExpectedToken(..) => 3,
+            RoanError::UnexpectedToken(..) => 4,
+            RoanError::SemanticError(..) => 5,
+            RoanError::ResolverError(..) => 6,
+            RoanError::ModuleError(..) => 7,
+            RoanError::ImportError(..) => 8,
+            RoanError::FailedToImportModule(..) => 9,
+            RoanError::VariableNotFoundError(..) => 10,
+            RoanError::UndefinedFunctionError(..) => 11,
+            RoanError::RestParameterNotLast(..) => 12,
+            RoanError::RestParameterNotLastPosition(..) => 13,
+            RoanError::MultipleRestParameters(..) => 14,
+            RoanError::Throw(..) => 15,
+            RoanError::InvalidEscapeSequence(..) => 16,
+            RoanError::NonBooleanCondition(..) => 17,
+            RoanError::IndexOutOfBounds(..) => 18,
+            RoanError::TypeMismatch(..) | RoanError::TypeMismatchWithDeclaration(..) => 19,
+            RoanError::InvalidAssignment(..) => 20,
+            RoanError::PropertyNotFoundError(..) => 21,
+            RoanError::InvalidPropertyAccess(..) => 22,
+            RoanError::InvalidBreakOrContinue(..) => 23,
+            RoanError::LoopBreak(..) => 24,
+            RoanError::LoopContinue(..) => 25,
+            RoanError::InvalidSpread(..) => 26,
+            RoanError::MultipleSelfParameters(..) => 27,
+            RoanError::SelfParameterNotFirst(..) => 28,
+            RoanError::SelfParameterCannotBeRest(..) => 29,
+            RoanError::StructNotFoundError(..) => 30,
+            RoanError::TraitNotFoundError(..) => 31,
+            RoanError::StructAlreadyImplementsTrait(..) => 32,
+            RoanError::TraitMethodNotImplemented(..) => 33,
+            RoanError::StaticMemberAssignment(..) => 34,
+            RoanError::StaticMemberAccess(..) => 35,
+            RoanError::StaticContext(..) => 36,
+            RoanError::InvalidUnaryOperation(..) => 37,
+            RoanError::MissingParameter(..) => 38,
+            RoanError::InvalidType(..) => 39,
+            RoanError::MissingField(..) => 40,
+            RoanError::TooManyArguments(..) => 41,
+            RoanError::PropertyAssignmentError(..) => 42,
+            RoanError::AbortedDueToPreviousErrors(..) => 43,
+            RoanError::ModuleNotFound(..) => 44,
+            RoanError::NonConstantExpression(..) => 45,
+            RoanError::WithFix(err, ..) => err.error_code(),
+            RoanError::UnexpectedEof(..) => 46,
+            RoanError::DuplicateDefinition(..) => 47,
+            RoanError::RethrowOutsideCatch(..) => 48,
+            RoanError::UnterminatedString(..) => 49,
+            RoanError::ChainedComparison(..) => 50,
+            RoanError::CapabilityDenied(..) => 51,
+            RoanError::VoidValue(..) => 52,
+            RoanError::ItemNotExported(..) => 53,
+            RoanError::IntegerOverflow(..) => 54,
+            RoanError::MaxDepthExceeded(..) => 55,
+            RoanError::Timeout(..) => 56,
+            RoanError::MissingReturn(..) => 57,
+            RoanError::Exit(..) => 58,
+        }
+    }
+
+    /// Attaches a suggested fix to the error, printed as a `help:` below its diagnostic.
+    ///
+    /// # Arguments
+    ///
+    /// * `span` - The source to replace.
+    /// * `replacement` - The text to replace it with.
+    pub fn with_fix(self, span: TextSpan, replacement: String) -> Self {
+        RoanError::WithFix(Box::new(self), span, replacement)
+    }
+
+    /// Returns the error code formatted as `E####`.
+    pub fn code(&self) -> String {
+        format_error_code(self.error_code())
+    }
+}
+
+/// Formats a numeric error code as `E####`.
+pub fn format_error_code(code: u16) -> String {
+    format!("E{:04}", code)
+}
+
+/// Parses an error code in the `E####` form (the `E` is optional and case-insensitive).
+pub fn parse_error_code(code: &str) -> Option<u16> {
+    let code = code.trim();
+    let digits = code
+        .strip_prefix('E')
+        .or_else(|| code.strip_prefix('e'))
+        .unwrap_or(code);
+
+    digits.parse().ok()
+}
+
+fn error_count(diagnostics: &[Diagnostic]) -> usize {
+    diagnostics
+        .iter()
+        .filter(|d| d.level == Level::Error)
+        .count()
 }
 
 pub fn get_span_from_err(err: &RoanError) -> Option<TextSpan> {
     match err {
-        RoanError::Io(_) | RoanError::ResolverError(_) | RoanError::ModuleError(_) => None,
+        RoanError::Io(_)
+        | RoanError::ResolverError(_)
+        | RoanError::ModuleError(_)
+        | RoanError::AbortedDueToPreviousErrors(_)
+        | RoanError::CapabilityDenied(..)
+        | RoanError::Timeout(_)
+        | RoanError::Exit(_) => None,
         RoanError::RestParameterNotLast(span)
         | RoanError::RestParameterNotLastPosition(span)
         | RoanError::MultipleRestParameters(span)
@@ -100,7 +282,10 @@ pub fn get_span_from_err(err: &RoanError) -> Option<TextSpan> {
         | RoanError::MultipleSelfParameters(span)
         | RoanError::StaticContext(span)
         | RoanError::StaticMemberAccess(span)
-        | RoanError::StaticMemberAssignment(span) => Some(span.clone()),
+        | RoanError::StaticMemberAssignment(span)
+        | RoanError::UnterminatedString(span)
+        | RoanError::ChainedComparison(span)
+        | RoanError::MissingReturn(span) => Some(span.clone()),
         RoanError::InvalidToken(_, span)
         | RoanError::SemanticError(_, span)
         | RoanError::UnexpectedToken(_, span)
@@ -114,6 +299,7 @@ pub fn get_span_from_err(err: &RoanError) -> Option<TextSpan> {
         | RoanError::ExpectedToken(_, _, span)
         | RoanError::FailedToImportModule(_, _, span)
         | RoanError::MissingField(_, _, span)
+        | RoanError::TypeMismatchWithDeclaration(_, span, _)
         | RoanError::InvalidType(_, _, span)
         | RoanError::IndexOutOfBounds(_, _, span) => Some(span.clone()),
         RoanError::UndefinedFunctionError(_, span)
@@ -123,13 +309,44 @@ pub fn get_span_from_err(err: &RoanError) -> Option<TextSpan> {
         | RoanError::TypeMismatch(_, span)
         | RoanError::InvalidAssignment(_, span)
         | RoanError::MissingParameter(_, span)
-        | RoanError::InvalidUnaryOperation(_, span) => Some(span.clone()),
+        | RoanError::InvalidUnaryOperation(_, span)
+        | RoanError::ModuleNotFound(_, span)
+        | RoanError::NonConstantExpression(_, span)
+        | RoanError::UnexpectedEof(_, span)
+        | RoanError::DuplicateDefinition(_, span)
+        | RoanError::VoidValue(_, span)
+        | RoanError::ItemNotExported(_, span)
+        | RoanError::IntegerOverflow(_, span)
+        | RoanError::MaxDepthExceeded(_, span) => Some(span.clone()),
         RoanError::InvalidPropertyAccess(span)
         | RoanError::InvalidSpread(span)
         | RoanError::InvalidBreakOrContinue(span)
         | RoanError::LoopBreak(span)
-        | RoanError::LoopContinue(span) => Some(span.clone()),
+        | RoanError::LoopContinue(span)
+        | RoanError::RethrowOutsideCatch(span) => Some(span.clone()),
         RoanError::TooManyArguments(_, _, _, span) => Some(span.clone()),
+        RoanError::WithFix(err, ..) => get_span_from_err(err),
         _ => None,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_code() {
+        let err = RoanError::TypeMismatch("int".to_string(), TextSpan::default());
+
+        assert_eq!(err.error_code(), 19);
+        assert_eq!(err.code(), "E0019");
+    }
+
+    #[test]
+    fn test_parse_error_code() {
+        assert_eq!(parse_error_code("E0042"), Some(42));
+        assert_eq!(parse_error_code("e7"), Some(7));
+        assert_eq!(parse_error_code("0019"), Some(19));
+        assert_eq!(parse_error_code("EXXXX"), None);
+    }
+}
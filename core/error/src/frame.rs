@@ -1,6 +1,10 @@
 use crate::TextSpan;
 use colored::Colorize;
-use std::{fmt::Debug, path::PathBuf};
+use std::{
+    fmt::Debug,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
 
 /// A frame represents a single function call.
 ///
@@ -13,6 +17,8 @@ pub struct Frame {
     pub span: TextSpan,
     /// The path of the file where the function is defined.
     pub path: String,
+    /// When the function was called.
+    pub started_at: Instant,
 }
 
 impl Frame {
@@ -37,9 +43,16 @@ impl Frame {
             name: name.into(),
             span,
             path: path.into(),
+            started_at: Instant::now(),
         }
     }
 
+    /// Returns the time since the function was called, including the time spent in the
+    /// functions it called.
+    pub fn elapsed(&self) -> Duration {
+        self.started_at.elapsed()
+    }
+
     /// If path is None returns "unknown" otherwise returns the path.
     pub fn path_or_unknown(path: Option<PathBuf>) -> String {
         let path = path
@@ -97,4 +110,16 @@ mod tests {
         let path = None;
         assert_eq!(Frame::path_or_unknown(path), "unknown");
     }
+
+    #[test]
+    fn test_frame_elapsed() {
+        let frame = Frame::new("main", TextSpan::default(), "main.roan");
+        let first = frame.elapsed();
+
+        std::thread::sleep(Duration::from_millis(5));
+
+        let second = frame.elapsed();
+        assert!(second >= Duration::from_millis(5));
+        assert!(second > first);
+    }
 }
@@ -37,6 +37,33 @@ impl Position {
         }
     }
 
+    /// Returns the position of the byte `offset` in `content`, e.g. reported by an external tool.
+    ///
+    /// Positions are counted like the lexer counts the positions of tokens: the column starts at
+    /// 0 and, like the index, counts characters. An offset past the end or inside a character is
+    /// moved back to the closest character.
+    ///
+    /// Use `Source::position_at` of `roan_ast` when the source is at hand, it finds the line
+    /// without scanning.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use roan_error::Position;
+    /// let pos = Position::from_offset("let x;\nlet y;", 11);
+    /// assert_eq!(pos, Position::new(2, 4, 11));
+    /// ```
+    pub fn from_offset(content: &str, offset: usize) -> Self {
+        let before = &content[..floor_char_boundary(content, offset)];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+
+        Self::new(
+            before.matches('\n').count() as u32 + 1,
+            before[line_start..].chars().count() as u32,
+            before.chars().count(),
+        )
+    }
+
     /// Increments the line number, resets the column to 1, and increments the index by 1.
     pub fn increment_line(&mut self) {
         self.line += 1;
@@ -88,6 +115,16 @@ impl Position {
     }
 }
 
+/// Returns the largest char boundary of `content` that isn't after `offset`.
+pub fn floor_char_boundary(content: &str, offset: usize) -> usize {
+    let mut offset = offset.min(content.len());
+    while !content.is_char_boundary(offset) {
+        offset -= 1;
+    }
+
+    offset
+}
+
 impl fmt::Display for Position {
     /// Formats the position as `line:column (index: byte_index)`.
     ///
@@ -159,4 +196,20 @@ mod tests {
         let default_pos = Position::default();
         assert_eq!(default_pos, Position::new(1, 1, 0));
     }
+
+    #[test]
+    fn test_position_from_offset() {
+        let content = "fn main() {\n    let s = \"ñ\";\n}";
+
+        assert_eq!(Position::from_offset(content, 0), Position::new(1, 0, 0));
+        assert_eq!(Position::from_offset(content, 11), Position::new(1, 11, 11));
+        assert_eq!(Position::from_offset(content, 12), Position::new(2, 0, 12));
+        assert_eq!(Position::from_offset(content, 20), Position::new(2, 8, 20));
+        // After the two bytes of `ñ`, the character is counted once
+        assert_eq!(Position::from_offset(content, 27), Position::new(2, 14, 26));
+        // The last character, inside `ñ` and past the end
+        assert_eq!(Position::from_offset(content, 30), Position::new(3, 0, 29));
+        assert_eq!(Position::from_offset(content, 26), Position::new(2, 13, 25));
+        assert_eq!(Position::from_offset(content, 100), Position::new(3, 1, 30));
+    }
 }
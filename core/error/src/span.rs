@@ -1,7 +1,10 @@
 use crate::position::Position;
+use std::cmp::Ordering;
 
 /// Represents a span of text between two positions, including the literal text.
-#[derive(Clone, PartialEq, Eq)]
+///
+/// Spans are ordered by their start index, then by their end index.
+#[derive(Clone, PartialEq, Eq, Hash)]
 pub struct TextSpan {
     /// The starting position of the text span.
     pub start: Position,
@@ -78,6 +81,146 @@ impl TextSpan {
         ))
     }
 
+    /// Merges two spans into the minimal span enclosing both of them.
+    ///
+    /// Unlike [`TextSpan::combine`], the spans don't have to be adjacent. The literal of the
+    /// merged span is built from the literals of both spans, overlapping text is only included
+    /// once. Text between non-adjacent spans is not known, use [`TextSpan::literal`] on the source
+    /// to get the full text of the merged span.
+    ///
+    /// # Arguments
+    ///
+    /// * `a` - The first span.
+    /// * `b` - The second span.
+    ///
+    /// # Returns
+    ///
+    /// A new `TextSpan` from the start of the earlier span to the end of the later one.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use roan_error::{Position, TextSpan};
+    /// let span1 = TextSpan::new(Position::new(1, 0, 0), Position::new(1, 3, 3), "let".to_string());
+    /// let span2 = TextSpan::new(Position::new(1, 8, 8), Position::new(1, 9, 9), "5".to_string());
+    /// let merged = TextSpan::merge(span2, span1);
+    /// assert_eq!(merged.start, Position::new(1, 0, 0));
+    /// assert_eq!(merged.end, Position::new(1, 9, 9));
+    /// ```
+    pub fn merge(a: TextSpan, b: TextSpan) -> TextSpan {
+        let (first, second) = if a.start.index <= b.start.index {
+            (a, b)
+        } else {
+            (b, a)
+        };
+
+        if second.end.index <= first.end.index {
+            return first;
+        }
+
+        let overlap = first.end.index.saturating_sub(second.start.index);
+        let literal = format!(
+            "{}{}",
+            first.literal,
+            second.literal.get(overlap..).unwrap_or_default()
+        );
+
+        TextSpan::new(first.start, second.end, literal)
+    }
+
+    /// Returns the span from the start of `self` to the end of `other`, see [`TextSpan::merge`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use roan_error::{Position, TextSpan};
+    /// let open = TextSpan::new(Position::new(1, 0, 0), Position::new(1, 1, 1), "[".to_string());
+    /// let close = TextSpan::new(Position::new(1, 5, 5), Position::new(1, 6, 6), "]".to_string());
+    /// assert_eq!(open.to(&close).length(), 6);
+    /// ```
+    pub fn to(&self, other: &TextSpan) -> TextSpan {
+        TextSpan::merge(self.clone(), other.clone())
+    }
+
+    /// Returns `true` if `position` is inside the span. The end of the span is exclusive.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use roan_error::{Position, TextSpan};
+    /// let span = TextSpan::new(Position::new(1, 4, 4), Position::new(1, 7, 7), "foo".to_string());
+    /// assert!(span.contains(Position::new(1, 4, 4)));
+    /// assert!(!span.contains(Position::new(1, 7, 7)));
+    /// ```
+    pub fn contains(&self, position: Position) -> bool {
+        self.start.index <= position.index && position.index < self.end.index
+    }
+
+    /// Renders the line of `content` the span starts on, underlined with `^` from the start to the
+    /// end column of the span, followed by the label.
+    ///
+    /// Spans covering multiple lines are underlined until the end of their first line.
+    ///
+    /// # Arguments
+    ///
+    /// * `content` - The source code the span points into.
+    /// * `label` - Text printed after the underline. Nothing is printed if it's empty.
+    ///
+    /// # Returns
+    ///
+    /// A two-line string with the source line and the underline.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use roan_error::{Position, TextSpan};
+    /// let span = TextSpan::new(Position::new(1, 8, 8), Position::new(1, 9, 9), "5".to_string());
+    /// assert_eq!(
+    ///     span.highlight("let x = 5;", "a number"),
+    ///     "1 | let x = 5;\n  |         ^ a number"
+    /// );
+    /// ```
+    pub fn highlight(&self, content: &str, label: &str) -> String {
+        let line = content
+            .lines()
+            .nth((self.start.line.max(1) - 1) as usize)
+            .unwrap_or("");
+
+        self.highlight_line(line, label)
+    }
+
+    /// Renders `line`, the line the span starts on, like [TextSpan::highlight].
+    ///
+    /// Useful when the line was already looked up, e.g. with an index of the lines of the source.
+    pub fn highlight_line(&self, line: &str, label: &str) -> String {
+        let line_number = self.start.line.max(1);
+        let line = line.trim_end();
+
+        let start = self.start.column as usize;
+        let end = if self.end.line == self.start.line {
+            self.end.column as usize
+        } else {
+            line.chars().count()
+        };
+
+        let gutter = line_number.to_string();
+        let mut highlighted = format!(
+            "{} | {}\n{} | {}{}",
+            gutter,
+            line,
+            " ".repeat(gutter.len()),
+            " ".repeat(start),
+            "^".repeat(end.saturating_sub(start).max(1))
+        );
+
+        if !label.is_empty() {
+            highlighted.push(' ');
+            highlighted.push_str(label);
+        }
+
+        highlighted
+    }
+
     /// Returns the length of the span, calculated as the difference between the end and start indices.
     ///
     /// # Returns
@@ -155,6 +298,25 @@ impl Default for TextSpan {
     }
 }
 
+impl Ord for TextSpan {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.start
+            .index
+            .cmp(&other.start.index)
+            .then(self.end.index.cmp(&other.end.index))
+            // Keeps the ordering consistent with `Eq` for spans built by hand
+            .then_with(|| self.start.cmp(&other.start))
+            .then_with(|| self.end.cmp(&other.end))
+            .then_with(|| self.literal.cmp(&other.literal))
+    }
+}
+
+impl PartialOrd for TextSpan {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
 impl std::fmt::Debug for TextSpan {
     /// Formats the `TextSpan` as `"literal" (line:column)`.
     ///
@@ -211,6 +373,106 @@ mod tests {
         assert_eq!(TextSpan::combine(vec![]), None);
     }
 
+    #[test]
+    fn test_merge() {
+        let span1 = TextSpan::new(
+            Position::new(1, 0, 0),
+            Position::new(1, 3, 3),
+            "let".to_string(),
+        );
+        let span2 = TextSpan::new(
+            Position::new(1, 8, 8),
+            Position::new(1, 9, 9),
+            "5".to_string(),
+        );
+        let merged = TextSpan::merge(span2.clone(), span1.clone());
+        assert_eq!(merged.start, Position::new(1, 0, 0));
+        assert_eq!(merged.end, Position::new(1, 9, 9));
+        assert_eq!(merged, TextSpan::merge(span1, span2));
+    }
+
+    #[test]
+    fn test_merge_overlapping() {
+        let span1 = TextSpan::new(
+            Position::new(1, 0, 0),
+            Position::new(1, 4, 4),
+            "test".to_string(),
+        );
+        let span2 = TextSpan::new(
+            Position::new(1, 2, 2),
+            Position::new(1, 6, 6),
+            "st12".to_string(),
+        );
+        let merged = TextSpan::merge(span1, span2);
+        assert_eq!(merged.literal, "test12");
+
+        let outer = TextSpan::new(
+            Position::new(1, 0, 0),
+            Position::new(1, 6, 6),
+            "test12".to_string(),
+        );
+        let inner = TextSpan::new(
+            Position::new(1, 1, 1),
+            Position::new(1, 3, 3),
+            "es".to_string(),
+        );
+        assert_eq!(TextSpan::merge(inner, outer.clone()), outer);
+    }
+
+    #[test]
+    fn test_ord() {
+        let span = |start, end| {
+            TextSpan::new(
+                Position::new(1, start as u32, start),
+                Position::new(1, end as u32, end),
+                String::new(),
+            )
+        };
+
+        let mut spans = vec![span(4, 6), span(0, 3), span(4, 5), span(0, 3)];
+        spans.sort();
+        spans.dedup();
+        assert_eq!(spans, [span(0, 3), span(4, 5), span(4, 6)]);
+
+        let unique: std::collections::HashSet<TextSpan> =
+            [span(0, 3), span(0, 3), span(1, 2)].into_iter().collect();
+        assert_eq!(unique.len(), 2);
+    }
+
+    #[test]
+    fn test_highlight() {
+        let content = "let a = 1;\nlet b = 2 + \"x\";\n";
+        let span = TextSpan::new(
+            Position::new(2, 8, 19),
+            Position::new(2, 15, 26),
+            "2 + \"x\"".to_string(),
+        );
+
+        assert_eq!(
+            span.highlight(content, "mismatched types"),
+            "2 | let b = 2 + \"x\";\n  |         ^^^^^^^ mismatched types"
+        );
+        assert_eq!(
+            span.highlight(content, ""),
+            "2 | let b = 2 + \"x\";\n  |         ^^^^^^^"
+        );
+    }
+
+    #[test]
+    fn test_highlight_multiline() {
+        let content = "fn main() {\n}\n";
+        let span = TextSpan::new(
+            Position::new(1, 10, 10),
+            Position::new(2, 1, 13),
+            "{\n}".to_string(),
+        );
+
+        assert_eq!(
+            span.highlight(content, "block"),
+            "1 | fn main() {\n  |           ^ block"
+        );
+    }
+
     #[test]
     fn test_length() {
         let span = TextSpan::new(
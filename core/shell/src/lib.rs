@@ -1,11 +1,17 @@
 //! Mostly derived from [cargo](https://github.com/rust-lang/cargo) source code.
 
 mod link;
+mod progress;
 pub mod styles;
+mod table;
+
+pub use progress::Progress;
 
 use crate::{
-    link::Link,
-    styles::{ERROR, HEADER, NOTE, WARN},
+    link::{supports_hyperlinks, Link},
+    progress::ProgressState,
+    styles::{ERROR, HEADER, NOP, NOTE, SUCCESS, WARN},
+    table::render_table,
 };
 use anstream::{AutoStream, ColorChoice};
 use anstyle::Style;
@@ -13,15 +19,36 @@ use anyhow::Result;
 use gethostname::gethostname;
 use std::{
     fmt,
-    io::{Stderr, Stdout, Write},
+    fs::OpenOptions,
+    io::{BufRead, BufWriter, IsTerminal, Stderr, Stdout, Write},
+    mem,
     path::PathBuf,
+    sync::{Arc, Mutex},
 };
 
-#[derive(Debug)]
+/// Controls which messages are written by the [Shell].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum VerbosityLevel {
+    /// Only errors and success messages are written.
+    Quiet,
+    /// Everything except verbose messages is written.
+    #[default]
+    Normal,
+    /// Every message is written.
+    Verbose,
+}
+
+/// Stream the shell writes its messages to, stderr unless a file was set with
+/// [Shell::write_to_file].
+pub type MessageStream = AutoStream<Box<dyn Write + Send>>;
+
 pub struct ShellOutput {
     pub stdout: AutoStream<Stdout>,
-    pub stderr: AutoStream<Stderr>,
+    pub stderr: MessageStream,
     pub color: ColorChoice,
+    pub verbosity: VerbosityLevel,
+    /// The file messages are written to instead of stderr, if any.
+    pub log_file: Option<PathBuf>,
 }
 
 impl ShellOutput {
@@ -29,25 +56,60 @@ impl ShellOutput {
         &mut self.stdout
     }
 
-    pub fn stderr(&mut self) -> &mut AutoStream<Stderr> {
+    pub fn stderr(&mut self) -> &mut MessageStream {
         &mut self.stderr
     }
 }
 
+impl fmt::Debug for ShellOutput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ShellOutput")
+            .field("stdout", &self.stdout)
+            .field("color", &self.color)
+            .field("verbosity", &self.verbosity)
+            .field("log_file", &self.log_file)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Creates the stream writing messages to stderr.
+fn stderr_stream(color_choice: ColorChoice) -> MessageStream {
+    // Once boxed, stderr can't tell whether it's a terminal, so `Auto` is resolved first
+    let choice = match color_choice {
+        ColorChoice::Auto => Shell::detect_color_choice(),
+        choice => choice,
+    };
+
+    AutoStream::new(Box::new(std::io::stderr()), choice)
+}
+
 #[derive(Debug)]
 pub struct Shell {
     pub output: ShellOutput,
+    /// Whether the user can be prompted, prompts return their default answer otherwise.
+    pub interactive: bool,
+    /// Maximum width of a table column, wider cells are truncated.
+    pub max_column_width: Option<usize>,
+    /// The progress bar that is currently shown, if any.
+    progress: Option<Arc<Mutex<ProgressState>>>,
 }
 
 impl Shell {
     pub fn new(color_choice: ColorChoice) -> Self {
         let output = ShellOutput {
             stdout: AutoStream::new(std::io::stdout(), color_choice),
-            stderr: AutoStream::new(std::io::stderr(), color_choice),
+            stderr: stderr_stream(color_choice),
             color: color_choice,
+            verbosity: VerbosityLevel::default(),
+            log_file: None,
         };
 
-        Self { output }
+        Self {
+            output,
+            interactive: true,
+            max_column_width: None,
+            progress: None,
+        }
     }
 
     pub fn print(
@@ -69,31 +131,224 @@ impl Shell {
             Some(message) => writeln!(buffer, " {message}")?,
             None => write!(buffer, " ")?,
         }
-        self.output.stderr().write_all(&buffer)?;
+        self.write_stderr(&buffer)
+    }
+
+    /// Writes `buffer` to stderr, moving the progress bar below it if one is shown.
+    fn write_stderr(&mut self, buffer: &[u8]) -> Result<()> {
+        if let Some(state) = &self.progress {
+            if state.lock().unwrap().is_finished() {
+                self.progress = None;
+            }
+        }
+
+        let progress = self.progress.clone();
+        let mut progress = progress.as_ref().map(|state| state.lock().unwrap());
+
+        if let Some(state) = progress.as_mut() {
+            state.clear();
+        }
+        self.output.stderr().write_all(buffer)?;
+        self.output.stderr().flush()?;
+        if let Some(state) = progress.as_mut() {
+            state.redraw();
+        }
+
         Ok(())
     }
 
     pub fn warn<T: fmt::Display>(&mut self, message: T) -> Result<()> {
+        if self.is_quiet() {
+            return Ok(());
+        }
+
         self.print(&"warning", Some(&message), &WARN, false)
     }
 
     pub fn note<T: fmt::Display>(&mut self, message: T) -> Result<()> {
+        if self.is_quiet() {
+            return Ok(());
+        }
+
         self.print(&"note", Some(&message), &NOTE, false)
     }
 
+    pub fn info<T: fmt::Display>(&mut self, message: T) -> Result<()> {
+        if self.is_quiet() {
+            return Ok(());
+        }
+
+        self.print(&"info", Some(&message), &NOP, false)
+    }
+
+    /// Prints a message that is only shown when the verbosity is [VerbosityLevel::Verbose].
+    pub fn verbose<T: fmt::Display>(&mut self, message: T) -> Result<()> {
+        if self.output.verbosity != VerbosityLevel::Verbose {
+            return Ok(());
+        }
+
+        self.print(&"verbose", Some(&message), &NOP, false)
+    }
+
     pub fn error<T: fmt::Display>(&mut self, message: T) -> Result<()> {
         self.print(&"error", Some(&message), &ERROR, false)
     }
 
+    pub fn success<T: fmt::Display>(&mut self, message: T) -> Result<()> {
+        let mut buffer = Vec::new();
+        writeln!(
+            buffer,
+            "{SUCCESS}\u{2713}{SUCCESS:#} {SUCCESS}{message}{SUCCESS:#}"
+        )?;
+        self.write_stderr(&buffer)
+    }
+
     pub fn status<T, U>(&mut self, status: T, message: U) -> Result<()>
     where
         T: fmt::Display,
         U: fmt::Display,
     {
+        if self.is_quiet() {
+            return Ok(());
+        }
+
         self.print(&status, Some(&message), &HEADER, true)
     }
 
-    pub fn set_color_choice(&mut self, color_choice: ColorChoice) {
+    /// Prints `rows` as a left-aligned table with `headers` in the header style.
+    pub fn table(&mut self, headers: &[&str], rows: &[Vec<String>]) -> Result<()> {
+        let mut buffer = Vec::new();
+        render_table(&mut buffer, headers, rows, &HEADER, self.max_column_width)?;
+        self.write_stderr(&buffer)
+    }
+
+    /// Same as [Shell::table], but the table is printed to stdout so it can be piped.
+    pub fn print_table(&mut self, headers: &[&str], rows: &[Vec<String>]) -> Result<()> {
+        let mut buffer = Vec::new();
+        render_table(&mut buffer, headers, rows, &HEADER, self.max_column_width)?;
+        self.output.stdout().write_all(&buffer)?;
+        Ok(())
+    }
+
+    /// Starts a progress indicator for `total` steps.
+    ///
+    /// A bar is drawn on stderr if it's a terminal, otherwise a status line is written every
+    /// quarter. Messages printed through the shell while the progress is running are written
+    /// above the bar.
+    pub fn progress(&mut self, total: u64) -> Progress {
+        let is_tty = std::io::stderr().is_terminal();
+        let styled = self.output.color != ColorChoice::Never;
+        let state = Arc::new(Mutex::new(ProgressState::new(
+            total,
+            is_tty,
+            styled,
+            Box::new(std::io::stderr()),
+        )));
+
+        // Drop the previous progress once a new one starts
+        self.progress = Some(state.clone());
+        Progress::new(state)
+    }
+
+    /// Asks a yes/no question, pressing Enter without typing an answer declines.
+    pub fn confirm(&mut self, question: &str) -> Result<bool> {
+        self.confirm_with_default(question, false)
+    }
+
+    /// Asks a yes/no question, pressing Enter without typing an answer returns `default`.
+    pub fn confirm_with_default(&mut self, question: &str, default: bool) -> Result<bool> {
+        self.confirm_from(&mut std::io::stdin().lock(), question, default)
+    }
+
+    /// Like [Shell::confirm_with_default], reading the answer from `input`.
+    pub fn confirm_from<R: BufRead>(
+        &mut self,
+        input: &mut R,
+        question: &str,
+        default: bool,
+    ) -> Result<bool> {
+        if !self.interactive {
+            return Ok(default);
+        }
+
+        let choices = if default { "[Y/n]" } else { "[y/N]" };
+        write!(self.output.stderr(), "{question} {choices}: ")?;
+        self.output.stderr().flush()?;
+
+        let mut answer = String::new();
+        input.read_line(&mut answer)?;
+
+        Ok(match answer.trim().to_lowercase().as_str() {
+            "y" | "yes" => true,
+            "n" | "no" => false,
+            _ => default,
+        })
+    }
+
+    /// Asks for a line of text, pressing Enter without typing an answer returns `default`.
+    pub fn prompt(&mut self, question: &str, default: &str) -> Result<String> {
+        self.prompt_from(&mut std::io::stdin().lock(), question, default)
+    }
+
+    /// Like [Shell::prompt], reading the answer from `input`.
+    pub fn prompt_from<R: BufRead>(
+        &mut self,
+        input: &mut R,
+        question: &str,
+        default: &str,
+    ) -> Result<String> {
+        if !self.interactive {
+            return Ok(default.to_string());
+        }
+
+        if default.is_empty() {
+            write!(self.output.stderr(), "{question}: ")?;
+        } else {
+            write!(self.output.stderr(), "{question} ({default}): ")?;
+        }
+        self.output.stderr().flush()?;
+
+        let mut answer = String::new();
+        input.read_line(&mut answer)?;
+
+        Ok(match answer.trim() {
+            "" => default.to_string(),
+            answer => answer.to_string(),
+        })
+    }
+
+    pub fn set_interactive(&mut self, interactive: bool) {
+        self.interactive = interactive;
+    }
+
+    pub fn set_max_column_width(&mut self, max_width: Option<usize>) {
+        self.max_column_width = max_width;
+    }
+
+    pub fn set_verbosity(&mut self, level: VerbosityLevel) {
+        self.output.verbosity = level;
+    }
+
+    pub fn verbosity(&self) -> VerbosityLevel {
+        self.output.verbosity
+    }
+
+    fn is_quiet(&self) -> bool {
+        self.output.verbosity == VerbosityLevel::Quiet
+    }
+
+    /// Returns the color choice the environment asks for.
+    ///
+    /// `NO_COLOR`, `CLICOLOR_FORCE`, `CLICOLOR` and `TERM` are read, otherwise colors are only
+    /// written if stderr is a terminal, see [AutoStream::choice].
+    pub fn detect_color_choice() -> ColorChoice {
+        AutoStream::<Stderr>::choice(&std::io::stderr())
+    }
+
+    /// Sets whether colors are written, `None` detects it from the environment again, see
+    /// [Shell::detect_color_choice].
+    pub fn set_color_choice(&mut self, color_choice: Option<ColorChoice>) {
+        let color_choice = color_choice.unwrap_or_else(Self::detect_color_choice);
         let (stdout, stderr, color) = (
             &mut self.output.stdout,
             &mut self.output.stderr,
@@ -102,7 +357,32 @@ impl Shell {
 
         *color = color_choice;
         *stdout = AutoStream::new(std::io::stdout(), color_choice);
-        *stderr = AutoStream::new(std::io::stderr(), color_choice);
+        *stderr = match self.output.log_file {
+            // Keep writing to the log file, only the colors change
+            Some(_) => {
+                let raw = mem::replace(stderr, AutoStream::never(Box::new(std::io::sink())));
+                AutoStream::new(raw.into_inner(), color_choice)
+            }
+            None => stderr_stream(color_choice),
+        };
+    }
+
+    /// Writes messages to the file at `path` instead of stderr, e.g. to keep a log of a CI run.
+    /// The file is truncated unless `append` is `true`.
+    ///
+    /// Colors and hyperlinks are stripped unless the color choice is [ColorChoice::Always].
+    pub fn write_to_file(&mut self, path: PathBuf, append: bool) -> Result<()> {
+        let file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .append(append)
+            .truncate(!append)
+            .open(&path)?;
+
+        self.output.stderr = AutoStream::new(Box::new(BufWriter::new(file)), self.output.color);
+        self.output.log_file = Some(path);
+
+        Ok(())
     }
 
     pub fn file_link(&mut self, file: PathBuf) -> Result<url::Url> {
@@ -117,7 +397,148 @@ impl Shell {
         Ok(url)
     }
 
+    /// Returns `true` if links written to stderr are rendered as clickable hyperlinks.
+    pub fn supports_hyperlinks(&self) -> bool {
+        let is_tty = self.output.log_file.is_none() && std::io::stderr().is_terminal();
+
+        supports_hyperlinks(self.output.color, is_tty, |name| std::env::var(name).ok())
+    }
+
+    /// Creates a link to `url`. Only `text` is written if the terminal doesn't support
+    /// hyperlinks, see [Shell::supports_hyperlinks].
     pub fn hyperlink<'a>(&mut self, url: &'a str, text: &'a str) -> Result<Link<'a>> {
-        Ok(Link::new(text, url))
+        Ok(Link::new(text, url, self.supports_hyperlinks()))
+    }
+
+    /// Creates a link to `file` showing `text`, see [Shell::file_link] and [Shell::hyperlink].
+    pub fn file_link_text<'a>(&mut self, file: PathBuf, text: &'a str) -> Result<Link<'a>> {
+        let url = self.file_link(file)?;
+
+        Ok(Link::new(text, url.to_string(), self.supports_hyperlinks()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn confirm(answer: &str, default: bool) -> bool {
+        let mut shell = Shell::new(ColorChoice::Never);
+        shell
+            .confirm_from(&mut Cursor::new(answer), "Continue?", default)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_confirm() {
+        assert!(confirm("y\n", false));
+        assert!(confirm("YES\n", false));
+        assert!(confirm("  yes  \n", false));
+        assert!(!confirm("n\n", false));
+        assert!(!confirm("maybe\n", false));
+        assert!(!confirm("\n", false));
+        assert!(!confirm("", false));
+    }
+
+    #[test]
+    fn test_confirm_with_default() {
+        assert!(confirm("\n", true));
+        assert!(confirm("y\n", true));
+        assert!(!confirm("no\n", true));
+    }
+
+    #[test]
+    fn test_confirm_non_interactive() {
+        let mut shell = Shell::new(ColorChoice::Never);
+        shell.set_interactive(false);
+
+        let mut input = Cursor::new("y\n");
+        assert!(!shell.confirm_from(&mut input, "Continue?", false).unwrap());
+        assert!(shell.confirm_from(&mut input, "Continue?", true).unwrap());
+        // Nothing was read from the input
+        assert_eq!(input.position(), 0);
+    }
+
+    #[test]
+    fn test_prompt() {
+        let mut shell = Shell::new(ColorChoice::Never);
+        let mut input = Cursor::new("  roan  \n\n");
+        assert_eq!(
+            shell.prompt_from(&mut input, "Name", "app").unwrap(),
+            "roan"
+        );
+        assert_eq!(shell.prompt_from(&mut input, "Name", "app").unwrap(), "app");
+        assert_eq!(shell.prompt_from(&mut input, "Name", "app").unwrap(), "app");
+
+        shell.set_interactive(false);
+        let mut input = Cursor::new("roan\n");
+        assert_eq!(shell.prompt_from(&mut input, "Name", "").unwrap(), "");
+        assert_eq!(input.position(), 0);
+    }
+
+    #[test]
+    fn test_file_link_text() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("main.roan");
+
+        let mut buffer = vec![];
+        let mut shell = Shell::new(ColorChoice::Never);
+        let link = shell.file_link_text(file.clone(), "main.roan:1:1").unwrap();
+        write!(buffer, "{}", link).unwrap();
+        assert_eq!(String::from_utf8(buffer).unwrap(), "main.roan:1:1");
+
+        // Written to a file, so whether the terminal supports them doesn't matter
+        let mut buffer = vec![];
+        let mut shell = Shell::new(ColorChoice::Always);
+        shell
+            .write_to_file(dir.path().join("roan.log"), false)
+            .unwrap();
+        let link = shell.file_link_text(file, "main.roan:1:1").unwrap();
+        write!(buffer, "{}", link).unwrap();
+        let written = String::from_utf8(buffer).unwrap();
+        assert!(written.starts_with("\u{1b}]8;;file://"));
+        assert!(written.ends_with("/main.roan\u{7}main.roan:1:1\u{1b}]8;;\u{7}"));
+    }
+
+    #[test]
+    fn test_detect_color_choice() {
+        std::env::set_var("NO_COLOR", "1");
+        assert_eq!(Shell::detect_color_choice(), ColorChoice::Never);
+
+        let mut shell = Shell::new(ColorChoice::Always);
+        shell.set_color_choice(None);
+        assert_eq!(shell.output.color, ColorChoice::Never);
+        assert_eq!(shell.output.stderr().current_choice(), ColorChoice::Never);
+        shell.set_color_choice(Some(ColorChoice::Always));
+        assert_eq!(shell.output.color, ColorChoice::Always);
+    }
+
+    #[test]
+    fn test_write_to_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("roan.log");
+
+        let mut shell = Shell::new(ColorChoice::Auto);
+        shell.write_to_file(path.clone(), false).unwrap();
+        shell.warn("disk almost full").unwrap();
+        shell.status("Compiling", "app").unwrap();
+        assert!(!shell.supports_hyperlinks());
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "warning: disk almost full\n    Compiling app\n"
+        );
+
+        let mut shell = Shell::new(ColorChoice::Never);
+        shell.write_to_file(path.clone(), true).unwrap();
+        shell.error("out of space").unwrap();
+        let log = std::fs::read_to_string(&path).unwrap();
+        assert!(log.starts_with("warning: disk almost full\n"));
+        assert!(log.ends_with("error: out of space\n"));
+
+        shell.write_to_file(path.clone(), false).unwrap();
+        shell.set_color_choice(Some(ColorChoice::Never));
+        shell.note("fresh").unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "note: fresh\n");
     }
 }
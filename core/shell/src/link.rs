@@ -1,23 +1,193 @@
-use std::fmt;
+use anstream::ColorChoice;
+use std::{borrow::Cow, fmt};
 
+/// Text pointing to a URL.
+///
+/// If the terminal supports hyperlinks the text is wrapped in an OSC 8 escape sequence, so it can
+/// be clicked. Otherwise only the text is written.
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub struct Link<'a> {
     pub text: &'a str,
-    pub url: &'a str,
+    pub url: Cow<'a, str>,
+    /// Whether the escape sequence is written.
+    pub supported: bool,
 }
 
 impl<'a> Link<'a> {
-    pub fn new(text: &'a str, url: &'a str) -> Self {
-        Self { text, url }
+    pub fn new(text: &'a str, url: impl Into<Cow<'a, str>>, supported: bool) -> Self {
+        Self {
+            text,
+            url: url.into(),
+            supported,
+        }
     }
 }
 
 impl fmt::Display for Link<'_> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(
-            f,
-            "\u{1b}]8;;{}\u{1b}\\{}\u{1b}]8;;\u{1b}\\",
-            self.url, self.text
-        )
+        if self.supported {
+            write!(f, "\u{1b}]8;;{}\u{7}{}\u{1b}]8;;\u{7}", self.url, self.text)
+        } else {
+            write!(f, "{}", self.text)
+        }
+    }
+}
+
+/// Terminals known to render OSC 8 hyperlinks, matched against `TERM_PROGRAM`.
+const HYPERLINK_TERM_PROGRAMS: &[&str] = &[
+    "Hyper",
+    "iTerm.app",
+    "terminology",
+    "WezTerm",
+    "vscode",
+    "ghostty",
+];
+
+/// Terminals known to render OSC 8 hyperlinks, matched against `TERM`.
+const HYPERLINK_TERMS: &[&str] = &[
+    "xterm-kitty",
+    "alacritty",
+    "foot",
+    "wezterm",
+    "xterm-ghostty",
+];
+
+/// Guesses whether the terminal renders hyperlinks.
+///
+/// Hyperlinks are never written with [ColorChoice::Never] or in CI runs. `FORCE_HYPERLINK`
+/// overrides the rest of the detection, otherwise output that isn't a terminal only gets
+/// hyperlinks with [ColorChoice::Always], like colors, and the terminal is recognised from its
+/// environment variables. Unknown terminals are assumed not to support them, as they would print
+/// the escape sequence as is.
+pub(crate) fn supports_hyperlinks(
+    color: ColorChoice,
+    is_tty: bool,
+    env: impl Fn(&str) -> Option<String>,
+) -> bool {
+    if color == ColorChoice::Never || env("CI").is_some() {
+        return false;
+    }
+
+    if let Some(force) = env("FORCE_HYPERLINK") {
+        return !force.is_empty() && force != "0";
+    }
+
+    if !is_tty {
+        return matches!(color, ColorChoice::Always | ColorChoice::AlwaysAnsi);
+    }
+
+    if env("DOMTERM").is_some() || env("WT_SESSION").is_some() || env("KONSOLE_VERSION").is_some() {
+        return true;
+    }
+
+    if let Some(version) = env("VTE_VERSION") {
+        // Hyperlinks were added in VTE 0.50
+        if version.parse::<u32>().is_ok_and(|version| version >= 5000) {
+            return true;
+        }
+    }
+
+    if env("TERM_PROGRAM").is_some_and(|program| HYPERLINK_TERM_PROGRAMS.contains(&&*program)) {
+        return true;
+    }
+
+    env("TERM").is_some_and(|term| HYPERLINK_TERMS.contains(&&*term))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn supports(color: ColorChoice, is_tty: bool, vars: &[(&str, &str)]) -> bool {
+        supports_hyperlinks(color, is_tty, |name| {
+            vars.iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| value.to_string())
+        })
+    }
+
+    #[test]
+    fn test_link_fallback() {
+        let url = "https://roan.rs";
+
+        assert_eq!(
+            Link::new("docs", url, true).to_string(),
+            "\u{1b}]8;;https://roan.rs\u{7}docs\u{1b}]8;;\u{7}"
+        );
+        assert_eq!(Link::new("docs", url, false).to_string(), "docs");
+        assert_eq!(Link::new("docs", "", false).to_string(), "docs");
+    }
+
+    #[test]
+    fn test_supports_hyperlinks() {
+        let kitty = [("TERM", "xterm-kitty")];
+
+        assert!(supports(ColorChoice::Auto, true, &kitty));
+        assert!(supports(
+            ColorChoice::Auto,
+            true,
+            &[("VTE_VERSION", "6003")]
+        ));
+        assert!(!supports(
+            ColorChoice::Auto,
+            true,
+            &[("VTE_VERSION", "4600")]
+        ));
+        assert!(supports(
+            ColorChoice::Auto,
+            true,
+            &[("TERM_PROGRAM", "WezTerm")]
+        ));
+        assert!(!supports(ColorChoice::Auto, true, &[("TERM", "xterm")]));
+        assert!(!supports(ColorChoice::Auto, true, &[]));
+    }
+
+    #[test]
+    fn test_hyperlinks_with_color_always() {
+        // Like colors, they're written to output that isn't a terminal if asked for
+        assert!(supports(ColorChoice::Always, false, &[]));
+        assert!(!supports(ColorChoice::Auto, false, &[]));
+        // A terminal still has to support them
+        assert!(!supports(ColorChoice::Always, true, &[("TERM", "xterm")]));
+        assert!(supports(
+            ColorChoice::Always,
+            true,
+            &[("TERM", "xterm-kitty")]
+        ));
+        assert!(!supports(ColorChoice::Always, false, &[("CI", "true")]));
+    }
+
+    #[test]
+    fn test_no_hyperlinks_in_ci_or_without_color() {
+        let kitty = [("TERM", "xterm-kitty")];
+
+        assert!(!supports(ColorChoice::Never, true, &kitty));
+        assert!(!supports(ColorChoice::Auto, false, &kitty));
+        assert!(!supports(
+            ColorChoice::Auto,
+            true,
+            &[("TERM", "xterm-kitty"), ("CI", "true")]
+        ));
+        // `--no-color` and CI win over forcing them
+        assert!(!supports(
+            ColorChoice::Never,
+            true,
+            &[("FORCE_HYPERLINK", "1")]
+        ));
+        assert!(!supports(
+            ColorChoice::Auto,
+            true,
+            &[("FORCE_HYPERLINK", "1"), ("CI", "1")]
+        ));
+        assert!(supports(
+            ColorChoice::Auto,
+            false,
+            &[("FORCE_HYPERLINK", "1")]
+        ));
+        assert!(!supports(
+            ColorChoice::Auto,
+            true,
+            &[("TERM", "xterm-kitty"), ("FORCE_HYPERLINK", "0")]
+        ));
     }
 }
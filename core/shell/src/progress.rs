@@ -0,0 +1,241 @@
+use crate::styles::HEADER;
+use anstyle::Style;
+use std::{
+    io::Write,
+    sync::{Arc, Mutex},
+};
+
+/// Width of the bar between the brackets, in characters.
+const BAR_WIDTH: usize = 30;
+
+/// Number of status lines written for a progress when stderr isn't a terminal.
+const NON_TTY_STEPS: u64 = 4;
+
+/// Handle to a progress indicator created by [crate::Shell::progress].
+///
+/// On a terminal the progress is drawn as a bar that is redrawn in place and cleared by
+/// [Progress::finish]. Otherwise a plain status line is written every quarter, so logs of CI
+/// runs stay readable.
+///
+/// Messages printed through the shell while the bar is visible clear it first and redraw it
+/// afterwards, so they never end up in the middle of the bar.
+#[derive(Debug, Clone)]
+pub struct Progress {
+    state: Arc<Mutex<ProgressState>>,
+}
+
+impl Progress {
+    pub(crate) fn new(state: Arc<Mutex<ProgressState>>) -> Self {
+        Self { state }
+    }
+
+    /// Advances the progress by `n` steps.
+    pub fn inc(&self, n: u64) {
+        let mut state = self.state.lock().unwrap();
+        state.current = (state.current + n).min(state.total);
+        state.draw();
+    }
+
+    /// Returns the number of steps done so far.
+    pub fn position(&self) -> u64 {
+        self.state.lock().unwrap().current
+    }
+
+    /// Marks the progress as done and removes the bar from the terminal.
+    pub fn finish(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.current = state.total;
+        if !state.is_tty {
+            state.draw();
+        }
+        state.clear();
+        state.finished = true;
+    }
+}
+
+pub(crate) struct ProgressState {
+    total: u64,
+    current: u64,
+    is_tty: bool,
+    /// Whether the label of the bar is written with the header style.
+    styled: bool,
+    /// Whether the bar is currently visible on the terminal.
+    drawn: bool,
+    /// Last step written as a status line when stderr isn't a terminal.
+    last_step: u64,
+    finished: bool,
+    out: Box<dyn Write + Send>,
+}
+
+impl std::fmt::Debug for ProgressState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ProgressState")
+            .field("total", &self.total)
+            .field("current", &self.current)
+            .field("is_tty", &self.is_tty)
+            .field("finished", &self.finished)
+            .finish()
+    }
+}
+
+impl ProgressState {
+    pub(crate) fn new(total: u64, is_tty: bool, styled: bool, out: Box<dyn Write + Send>) -> Self {
+        Self {
+            total,
+            current: 0,
+            is_tty,
+            styled,
+            drawn: false,
+            last_step: 0,
+            finished: false,
+            out,
+        }
+    }
+
+    pub(crate) fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Draws the bar, or writes a status line if another quarter of the work is done.
+    pub(crate) fn draw(&mut self) {
+        if self.finished {
+            return;
+        }
+
+        if self.is_tty {
+            let line = self.render_bar();
+            let _ = write!(self.out, "\r\x1b[2K{line}");
+            self.drawn = true;
+        } else {
+            let step = (self.current * NON_TTY_STEPS)
+                .checked_div(self.total)
+                .unwrap_or(NON_TTY_STEPS);
+            if step <= self.last_step {
+                return;
+            }
+            self.last_step = step;
+
+            let _ = writeln!(
+                self.out,
+                "{:>13} {}/{} ({}%)",
+                "Progress",
+                self.current,
+                self.total,
+                self.percent()
+            );
+        }
+        let _ = self.out.flush();
+    }
+
+    /// Removes the bar from the terminal, so a message can be written on its line.
+    pub(crate) fn clear(&mut self) {
+        if self.drawn {
+            let _ = write!(self.out, "\r\x1b[2K");
+            let _ = self.out.flush();
+            self.drawn = false;
+        }
+    }
+
+    /// Draws the bar again after it was cleared.
+    pub(crate) fn redraw(&mut self) {
+        if self.is_tty {
+            self.draw();
+        }
+    }
+
+    fn percent(&self) -> u64 {
+        (self.current * 100).checked_div(self.total).unwrap_or(100)
+    }
+
+    fn render_bar(&self) -> String {
+        let filled = (self.current * BAR_WIDTH as u64)
+            .checked_div(self.total)
+            .map_or(BAR_WIDTH, |filled| filled as usize);
+
+        let bar = if filled >= BAR_WIDTH {
+            "=".repeat(BAR_WIDTH)
+        } else if filled == 0 {
+            " ".repeat(BAR_WIDTH)
+        } else {
+            format!(
+                "{}>{}",
+                "=".repeat(filled - 1),
+                " ".repeat(BAR_WIDTH - filled)
+            )
+        };
+
+        let style = if self.styled { HEADER } else { Style::new() };
+
+        format!(
+            "{style}{:>13}{style:#} [{bar}] {}/{}",
+            "Progress", self.current, self.total
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone, Default)]
+    struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Buffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl Buffer {
+        fn contents(&self) -> String {
+            String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+        }
+    }
+
+    fn progress(total: u64, is_tty: bool) -> (Progress, Buffer) {
+        let buffer = Buffer::default();
+        let state = ProgressState::new(total, is_tty, false, Box::new(buffer.clone()));
+
+        (Progress::new(Arc::new(Mutex::new(state))), buffer)
+    }
+
+    #[test]
+    fn test_progress_non_tty() {
+        let (progress, buffer) = progress(8, false);
+        for _ in 0..7 {
+            progress.inc(1);
+        }
+        progress.finish();
+
+        assert_eq!(
+            buffer.contents(),
+            "     Progress 2/8 (25%)\n     \
+                  Progress 4/8 (50%)\n     \
+                  Progress 6/8 (75%)\n     \
+                  Progress 8/8 (100%)\n"
+        );
+    }
+
+    #[test]
+    fn test_progress_tty_clears_on_finish() {
+        let (progress, buffer) = progress(4, true);
+        progress.inc(2);
+        progress.inc(10);
+
+        assert_eq!(progress.position(), 4);
+        progress.finish();
+
+        let output = buffer.contents();
+        assert!(output.contains("[==============>               ] 2/4"));
+        assert!(output.contains("[==============================] 4/4"));
+        assert!(output.ends_with("\r\x1b[2K"));
+
+        // Nothing is drawn after the progress is finished
+        progress.inc(1);
+        assert_eq!(buffer.contents(), output);
+    }
+}
@@ -8,6 +8,7 @@ pub const PLACEHOLDER: Style = AnsiColor::Cyan.on_default();
 pub const ERROR: Style = AnsiColor::Red.on_default().effects(Effects::BOLD);
 pub const WARN: Style = AnsiColor::Yellow.on_default().effects(Effects::BOLD);
 pub const NOTE: Style = AnsiColor::Cyan.on_default().effects(Effects::BOLD);
+pub const SUCCESS: Style = AnsiColor::BrightGreen.on_default().effects(Effects::BOLD);
 pub const GOOD: Style = AnsiColor::Green.on_default().effects(Effects::BOLD);
 pub const VALID: Style = AnsiColor::Cyan.on_default().effects(Effects::BOLD);
 pub const INVALID: Style = AnsiColor::Yellow.on_default().effects(Effects::BOLD);
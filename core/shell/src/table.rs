@@ -0,0 +1,211 @@
+use anstyle::Style;
+use std::{borrow::Cow, io::Write};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// Separator written between two columns.
+const COLUMN_GAP: &str = "  ";
+
+/// Appended to cells that were cut to fit the maximum column width.
+const ELLIPSIS: char = '…';
+
+/// Renders `headers` and `rows` as a left-aligned table.
+///
+/// Every column is as wide as its longest cell, measured in terminal columns so wide and
+/// multibyte characters stay aligned. Cells wider than `max_width` are truncated with an
+/// ellipsis. Missing cells are rendered as empty strings and the last column is not padded, so
+/// lines never end with whitespace.
+pub fn render_table(
+    buffer: &mut Vec<u8>,
+    headers: &[&str],
+    rows: &[Vec<String>],
+    header_style: &Style,
+    max_width: Option<usize>,
+) -> std::io::Result<()> {
+    let headers = headers
+        .iter()
+        .map(|header| truncate(header, max_width))
+        .collect::<Vec<_>>();
+    let rows = rows
+        .iter()
+        .map(|row| {
+            row.iter()
+                .map(|cell| truncate(cell, max_width))
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    let columns = rows
+        .iter()
+        .map(|row| row.len())
+        .chain(std::iter::once(headers.len()))
+        .max()
+        .unwrap_or(0);
+
+    let mut widths = vec![0; columns];
+    for (i, header) in headers.iter().enumerate() {
+        widths[i] = widths[i].max(header.width());
+    }
+    for row in &rows {
+        for (i, cell) in row.iter().enumerate() {
+            widths[i] = widths[i].max(cell.width());
+        }
+    }
+
+    let header_cells = (0..columns)
+        .map(|i| headers.get(i).map(Cow::as_ref).unwrap_or_default())
+        .collect::<Vec<_>>();
+    write_row(buffer, &header_cells, &widths, header_style)?;
+
+    let separator = widths.iter().map(|w| "-".repeat(*w)).collect::<Vec<_>>();
+    let separator = separator.iter().map(String::as_str).collect::<Vec<_>>();
+    write_row(buffer, &separator, &widths, &Style::new())?;
+
+    for row in &rows {
+        let cells = (0..columns)
+            .map(|i| row.get(i).map(Cow::as_ref).unwrap_or_default())
+            .collect::<Vec<_>>();
+        write_row(buffer, &cells, &widths, &Style::new())?;
+    }
+
+    Ok(())
+}
+
+fn write_row(
+    buffer: &mut Vec<u8>,
+    cells: &[&str],
+    widths: &[usize],
+    style: &Style,
+) -> std::io::Result<()> {
+    let mut line = String::new();
+    for (i, cell) in cells.iter().enumerate() {
+        if i > 0 {
+            line.push_str(COLUMN_GAP);
+        }
+        line.push_str(cell);
+
+        if i + 1 < cells.len() {
+            let padding = widths[i] - cell.width();
+            line.push_str(&" ".repeat(padding));
+        }
+    }
+
+    writeln!(buffer, "{style}{}{style:#}", line.trim_end())
+}
+
+/// Cuts `cell` so it's at most `max_width` columns wide, including the ellipsis.
+fn truncate(cell: &str, max_width: Option<usize>) -> Cow<'_, str> {
+    let max_width = match max_width {
+        Some(max_width) if cell.width() > max_width => max_width,
+        _ => return Cow::Borrowed(cell),
+    };
+
+    let mut truncated = String::new();
+    let mut width = ELLIPSIS.width().unwrap_or(1);
+    for c in cell.chars() {
+        width += c.width().unwrap_or(0);
+        if width > max_width {
+            break;
+        }
+        truncated.push(c);
+    }
+    truncated.push(ELLIPSIS);
+
+    Cow::Owned(truncated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(headers: &[&str], rows: &[Vec<String>]) -> String {
+        render_with_max_width(headers, rows, None)
+    }
+
+    fn render_with_max_width(
+        headers: &[&str],
+        rows: &[Vec<String>],
+        max_width: Option<usize>,
+    ) -> String {
+        let mut buffer = Vec::new();
+        render_table(&mut buffer, headers, rows, &Style::new(), max_width).unwrap();
+        String::from_utf8(buffer).unwrap()
+    }
+
+    fn row(cells: &[&str]) -> Vec<String> {
+        cells.iter().map(|c| c.to_string()).collect()
+    }
+
+    #[test]
+    fn test_render_table() {
+        let table = render(
+            &["name", "version", "source"],
+            &[
+                row(&["std", "0.1.0", "github"]),
+                row(&["http-client", "1.12.0", "path"]),
+            ],
+        );
+
+        assert_eq!(
+            table,
+            "name         version  source\n\
+             -----------  -------  ------\n\
+             std          0.1.0    github\n\
+             http-client  1.12.0   path\n"
+        );
+    }
+
+    #[test]
+    fn test_render_table_missing_cells() {
+        let table = render(
+            &["test", "result"],
+            &[row(&["adds"]), row(&["subtracts", "ok"])],
+        );
+
+        assert_eq!(
+            table,
+            "test       result\n\
+             ---------  ------\n\
+             adds\n\
+             subtracts  ok\n"
+        );
+    }
+
+    #[test]
+    fn test_render_table_unicode_width() {
+        let table = render(&["a", "b"], &[row(&["żółw", "x"])]);
+
+        assert_eq!(table, "a     b\n----  -\nżółw  x\n");
+    }
+
+    #[test]
+    fn test_render_table_no_rows() {
+        assert_eq!(render(&["name"], &[]), "name\n----\n");
+    }
+
+    #[test]
+    fn test_render_table_wide_chars() {
+        let table = render(&["name", "id"], &[row(&["日本", "1"]), row(&["abc", "2"])]);
+
+        assert_eq!(table, "name  id\n----  --\n日本  1\nabc   2\n");
+    }
+
+    #[test]
+    fn test_render_table_max_width() {
+        let table = render_with_max_width(
+            &["package", "description"],
+            &[
+                row(&["std", "The standard library"]),
+                row(&["日本語です", "ok"]),
+            ],
+            Some(6),
+        );
+
+        assert_eq!(
+            table,
+            "packa…  descr…\n\
+             ------  ------\n\
+             std     The s…\n\
+             日本…   ok\n"
+        );
+    }
+}
@@ -0,0 +1,179 @@
+use anstream::ColorChoice;
+use log::Level;
+use roan_cli::{context::GlobalContext, module_loader::RoanModuleLoader};
+use roan_engine::{
+    context::Context,
+    interpreter::passes::{types::TypePass, Pass},
+    module::Module,
+    source::Source,
+    vm::VM,
+};
+use roan_error::{error::format_error_code, Diagnostic, Position, TextSpan};
+use std::{
+    cell::RefCell,
+    io,
+    panic::{self, AssertUnwindSafe},
+    path::Path,
+    rc::Rc,
+};
+use tower_lsp::lsp_types::{self, DiagnosticSeverity, NumberOrString, Range};
+
+/// What the server knows about an open document.
+#[derive(Default)]
+pub struct Analysis {
+    pub diagnostics: Vec<lsp_types::Diagnostic>,
+    /// Types of the variables of the document, `None` if it doesn't parse.
+    types: Option<TypePass>,
+}
+
+impl Analysis {
+    /// Returns the type of the variable at `position`.
+    pub fn type_at(&self, position: lsp_types::Position) -> Option<String> {
+        let types = self.types.as_ref()?;
+
+        types
+            .variable_type_at(position.line + 1, position.character)
+            .map(ToString::to_string)
+    }
+}
+
+/// Lexes, parses and type checks `text`, the content of the document at `path`, with the same
+/// passes as `roan run`.
+pub fn analyze(path: &Path, text: &str) -> Analysis {
+    // A bug in a pass shouldn't take down the server, the document just isn't analyzed
+    panic::catch_unwind(AssertUnwindSafe(|| analyze_module(path, text))).unwrap_or_default()
+}
+
+fn analyze_module(path: &Path, text: &str) -> Analysis {
+    let cwd = path.parent().unwrap_or(path).to_path_buf();
+    let Ok(global) = GlobalContext::from_cwd(cwd, ColorChoice::Never) else {
+        return Analysis::default();
+    };
+
+    // Imports of packages are resolved from the workspace root, like in `roan run`
+    let mut ctx = Context::builder()
+        .cwd(global.workspace_root().unwrap_or(global.cwd.clone()))
+        .module_loader(Rc::new(RefCell::new(RoanModuleLoader::new())))
+        .build();
+    ctx.set_output(Box::new(io::sink()));
+    ctx.set_error_output(Box::new(io::sink()));
+
+    let source = Source::from_string(text.to_string()).with_path(path.to_path_buf());
+    let mut module = Module::new(source);
+    let vm = &mut VM::new();
+
+    let diagnostics = match module.check(&mut ctx, vm) {
+        Ok(diagnostics) => diagnostics.iter().map(to_lsp_diagnostic).collect(),
+        Err(err) => vec![lsp_types::Diagnostic {
+            severity: Some(DiagnosticSeverity::ERROR),
+            source: Some("roan".to_string()),
+            message: err.to_string(),
+            ..Default::default()
+        }],
+    };
+
+    let mut types = TypePass::new();
+    let types = match module.ast.stmts.is_empty() {
+        true => None,
+        false => types
+            .run(&mut module, &mut ctx, vm, &mut vec![])
+            .ok()
+            .map(|_| types),
+    };
+
+    Analysis { diagnostics, types }
+}
+
+fn to_lsp_diagnostic(diagnostic: &Diagnostic) -> lsp_types::Diagnostic {
+    let message = match &diagnostic.hint {
+        Some(hint) => format!("{}\n{}", diagnostic.title, hint),
+        None => diagnostic.title.clone(),
+    };
+
+    lsp_types::Diagnostic {
+        range: diagnostic
+            .location
+            .as_ref()
+            .map(to_lsp_range)
+            .unwrap_or_default(),
+        severity: Some(match diagnostic.level {
+            Level::Error => DiagnosticSeverity::ERROR,
+            Level::Warn => DiagnosticSeverity::WARNING,
+            _ => DiagnosticSeverity::INFORMATION,
+        }),
+        code: diagnostic
+            .code
+            .map(|code| NumberOrString::String(format_error_code(code))),
+        source: Some("roan".to_string()),
+        message,
+        ..Default::default()
+    }
+}
+
+fn to_lsp_range(span: &TextSpan) -> Range {
+    Range::new(to_lsp_position(&span.start), to_lsp_position(&span.end))
+}
+
+/// Lines of [Position]s start at 1, lines of LSP positions at 0.
+fn to_lsp_position(position: &Position) -> lsp_types::Position {
+    lsp_types::Position::new(position.line.saturating_sub(1), position.column)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    /// The documents are analyzed in the runtime of the server, which the GitHub client of
+    /// [GlobalContext] needs.
+    fn analyze_str(text: &str) -> Analysis {
+        analyze(&env::temp_dir().join("main.roan"), text)
+    }
+
+    #[tokio::test]
+    async fn test_type_errors_are_reported() {
+        let analysis = analyze_str("let a = 1;\nlet b = a + \"x\";\nlet c = true + 1;\n");
+
+        assert_eq!(analysis.diagnostics.len(), 2, "{:?}", analysis.diagnostics);
+        let diagnostic = &analysis.diagnostics[0];
+        assert_eq!(diagnostic.severity, Some(DiagnosticSeverity::ERROR));
+        assert_eq!(
+            diagnostic.code,
+            Some(NumberOrString::String("E0019".to_string()))
+        );
+        assert_eq!(diagnostic.range.start, lsp_types::Position::new(1, 8));
+        assert_eq!(analysis.diagnostics[1].range.start.line, 2);
+    }
+
+    #[tokio::test]
+    async fn test_syntax_errors_are_reported() {
+        let analysis = analyze_str("let a = ;\nfn f( {}\n");
+
+        assert!(!analysis.diagnostics.is_empty());
+        assert_eq!(analysis.diagnostics[0].range.start.line, 0);
+
+        let analysis = analyze_str("let s = r\"open");
+        assert_eq!(analysis.diagnostics.len(), 1);
+        assert!(analysis.diagnostics[0].message.contains("Unterminated"));
+    }
+
+    #[tokio::test]
+    async fn test_type_at() {
+        let analysis = analyze_str("let items = [1, 2];\nfn f(name: string) { name; }\n");
+
+        assert!(
+            analysis.diagnostics.is_empty(),
+            "{:?}",
+            analysis.diagnostics
+        );
+        assert_eq!(
+            analysis.type_at(lsp_types::Position::new(0, 6)).as_deref(),
+            Some("vec<int>")
+        );
+        assert_eq!(
+            analysis.type_at(lsp_types::Position::new(1, 22)).as_deref(),
+            Some("string")
+        );
+        assert_eq!(analysis.type_at(lsp_types::Position::new(1, 0)), None);
+    }
+}
@@ -0,0 +1,115 @@
+use crate::analysis::{analyze, Analysis};
+use std::{collections::HashMap, path::PathBuf, sync::RwLock};
+use tower_lsp::{
+    jsonrpc::Result,
+    lsp_types::{
+        DidChangeTextDocumentParams, DidCloseTextDocumentParams, DidOpenTextDocumentParams, Hover,
+        HoverContents, HoverParams, HoverProviderCapability, InitializeParams, InitializeResult,
+        InitializedParams, LanguageString, MarkedString, MessageType, ServerCapabilities,
+        ServerInfo, TextDocumentSyncCapability, TextDocumentSyncKind, Url,
+    },
+    Client, LanguageServer,
+};
+
+pub struct Backend {
+    client: Client,
+    /// Analysis of every open document, updated whenever it changes.
+    documents: RwLock<HashMap<Url, Analysis>>,
+}
+
+impl Backend {
+    pub fn new(client: Client) -> Self {
+        Self {
+            client,
+            documents: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Analyzes the new `text` of the document at `uri` and publishes its diagnostics.
+    async fn update(&self, uri: Url, text: &str, version: i32) {
+        let path = uri
+            .to_file_path()
+            .unwrap_or_else(|_| PathBuf::from(uri.path()));
+        let analysis = analyze(&path, text);
+        let diagnostics = analysis.diagnostics.clone();
+
+        self.documents
+            .write()
+            .unwrap()
+            .insert(uri.clone(), analysis);
+        self.client
+            .publish_diagnostics(uri, diagnostics, Some(version))
+            .await;
+    }
+}
+
+#[tower_lsp::async_trait]
+impl LanguageServer for Backend {
+    async fn initialize(&self, _: InitializeParams) -> Result<InitializeResult> {
+        Ok(InitializeResult {
+            capabilities: ServerCapabilities {
+                text_document_sync: Some(TextDocumentSyncCapability::Kind(
+                    TextDocumentSyncKind::FULL,
+                )),
+                hover_provider: Some(HoverProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            server_info: Some(ServerInfo {
+                name: env!("CARGO_PKG_NAME").to_string(),
+                version: Some(env!("CARGO_PKG_VERSION").to_string()),
+            }),
+        })
+    }
+
+    async fn initialized(&self, _: InitializedParams) {
+        self.client
+            .log_message(MessageType::INFO, "Roan language server initialized")
+            .await;
+    }
+
+    async fn shutdown(&self) -> Result<()> {
+        Ok(())
+    }
+
+    async fn did_open(&self, params: DidOpenTextDocumentParams) {
+        let document = params.text_document;
+
+        self.update(document.uri, &document.text, document.version)
+            .await;
+    }
+
+    async fn did_change(&self, params: DidChangeTextDocumentParams) {
+        // Documents are synced in full, the last change is the whole new text
+        let Some(change) = params.content_changes.last() else {
+            return;
+        };
+        let document = params.text_document;
+
+        self.update(document.uri, &change.text, document.version)
+            .await;
+    }
+
+    async fn did_close(&self, params: DidCloseTextDocumentParams) {
+        let uri = params.text_document.uri;
+
+        self.documents.write().unwrap().remove(&uri);
+        self.client.publish_diagnostics(uri, vec![], None).await;
+    }
+
+    async fn hover(&self, params: HoverParams) -> Result<Option<Hover>> {
+        let position = params.text_document_position_params;
+        let documents = self.documents.read().unwrap();
+
+        let typ = documents
+            .get(&position.text_document.uri)
+            .and_then(|analysis| analysis.type_at(position.position));
+
+        Ok(typ.map(|typ| Hover {
+            contents: HoverContents::Scalar(MarkedString::LanguageString(LanguageString {
+                language: "roan".to_string(),
+                value: typ,
+            })),
+            range: None,
+        }))
+    }
+}
@@ -0,0 +1,14 @@
+use backend::Backend;
+use tower_lsp::{LspService, Server};
+
+mod analysis;
+mod backend;
+
+#[tokio::main]
+async fn main() {
+    let (service, socket) = LspService::new(Backend::new);
+
+    Server::new(tokio::io::stdin(), tokio::io::stdout(), socket)
+        .serve(service)
+        .await;
+}